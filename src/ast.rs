@@ -1,5 +1,6 @@
 use crate::errors::Error;
 use crate::lexer::TokenType;
+use crate::symbol::Symbol;
 use crate::visitor::{CompilerVisitor, Visitor};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -18,6 +19,7 @@ pub enum ASTNode {
     MethodDef(MethodDef),
     ClassDef(ClassDef),
     Attribute(Attribute),
+    Slice(Slice),
     Compare(Compare),
     If(If),
     Import(Import),
@@ -25,6 +27,8 @@ pub enum ASTNode {
     UnaryOp(UnaryOp),
     Return(Return),
     Raise(Raise),
+    Try(Try),
+    With(With),
 }
 
 impl ASTNode {
@@ -37,6 +41,7 @@ impl ASTNode {
                 | ASTNode::MethodCall(_)
                 | ASTNode::Assignment(_)
                 | ASTNode::Attribute(_)
+                | ASTNode::Slice(_)
                 | ASTNode::Compare(_)
                 | ASTNode::BinOp(_)
                 | ASTNode::UnaryOp(_)
@@ -57,12 +62,12 @@ impl Module {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Identifier {
-    pub name: String,
+    pub name: Symbol,
 }
 
 impl Identifier {
-    pub fn new(name: String) -> Self {
-        Identifier { name }
+    pub fn new(name: impl Into<Symbol>) -> Self {
+        Identifier { name: name.into() }
     }
 }
 
@@ -95,6 +100,7 @@ pub struct MethodDef {
     pub name: String,
     pub parameters: Vec<Box<ASTNode>>,
     pub body: Box<ASTNode>,
+    pub doc: Option<String>,
 }
 
 impl MethodDef {
@@ -103,6 +109,7 @@ impl MethodDef {
             name,
             parameters,
             body,
+            doc: None,
         }
     }
 }
@@ -111,11 +118,16 @@ impl MethodDef {
 pub struct ClassDef {
     pub name: String,
     pub body: Box<ASTNode>,
+    pub doc: Option<String>,
 }
 
 impl ClassDef {
     pub fn new(name: String, body: Box<ASTNode>) -> Self {
-        ClassDef { name, body }
+        ClassDef {
+            name,
+            body,
+            doc: None,
+        }
     }
 }
 
@@ -139,6 +151,8 @@ pub enum Operator {
     Gte,
     Lte,
     Neq,
+    Is,
+    IsNot,
     Plus,
     Minus,
 }
@@ -152,6 +166,7 @@ impl Operator {
             TokenType::Gte => Some(Operator::Gte),
             TokenType::Lte => Some(Operator::Lte),
             TokenType::Neq => Some(Operator::Neq),
+            TokenType::Is => Some(Operator::Is),
             TokenType::Plus => Some(Operator::Plus),
             TokenType::Minus => Some(Operator::Minus),
             _ => None,
@@ -201,17 +216,42 @@ impl While {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Vec<Box<ASTNode>>,
+    // The line each statement starts on, or 0 when it was not parsed from
+    // source. Empty for blocks built by the compiler.
+    pub lines: Vec<usize>,
 }
 
 impl Block {
     pub fn new(statements: Vec<Box<ASTNode>>) -> Self {
-        Block { statements }
+        Block {
+            statements,
+            lines: vec![],
+        }
+    }
+
+    pub fn line(&self, index: usize) -> Option<usize> {
+        self.lines.get(index).copied().filter(|line| *line > 0)
+    }
+}
+
+// Where the statements are in the source does not make two blocks differ.
+impl PartialEq for Block {
+    fn eq(&self, other: &Self) -> bool {
+        self.statements == other.statements
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Slice {
+    pub value: Box<ASTNode>,
+    pub start: Option<Box<ASTNode>>,
+    pub end: Option<Box<ASTNode>>,
+    pub step: Option<Box<ASTNode>>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct BinOp {
     pub left: Box<ASTNode>,
@@ -233,6 +273,20 @@ pub struct Return {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Raise {
     pub message: Option<Box<ASTNode>>,
+    pub cause: Option<Box<ASTNode>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Try {
+    pub body: Box<ASTNode>,
+    pub ensure: Box<ASTNode>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct With {
+    pub value: Box<ASTNode>,
+    pub name: Option<String>,
+    pub body: Box<ASTNode>,
 }
 
 impl ASTNode {
@@ -247,6 +301,7 @@ impl ASTNode {
             ASTNode::MethodDef(method_def) => visitor.visit_method_def(&method_def),
             ASTNode::ClassDef(class_def) => visitor.visit_class_def(&class_def),
             ASTNode::Attribute(attribute) => visitor.visit_attribute(&attribute),
+            ASTNode::Slice(slice) => visitor.visit_slice(&slice),
             ASTNode::Compare(compare) => visitor.visit_compare(&compare),
             ASTNode::If(if_node) => visitor.visit_if(&if_node),
             ASTNode::Import(import) => visitor.visit_import(&import),
@@ -257,10 +312,18 @@ impl ASTNode {
             ASTNode::Block(block) => visitor.visit_block(&block),
             ASTNode::Return(return_node) => visitor.visit_return(&return_node),
             ASTNode::Raise(raise) => visitor.visit_raise(&raise),
+            ASTNode::Try(try_node) => visitor.visit_try(try_node),
+            ASTNode::With(with) => visitor.visit_with(with),
         }
     }
 
     pub fn compile(&self, compiler: &mut dyn CompilerVisitor) -> Result<(), Error> {
+        compiler.before_node(self)?;
+        self.compile_node(compiler)?;
+        compiler.after_node(self)
+    }
+
+    fn compile_node(&self, compiler: &mut dyn CompilerVisitor) -> Result<(), Error> {
         match self {
             ASTNode::Module(module) => compiler.compile_module(&module),
             ASTNode::Identifier(identifier) => compiler.compile_identifier(&identifier),
@@ -275,6 +338,7 @@ impl ASTNode {
             ASTNode::MethodDef(method_def) => compiler.compile_method_def(&method_def),
             ASTNode::ClassDef(class_def) => compiler.compile_class_def(&class_def),
             ASTNode::Attribute(attribute) => compiler.compile_attribute(&attribute),
+            ASTNode::Slice(slice) => compiler.compile_slice(&slice),
             ASTNode::Compare(compare) => compiler.compile_compare(&compare),
             ASTNode::If(if_node) => compiler.compile_if(&if_node),
             ASTNode::Import(import) => compiler.compile_import(&import),
@@ -285,6 +349,8 @@ impl ASTNode {
             ASTNode::Block(block) => compiler.compile_block(&block),
             ASTNode::Return(return_node) => compiler.compile_return(&return_node),
             ASTNode::Raise(raise) => compiler.compile_raise(&raise),
+            ASTNode::Try(try_node) => compiler.compile_try(try_node),
+            ASTNode::With(with) => compiler.compile_with(with),
         }
     }
 }
@@ -0,0 +1,146 @@
+//! `kyanite bench`: times the functions of a script whose names start with
+//! `bench_`. The script runs once, then each function is called until it
+//! has run for a while to warm up, and then in growing batches until a batch
+//! takes long enough to measure. The time per call is that of the last
+//! batch, so it includes the call itself.
+
+use crate::errors::Error;
+use crate::internal::source::read_source;
+use crate::interpreter::{Interpreter, Session};
+use crate::testing::{compile, describe, functions_named, parse};
+
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const BENCH_PREFIX: &str = "bench_";
+
+const WARM_UP_TIME: Duration = Duration::from_millis(200);
+const MEASUREMENT_TIME: Duration = Duration::from_secs(1);
+
+pub struct Measurement {
+    pub name: String,
+    pub iterations: u64,
+    pub elapsed: Duration,
+}
+
+impl Measurement {
+    pub fn nanos_per_iteration(&self) -> u128 {
+        self.elapsed.as_nanos() / self.iterations.max(1) as u128
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<32}{:>14} ns/iter{:>12} iterations",
+            self.name,
+            self.nanos_per_iteration(),
+            self.iterations
+        )
+    }
+}
+
+/// Runs the benchmarks of the script at `path`, printing each result as it
+/// comes. Stops at the first one that raises.
+pub fn run(path: &Path) -> Result<Vec<Measurement>, String> {
+    measure_all(path, WARM_UP_TIME, MEASUREMENT_TIME, |measurement| {
+        println!("{}", measurement)
+    })
+}
+
+fn measure_all(
+    path: &Path,
+    warm_up: Duration,
+    target: Duration,
+    on_result: impl Fn(&Measurement),
+) -> Result<Vec<Measurement>, String> {
+    let error = |e: Error| format!("{}: {}", path.display(), describe(&e));
+
+    let source = read_source(path)?;
+    let module = parse(&source).map_err(error)?;
+    let code = compile(module.clone()).map_err(error)?;
+
+    let root = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut session = Session::new(Interpreter::new(&root.to_string_lossy()));
+
+    session.eval(&code).map_err(error)?;
+
+    let mut measurements = vec![];
+
+    for name in functions_named(&module, BENCH_PREFIX) {
+        let call = compile(parse(&format!("{}()\n", name)).map_err(error)?).map_err(error)?;
+        let mut run = |iterations: u64| -> Result<Duration, String> {
+            let started = Instant::now();
+
+            for _ in 0..iterations {
+                session
+                    .eval(&call)
+                    .map_err(|e| format!("{}: {}", name, describe(&e)))?;
+            }
+
+            Ok(started.elapsed())
+        };
+
+        let warm_up_started = Instant::now();
+
+        while warm_up_started.elapsed() < warm_up {
+            run(1)?;
+        }
+
+        let mut iterations = 1;
+        let mut elapsed = run(iterations)?;
+
+        while elapsed < target {
+            // Aim for the target from the time per call so far, without
+            // growing the batch more than tenfold at once.
+            let per_iteration = elapsed.as_nanos().max(1) / iterations as u128;
+            let wanted = (target.as_nanos() / per_iteration) as u64;
+
+            iterations = wanted.clamp(iterations + 1, iterations * 10);
+            elapsed = run(iterations)?;
+        }
+
+        let measurement = Measurement {
+            name,
+            iterations,
+            elapsed,
+        };
+
+        on_result(&measurement);
+        measurements.push(measurement);
+    }
+
+    Ok(measurements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_all() {
+        let path = std::env::temp_dir().join(format!("kyanite-bench-{}.k", std::process::id()));
+
+        std::fs::write(
+            &path,
+            "def bench_add\n  1 + 2\nend\n\ndef helper\n  raise Exception('not a bench')\nend\n",
+        )
+        .unwrap();
+
+        let measurements = measure_all(&path, Duration::ZERO, Duration::from_millis(10), |_| {});
+
+        std::fs::remove_file(&path).unwrap();
+
+        let measurements = measurements.unwrap();
+
+        assert_eq!(measurements.len(), 1);
+        assert_eq!(measurements[0].name, "bench_add");
+        assert!(measurements[0].elapsed >= Duration::from_millis(10));
+        assert!(measurements[0].iterations > 1);
+    }
+}
@@ -1,7 +1,13 @@
+use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
-use crate::objects::base::KyaObjectRef;
-use crate::objects::utils::string_object_to_string;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{kya_call, kya_compare, KyaObject, KyaObjectRef};
+use crate::objects::singletons::kya_none;
+use crate::objects::utils::{
+    kya_is_true, number_object_to_float, object_to_string_repr, string_object_to_string,
+};
+use std::thread;
+use std::time::Duration;
 
 pub fn kya_print(
     _callable: KyaObjectRef,
@@ -28,5 +34,265 @@ pub fn kya_print(
 
     println!("{}", output);
 
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
+}
+
+pub fn kya_assert(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(Error::TypeError(format!(
+            "assert() takes 1 or 2 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    if kya_is_true(args[0].clone())? {
+        return Ok(kya_none());
+    }
+
+    let message = match args.get(1) {
+        Some(message) => match string_object_to_string(message) {
+            Ok(message) => message,
+            Err(_) => object_to_string_repr(message)?,
+        },
+        None => "Assertion failed".to_string(),
+    };
+
+    Err(Error::exception("Exception".to_string(), message))
+}
+
+pub fn kya_assert_eq(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(Error::TypeError(format!(
+            "assert_eq() takes 2 or 3 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let equal = kya_compare(args[0].clone(), args[1].clone(), ComparisonOperator::Equal)?;
+
+    if kya_is_true(equal)? {
+        return Ok(kya_none());
+    }
+
+    let message = match args.get(2) {
+        Some(message) => match string_object_to_string(message) {
+            Ok(message) => message,
+            Err(_) => object_to_string_repr(message)?,
+        },
+        None => format!(
+            "Expected {}, but got {}",
+            object_to_string_repr(&args[1])?,
+            object_to_string_repr(&args[0])?
+        ),
+    };
+
+    Err(Error::exception("Exception".to_string(), message))
+}
+
+// Calls `function` with the rest of the arguments and passes if it raises an
+// exception of the class, by name. Errors raised by builtins get the type
+// they have once raised in the program, and any exception passes for
+// `Exception` itself.
+pub fn kya_assert_raises(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.len() < 2 {
+        return Err(Error::TypeError(format!(
+            "assert_raises() takes at least 2 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let expected = match &*args[0].lock().unwrap() {
+        KyaObject::ClassObject(class) => class.ob_type.lock().unwrap().name.clone(),
+        _ => {
+            return Err(Error::TypeError(
+                "The first argument of assert_raises() must be an exception class".to_string(),
+            ))
+        }
+    };
+
+    let function = args[1].clone();
+    let mut call_args = args[2..].to_vec();
+
+    let raised = match kya_call(function, &mut call_args, None) {
+        Ok(_) => {
+            return Err(Error::exception(
+                "Exception".to_string(),
+                format!("Expected {} to be raised", expected),
+            ))
+        }
+        Err(Error::Exception(raised, message, ..)) => (raised, message),
+        Err(Error::TypeError(message)) => ("TypeError".to_string(), message),
+        Err(Error::ValueError(message)) => ("ValueError".to_string(), message),
+        Err(error) => ("Exception".to_string(), error.message()),
+    };
+
+    if raised.0 == expected || expected == "Exception" {
+        return Ok(kya_none());
+    }
+
+    Err(Error::exception(
+        "Exception".to_string(),
+        format!(
+            "Expected {} to be raised, but got {}: {}",
+            expected, raised.0, raised.1
+        ),
+    ))
+}
+
+const MAX_RETRY_DELAY: f64 = 60.0;
+
+pub fn kya_retry(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(Error::TypeError(format!(
+            "retry() takes 2 or 3 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let times = number_object_to_float(&args[0])
+        .map_err(|_| Error::TypeError("The 'times' argument must be a number".to_string()))?;
+    let backoff = match args.get(2) {
+        Some(backoff) => number_object_to_float(backoff)
+            .map_err(|_| Error::TypeError("The 'backoff' argument must be a number".to_string()))?,
+        None => 0.0,
+    };
+
+    if times < 1.0 || times.fract() != 0.0 {
+        return Err(Error::ValueError(format!(
+            "retry() needs a positive number of attempts, got {}",
+            times
+        )));
+    }
+
+    if Duration::try_from_secs_f64(backoff).is_err() {
+        return Err(Error::ValueError(format!(
+            "retry() backoff must be a non-negative number of seconds, got {}",
+            backoff
+        )));
+    }
+
+    let function = args[1].clone();
+    let mut delay = backoff;
+    let mut attempt = 1.0;
+
+    loop {
+        match kya_call(function.clone(), &mut vec![], None) {
+            Ok(result) => return Ok(result),
+            Err(error) if attempt >= times => return Err(error),
+            Err(_) => {}
+        }
+
+        // The delay doubles after every failed attempt, up to a minute or
+        // the first delay if that was longer.
+        if delay > 0.0 {
+            kya_release_lock();
+
+            thread::sleep(Duration::from_secs_f64(delay));
+
+            kya_acquire_lock();
+        }
+
+        delay = (delay * 2.0).min(MAX_RETRY_DELAY.max(backoff));
+        attempt += 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::host_function_object::{host_function_new, ArgType, HostValue};
+    use crate::objects::number_object::number_new;
+    use crate::objects::singletons::{kya_false, kya_true};
+    use crate::objects::string_object::string_new;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn assert_message(mut args: Vec<KyaObjectRef>) -> Option<String> {
+        match kya_assert(kya_none(), &mut args, None) {
+            Ok(_) => None,
+            Err(Error::Exception(_, message, ..)) => Some(message),
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn test_assert() {
+        assert_eq!(assert_message(vec![kya_true()]), None);
+        assert_eq!(assert_message(vec![number_new(1.0)]), None);
+        assert_eq!(
+            assert_message(vec![kya_false()]),
+            Some("Assertion failed".to_string())
+        );
+        assert_eq!(
+            assert_message(vec![number_new(0.0), string_new("expected a match")]),
+            Some("expected a match".to_string())
+        );
+        assert!(kya_assert(kya_none(), &mut vec![], None).is_err());
+    }
+
+    #[test]
+    fn test_assert_eq() {
+        let mut equal = vec![number_new(1.0), number_new(1.0)];
+        let mut different = vec![string_new("a"), string_new("b")];
+
+        assert!(kya_assert_eq(kya_none(), &mut equal, None).is_ok());
+        assert!(matches!(
+            kya_assert_eq(kya_none(), &mut different, None),
+            Err(Error::Exception(_, message, ..)) if message == "Expected b, but got a"
+        ));
+    }
+
+    #[test]
+    fn test_retry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let flaky = host_function_new("flaky", &[], ArgType::Number, move |_| {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(Error::RuntimeError("try again".to_string())),
+                n => Ok(HostValue::Number(n as f64)),
+            }
+        });
+
+        let result = kya_retry(kya_none(), &mut vec![number_new(3.0), flaky.clone()], None);
+
+        assert_eq!(number_object_to_float(&result.unwrap()).unwrap(), 2.0);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        calls.store(0, Ordering::SeqCst);
+
+        let result = kya_retry(kya_none(), &mut vec![number_new(2.0), flaky.clone()], None);
+
+        assert!(matches!(result, Err(Error::RuntimeError(message)) if message == "try again"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        calls.store(0, Ordering::SeqCst);
+
+        for backoff in [1e20, f64::INFINITY, f64::NAN, -1.0] {
+            let result = kya_retry(
+                kya_none(),
+                &mut vec![number_new(2.0), flaky.clone(), number_new(backoff)],
+                None,
+            );
+
+            assert!(matches!(result, Err(Error::ValueError(_))));
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
 }
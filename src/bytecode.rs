@@ -1,4 +1,11 @@
-use crate::{ast, objects::base::KyaObjectRef};
+use crate::diagnostics::Diagnostic;
+use crate::errors::Error;
+use crate::symbol::Symbol;
+
+use crate::ast;
+use crate::objects::base::{kya_type_name, KyaObject, KyaObjectRef};
+use crate::objects::utils::{object_to_string_repr, string_object_to_string};
+use std::sync::Arc;
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,8 +26,29 @@ pub enum Opcode {
     Return = 13,
     Raise = 14,
     BinaryOp = 15,
+    Slice = 16,
+    SetupEnsure = 17,
+    PopBlock = 18,
+    EndEnsure = 19,
+    ExitResource = 20,
+    Probe = 21,
+    LoadFast = 22,
+    StoreFast = 23,
+    LoadMethod = 24,
+    CallMethod = 25,
+    IsOp = 26,
+    Import = 27,
+    LoadSlot = 28,
+    StoreSlot = 29,
 }
 
+pub const WIDE_OPERAND_SIZE: usize = 2;
+
+// Operand bits of `Slice`, telling which bounds were pushed after the value.
+pub const SLICE_HAS_START: u8 = 1;
+pub const SLICE_HAS_END: u8 = 2;
+pub const SLICE_HAS_STEP: u8 = 4;
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ComparisonOperator {
@@ -45,6 +73,17 @@ impl ComparisonOperator {
         }
     }
 
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ComparisonOperator::Equal => "==",
+            ComparisonOperator::Gt => ">",
+            ComparisonOperator::Lt => "<",
+            ComparisonOperator::Gte => ">=",
+            ComparisonOperator::Lte => "<=",
+            ComparisonOperator::Neq => "!=",
+        }
+    }
+
     pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(ComparisonOperator::Equal),
@@ -83,6 +122,15 @@ impl Operator {
     }
 }
 
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operator::Plus => write!(f, "PLUS"),
+            Operator::Minus => write!(f, "MINUS"),
+        }
+    }
+}
+
 impl std::fmt::Display for ComparisonOperator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -97,6 +145,45 @@ impl std::fmt::Display for ComparisonOperator {
 }
 
 impl Opcode {
+    /// How many bytes of operand follow the opcode. Indices into the
+    /// constants, names and probes and every jump target or offset take two
+    /// bytes, stored little-endian; operator codes, argument counts and
+    /// flags take one. `LoadSlot` and `StoreSlot` take a name index and then
+    /// a slot index of one byte.
+    pub fn operand_size(&self) -> usize {
+        match self {
+            Opcode::LoadConst
+            | Opcode::StoreName
+            | Opcode::LoadName
+            | Opcode::LoadAttr
+            | Opcode::StoreAttr
+            | Opcode::JumpBack
+            | Opcode::PopAndJumpIfFalse
+            | Opcode::Jump
+            | Opcode::SetupEnsure
+            | Opcode::Probe
+            | Opcode::LoadFast
+            | Opcode::StoreFast
+            | Opcode::LoadMethod
+            | Opcode::Import => WIDE_OPERAND_SIZE,
+            Opcode::Call
+            | Opcode::CallMethod
+            | Opcode::Compare
+            | Opcode::BinaryOp
+            | Opcode::Raise
+            | Opcode::Slice
+            | Opcode::IsOp => 1,
+            Opcode::LoadSlot | Opcode::StoreSlot => WIDE_OPERAND_SIZE + 1,
+            Opcode::PopTop
+            | Opcode::MakeFunction
+            | Opcode::MakeClass
+            | Opcode::Return
+            | Opcode::PopBlock
+            | Opcode::EndEnsure
+            | Opcode::ExitResource => 0,
+        }
+    }
+
     pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(Opcode::LoadConst),
@@ -115,6 +202,20 @@ impl Opcode {
             13 => Some(Opcode::Return),
             14 => Some(Opcode::Raise),
             15 => Some(Opcode::BinaryOp),
+            16 => Some(Opcode::Slice),
+            17 => Some(Opcode::SetupEnsure),
+            18 => Some(Opcode::PopBlock),
+            19 => Some(Opcode::EndEnsure),
+            20 => Some(Opcode::ExitResource),
+            21 => Some(Opcode::Probe),
+            22 => Some(Opcode::LoadFast),
+            23 => Some(Opcode::StoreFast),
+            24 => Some(Opcode::LoadMethod),
+            25 => Some(Opcode::CallMethod),
+            26 => Some(Opcode::IsOp),
+            27 => Some(Opcode::Import),
+            28 => Some(Opcode::LoadSlot),
+            29 => Some(Opcode::StoreSlot),
             _ => None,
         }
     }
@@ -139,6 +240,20 @@ impl std::fmt::Display for Opcode {
             Opcode::Return => write!(f, "RETURN"),
             Opcode::Raise => write!(f, "RAISE"),
             Opcode::BinaryOp => write!(f, "BINARY_OP"),
+            Opcode::Slice => write!(f, "SLICE"),
+            Opcode::SetupEnsure => write!(f, "SETUP_ENSURE"),
+            Opcode::PopBlock => write!(f, "POP_BLOCK"),
+            Opcode::EndEnsure => write!(f, "END_ENSURE"),
+            Opcode::ExitResource => write!(f, "EXIT_RESOURCE"),
+            Opcode::Probe => write!(f, "PROBE"),
+            Opcode::LoadFast => write!(f, "LOAD_FAST"),
+            Opcode::StoreFast => write!(f, "STORE_FAST"),
+            Opcode::LoadMethod => write!(f, "LOAD_METHOD"),
+            Opcode::CallMethod => write!(f, "CALL_METHOD"),
+            Opcode::IsOp => write!(f, "IS_OP"),
+            Opcode::Import => write!(f, "IMPORT"),
+            Opcode::LoadSlot => write!(f, "LOAD_SLOT"),
+            Opcode::StoreSlot => write!(f, "STORE_SLOT"),
         }
     }
 }
@@ -146,9 +261,23 @@ impl std::fmt::Display for Opcode {
 pub struct CodeObject {
     pub code: Vec<u8>,
     pub consts: Vec<KyaObjectRef>,
-    pub names: Vec<String>,
-    pub args: Vec<String>,
+    pub names: Vec<Symbol>,
+    pub args: Vec<Symbol>,
     pub name: String,
+    // Ids of the instrumentation probes, indexed by the operand of `Probe`.
+    pub probes: Vec<usize>,
+    // For a function: the names of its local slots, indexed by the operand
+    // of `LoadFast` and `StoreFast`. The parameters come first.
+    pub varnames: Vec<Symbol>,
+    // For the body of a class: the attribute names declared with `slots(...)`,
+    // in the order of their index in each instance.
+    pub slots: Option<Vec<Symbol>>,
+    // For the body of a class: how many attributes its constructor assigns on
+    // `self`, used to size the dict of each instance up front.
+    pub instance_size: usize,
+    // The offset where each statement starts and the line it is on, by
+    // increasing offset.
+    pub lines: Vec<(usize, usize)>,
 }
 
 impl Clone for CodeObject {
@@ -159,10 +288,21 @@ impl Clone for CodeObject {
             names: self.names.clone(),
             args: self.args.clone(),
             name: self.name.clone(),
+            probes: self.probes.clone(),
+            varnames: self.varnames.clone(),
+            slots: self.slots.clone(),
+            instance_size: self.instance_size,
+            lines: self.lines.clone(),
         }
     }
 }
 
+impl Default for CodeObject {
+    fn default() -> Self {
+        CodeObject::new()
+    }
+}
+
 impl CodeObject {
     pub fn new() -> Self {
         CodeObject {
@@ -171,6 +311,11 @@ impl CodeObject {
             names: Vec::new(),
             args: Vec::new(),
             name: String::new(),
+            probes: Vec::new(),
+            varnames: Vec::new(),
+            slots: None,
+            instance_size: 0,
+            lines: Vec::new(),
         }
     }
 
@@ -178,20 +323,60 @@ impl CodeObject {
         self.code.push(opcode);
     }
 
-    pub fn add_const(&mut self, const_value: KyaObjectRef) -> u8 {
+    /// Appends a two-byte operand, failing when the value does not fit.
+    pub fn add_wide_operand(&mut self, value: usize) -> Result<(), Error> {
+        let bytes = wide_operand(value)?;
+
+        self.code.extend_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    pub fn add_const(&mut self, const_value: KyaObjectRef) -> usize {
         self.consts.push(const_value);
-        (self.consts.len() - 1) as u8
+        self.consts.len() - 1
     }
 
-    pub fn add_name(&mut self, name: String) -> u8 {
-        for (index, existing_name) in self.names.iter().enumerate() {
-            if existing_name == &name {
-                return index as u8;
-            }
+    pub fn add_name(&mut self, name: impl Into<Symbol>) -> usize {
+        let name = name.into();
+
+        if let Some(index) = self.names.iter().position(|existing| *existing == name) {
+            return index;
         }
 
         self.names.push(name);
-        (self.names.len() - 1) as u8
+        self.names.len() - 1
+    }
+
+    pub fn add_probe(&mut self, probe: usize) -> usize {
+        self.probes.push(probe);
+        self.probes.len() - 1
+    }
+
+    /// Records that the next instruction starts a statement on `line`.
+    pub fn add_line(&mut self, line: usize) {
+        let offset = self.code.len();
+
+        match self.lines.last_mut() {
+            // A statement that emitted no code yet is replaced by this one.
+            Some(last) if last.0 == offset => *last = (offset, line),
+            _ => self.lines.push((offset, line)),
+        }
+    }
+
+    /// The line of the statement the instruction at `offset` belongs to.
+    pub fn line_at(&self, offset: usize) -> Option<usize> {
+        let index = self.lines.partition_point(|(start, _)| *start <= offset);
+
+        index.checked_sub(1).map(|index| self.lines[index].1)
+    }
+
+    /// The line of the statement that starts at `offset`, if one does.
+    pub fn line_starting_at(&self, offset: usize) -> Option<usize> {
+        self.lines
+            .binary_search_by_key(&offset, |(start, _)| *start)
+            .ok()
+            .map(|index| self.lines[index].1)
     }
 
     pub fn instructions_count(&self) -> usize {
@@ -214,31 +399,83 @@ impl CodeObject {
         }
     }
 
+    pub fn wide_operand_at(&self, offset: usize) -> usize {
+        u16::from_le_bytes([self.instruction_at(offset), self.instruction_at(offset + 1)]) as usize
+    }
+
+    pub fn set_wide_operand_at(&mut self, offset: usize, value: usize) -> Result<(), Error> {
+        let [low, high] = wide_operand(value)?;
+
+        self.set_instruction_at(offset, low);
+        self.set_instruction_at(offset + 1, high);
+
+        Ok(())
+    }
+
+    /// The instructions of the code, followed by those of the functions and
+    /// class bodies among its constants, each under a header.
     pub fn dis(&self) -> String {
-        let mut disassembler = Disassembler::new(self.clone());
+        self.disassemble(None)
+    }
+
+    /// Like `dis`, with each statement preceded by its line of `source`.
+    pub fn dis_with_source(&self, source: &str) -> String {
+        self.disassemble(Some(source))
+    }
+
+    fn disassemble(&self, source: Option<&str>) -> String {
+        let mut disassembler = Disassembler::new(self.clone(), source);
         disassembler.disassemble();
+
+        for nested in self.nested_code() {
+            disassembler
+                .output
+                .push_str(&format!("\n\nDisassembly of <code {}>:\n", nested.name));
+            disassembler.output.push_str(&nested.disassemble(source));
+        }
+
         disassembler.output
     }
+
+    fn nested_code(&self) -> Vec<Arc<CodeObject>> {
+        self.consts
+            .iter()
+            .filter_map(|constant| match &*constant.lock().unwrap() {
+                KyaObject::CodeObject(code_object) => Some(code_object.code.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn wide_operand(value: usize) -> Result<[u8; 2], Error> {
+    u16::try_from(value)
+        .map(u16::to_le_bytes)
+        .map_err(|_| Error::CompilationError(Diagnostic::OperandTooLarge(value)))
 }
 
 struct Disassembler {
     output: String,
     code_object: CodeObject,
+    source_lines: Option<Vec<String>>,
 }
 
 impl Disassembler {
-    pub fn new(code_object: CodeObject) -> Self {
+    pub fn new(code_object: CodeObject, source: Option<&str>) -> Self {
         Disassembler {
             output: String::new(),
             code_object,
+            source_lines: source.map(|source| source.lines().map(str::to_string).collect()),
         }
     }
 
     pub fn disassemble(&mut self) {
-        let mut pc: u8 = 0;
+        let mut pc = 0;
 
-        while pc < self.instructions_count() as u8 {
-            let opcode = self.instruction_at(pc.into());
+        while pc < self.instructions_count() {
+            let opcode = self.instruction_at(pc);
+
+            self.write_source_line(pc);
 
             self.output.push_str(&format!("{:04}: ", pc));
 
@@ -291,12 +528,54 @@ impl Disassembler {
                 15 => {
                     pc = self.write_binary_op(pc);
                 }
+                16 => {
+                    pc = self.write_slice(pc);
+                }
+                17 => {
+                    pc = self.write_setup_ensure(pc);
+                }
+                18 => {
+                    pc = self.write_pop_block(pc);
+                }
+                19 => {
+                    pc = self.write_end_ensure(pc);
+                }
+                20 => {
+                    pc = self.write_exit_resource(pc);
+                }
+                21 => {
+                    pc = self.write_probe(pc);
+                }
+                22 => {
+                    pc = self.write_load_fast(pc);
+                }
+                23 => {
+                    pc = self.write_store_fast(pc);
+                }
+                24 => {
+                    pc = self.write_load_method(pc);
+                }
+                25 => {
+                    pc = self.write_call_method(pc);
+                }
+                26 => {
+                    pc = self.write_is_op(pc);
+                }
+                27 => {
+                    pc = self.write_import(pc);
+                }
+                28 => {
+                    pc = self.write_slot("LOAD_SLOT", pc);
+                }
+                29 => {
+                    pc = self.write_slot("STORE_SLOT", pc);
+                }
                 _ => {
                     panic!("Unknown opcode: {}", opcode);
                 }
             }
 
-            if pc < self.instructions_count() as u8 {
+            if pc < self.instructions_count() {
                 self.output.push('\n');
             }
         }
@@ -306,46 +585,168 @@ impl Disassembler {
         self.code_object.code.len()
     }
 
-    fn instruction_at(&self, offset: usize) -> u8 {
-        if offset < self.code_object.code.len() {
-            self.code_object.code[offset]
-        } else {
-            panic!("Offset out of bounds")
+    // Writes the source of the statement that starts at `pc`, set apart
+    // from the instructions of the one before.
+    fn write_source_line(&mut self, pc: usize) {
+        let Some(line) = self.code_object.line_starting_at(pc) else {
+            return;
+        };
+        let Some(text) = self
+            .source_lines
+            .as_ref()
+            .and_then(|lines| lines.get(line.wrapping_sub(1)))
+        else {
+            return;
+        };
+
+        if pc > 0 {
+            self.output.push('\n');
         }
+
+        self.output
+            .push_str(&format!("# {}: {}\n", line, text.trim()));
     }
 
-    fn write_load_const(&mut self, pc: u8) -> u8 {
-        let const_index = self.instruction_at((pc + 1).into());
+    // How a constant reads in the source: strings are quoted, and code
+    // objects are named since they are disassembled after.
+    fn const_repr(&self, index: usize) -> String {
+        let Some(constant) = self.code_object.consts.get(index) else {
+            return "?".to_string();
+        };
+
+        if let KyaObject::CodeObject(code_object) = &*constant.lock().unwrap() {
+            return format!("<code {}>", code_object.code.name);
+        }
 
-        self.output.push_str(&format!("LOAD_CONST {}", const_index));
+        if let Ok(string) = string_object_to_string(constant) {
+            return format!("{:?}", string);
+        }
 
-        pc + 2
+        object_to_string_repr(constant).unwrap_or_else(|_| format!("<{}>", kya_type_name(constant)))
     }
 
-    fn write_store_name(&mut self, pc: u8) -> u8 {
-        let name_index = self.instruction_at((pc + 1).into());
+    fn instruction_at(&self, offset: usize) -> u8 {
+        self.code_object.instruction_at(offset)
+    }
+
+    fn wide_operand_at(&self, offset: usize) -> usize {
+        self.code_object.wide_operand_at(offset)
+    }
 
-        self.output.push_str(&format!("STORE_NAME {}", name_index));
+    fn write_load_const(&mut self, pc: usize) -> usize {
+        let const_index = self.wide_operand_at(pc + 1);
 
-        pc + 2
+        self.output.push_str(&format!(
+            "LOAD_CONST {} ({})",
+            const_index,
+            self.const_repr(const_index)
+        ));
+
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    fn write_load_name(&mut self, pc: u8) -> u8 {
-        let name_index = self.instruction_at((pc + 1).into());
+    fn write_store_name(&mut self, pc: usize) -> usize {
+        let name_index = self.wide_operand_at(pc + 1);
         let name = self
             .code_object
             .names
-            .get(name_index as usize)
+            .get(name_index)
+            .expect("Name index out of bounds");
+
+        self.output
+            .push_str(&format!("STORE_NAME {} ({})", name_index, name));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_load_name(&mut self, pc: usize) -> usize {
+        let name_index = self.wide_operand_at(pc + 1);
+        let name = self
+            .code_object
+            .names
+            .get(name_index)
             .expect("Name index out of bounds");
 
         self.output
             .push_str(&format!("LOAD_NAME {} ({})", name_index, name));
 
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_load_fast(&mut self, pc: usize) -> usize {
+        let index = self.wide_operand_at(pc + 1);
+        let name = self
+            .code_object
+            .varnames
+            .get(index)
+            .expect("Local index out of bounds");
+
+        self.output
+            .push_str(&format!("LOAD_FAST {} ({})", index, name));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_store_fast(&mut self, pc: usize) -> usize {
+        let index = self.wide_operand_at(pc + 1);
+        let name = self
+            .code_object
+            .varnames
+            .get(index)
+            .expect("Local index out of bounds");
+
+        self.output
+            .push_str(&format!("STORE_FAST {} ({})", index, name));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_load_method(&mut self, pc: usize) -> usize {
+        let name_index = self.wide_operand_at(pc + 1);
+        let name = self
+            .code_object
+            .names
+            .get(name_index)
+            .expect("Attribute index out of bounds");
+
+        self.output
+            .push_str(&format!("LOAD_METHOD {} ({})", name_index, name));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_call_method(&mut self, pc: usize) -> usize {
+        let arg_count = self.instruction_at(pc + 1);
+
+        self.output.push_str(&format!("CALL_METHOD {}", arg_count));
+
         pc + 2
     }
 
-    fn write_call_function(&mut self, pc: u8) -> u8 {
-        let arg_count = self.instruction_at((pc + 1).into());
+    fn write_is_op(&mut self, pc: usize) -> usize {
+        let negated = self.instruction_at(pc + 1);
+
+        self.output.push_str(&format!("IS_OP {}", negated));
+
+        pc + 2
+    }
+
+    fn write_import(&mut self, pc: usize) -> usize {
+        let name_index = self.wide_operand_at(pc + 1);
+        let name = self
+            .code_object
+            .names
+            .get(name_index)
+            .expect("Name index out of bounds");
+
+        self.output
+            .push_str(&format!("IMPORT {} ({})", name_index, name));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_call_function(&mut self, pc: usize) -> usize {
+        let arg_count = self.instruction_at(pc + 1);
 
         self.output
             .push_str(&format!("CALL_FUNCTION {}", arg_count));
@@ -353,32 +754,32 @@ impl Disassembler {
         pc + 2
     }
 
-    fn write_pop_top(&mut self, pc: u8) -> u8 {
+    fn write_pop_top(&mut self, pc: usize) -> usize {
         self.output.push_str("POP_TOP");
         pc + 1
     }
 
-    fn write_make_function(&mut self, pc: u8) -> u8 {
+    fn write_make_function(&mut self, pc: usize) -> usize {
         self.output.push_str("MAKE_FUNCTION");
         pc + 1
     }
 
-    fn write_load_attr(&mut self, pc: u8) -> u8 {
-        let attr_index = self.instruction_at((pc + 1).into());
+    fn write_load_attr(&mut self, pc: usize) -> usize {
+        let attr_index = self.wide_operand_at(pc + 1);
         let attr_name = self
             .code_object
             .names
-            .get(attr_index as usize)
+            .get(attr_index)
             .expect("Attribute index out of bounds");
 
         self.output
             .push_str(&format!("LOAD_ATTR {} ({})", attr_index, attr_name));
 
-        pc + 2
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    fn write_compare(&mut self, pc: u8) -> u8 {
-        let op_index = self.instruction_at((pc + 1).into());
+    fn write_compare(&mut self, pc: usize) -> usize {
+        let op_index = self.instruction_at(pc + 1);
         let op = ComparisonOperator::from_u8(op_index).expect("Invalid comparison operation index");
 
         self.output.push_str(&format!("COMPARE {}", op));
@@ -386,59 +787,217 @@ impl Disassembler {
         pc + 2
     }
 
-    fn write_jump_back(&mut self, pc: u8) -> u8 {
-        let offset = self.instruction_at((pc + 1).into());
+    fn write_jump_back(&mut self, pc: usize) -> usize {
+        let offset = self.wide_operand_at(pc + 1);
         self.output.push_str(&format!("JUMP_BACK {}", offset));
-        pc + 2
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    fn write_jump_if_false(&mut self, pc: u8) -> u8 {
-        let offset = self.instruction_at((pc + 1).into());
+    fn write_jump_if_false(&mut self, pc: usize) -> usize {
+        let offset = self.wide_operand_at(pc + 1);
         self.output.push_str(&format!("JUMP_IF_FALSE {}", offset));
-        pc + 2
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    fn write_jump(&mut self, pc: u8) -> u8 {
-        let offset = self.instruction_at((pc + 1).into());
+    fn write_jump(&mut self, pc: usize) -> usize {
+        let offset = self.wide_operand_at(pc + 1);
         self.output.push_str(&format!("JUMP {}", offset));
-        pc + 2
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    pub fn write_make_class(&mut self, pc: u8) -> u8 {
+    pub fn write_make_class(&mut self, pc: usize) -> usize {
         self.output.push_str("MAKE_CLASS");
         pc + 1
     }
 
-    pub fn write_store_attr(&mut self, pc: u8) -> u8 {
-        let attr_index = self.instruction_at((pc + 1).into());
+    pub fn write_store_attr(&mut self, pc: usize) -> usize {
+        let attr_index = self.wide_operand_at(pc + 1);
         let attr_name = self
             .code_object
             .names
-            .get(attr_index as usize)
+            .get(attr_index)
             .expect("Attribute index out of bounds");
 
         self.output
             .push_str(&format!("STORE_ATTR {} ({})", attr_index, attr_name));
 
-        pc + 2
+        pc + 1 + WIDE_OPERAND_SIZE
     }
 
-    pub fn write_return(&mut self, pc: u8) -> u8 {
+    pub fn write_slot(&mut self, opname: &str, pc: usize) -> usize {
+        let attr_index = self.wide_operand_at(pc + 1);
+        let attr_name = self
+            .code_object
+            .names
+            .get(attr_index)
+            .expect("Attribute index out of bounds");
+        let slot = self.instruction_at(pc + 1 + WIDE_OPERAND_SIZE);
+
+        self.output.push_str(&format!(
+            "{} {} ({}) {}",
+            opname, attr_index, attr_name, slot
+        ));
+
+        pc + 2 + WIDE_OPERAND_SIZE
+    }
+
+    pub fn write_return(&mut self, pc: usize) -> usize {
         self.output.push_str("RETURN");
         pc + 1
     }
 
-    pub fn write_raise(&mut self, pc: u8) -> u8 {
-        self.output.push_str("RAISE");
-        pc + 1
+    pub fn write_raise(&mut self, pc: usize) -> usize {
+        let has_cause = self.instruction_at(pc + 1);
+
+        self.output.push_str(&format!("RAISE {}", has_cause));
+
+        pc + 2
     }
 
-    pub fn write_binary_op(&mut self, pc: u8) -> u8 {
-        let op_index = self.instruction_at((pc + 1).into());
-        let op = ComparisonOperator::from_u8(op_index).expect("Invalid binary operation index");
+    pub fn write_binary_op(&mut self, pc: usize) -> usize {
+        let op_index = self.instruction_at(pc + 1);
+        let op = Operator::from_u8(op_index).expect("Invalid binary operation index");
 
         self.output.push_str(&format!("BINARY_OP {}", op));
 
         pc + 2
     }
+
+    fn write_slice(&mut self, pc: usize) -> usize {
+        let flags = self.instruction_at(pc + 1);
+
+        self.output.push_str(&format!("SLICE {}", flags));
+
+        pc + 2
+    }
+
+    fn write_setup_ensure(&mut self, pc: usize) -> usize {
+        let handler = self.wide_operand_at(pc + 1);
+
+        self.output.push_str(&format!("SETUP_ENSURE {}", handler));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+
+    fn write_pop_block(&mut self, pc: usize) -> usize {
+        self.output.push_str("POP_BLOCK");
+        pc + 1
+    }
+
+    fn write_end_ensure(&mut self, pc: usize) -> usize {
+        self.output.push_str("END_ENSURE");
+        pc + 1
+    }
+
+    fn write_exit_resource(&mut self, pc: usize) -> usize {
+        self.output.push_str("EXIT_RESOURCE");
+        pc + 1
+    }
+
+    fn write_probe(&mut self, pc: usize) -> usize {
+        let probe_index = self.wide_operand_at(pc + 1);
+        let probe = self
+            .code_object
+            .probes
+            .get(probe_index)
+            .expect("Probe index out of bounds");
+
+        self.output
+            .push_str(&format!("PROBE {} ({})", probe_index, probe));
+
+        pc + 1 + WIDE_OPERAND_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::instrument::StatementProbes;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn compile(source: &str) -> CodeObject {
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.add_pass(StatementProbes::new());
+        compiler.compile().unwrap();
+
+        compiler.get_output()
+    }
+
+    // The opcodes in the code and the code nested in it.
+    fn opcodes(code: &CodeObject, found: &mut Vec<Opcode>) {
+        let mut pc = 0;
+
+        while pc < code.code.len() {
+            let opcode = Opcode::from_u8(code.code[pc]).unwrap();
+
+            if !found.contains(&opcode) {
+                found.push(opcode);
+            }
+
+            pc += 1 + opcode.operand_size();
+        }
+
+        for nested in code.nested_code() {
+            opcodes(&nested, found);
+        }
+    }
+
+    #[test]
+    fn test_dis_every_opcode() {
+        let code = compile(
+            "import json\n\
+             class Box\n  def constructor(value)\n    self.value = value + 1 - 1\n  end\n\n  \
+             def get\n    return self.value\n  end\nend\n\
+             class Pair\n  slots('left', 'right')\n  def swap\n    self.right = self.left\n  end\nend\n\
+             def check(box)\n  empty = box.value is None\n  if empty\n    raise Exception('empty')\n  end\n\n  \
+             while box.get() > 0\n    break\n  end\nend\n\
+             try\n  with Box(1) as box\n    check(box)\n  end\nensure\n  print('abc'[0..1])\nend\n",
+        );
+
+        let mut found = vec![];
+        opcodes(&code, &mut found);
+
+        let missing: Vec<Opcode> = (0..=u8::MAX)
+            .map_while(Opcode::from_u8)
+            .filter(|opcode| !found.contains(opcode))
+            .collect();
+        assert_eq!(missing, vec![]);
+
+        let output = code.dis();
+        assert!(output.contains("IMPORT 0 (json)"));
+        assert!(output.contains("BINARY_OP PLUS"));
+        assert!(output.contains("BINARY_OP MINUS"));
+        assert!(output.contains("(left) 0"));
+        assert!(output.contains("(right) 1"));
+    }
+
+    #[test]
+    fn test_dis_with_source() {
+        let source = "x = 'a'\n\nprint(x)\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        assert_eq!(
+            compiler.get_output().dis_with_source(source),
+            "# 1: x = 'a'\n0000: LOAD_CONST 0 (\"a\")\n0003: STORE_NAME 0 (x)\n\
+             0006: LOAD_NAME 0 (x)\n0009: POP_TOP\n\n\
+             # 3: print(x)\n0010: LOAD_NAME 1 (print)\n0013: LOAD_NAME 0 (x)\n\
+             0016: CALL_FUNCTION 1\n0018: POP_TOP"
+        );
+    }
+
+    #[test]
+    fn test_dis_nested_code() {
+        let output = compile("class Counter\n  def increment\n    return 1\n  end\nend\n").dis();
+
+        let counter = output.find("Disassembly of <code Counter>:").unwrap();
+        let increment = output.find("Disassembly of <code increment>:").unwrap();
+
+        assert!(counter < increment);
+        assert!(output[increment..].contains("RETURN"));
+    }
 }
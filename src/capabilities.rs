@@ -0,0 +1,115 @@
+//! What native functions may do on behalf of a program. Every capability is
+//! granted by default; a sandboxed interpreter runs without them, and the
+//! functions that need one raise a `PermissionError` instead. Imports of the
+//! program's own modules are not restricted.
+
+use crate::errors::Error;
+use crate::objects::exception_object::PERMISSION_ERROR_TYPE;
+use crate::runtime;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capability {
+    /// Sockets of every kind and the HTTP server.
+    Network,
+    /// Looking at the files on disk and writing logs to them.
+    Filesystem,
+    /// Starting other programs. No builtin does, but native modules should
+    /// check it.
+    Process,
+    /// Loading native extensions, which run outside of the sandbox.
+    Extensions,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::Network,
+        Capability::Filesystem,
+        Capability::Process,
+        Capability::Extensions,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "network" => Some(Capability::Network),
+            "filesystem" => Some(Capability::Filesystem),
+            "process" => Some(Capability::Process),
+            "extensions" => Some(Capability::Extensions),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Network => "network",
+            Capability::Filesystem => "filesystem",
+            Capability::Process => "process",
+            Capability::Extensions => "extensions",
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Capabilities::none().with(&Capability::ALL)
+    }
+
+    pub fn none() -> Self {
+        Capabilities(0)
+    }
+
+    pub fn with(self, capabilities: &[Capability]) -> Self {
+        Capabilities(
+            capabilities
+                .iter()
+                .fold(self.0, |bits, capability| bits | capability.bit()),
+        )
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.0 & capability.bit() != 0
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::all()
+    }
+}
+
+/// Raises a PermissionError unless the current runtime has `capability`.
+/// `what` names the operation, as in "tcp()".
+pub fn require(capability: Capability, what: &str) -> Result<(), Error> {
+    if runtime::current().capabilities().allows(capability) {
+        return Ok(());
+    }
+
+    Err(Error::exception(
+        PERMISSION_ERROR_TYPE.lock().unwrap().name.clone(),
+        format!(
+            "{} needs the {} capability, which this interpreter does not have",
+            what,
+            capability.name()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let sandbox = Capabilities::none().with(&[Capability::Filesystem]);
+
+        assert!(sandbox.allows(Capability::Filesystem));
+        assert!(!sandbox.allows(Capability::Network));
+        assert!(Capabilities::default().allows(Capability::Process));
+    }
+}
@@ -1,11 +1,23 @@
-use crate::bytecode::{CodeObject, ComparisonOperator, Opcode, Operator};
+use crate::ast;
+use crate::bytecode::{
+    CodeObject, ComparisonOperator, Opcode, Operator, SLICE_HAS_END, SLICE_HAS_START,
+    SLICE_HAS_STEP, WIDE_OPERAND_SIZE,
+};
+use crate::diagnostics::Diagnostic;
 use crate::errors::Error;
+use crate::lexer::TokenType;
+use crate::objects::base::KyaObjectRef;
 use crate::objects::code_object::code_object_new;
 use crate::objects::function_object::function_new;
 use crate::objects::number_object::number_new;
 use crate::objects::string_object::string_new;
-use crate::{ast, visitor::CompilerVisitor};
+use crate::optimizer;
+use crate::scoping;
+use crate::symbol::Symbol;
+use crate::visitor::{CompilerPass, CompilerVisitor};
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,10 +31,32 @@ pub struct Scope {
     jumps: Vec<usize>,
 }
 
+// What runs when a protected block is left: the ensure body of a `try`, or
+// the exit of the resource bound to a name by a `with`.
+#[derive(Clone)]
+enum Cleanup {
+    Ensure(Box<ast::ASTNode>),
+    Exit(String),
+}
+
+// An enclosing `try` or `with`, whose cleanup has to be emitted again on
+// every `return` and on any `break` that leaves the loop it was opened in.
+struct EnsureScope {
+    cleanup: Cleanup,
+    loop_depth: usize,
+}
+
 pub struct Compiler {
     ast: Arc<ast::ASTNode>,
     code: CodeObject,
     scopes: Vec<Scope>,
+    ensures: Vec<EnsureScope>,
+    resources: usize,
+    passes: Rc<RefCell<Vec<Box<dyn CompilerPass>>>>,
+    keep_result: bool,
+    // The slots of the class whose body or methods are compiled, so that
+    // `self.name` is read and written by its index among them.
+    slots: Option<Vec<Symbol>>,
 }
 
 impl Compiler {
@@ -31,11 +65,83 @@ impl Compiler {
             ast,
             code: CodeObject::new(),
             scopes: vec![],
+            ensures: vec![],
+            resources: 0,
+            passes: Rc::new(RefCell::new(vec![])),
+            keep_result: false,
+            slots: None,
         }
     }
 
+    // Functions and classes are compiled by a child compiler that runs the
+    // same passes.
+    fn child(&self, ast: Arc<ast::ASTNode>) -> Self {
+        Compiler {
+            passes: self.passes.clone(),
+            slots: self.slots.clone(),
+            ..Compiler::new(ast)
+        }
+    }
+
+    /// Leaves the value of the module's last statement on the stack when it
+    /// is an expression other than an assignment, so that `Interpreter::eval`
+    /// returns it.
+    pub fn keep_result(&mut self) {
+        self.keep_result = true;
+    }
+
+    fn compile_statements(&mut self, block: &ast::Block, keep_last: bool) -> Result<(), Error> {
+        for (index, statement) in block.statements.iter().enumerate() {
+            if let Some(line) = block.line(index) {
+                self.code.add_line(line);
+            }
+
+            for pass in self.passes.borrow_mut().iter_mut() {
+                pass.before_statement(statement, &mut self.code)?;
+            }
+
+            if let ast::ASTNode::Break() = &**statement {
+                statement.compile(self)?;
+
+                return Ok(());
+            }
+
+            statement.compile(self)?;
+
+            // An assignment is a statement here, so its value is not kept.
+            let is_result = keep_last
+                && index + 1 == block.statements.len()
+                && !matches!(&**statement, ast::ASTNode::Assignment(_));
+
+            if statement.is_expression() && !is_result {
+                self.code.add_instruction(Opcode::PopTop as u8);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_pass(&mut self, pass: impl CompilerPass + 'static) {
+        self.passes.borrow_mut().push(Box::new(pass));
+    }
+
     pub fn compile(&mut self) -> Result<(), Error> {
+        let mut ast = (*self.ast).clone();
+
+        for pass in self.passes.borrow_mut().iter_mut() {
+            ast = pass.transform_ast(ast)?;
+        }
+
+        self.ast = Arc::new(ast);
         self.ast.clone().compile(self)?;
+        self.finish()
+    }
+
+    // Runs the bytecode passes once every jump has been patched.
+    fn finish(&mut self) -> Result<(), Error> {
+        for pass in self.passes.borrow_mut().iter_mut() {
+            pass.transform_code(&mut self.code)?;
+        }
 
         Ok(())
     }
@@ -51,13 +157,15 @@ impl Compiler {
         });
     }
 
-    fn exit_scope(&mut self) {
+    fn exit_scope(&mut self) -> Result<(), Error> {
         if let Some(scope) = self.scopes.pop() {
             for jump in scope.jumps {
                 self.code
-                    .set_instruction_at(jump, self.code.instructions_count() as u8);
+                    .set_wide_operand_at(jump, self.code.instructions_count())?;
             }
         }
+
+        Ok(())
     }
 
     fn current_scope(&mut self) -> &mut Scope {
@@ -68,135 +176,374 @@ impl Compiler {
         self.current_scope().jumps.push(jump);
     }
 
-    fn backpatch(&mut self, target: usize) {
-        for jump in self.current_scope().jumps.clone() {
-            self.code.set_instruction_at(jump, target as u8);
+    fn loop_depth(&self) -> usize {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.scope_type == ScopeType::While)
+            .count()
+    }
+
+    // Emits the cleanups of the innermost `count` enclosing protected blocks,
+    // popping each handler first so a failure inside the cleanup does not run
+    // it twice. Each one is compiled with only its outer blocks active.
+    fn emit_ensures(&mut self, count: usize) -> Result<(), Error> {
+        let inner = self.ensures.len() - count;
+
+        for index in (inner..self.ensures.len()).rev() {
+            let saved = self.ensures.split_off(index);
+
+            self.code.add_instruction(Opcode::PopBlock as u8);
+            let result = self.compile_cleanup(&saved[0].cleanup);
+
+            self.ensures.extend(saved);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn compile_cleanup(&mut self, cleanup: &Cleanup) -> Result<(), Error> {
+        match cleanup {
+            Cleanup::Ensure(body) => body.compile(self),
+            Cleanup::Exit(name) => {
+                self.load_variable(name.clone())?;
+                self.code.add_instruction(Opcode::ExitResource as u8);
+
+                Ok(())
+            }
         }
     }
 
-    fn store_variable(&mut self, name: String) {
+    fn compile_protected(&mut self, body: &ast::ASTNode, cleanup: Cleanup) -> Result<(), Error> {
+        self.code.add_instruction(Opcode::SetupEnsure as u8);
+
+        let handler_index = self.code.instructions_count();
+
+        self.code.add_wide_operand(0)?;
+
+        self.ensures.push(EnsureScope {
+            cleanup: cleanup.clone(),
+            loop_depth: self.loop_depth(),
+        });
+
+        let result = body.compile(self);
+
+        self.ensures.pop();
+        result?;
+
+        // Fallthrough: drop the handler, run the cleanup and skip over the
+        // copy that the VM jumps to when the body raises.
+        self.code.add_instruction(Opcode::PopBlock as u8);
+        self.compile_cleanup(&cleanup)?;
+
+        self.code.add_instruction(Opcode::Jump as u8);
+
+        let end_index = self.code.instructions_count();
+
+        self.code.add_wide_operand(0)?;
+        self.code
+            .set_wide_operand_at(handler_index, self.code.instructions_count())?;
+
+        self.compile_cleanup(&cleanup)?;
+        self.code.add_instruction(Opcode::EndEnsure as u8);
+
+        self.code
+            .set_wide_operand_at(end_index, self.code.instructions_count())?;
+
+        Ok(())
+    }
+
+    // The slot of `name` when it is a local of the function being compiled.
+    fn local_index(&self, name: Symbol) -> Option<usize> {
+        self.code.varnames.iter().position(|local| *local == name)
+    }
+
+    fn store_variable(&mut self, name: impl Into<Symbol>) -> Result<(), Error> {
+        let name = name.into();
+
+        if let Some(index) = self.local_index(name) {
+            self.code.add_instruction(Opcode::StoreFast as u8);
+            return self.code.add_wide_operand(index);
+        }
+
         let index = self.code.add_name(name);
 
         self.code.add_instruction(Opcode::StoreName as u8);
-        self.code.add_instruction(index);
+        self.code.add_wide_operand(index)
     }
 
-    fn load_variable(&mut self, name: String) {
+    fn load_variable(&mut self, name: impl Into<Symbol>) -> Result<(), Error> {
+        let name = name.into();
+
+        if let Some(index) = self.local_index(name) {
+            self.code.add_instruction(Opcode::LoadFast as u8);
+            return self.code.add_wide_operand(index);
+        }
+
         let index = self.code.add_name(name);
 
         self.code.add_instruction(Opcode::LoadName as u8);
-        self.code.add_instruction(index);
+        self.code.add_wide_operand(index)
     }
 
-    fn load_attr(&mut self, value: &str) {
+    fn load_const(&mut self, object: KyaObjectRef) -> Result<(), Error> {
+        let index = self.code.add_const(object);
+
+        self.code.add_instruction(Opcode::LoadConst as u8);
+        self.code.add_wide_operand(index)
+    }
+
+    fn load_attr(&mut self, value: &str) -> Result<(), Error> {
         self.code.add_instruction(Opcode::LoadAttr as u8);
         let index = self.code.add_name(value.to_string());
-        self.code.add_instruction(index);
+        self.code.add_wide_operand(index)
     }
 
-    fn store_attr(&mut self, value: &str) {
+    fn store_attr(&mut self, value: &str) -> Result<(), Error> {
         self.code.add_instruction(Opcode::StoreAttr as u8);
         let index = self.code.add_name(value.to_string());
-        self.code.add_instruction(index);
+        self.code.add_wide_operand(index)
+    }
+
+    // The index of `self.name` among the slots of the class, when it fits
+    // in the operand of `LoadSlot` and `StoreSlot`.
+    fn slot_index(&self, attribute: &ast::Attribute) -> Option<u8> {
+        let ast::ASTNode::Identifier(receiver) = &*attribute.name else {
+            return None;
+        };
+
+        if receiver.name != Symbol::SELF {
+            return None;
+        }
+
+        let name = Symbol::from(attribute.value.as_str());
+        let index = self.slots.as_ref()?.iter().position(|slot| *slot == name)?;
+
+        u8::try_from(index).ok()
+    }
+
+    fn slot_access(&mut self, opcode: Opcode, value: &str, slot: u8) -> Result<(), Error> {
+        self.code.add_instruction(opcode as u8);
+        let index = self.code.add_name(value.to_string());
+        self.code.add_wide_operand(index)?;
+        self.code.add_instruction(slot);
+
+        Ok(())
+    }
+}
+
+// Takes the `slots("x", "y")` declaration out of a class body, returning the
+// declared names and the rest of the body.
+fn class_slots(class_def: &ast::ClassDef) -> Result<(Option<Vec<Symbol>>, ast::ASTNode), Error> {
+    let ast::ASTNode::Block(block) = &*class_def.body else {
+        return Ok((None, *class_def.body.clone()));
+    };
+
+    let mut slots: Option<Vec<Symbol>> = None;
+    let mut body = ast::Block::new(vec![]);
+
+    for (index, statement) in block.statements.iter().enumerate() {
+        let line = block.lines.get(index).copied().unwrap_or(0);
+
+        let ast::ASTNode::MethodCall(call) = &**statement else {
+            body.statements.push(statement.clone());
+            body.lines.push(line);
+            continue;
+        };
+
+        if !matches!(&*call.name, ast::ASTNode::Identifier(name) if name.name == "slots") {
+            body.statements.push(statement.clone());
+            body.lines.push(line);
+            continue;
+        }
+
+        if slots.is_some() {
+            return Err(Error::CompilationError(Diagnostic::SlotsDeclaredTwice(
+                class_def.name.clone(),
+            )));
+        }
+
+        let mut names: Vec<Symbol> = vec![];
+
+        for argument in &call.arguments {
+            let ast::ASTNode::StringLiteral(name) = &**argument else {
+                return Err(Error::CompilationError(Diagnostic::SlotsNotStrings(
+                    class_def.name.clone(),
+                )));
+            };
+
+            let name = Symbol::intern(name);
+
+            if names.contains(&name) {
+                return Err(Error::CompilationError(Diagnostic::DuplicateSlot(
+                    class_def.name.clone(),
+                    name.to_string(),
+                )));
+            }
+
+            names.push(name);
+        }
+
+        slots = Some(names);
+    }
+
+    Ok((slots, ast::ASTNode::Block(body)))
+}
+
+// Counts the distinct attributes the class constructor assigns on `self`.
+fn constructor_fields(body: &ast::ASTNode) -> usize {
+    let ast::ASTNode::Block(block) = body else {
+        return 0;
+    };
+
+    let constructor = block
+        .statements
+        .iter()
+        .find_map(|statement| match &**statement {
+            ast::ASTNode::MethodDef(method_def) if method_def.name == "constructor" => {
+                Some(method_def)
+            }
+            _ => None,
+        });
+
+    let Some(ast::ASTNode::Block(constructor_body)) = constructor.map(|c| &*c.body) else {
+        return 0;
+    };
+
+    let mut fields: Vec<&str> = vec![];
+
+    for statement in &constructor_body.statements {
+        let ast::ASTNode::Assignment(assignment) = &**statement else {
+            continue;
+        };
+
+        let ast::ASTNode::Attribute(attribute) = &*assignment.name else {
+            continue;
+        };
+
+        if matches!(&*attribute.name, ast::ASTNode::Identifier(name) if name.name == Symbol::SELF)
+            && !fields.contains(&attribute.value.as_str())
+        {
+            fields.push(&attribute.value);
+        }
     }
+
+    fields.len()
 }
 
 impl CompilerVisitor for Compiler {
-    fn compile_module(&mut self, module: &ast::Module) -> Result<(), Error> {
-        module.block.compile(self)?;
+    fn before_node(&mut self, node: &ast::ASTNode) -> Result<(), Error> {
+        for pass in self.passes.borrow_mut().iter_mut() {
+            pass.before_node(node, &mut self.code)?;
+        }
 
         Ok(())
     }
 
-    fn compile_identifier(&mut self, identifier: &ast::Identifier) -> Result<(), Error> {
-        self.load_variable(identifier.name.clone());
+    fn after_node(&mut self, node: &ast::ASTNode) -> Result<(), Error> {
+        for pass in self.passes.borrow_mut().iter_mut() {
+            pass.after_node(node, &mut self.code)?;
+        }
 
         Ok(())
     }
 
+    fn compile_module(&mut self, module: &ast::Module) -> Result<(), Error> {
+        match &*module.block {
+            ast::ASTNode::Block(block) if self.keep_result => self.compile_statements(block, true),
+            block => block.compile(self),
+        }
+    }
+
+    fn compile_identifier(&mut self, identifier: &ast::Identifier) -> Result<(), Error> {
+        self.load_variable(identifier.name)
+    }
+
     fn compile_method_call(&mut self, method_call: &ast::MethodCall) -> Result<(), Error> {
-        method_call.name.compile(self)?;
+        // `obj.name(...)` looks the method up with the receiver kept on the
+        // stack instead of creating a bound method for the call.
+        let call = match &*method_call.name {
+            ast::ASTNode::Attribute(attribute) => {
+                attribute.name.compile(self)?;
+                self.code.add_instruction(Opcode::LoadMethod as u8);
+                let index = self.code.add_name(attribute.value.clone());
+                self.code.add_wide_operand(index)?;
+
+                Opcode::CallMethod
+            }
+            name => {
+                name.compile(self)?;
+
+                Opcode::Call
+            }
+        };
 
         for arg in &method_call.arguments {
             arg.compile(self)?;
         }
 
-        let arg_count = method_call.arguments.len() as u8;
+        let arg_count = u8::try_from(method_call.arguments.len()).map_err(|_| {
+            Error::CompilationError(Diagnostic::TooManyArguments(method_call.arguments.len()))
+        })?;
 
-        self.code.add_instruction(Opcode::Call as u8);
+        self.code.add_instruction(call as u8);
         self.code.add_instruction(arg_count);
 
         Ok(())
     }
 
     fn compile_string_literal(&mut self, string_literal: &str) -> Result<(), Error> {
-        let object = string_new(string_literal);
-
-        let index = self.code.add_const(object);
-
-        self.code.add_instruction(Opcode::LoadConst as u8);
-        self.code.add_instruction(index);
-
-        Ok(())
+        self.load_const(string_new(string_literal))
     }
 
     fn compile_assignment(&mut self, assignment: &ast::Assignment) -> Result<(), Error> {
         assignment.value.compile(self)?;
 
         if let ast::ASTNode::Identifier(identifier) = &*assignment.name {
-            self.store_variable(identifier.name.clone());
-            self.load_variable(identifier.name.clone());
+            self.store_variable(identifier.name)?;
+            self.load_variable(identifier.name)?;
         } else if let ast::ASTNode::Attribute(attribute) = &*assignment.name {
             attribute.name.compile(self)?;
-            self.store_attr(&attribute.value);
+
+            match self.slot_index(attribute) {
+                Some(slot) => self.slot_access(Opcode::StoreSlot, &attribute.value, slot)?,
+                None => self.store_attr(&attribute.value)?,
+            }
         } else {
-            return Err(Error::CompilationError(
-                "Assignment name must be an identifier".to_string(),
-            ));
+            return Err(Error::CompilationError(Diagnostic::InvalidAssignmentTarget));
         }
 
         Ok(())
     }
 
     fn compile_number_literal(&mut self, number_literal: &f64) -> Result<(), Error> {
-        let object = number_new(*number_literal);
-
-        let index = self.code.add_const(object);
-
-        self.code.add_instruction(Opcode::LoadConst as u8);
-        self.code.add_instruction(index);
-
-        Ok(())
+        self.load_const(number_new(*number_literal))
     }
 
     fn compile_method_def(&mut self, method_def: &ast::MethodDef) -> Result<(), Error> {
-        let mut compiler = Compiler::new(Arc::new(*method_def.body.clone()));
+        let mut compiler = self.child(Arc::new(*method_def.body.clone()));
 
+        compiler.code.varnames = scoping::function_locals(method_def);
         compiler.enter_scope(ScopeType::Function);
 
-        let _ = compiler.compile()?;
+        method_def.body.compile(&mut compiler)?;
 
-        compiler.exit_scope();
+        compiler.exit_scope()?;
+        compiler.finish()?;
 
         let mut code = compiler.get_output();
 
         for param in &method_def.parameters {
             if let ast::ASTNode::Identifier(identifier) = &**param {
-                code.args.push(identifier.name.clone());
+                code.args.push(identifier.name);
             } else {
-                return Err(Error::CompilationError(
-                    "Method parameters must be identifiers".to_string(),
-                ));
+                return Err(Error::CompilationError(Diagnostic::InvalidParameter));
             }
         }
 
         code.name = method_def.name.clone();
 
-        let code_object = code_object_new(Arc::new(code));
-
-        let index = self.code.add_const(code_object);
-        self.code.add_instruction(Opcode::LoadConst as u8);
-        self.code.add_instruction(index);
+        self.load_const(code_object_new(Arc::new(code)))?;
 
         self.code.add_instruction(Opcode::MakeFunction as u8);
 
@@ -204,17 +551,18 @@ impl CompilerVisitor for Compiler {
     }
 
     fn compile_class_def(&mut self, class_def: &ast::ClassDef) -> Result<(), Error> {
-        let mut compiler = Compiler::new(Arc::new(*class_def.body.clone()));
-        let _ = compiler.compile()?;
+        let (slots, body) = class_slots(class_def)?;
+        let mut compiler = self.child(Arc::new(body.clone()));
+        compiler.slots = slots.clone();
+        body.compile(&mut compiler)?;
+        compiler.finish()?;
         let mut code = compiler.get_output();
 
         code.name = class_def.name.clone();
+        code.instance_size = constructor_fields(&body);
+        code.slots = slots;
 
-        let code_object = code_object_new(Arc::new(code));
-
-        let index = self.code.add_const(code_object);
-        self.code.add_instruction(Opcode::LoadConst as u8);
-        self.code.add_instruction(index);
+        self.load_const(code_object_new(Arc::new(code)))?;
 
         self.code.add_instruction(Opcode::MakeClass as u8);
 
@@ -223,21 +571,57 @@ impl CompilerVisitor for Compiler {
 
     fn compile_attribute(&mut self, attribute: &ast::Attribute) -> Result<(), Error> {
         attribute.name.compile(self)?;
-        self.load_attr(&attribute.value);
+
+        match self.slot_index(attribute) {
+            Some(slot) => self.slot_access(Opcode::LoadSlot, &attribute.value, slot),
+            None => self.load_attr(&attribute.value),
+        }
+    }
+
+    fn compile_slice(&mut self, slice: &ast::Slice) -> Result<(), Error> {
+        slice.value.compile(self)?;
+
+        let mut flags = 0;
+
+        for (flag, bound) in [
+            (SLICE_HAS_START, &slice.start),
+            (SLICE_HAS_END, &slice.end),
+            (SLICE_HAS_STEP, &slice.step),
+        ] {
+            if let Some(bound) = bound {
+                bound.compile(self)?;
+                flags |= flag;
+            }
+        }
+
+        self.code.add_instruction(Opcode::Slice as u8);
+        self.code.add_instruction(flags);
 
         Ok(())
     }
 
     fn compile_compare(&mut self, compare: &ast::Compare) -> Result<(), Error> {
+        if let Some(constant) = optimizer::evaluate_compare(compare) {
+            return self.load_const(constant.to_object());
+        }
+
         compare.left.compile(self)?;
         compare.right.compile(self)?;
 
+        if let ast::Operator::Is | ast::Operator::IsNot = compare.operator {
+            self.code.add_instruction(Opcode::IsOp as u8);
+            self.code
+                .add_instruction((compare.operator == ast::Operator::IsNot) as u8);
+
+            return Ok(());
+        }
+
         let operator =
             if let Some(op) = ComparisonOperator::from_ast_operator(compare.operator.clone()) {
                 op
             } else {
                 return Err(Error::CompilationError(
-                    "Comparison operator is missing".to_string(),
+                    Diagnostic::MissingComparisonOperator,
                 ));
             };
 
@@ -251,31 +635,40 @@ impl CompilerVisitor for Compiler {
         if_node.test.compile(self)?;
 
         self.code.add_instruction(Opcode::PopAndJumpIfFalse as u8);
-        self.code.add_instruction(0);
 
-        let jump_index = self.code.instructions_count() as u8 - 1;
+        let jump_index = self.code.instructions_count();
+
+        self.code.add_wide_operand(0)?;
 
         if_node.body.compile(self)?;
 
         self.code
-            .set_instruction_at(jump_index as usize, self.code.instructions_count() as u8);
-
-        Ok(())
+            .set_wide_operand_at(jump_index, self.code.instructions_count())
     }
 
     fn compile_import(&mut self, import: &ast::Import) -> Result<(), Error> {
-        Ok(())
+        let index = self.code.add_name(import.name.as_str());
+
+        self.code.add_instruction(Opcode::Import as u8);
+        self.code.add_wide_operand(index)?;
+
+        // A dotted import binds its first name.
+        let name = import.name.split('.').next().unwrap_or_default();
+
+        self.store_variable(name)
     }
 
     fn compile_bin_op(&mut self, bin_op: &ast::BinOp) -> Result<(), Error> {
+        if let Some(constant) = optimizer::evaluate_bin_op(bin_op) {
+            return self.load_const(constant.to_object());
+        }
+
         bin_op.left.compile(self)?;
         bin_op.right.compile(self)?;
         let operator = if let Some(op) = Operator::from_ast_operator(bin_op.operator.clone()) {
             op
         } else {
-            return Err(Error::CompilationError(
-                "Binary operator is missing".to_string(),
-            ));
+            return Err(Error::CompilationError(Diagnostic::MissingBinaryOperator));
         };
         self.code.add_instruction(Opcode::BinaryOp as u8);
         self.code.add_instruction(operator as u8);
@@ -284,68 +677,79 @@ impl CompilerVisitor for Compiler {
     }
 
     fn compile_unary_op(&mut self, unary_op: &ast::UnaryOp) -> Result<(), Error> {
-        Ok(())
+        if let Some(constant) = optimizer::evaluate_unary_op(unary_op) {
+            return self.load_const(constant.to_object());
+        }
+
+        match unary_op.operator {
+            TokenType::Plus => unary_op.operand.compile(self),
+            TokenType::Minus => {
+                self.compile_number_literal(&0.0)?;
+                unary_op.operand.compile(self)?;
+
+                self.code.add_instruction(Opcode::BinaryOp as u8);
+                self.code.add_instruction(Operator::Minus as u8);
+
+                Ok(())
+            }
+            _ => Err(Error::CompilationError(
+                Diagnostic::UnsupportedUnaryOperator(format!("{:?}", unary_op.operator)),
+            )),
+        }
     }
 
     fn compile_while(&mut self, while_node: &ast::While) -> Result<(), Error> {
         self.enter_scope(ScopeType::While);
 
-        let condition_target = self.code.instructions_count() as u8;
-
-        while_node.condition.compile(self)?;
+        let condition_target = self.code.instructions_count();
 
-        self.code.add_instruction(Opcode::PopAndJumpIfFalse as u8);
+        // A loop on a non-zero literal, which is what `ConstantBranches` turns
+        // `while true` into, needs no test before each iteration.
+        let unconditional =
+            matches!(&*while_node.condition, ast::ASTNode::NumberLiteral(value) if *value != 0.0);
 
-        let jump_target = self.code.instructions_count() as u8;
+        if !unconditional {
+            while_node.condition.compile(self)?;
 
-        self.code.add_instruction(0);
-        self.push_jump(jump_target as usize);
+            self.code.add_instruction(Opcode::PopAndJumpIfFalse as u8);
+            self.push_jump(self.code.instructions_count());
+            self.code.add_wide_operand(0)?;
+        }
 
         while_node.body.compile(self)?;
 
-        let end_target = self.code.instructions_count() as u8;
-        let jump_offset = end_target - condition_target + 2;
+        // The offset is taken from just after the operand of `JumpBack`.
+        let end_target = self.code.instructions_count();
+        let jump_offset = end_target + 1 + WIDE_OPERAND_SIZE - condition_target;
 
         self.code.add_instruction(Opcode::JumpBack as u8);
-        self.code.add_instruction(jump_offset);
-
-        self.backpatch(self.code.instructions_count() - 1);
+        self.code.add_wide_operand(jump_offset)?;
 
-        self.exit_scope();
-
-        Ok(())
+        self.exit_scope()
     }
 
     fn compile_break(&mut self) -> Result<(), Error> {
-        // if self.scopes.is_empty() || self.current_scope().scope_type != ScopeType::While {
-        //     return Err(Error::SyntaxError(
-        //         "Break statement outside of loop".to_string(),
-        //     ));
-        // }
+        if self.scopes.last().map(|scope| &scope.scope_type) != Some(&ScopeType::While) {
+            return Err(Error::CompilationError(Diagnostic::BreakOutsideLoop));
+        }
 
-        self.code.add_instruction(Opcode::Jump as u8);
-        self.code.add_instruction(0);
-        self.push_jump(self.code.instructions_count() - 1);
+        let loop_depth = self.loop_depth();
+        let count = self
+            .ensures
+            .iter()
+            .rev()
+            .take_while(|ensure| ensure.loop_depth >= loop_depth && loop_depth > 0)
+            .count();
 
-        Ok(())
+        self.emit_ensures(count)?;
+
+        self.code.add_instruction(Opcode::Jump as u8);
+        self.push_jump(self.code.instructions_count());
+        self.code.add_wide_operand(0)
     }
 
     fn compile_block(&mut self, block: &ast::Block) -> Result<(), Error> {
-        for statement in &block.statements {
-            if let ast::ASTNode::Break() = &**statement {
-                statement.compile(self)?;
-
-                return Ok(());
-            }
-
-            statement.compile(self)?;
-
-            if statement.is_expression() {
-                self.code.add_instruction(Opcode::PopTop as u8);
-            }
-        }
-
-        Ok(())
+        self.compile_statements(block, false)
     }
 
     fn compile_return(&mut self, return_node: &ast::Return) -> Result<(), Error> {
@@ -358,9 +762,11 @@ impl CompilerVisitor for Compiler {
         if let Some(value) = &return_node.value {
             value.compile(self)?;
         } else {
-            self.load_variable("None".to_string());
+            self.load_variable("None".to_string())?;
         }
 
+        self.emit_ensures(self.ensures.len())?;
+
         self.code.add_instruction(Opcode::Return as u8);
 
         Ok(())
@@ -370,18 +776,44 @@ impl CompilerVisitor for Compiler {
         if let Some(message) = &raise.message {
             message.compile(self)?;
         } else {
-            self.load_variable("None".to_string());
+            self.load_variable("None".to_string())?;
+        }
+
+        if let Some(cause) = &raise.cause {
+            cause.compile(self)?;
         }
 
         self.code.add_instruction(Opcode::Raise as u8);
+        self.code.add_instruction(raise.cause.is_some() as u8);
 
         Ok(())
     }
+
+    fn compile_try(&mut self, try_node: &ast::Try) -> Result<(), Error> {
+        self.compile_protected(&try_node.body, Cleanup::Ensure(try_node.ensure.clone()))
+    }
+
+    fn compile_with(&mut self, with: &ast::With) -> Result<(), Error> {
+        // Resources bound without `as` still need a name to be exited through,
+        // so they get one that no identifier can clash with.
+        let name = with.name.clone().unwrap_or_else(|| {
+            self.resources += 1;
+            format!("<resource {}>", self.resources)
+        });
+
+        with.value.compile(self)?;
+        self.store_variable(name.clone())?;
+
+        self.compile_protected(&with.body, Cleanup::Exit(name))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
     use crate::{
         ast::{ASTNode, Module},
         objects::base::KyaObject,
@@ -410,19 +842,24 @@ mod tests {
         let code_object = compiler.get_output();
 
         let expected_output = vec![
-            Opcode::LoadName as u8,  // Load variable 'x'
-            0,                       // Index for 'x'
+            Opcode::LoadName as u8, // Load variable 'x'
+            0,                      // Index for 'x'
+            0,
             Opcode::LoadConst as u8, // Load constant 0.0
             0,                       // Index for constant 0.0
-            Opcode::Compare as u8,   // Compare x == 0.0
+            0,
+            Opcode::Compare as u8, // Compare x == 0.0
             ComparisonOperator::Equal as u8,
             Opcode::PopAndJumpIfFalse as u8, // Jump if condition is false
-            13,                              // Jump target
-            Opcode::LoadName as u8,          // Load variable 'x' again in the body
-            0,                               // Index for 'x'
-            Opcode::PopTop as u8,            // Pop the result of the body
-            Opcode::JumpBack as u8,          // Jump back to the condition check
-            13,                              // Offset to jump back to the condition check
+            18,                              // Jump target
+            0,
+            Opcode::LoadName as u8, // Load variable 'x' again in the body
+            0,                      // Index for 'x'
+            0,
+            Opcode::PopTop as u8,   // Pop the result of the body
+            Opcode::JumpBack as u8, // Jump back to the condition check
+            18,                     // Offset to jump back to the condition check
+            0,
         ];
 
         assert_eq!(expected_output, code_object.code);
@@ -452,21 +889,27 @@ mod tests {
         let code_object = compiler.get_output();
 
         let expected_output = vec![
-            Opcode::LoadName as u8,  // Load variable 'x'
-            0,                       // Index for 'x'
+            Opcode::LoadName as u8, // Load variable 'x'
+            0,                      // Index for 'x'
+            0,
             Opcode::LoadConst as u8, // Load constant 0.0
             0,                       // Index for constant 0.0
-            Opcode::Compare as u8,   // Compare x == 0.0
+            0,
+            Opcode::Compare as u8, // Compare x == 0.0
             ComparisonOperator::Equal as u8,
             Opcode::PopAndJumpIfFalse as u8, // Jump if condition is false
-            15,                              // Jump target
-            Opcode::LoadName as u8,          // Load variable 'x' again in the body
-            0,                               // Index for 'x'
-            Opcode::PopTop as u8,            // Pop the result of the body
-            Opcode::Jump as u8,              // Jump to the end of the loop
-            15,                              // Offset to jump to the end of the loop
-            Opcode::JumpBack as u8,          // Jump back to the condition check
-            15,
+            21,                              // Jump target
+            0,
+            Opcode::LoadName as u8, // Load variable 'x' again in the body
+            0,                      // Index for 'x'
+            0,
+            Opcode::PopTop as u8, // Pop the result of the body
+            Opcode::Jump as u8,   // Jump to the end of the loop
+            21,                   // Offset to jump to the end of the loop
+            0,
+            Opcode::JumpBack as u8, // Jump back to the condition check
+            21,
+            0,
         ];
 
         assert_eq!(expected_output, code_object.code);
@@ -495,17 +938,21 @@ mod tests {
         let code_object = compiler.get_output();
 
         let expected_output = vec![
-            Opcode::LoadName as u8,  // Load variable 'x'
-            0,                       // Index for 'x'
+            Opcode::LoadName as u8, // Load variable 'x'
+            0,                      // Index for 'x'
+            0,
             Opcode::LoadConst as u8, // Load constant 0.0
             0,                       // Index for constant 0.0
-            Opcode::Compare as u8,   // Compare x == 0.0
+            0,
+            Opcode::Compare as u8, // Compare x == 0.0
             ComparisonOperator::Equal as u8,
             Opcode::PopAndJumpIfFalse as u8, // Jump if condition is false
-            11,                              // Jump target
-            Opcode::LoadName as u8,          // Load variable 'x' in the body
-            0,                               // Index for 'x'
-            Opcode::PopTop as u8,            // Pop the result of the body
+            15,                              // Jump target
+            0,
+            Opcode::LoadName as u8, // Load variable 'x' in the body
+            0,                      // Index for 'x'
+            0,
+            Opcode::PopTop as u8, // Pop the result of the body
         ];
 
         assert_eq!(expected_output, code_object.code);
@@ -516,6 +963,7 @@ mod tests {
         let class_def = ASTNode::ClassDef(ast::ClassDef {
             name: "MyClass".to_string(),
             body: Box::new(ASTNode::Block(ast::Block::new(vec![]))),
+            doc: None,
         });
 
         let mut compiler = Compiler::new(Arc::new(class_def));
@@ -526,6 +974,7 @@ mod tests {
         let expected_output = vec![
             Opcode::LoadConst as u8, // Load class definition
             0,                       // Index for class definition
+            0,
             Opcode::MakeClass as u8, // Create class object
         ];
 
@@ -546,6 +995,7 @@ mod tests {
                     )))),
                 }),
             )]))),
+            doc: None,
         });
 
         let mut compiler = Compiler::new(Arc::new(return_node));
@@ -559,9 +1009,10 @@ mod tests {
         };
 
         let expected_output = vec![
-            Opcode::LoadName as u8, // Load variable 'x'
-            0,                      // Index for 'x'
-            Opcode::Return as u8,   // Return from method
+            Opcode::LoadFast as u8, // Load parameter 'x'
+            0,                      // Slot of 'x'
+            0,
+            Opcode::Return as u8, // Return from method
         ];
 
         assert_eq!(expected_output, function_code_object.code.code);
@@ -570,7 +1021,7 @@ mod tests {
     #[test]
     fn test_compile_bin_op() {
         let bin_op = ASTNode::BinOp(ast::BinOp {
-            left: Box::new(ASTNode::NumberLiteral(5.0)),
+            left: Box::new(ASTNode::Identifier(ast::Identifier::new("x".to_string()))),
             operator: ast::Operator::Plus,
             right: Box::new(ASTNode::NumberLiteral(3.0)),
         });
@@ -581,14 +1032,249 @@ mod tests {
         let code_object = compiler.get_output();
 
         let expected_output = vec![
-            Opcode::LoadConst as u8, // Load constant 5.0
-            0,                       // Index for constant 5.0
+            Opcode::LoadName as u8, // Load variable 'x'
+            0,                      // Index for 'x'
+            0,
             Opcode::LoadConst as u8, // Load constant 3.0
-            1,                       // Index for constant 3.0
-            Opcode::BinaryOp as u8,  // Perform addition
+            0,                       // Index for constant 3.0
+            0,
+            Opcode::BinaryOp as u8, // Perform addition
             Operator::Plus as u8,
         ];
 
         assert_eq!(expected_output, code_object.code);
     }
+
+    #[test]
+    fn test_constant_folding() {
+        for (source, expected) in [
+            ("1 + 2 - 4\n", "-1"),
+            ("-3 + 1\n", "-2"),
+            ("\"kya\" + \"nite\"\n", "kyanite"),
+            ("1 + 2 < 4\n", "true"),
+            ("\"a\" == \"b\"\n", "false"),
+        ] {
+            let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+            let mut compiler = Compiler::new(Arc::new(ast));
+            compiler.compile().unwrap();
+
+            let code = compiler.get_output();
+
+            assert_eq!(
+                code.code,
+                [Opcode::LoadConst as u8, 0, 0, Opcode::PopTop as u8],
+                "{}",
+                source
+            );
+            assert_eq!(code.consts.len(), 1);
+            assert_eq!(
+                crate::objects::utils::object_to_string_repr(&code.consts[0]).unwrap(),
+                expected
+            );
+        }
+    }
+
+    struct Recorder {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl CompilerPass for Recorder {
+        fn transform_ast(&mut self, ast: ASTNode) -> Result<ASTNode, Error> {
+            match ast {
+                ASTNode::NumberLiteral(value) => Ok(ASTNode::NumberLiteral(value * 2.0)),
+                other => Ok(other),
+            }
+        }
+
+        fn before_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+            if let ASTNode::Identifier(identifier) = node {
+                self.log
+                    .borrow_mut()
+                    .push(format!("before {}", identifier.name));
+            }
+
+            Ok(())
+        }
+
+        fn after_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+            if let ASTNode::Identifier(identifier) = node {
+                self.log
+                    .borrow_mut()
+                    .push(format!("after {}", identifier.name));
+            }
+
+            Ok(())
+        }
+
+        fn transform_code(&mut self, code: &mut CodeObject) -> Result<(), Error> {
+            self.log
+                .borrow_mut()
+                .push(format!("code of {} bytes", code.code.len()));
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_passes_run_in_nested_code() {
+        let method_def = ASTNode::MethodDef(ast::MethodDef {
+            name: "identity".to_string(),
+            parameters: vec![],
+            body: Box::new(ASTNode::Return(ast::Return {
+                value: Some(Box::new(ASTNode::Identifier(ast::Identifier::new(
+                    "x".to_string(),
+                )))),
+            })),
+            doc: None,
+        });
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut compiler = Compiler::new(Arc::new(method_def));
+        compiler.add_pass(Recorder { log: log.clone() });
+        compiler.compile().unwrap();
+
+        assert_eq!(
+            *log.borrow(),
+            vec!["before x", "after x", "code of 4 bytes", "code of 4 bytes"]
+        );
+    }
+
+    fn run(source: &str) -> Result<(), Error> {
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile()?;
+
+        Interpreter::new(".")
+            .eval(&compiler.get_output())
+            .map(|_| ())
+    }
+
+    #[test]
+    fn test_function_scoping() {
+        // Blocks share the function scope, and a nested function assigning
+        // an outer name gets a local of its own.
+        run("x = 1\ndef shadow\n    x = 2\n    return x\nend\nassert(shadow() == 2)\nassert(x == 1)\nif true\n    y = 3\nend\nassert(y == 3)\ndef outer(a)\n    def inner\n        a = 5\n        return a\n    end\n    assert(inner() == 5)\n    return a\nend\nassert(outer(4) == 4)\n").unwrap();
+
+        // Globals are visible from functions, enclosing locals are not.
+        run("x = 1\ndef read\n    return x\nend\nassert(read() == 1)\n").unwrap();
+        assert!(run("def outer(a)\n    def inner\n        return a\n    end\n    return inner()\nend\nouter(1)\n").is_err());
+    }
+
+    #[test]
+    fn test_function_locals_use_slots() {
+        // A local read before its first assignment in the call still sees
+        // the global of the same name.
+        run("x = 1\ndef f(a)\n    b = x\n    x = a\n    return b + x\nend\nassert(f(2) == 3)\nassert(x == 1)\nclass Counter\n    def constructor(start)\n        self.count = start\n    end\nend\nassert(Counter(4).count == 4)\n").unwrap();
+
+        let source = "def f(a)\n    b = a\n    return print(b)\nend\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let code = compiler.get_output();
+        let function_code = match &*code.consts[0].lock().unwrap() {
+            KyaObject::CodeObject(code_object) => code_object.code.clone(),
+            _ => panic!("Expected CodeObject"),
+        };
+
+        assert_eq!(
+            function_code.dis(),
+            "0000: LOAD_FAST 0 (a)\n0003: STORE_FAST 2 (b)\n0006: LOAD_FAST 2 (b)\n0009: POP_TOP\n0010: LOAD_NAME 0 (print)\n0013: LOAD_FAST 2 (b)\n0016: CALL_FUNCTION 1\n0018: RETURN"
+        );
+    }
+
+    #[test]
+    fn test_method_calls() {
+        // Methods of instances and builtins get their receiver, while
+        // functions stored in fields and modules are called without one.
+        run("class Box\n    def constructor(f)\n        self.f = f\n        self.items = List()\n    end\n    def add(x)\n        self.items.append(x)\n        return self\n    end\nend\ndef twice(x)\n    return x + x\nend\nb = Box(twice)\nb.add(1).add(2)\nassert(b.items.length() == 2)\nassert(b.f(3) == 6)\nassert(json.dump(1) == \"1\")\n").unwrap();
+
+        let ast = Parser::new(Lexer::new("items.append(1)\n".to_string()))
+            .parse()
+            .unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        assert_eq!(
+            compiler.get_output().dis(),
+            "0000: LOAD_NAME 0 (items)\n0003: LOAD_METHOD 1 (append)\n0006: LOAD_CONST 0 (1)\n0009: CALL_METHOD 1\n0011: POP_TOP"
+        );
+    }
+
+    #[test]
+    fn test_folded_comparison_is_true() {
+        run("x = 1 < 2\nassert(x is true)\ny = 2 < 1\nassert(y is false)\n").unwrap();
+    }
+
+    #[test]
+    fn test_is() {
+        run("a = List()\nb = a\nassert(a is b)\nassert(a is not List())\nassert(None is None)\nassert(a is not None)\nnot = 1\nassert(not == 1)\n").unwrap();
+
+        let ast = Parser::new(Lexer::new("a is not b\n".to_string()))
+            .parse()
+            .unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        assert_eq!(
+            compiler.get_output().dis(),
+            "0000: LOAD_NAME 0 (a)\n0003: LOAD_NAME 1 (b)\n0006: IS_OP 1\n0008: POP_TOP"
+        );
+    }
+
+    #[test]
+    fn test_long_code_uses_wide_operands() {
+        // Over 300 distinct constants and a loop body well past 255 bytes.
+        let body: String = (1..=300)
+            .map(|n| format!("        total = total + {}\n", n))
+            .collect();
+        let source = format!(
+            "def sum_twice\n    total = 0\n    i = 0\n    while i < 2\n{}        i = i + 1\n    end\n    return total\nend\nassert(sum_twice() == 90300)\n",
+            body
+        );
+
+        run(&source).unwrap();
+
+        let ast = Parser::new(Lexer::new(source)).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let code = compiler.get_output();
+        let function_code = match &*code.consts[0].lock().unwrap() {
+            KyaObject::CodeObject(code_object) => code_object.code.clone(),
+            _ => panic!("Expected CodeObject"),
+        };
+
+        assert!(function_code.dis().contains("LOAD_CONST 300"));
+    }
+
+    #[test]
+    fn test_instance_repr() {
+        run("class Point\n    def constructor(x, y)\n        self.y = y\n        self.x = x\n    end\nend\np = Point(1, \"a\")\nassert(p.__repr__() == \"<Point x=1, y=a>\")\np.x = p\nassert(p.__repr__() == \"<Point x=<Point x=<Point x=<Point ...>, y=a>, y=a>, y=a>\")\n").unwrap();
+    }
+
+    #[test]
+    fn test_class_slots() {
+        run("class Point\n    slots(\"x\", \"y\")\n    def constructor(x, y)\n        self.x = x\n        self.y = y\n    end\nend\np = Point(1, 2)\np.x = 3\nassert(p.x + p.y == 5)\nassert(p.__repr__() == \"<Point x=3, y=2>\")\n").unwrap();
+
+        assert!(run("class Point\n    slots(\"x\")\nend\np = Point()\np.y = 1\n").is_err());
+        assert!(run("class Point\n    slots(\"x\", \"y\")\n    def get\n        return self.y\n    end\nend\nPoint().get()\n").is_err());
+        assert!(run("class Point\n    slots(\"x\", \"x\")\nend\n").is_err());
+    }
+
+    #[test]
+    fn test_pass_rewrites_ast() {
+        let mut compiler = Compiler::new(Arc::new(ASTNode::NumberLiteral(21.0)));
+        compiler.add_pass(Recorder {
+            log: Rc::new(RefCell::new(vec![])),
+        });
+        compiler.compile().unwrap();
+
+        let code = compiler.get_output();
+
+        match &*code.consts[0].lock().unwrap() {
+            KyaObject::NumberObject(number) => assert_eq!(number.value, 42.0),
+            _ => panic!("Expected a number constant"),
+        }
+    }
 }
@@ -0,0 +1,600 @@
+//! Conversions between objects and Rust types that implement serde's traits,
+//! by way of `embed::Value`. Structs and maps become hashes, sequences and
+//! tuples become lists, and `()` and `Option::None` become `None`. Every
+//! number becomes a Number, so integers beyond 2^53 lose precision. Enums
+//! are externally tagged: a unit variant becomes its name, and any other
+//! variant becomes a hash from its name to its contents.
+//!
+//! Like `Value::from_object`, both functions must be called with the
+//! interpreter lock held.
+
+use crate::embed::Value;
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+use serde::Deserializer;
+use std::fmt;
+
+/// Makes an object out of `value`.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<KyaObjectRef, Error> {
+    value.serialize(ValueSerializer)?.into_object()
+}
+
+/// Reads a `T` out of `object`.
+pub fn from_value<T: DeserializeOwned>(object: &KyaObjectRef) -> Result<T, Error> {
+    Ok(T::deserialize(Value::from_object(object)?)?)
+}
+
+/// Why a value could not be converted. It becomes a `ValueError`.
+#[derive(Debug)]
+pub struct ConversionError(String);
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl ser::Error for ConversionError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ConversionError(message.to_string())
+    }
+}
+
+impl de::Error for ConversionError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ConversionError(message.to_string())
+    }
+}
+
+impl From<ConversionError> for Error {
+    fn from(error: ConversionError) -> Self {
+        Error::ValueError(error.0)
+    }
+}
+
+// Wraps the contents of an enum variant in a hash keyed by its name.
+fn tagged(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(name) => Value::Hash(vec![(Value::String(name.to_string()), value)]),
+        None => value,
+    }
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, value: bool) -> Result<Value, ConversionError> {
+        Ok(Value::Bool(value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Value, ConversionError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Value, ConversionError> {
+        Ok(Value::Number(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Value, ConversionError> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Value, ConversionError> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Value, ConversionError> {
+        Ok(Value::Bytes(value.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, ConversionError> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, ConversionError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, ConversionError> {
+        Ok(Value::None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, ConversionError> {
+        Ok(Value::None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, ConversionError> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, ConversionError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, ConversionError> {
+        Ok(tagged(Some(variant), value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, ConversionError> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, ConversionError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, ConversionError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, ConversionError> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, ConversionError> {
+        Ok(MapSerializer {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer, ConversionError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer, ConversionError> {
+        Ok(MapSerializer {
+            entries: Vec::with_capacity(len),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+struct SeqSerializer {
+    items: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConversionError> {
+        self.items.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, ConversionError> {
+        Ok(tagged(self.variant, Value::List(self.items)))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConversionError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConversionError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    entries: Vec<(Value, Value)>,
+    // The key whose value comes next, for maps.
+    key: Option<Value>,
+    variant: Option<&'static str>,
+}
+
+impl MapSerializer {
+    fn field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        self.entries.push((
+            Value::String(key.to_string()),
+            value.serialize(ValueSerializer)?,
+        ));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, ConversionError> {
+        Ok(tagged(self.variant, Value::Hash(self.entries)))
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ConversionError> {
+        self.key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConversionError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ConversionError("A map value came before its key".to_string()))?;
+
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = ConversionError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, ConversionError> {
+        self.finish()
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ConversionError> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = ConversionError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConversionError> {
+        match self {
+            Value::None => visitor.visit_unit(),
+            Value::Bool(value) => visitor.visit_bool(value),
+            // Whole numbers are offered as integers, so that they can be read
+            // into integer fields.
+            Value::Number(value) if value.fract() == 0.0 && value.abs() < 2f64.powi(63) => {
+                if value < 0.0 {
+                    visitor.visit_i64(value as i64)
+                } else {
+                    visitor.visit_u64(value as u64)
+                }
+            }
+            Value::Number(value) => visitor.visit_f64(value),
+            Value::String(value) => visitor.visit_string(value),
+            Value::Bytes(value) => visitor.visit_byte_buf(value),
+            Value::List(items) => {
+                let mut items = SeqDeserializer::new(items.into_iter());
+                let value = visitor.visit_seq(&mut items)?;
+
+                items.end()?;
+                Ok(value)
+            }
+            Value::Hash(entries) => {
+                let mut entries = MapDeserializer::new(entries.into_iter());
+                let value = visitor.visit_map(&mut entries)?;
+
+                entries.end()?;
+                Ok(value)
+            }
+            Value::Object(repr) => Err(ConversionError(format!(
+                "Cannot convert {} to a Rust value",
+                repr
+            ))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConversionError> {
+        match self {
+            Value::None => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ConversionError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConversionError> {
+        match self {
+            Value::String(variant) => {
+                let variant: StringDeserializer<ConversionError> = variant.into_deserializer();
+
+                visitor.visit_enum(variant)
+            }
+            Value::Hash(mut entries) if entries.len() == 1 => match entries.pop() {
+                Some((Value::String(variant), value)) => {
+                    visitor.visit_enum(EnumDeserializer { variant, value })
+                }
+                _ => Err(ConversionError(
+                    "Expected the name of an enum variant as the key".to_string(),
+                )),
+            },
+            _ => Err(ConversionError(
+                "Expected an enum variant: a String or a Hash with one key".to_string(),
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = ConversionError;
+    type Variant = Value;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Value), ConversionError> {
+        let variant: StringDeserializer<ConversionError> = self.variant.into_deserializer();
+
+        Ok((seed.deserialize(variant)?, self.value))
+    }
+}
+
+// The contents of a variant that is not a unit.
+impl<'de> VariantAccess<'de> for Value {
+    type Error = ConversionError;
+
+    fn unit_variant(self) -> Result<(), ConversionError> {
+        match self {
+            Value::None => Ok(()),
+            _ => Err(ConversionError(
+                "Expected no contents for a unit variant".to_string(),
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ConversionError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ConversionError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConversionError> {
+        self.deserialize_map(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::utils::object_to_string_repr;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Mode {
+        Fast,
+        Retry { attempts: u8 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        tags: Vec<String>,
+        timeout: Option<u32>,
+        modes: Vec<Mode>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = Config {
+            name: "api".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            tags: vec!["a".to_string()],
+            timeout: None,
+            modes: vec![Mode::Fast, Mode::Retry { attempts: 3 }],
+        };
+
+        let object = to_value(&config).unwrap();
+
+        assert_eq!(
+            object_to_string_repr(&to_value(&config.tags).unwrap()).unwrap(),
+            "[a]"
+        );
+        assert_eq!(from_value::<Config>(&object).unwrap(), config);
+        assert!(matches!(
+            from_value::<Vec<u8>>(&to_value("text").unwrap()),
+            Err(Error::ValueError(_))
+        ));
+    }
+}
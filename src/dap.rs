@@ -0,0 +1,542 @@
+//! A Debug Adapter Protocol server, so that editors can debug programs with
+//! `debugger`. `kyanite dap` waits on a local port for one client, like VS
+//! Code with `"debugServer": 4711` in its launch configuration, and serves it
+//! until it disconnects. The client's `launch` request names the script,
+//! which starts once the client is done setting breakpoints. What the script
+//! prints goes to the server's own output.
+
+use crate::bytecode::CodeObject;
+use crate::compiler::Compiler;
+use crate::debugger::{statement_lines, Debugger, Event, Resume, Scope};
+use crate::internal::source::read_source;
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::optimizer;
+use crate::parser::Parser;
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// The program has a single thread the client can see.
+const THREAD_ID: u64 = 1;
+
+pub fn serve(port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Error: Could not listen on port {}: {}", port, e))?;
+
+    eprintln!("Waiting for a debugger client on 127.0.0.1:{}", port);
+
+    let (stream, _) = listener
+        .accept()
+        .map_err(|e| format!("Error: Could not accept the client: {}", e))?;
+
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| format!("Error: {}", e))?);
+    let mut adapter = Adapter::new(Arc::new(Client {
+        stream: Mutex::new(stream),
+        seq: AtomicU64::new(1),
+    }));
+
+    while let Some(request) = read_message(&mut reader)? {
+        if !adapter.handle(&request) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Reads a message framed by a `Content-Length` header, or `None` once the
+// client closed the connection.
+pub(crate) fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+
+        if reader
+            .read_line(&mut header)
+            .map_err(|e| format!("Error: Could not read from the client: {}", e))?
+            == 0
+        {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or("Error: A message from the client has no Content-Length")?;
+    let mut body = vec![0; length];
+
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| format!("Error: Could not read from the client: {}", e))?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| format!("Error: Invalid message from the client: {}", e))
+}
+
+struct Client {
+    stream: Mutex<TcpStream>,
+    seq: AtomicU64,
+}
+
+impl Client {
+    fn send(&self, mut message: Value) {
+        message["seq"] = json!(self.seq.fetch_add(1, Ordering::SeqCst));
+
+        let body = message.to_string();
+        let mut stream = self.stream.lock().unwrap();
+
+        // A client that went away is noticed when reading its next request.
+        let _ = write!(stream, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let _ = stream.flush();
+    }
+
+    fn respond(&self, request: &Value, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn fail(&self, request: &Value, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn event(&self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    fn report(&self, event: Event) {
+        match event {
+            Event::Stopped(reason) => self.event(
+                "stopped",
+                json!({
+                    "reason": reason.name(),
+                    "threadId": THREAD_ID,
+                    "allThreadsStopped": true,
+                }),
+            ),
+            Event::Exited(error) => {
+                if let Some(error) = &error {
+                    self.event(
+                        "output",
+                        json!({ "category": "stderr", "output": format!("{}\n", error) }),
+                    );
+                }
+
+                self.event("exited", json!({ "exitCode": error.is_some() as u8 }));
+                self.event("terminated", json!({}));
+            }
+        }
+    }
+}
+
+// A script to debug, compiled when the client launches it.
+struct Launch {
+    path: PathBuf,
+    code: CodeObject,
+    lines: BTreeSet<usize>,
+    stop_on_entry: bool,
+}
+
+struct Adapter {
+    client: Arc<Client>,
+    launch: Option<Launch>,
+    configured: bool,
+    breakpoints: Vec<usize>,
+    debugger: Option<Debugger>,
+}
+
+impl Adapter {
+    fn new(client: Arc<Client>) -> Self {
+        Adapter {
+            client,
+            launch: None,
+            configured: false,
+            breakpoints: vec![],
+            debugger: None,
+        }
+    }
+
+    // Answers a request, returning whether to keep serving the client.
+    fn handle(&mut self, request: &Value) -> bool {
+        let arguments = &request["arguments"];
+
+        match request["command"].as_str().unwrap_or("") {
+            "initialize" => {
+                self.client
+                    .respond(request, json!({ "supportsConfigurationDoneRequest": true }));
+                self.client.event("initialized", json!({}));
+            }
+            "launch" => match compile(arguments) {
+                Ok(launch) => {
+                    self.launch = Some(launch);
+                    self.client.respond(request, json!({}));
+                    self.start();
+                }
+                Err(message) => self.client.fail(request, &message),
+            },
+            "setBreakpoints" => {
+                let source = arguments["source"]["path"].as_str().map(Path::new);
+                let lines: Vec<usize> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .map(|line| line as usize)
+                    .collect();
+
+                let breakpoints: Vec<Value> = lines
+                    .iter()
+                    .map(|line| json!({ "verified": self.is_valid(source, *line), "line": line }))
+                    .collect();
+
+                self.breakpoints = lines;
+
+                if let Some(debugger) = &self.debugger {
+                    debugger.set_breakpoints(&self.breakpoints);
+                }
+
+                self.client
+                    .respond(request, json!({ "breakpoints": breakpoints }));
+            }
+            "configurationDone" => {
+                self.configured = true;
+                self.client.respond(request, json!({}));
+                self.start();
+            }
+            "threads" => self.client.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "stackTrace" => {
+                let source = self.launch.as_ref().map(|launch| {
+                    json!({
+                        "name": launch.path.file_name().map(|name| name.to_string_lossy()),
+                        "path": launch.path,
+                    })
+                });
+
+                let frames: Vec<Value> = self
+                    .debugger
+                    .as_ref()
+                    .map(Debugger::stack_trace)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, frame)| {
+                        json!({
+                            "id": index + 1,
+                            "name": frame.name,
+                            "line": frame.line,
+                            "column": 1,
+                            "source": source,
+                        })
+                    })
+                    .collect();
+
+                self.client.respond(
+                    request,
+                    json!({ "totalFrames": frames.len(), "stackFrames": frames }),
+                );
+            }
+            "scopes" => {
+                // Each frame has two references: twice its id for its locals
+                // and the next one for the globals.
+                let frame = arguments["frameId"].as_u64().unwrap_or(1);
+
+                self.client.respond(
+                    request,
+                    json!({ "scopes": [
+                        { "name": "Locals", "variablesReference": frame * 2, "expensive": false },
+                        { "name": "Globals", "variablesReference": frame * 2 + 1, "expensive": false },
+                    ] }),
+                );
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or(0);
+                let scope = if reference.is_multiple_of(2) {
+                    Scope::Locals
+                } else {
+                    Scope::Globals
+                };
+                let frame = (reference / 2).saturating_sub(1) as usize;
+
+                let variables: Vec<Value> = self
+                    .debugger
+                    .as_ref()
+                    .map(|debugger| debugger.variables(frame, scope))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|variable| {
+                        json!({
+                            "name": variable.name,
+                            "value": variable.value,
+                            "variablesReference": 0,
+                        })
+                    })
+                    .collect();
+
+                self.client
+                    .respond(request, json!({ "variables": variables }));
+            }
+            command @ ("continue" | "next" | "stepIn" | "stepOut") => {
+                let how = match command {
+                    "continue" => Resume::Continue,
+                    "next" => Resume::StepOver,
+                    "stepIn" => Resume::StepIn,
+                    _ => Resume::StepOut,
+                };
+
+                self.client
+                    .respond(request, json!({ "allThreadsContinued": true }));
+
+                if let Some(debugger) = &self.debugger {
+                    debugger.resume(how);
+                }
+            }
+            "pause" => {
+                if let Some(debugger) = &self.debugger {
+                    debugger.pause();
+                }
+
+                self.client.respond(request, json!({}));
+            }
+            "disconnect" | "terminate" => {
+                self.client.respond(request, json!({}));
+
+                return false;
+            }
+            command => self
+                .client
+                .fail(request, &format!("Unsupported request '{}'", command)),
+        }
+
+        true
+    }
+
+    // Whether a breakpoint can be set on `line` of `source`. Before the
+    // script is launched, any line is taken.
+    fn is_valid(&self, source: Option<&Path>, line: usize) -> bool {
+        let Some(launch) = &self.launch else {
+            return true;
+        };
+
+        let is_script = source.is_none_or(|source| same_file(source, &launch.path));
+
+        is_script && launch.lines.contains(&line)
+    }
+
+    // Runs the script once it is launched and the client is configured.
+    fn start(&mut self) {
+        if !self.configured || self.debugger.is_some() {
+            return;
+        }
+
+        let Some(launch) = &self.launch else {
+            return;
+        };
+
+        let root = launch
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let client = self.client.clone();
+
+        match Debugger::launch(
+            Interpreter::new(&root.to_string_lossy()),
+            launch.code.clone(),
+            &self.breakpoints,
+            launch.stop_on_entry,
+            move |event| client.report(event),
+        ) {
+            Ok(debugger) => self.debugger = Some(debugger),
+            Err(message) => {
+                self.client.event(
+                    "output",
+                    json!({ "category": "stderr", "output": format!("{}\n", message) }),
+                );
+                self.client.event("terminated", json!({}));
+            }
+        }
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn compile(arguments: &Value) -> Result<Launch, String> {
+    let path = arguments["program"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or("The launch request needs a 'program' to run")?;
+    let input = read_source(&path)?;
+
+    let ast = Parser::new(Lexer::new(input))
+        .parse()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.add_pass(optimizer::ConstantBranches::new());
+    compiler
+        .compile()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let code = compiler.get_output();
+
+    Ok(Launch {
+        lines: statement_lines(&code),
+        path,
+        code,
+        stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // An adapter serving one end of a loopback connection, and a reader for
+    // what it sends to the other.
+    fn connect() -> (Adapter, BufReader<TcpStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let peer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        peer.set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+
+        let adapter = Adapter::new(Arc::new(Client {
+            stream: Mutex::new(stream),
+            seq: AtomicU64::new(1),
+        }));
+
+        (adapter, BufReader::new(peer))
+    }
+
+    fn request(seq: u64, command: &str, arguments: Value) -> Value {
+        json!({ "seq": seq, "type": "request", "command": command, "arguments": arguments })
+    }
+
+    // Skips messages until one matches `kind` and `name`, the command of a
+    // response or the name of an event.
+    fn next(reader: &mut BufReader<TcpStream>, kind: &str, name: &str) -> Value {
+        loop {
+            let message = read_message(reader).unwrap().unwrap();
+            let key = if kind == "event" { "event" } else { "command" };
+
+            if message["type"] == kind && message[key] == name {
+                return message;
+            }
+        }
+    }
+
+    #[test]
+    fn test_debug_session() {
+        let path = std::env::temp_dir().join(format!("kyanite-dap-{}.k", std::process::id()));
+        std::fs::write(
+            &path,
+            "def double(n)\n    m = n + n\n    return m\nend\n\nx = double(21)\nprint(x)\n",
+        )
+        .unwrap();
+
+        let (mut adapter, mut reader) = connect();
+
+        adapter.handle(&request(1, "initialize", json!({})));
+        assert_eq!(next(&mut reader, "response", "initialize")["success"], true);
+        next(&mut reader, "event", "initialized");
+
+        adapter.handle(&request(2, "launch", json!({ "program": path })));
+        assert_eq!(next(&mut reader, "response", "launch")["success"], true);
+
+        adapter.handle(&request(
+            3,
+            "setBreakpoints",
+            json!({ "source": { "path": path }, "breakpoints": [{ "line": 3 }, { "line": 5 }] }),
+        ));
+        assert_eq!(
+            next(&mut reader, "response", "setBreakpoints")["body"]["breakpoints"],
+            json!([{ "verified": true, "line": 3 }, { "verified": false, "line": 5 }])
+        );
+
+        adapter.handle(&request(4, "configurationDone", json!({})));
+        assert_eq!(
+            next(&mut reader, "event", "stopped")["body"]["reason"],
+            "breakpoint"
+        );
+
+        adapter.handle(&request(5, "stackTrace", json!({ "threadId": THREAD_ID })));
+        let frames = &next(&mut reader, "response", "stackTrace")["body"]["stackFrames"];
+        assert_eq!(frames[0]["name"], "double");
+        assert_eq!(frames[0]["line"], 3);
+        assert_eq!(frames[1]["line"], 6);
+
+        adapter.handle(&request(6, "variables", json!({ "variablesReference": 2 })));
+        assert_eq!(
+            next(&mut reader, "response", "variables")["body"]["variables"],
+            json!([
+                { "name": "m", "value": "42", "variablesReference": 0 },
+                { "name": "n", "value": "21", "variablesReference": 0 },
+            ])
+        );
+
+        adapter.handle(&request(7, "continue", json!({ "threadId": THREAD_ID })));
+        assert_eq!(next(&mut reader, "event", "exited")["body"]["exitCode"], 0);
+        next(&mut reader, "event", "terminated");
+
+        adapter.handle(&request(8, "evaluate", json!({})));
+        assert_eq!(next(&mut reader, "response", "evaluate")["success"], false);
+
+        assert!(!adapter.handle(&request(9, "disconnect", json!({}))));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_launch_without_a_program_fails() {
+        let (mut adapter, mut reader) = connect();
+
+        adapter.handle(&request(1, "launch", json!({})));
+
+        let response = next(&mut reader, "response", "launch");
+        assert_eq!(response["success"], false);
+        assert_eq!(
+            response["message"],
+            "The launch request needs a 'program' to run"
+        );
+    }
+}
@@ -0,0 +1,519 @@
+//! Stopping a program at breakpoints and stepping through it, for the DAP
+//! server in `dap`. `Debugger::launch` runs the program on a thread of its
+//! own, which checks whether to stop before the first instruction of each
+//! statement. While stopped, that thread waits for the client and answers
+//! its requests to inspect the frames, since only it can read them safely.
+//! Threads started by the program run without stopping.
+
+use crate::bytecode::CodeObject;
+use crate::gc::Gc;
+use crate::interpreter::{Frame, Interpreter};
+use crate::objects::base::{kya_type_name, DictRef, KyaObject, KyaObjectRef};
+use crate::objects::utils::object_to_string_repr;
+use crate::runtime;
+use crate::symbol::Symbol;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// The program runs on a thread of its own, given as much stack as a main
+// thread gets on Linux.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Entry,
+    Breakpoint,
+    Step,
+    Pause,
+}
+
+impl StopReason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopReason::Entry => "entry",
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step => "step",
+            StopReason::Pause => "pause",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Locals,
+    Globals,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    Stopped(StopReason),
+    /// The program finished, with the error it raised if it did.
+    Exited(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub name: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+}
+
+enum Command {
+    Resume(Resume),
+    StackTrace(Sender<Vec<StackFrame>>),
+    Variables(usize, Scope, Sender<Vec<Variable>>),
+}
+
+// What the client changes while the program runs.
+#[derive(Default)]
+struct Shared {
+    breakpoints: Mutex<HashSet<usize>>,
+    pause: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// The client's end of a program started by `launch`. Dropping it lets the
+/// program run to its end.
+pub struct Debugger {
+    shared: Arc<Shared>,
+    commands: Sender<Command>,
+}
+
+impl Debugger {
+    /// Runs `code` on a new thread, stopping at `breakpoints`, and before its
+    /// first statement if `stop_on_entry`. `on_event` is called on that
+    /// thread when it stops and once it finishes.
+    pub fn launch(
+        mut interpreter: Interpreter,
+        code: CodeObject,
+        breakpoints: &[usize],
+        stop_on_entry: bool,
+        on_event: impl Fn(Event) + Send + 'static,
+    ) -> Result<Debugger, String> {
+        let shared = Arc::new(Shared::default());
+        let (commands, receiver) = mpsc::channel();
+
+        shared
+            .breakpoints
+            .lock()
+            .unwrap()
+            .extend(breakpoints.iter().copied());
+
+        let session_shared = shared.clone();
+
+        thread::Builder::new()
+            .name("debuggee".to_string())
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let on_event: Rc<dyn Fn(Event)> = Rc::new(on_event);
+
+                SESSION.with(|session| {
+                    *session.borrow_mut() = Some(Session {
+                        shared: session_shared,
+                        commands: receiver,
+                        on_event: on_event.clone(),
+                        mode: if stop_on_entry {
+                            Mode::Entry
+                        } else {
+                            Mode::Run
+                        },
+                    })
+                });
+
+                let result = interpreter.eval(&code);
+
+                SESSION.with(|session| session.borrow_mut().take());
+
+                on_event(Event::Exited(result.err().map(|error| error.to_string())));
+            })
+            .map_err(|e| format!("Error: Could not start the program: {}", e))?;
+
+        Ok(Debugger { shared, commands })
+    }
+
+    pub fn set_breakpoints(&self, lines: &[usize]) {
+        let mut breakpoints = self.shared.breakpoints.lock().unwrap();
+
+        breakpoints.clear();
+        breakpoints.extend(lines.iter().copied());
+    }
+
+    /// Stops the program before its next statement.
+    pub fn pause(&self) {
+        self.shared.pause.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self, how: Resume) {
+        if self.is_stopped() {
+            let _ = self.commands.send(Command::Resume(how));
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.shared.stopped.load(Ordering::SeqCst)
+    }
+
+    /// The frames of the stopped program, innermost first. Empty while it runs.
+    pub fn stack_trace(&self) -> Vec<StackFrame> {
+        self.ask(Command::StackTrace)
+    }
+
+    /// The variables of a scope of `frame`, an index into `stack_trace`.
+    pub fn variables(&self, frame: usize, scope: Scope) -> Vec<Variable> {
+        self.ask(|reply| Command::Variables(frame, scope, reply))
+    }
+
+    fn ask<T: Default>(&self, command: impl FnOnce(Sender<T>) -> Command) -> T {
+        if !self.is_stopped() {
+            return T::default();
+        }
+
+        let (reply, answer) = mpsc::channel();
+
+        if self.commands.send(command(reply)).is_err() {
+            return T::default();
+        }
+
+        answer.recv().unwrap_or_default()
+    }
+}
+
+/// The lines a breakpoint can be set on: those where a statement of `code`
+/// or of the functions and classes it defines starts.
+pub fn statement_lines(code: &CodeObject) -> BTreeSet<usize> {
+    let mut lines: BTreeSet<usize> = code.lines.iter().map(|(_, line)| *line).collect();
+
+    for constant in &code.consts {
+        if let KyaObject::CodeObject(nested) = &*constant.lock().unwrap() {
+            lines.extend(statement_lines(&nested.code));
+        }
+    }
+
+    lines
+}
+
+enum Mode {
+    Run,
+    Entry,
+    StepIn,
+    // Stop at the next statement of a frame this deep or shallower.
+    StepOver(usize),
+    StepOut(usize),
+}
+
+struct Session {
+    shared: Arc<Shared>,
+    commands: Receiver<Command>,
+    on_event: Rc<dyn Fn(Event)>,
+    mode: Mode,
+}
+
+// What the client can see of a frame of the program.
+#[derive(Clone)]
+struct FrameInfo {
+    name: String,
+    line: usize,
+    locals: DictRef,
+    globals: DictRef,
+    varnames: Vec<Symbol>,
+    // The frame's local slots as they were when its current statement
+    // started.
+    fast_locals: Vec<Option<KyaObjectRef>>,
+}
+
+thread_local! {
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+    static FRAMES: RefCell<Vec<FrameInfo>> = const { RefCell::new(Vec::new()) };
+    // Set while the values of the stopped program are described, so that
+    // the code their reprs run does not stop.
+    static INSPECTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` out of the debugger's sight: its frames neither stop nor show in
+/// the stack, like the prelude or the reprs of a stopped program's values.
+pub fn unseen<T>(f: impl FnOnce() -> T) -> T {
+    let inspecting = INSPECTING.with(|inspecting| inspecting.replace(true));
+    let result = f();
+
+    INSPECTING.with(|cell| cell.set(inspecting));
+
+    result
+}
+
+/// Whether the current thread runs a program under the debugger.
+pub fn is_attached() -> bool {
+    !INSPECTING.with(|inspecting| inspecting.get())
+        && SESSION.with(|session| session.borrow().is_some())
+}
+
+/// Keeps a frame on the debugger's stack while it runs.
+pub struct FrameGuard;
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| frames.borrow_mut().pop());
+    }
+}
+
+pub fn enter(frame: &Frame) -> FrameGuard {
+    let name = if frame.code.name.is_empty() {
+        "<main>".to_string()
+    } else {
+        frame.code.name.clone()
+    };
+
+    FRAMES.with(|frames| {
+        frames.borrow_mut().push(FrameInfo {
+            name,
+            line: 0,
+            locals: frame.locals.clone(),
+            globals: frame.globals.clone(),
+            varnames: frame.code.varnames.clone(),
+            fast_locals: vec![],
+        })
+    });
+
+    FrameGuard
+}
+
+/// Called before each instruction of a frame passed to `enter`. Stops there
+/// if a statement starts at `pc` and the client asked to stop at it.
+pub fn before_instruction(frame: &Frame, pc: usize) {
+    let Some(line) = frame.code.line_starting_at(pc) else {
+        return;
+    };
+
+    let depth = FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+
+        if let Some(top) = frames.last_mut() {
+            top.line = line;
+            top.fast_locals.clone_from(&frame.fast_locals);
+        }
+
+        frames.len()
+    });
+
+    SESSION.with(|session| {
+        if let Some(session) = session.borrow_mut().as_mut()
+            && let Some(reason) = session.stop_reason(line, depth)
+        {
+            session.stop(reason, depth);
+        }
+    });
+}
+
+impl Session {
+    fn stop_reason(&self, line: usize, depth: usize) -> Option<StopReason> {
+        if self.shared.pause.swap(false, Ordering::SeqCst) {
+            return Some(StopReason::Pause);
+        }
+
+        if self.shared.breakpoints.lock().unwrap().contains(&line) {
+            return Some(StopReason::Breakpoint);
+        }
+
+        match self.mode {
+            Mode::Entry => Some(StopReason::Entry),
+            Mode::StepIn => Some(StopReason::Step),
+            Mode::StepOver(deepest) if depth <= deepest => Some(StopReason::Step),
+            Mode::StepOut(deepest) if depth < deepest => Some(StopReason::Step),
+            _ => None,
+        }
+    }
+
+    fn stop(&mut self, reason: StopReason, depth: usize) {
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        (self.on_event)(Event::Stopped(reason));
+
+        self.mode = loop {
+            match self.commands.recv() {
+                Ok(Command::Resume(how)) => {
+                    break match how {
+                        Resume::Continue => Mode::Run,
+                        Resume::StepIn => Mode::StepIn,
+                        Resume::StepOver => Mode::StepOver(depth),
+                        Resume::StepOut => Mode::StepOut(depth),
+                    };
+                }
+                Ok(Command::StackTrace(reply)) => {
+                    let _ = reply.send(stack_trace());
+                }
+                Ok(Command::Variables(frame, scope, reply)) => {
+                    let _ = reply.send(variables(frame, scope));
+                }
+                // The client went away, so the program runs to its end.
+                Err(_) => {
+                    self.shared.breakpoints.lock().unwrap().clear();
+                    break Mode::Run;
+                }
+            }
+        };
+
+        self.shared.stopped.store(false, Ordering::SeqCst);
+    }
+}
+
+fn stack_trace() -> Vec<StackFrame> {
+    FRAMES.with(|frames| {
+        frames
+            .borrow()
+            .iter()
+            .rev()
+            .map(|frame| StackFrame {
+                name: frame.name.clone(),
+                line: frame.line,
+            })
+            .collect()
+    })
+}
+
+fn variables(frame: usize, scope: Scope) -> Vec<Variable> {
+    let Some(frame) = FRAMES.with(|frames| frames.borrow().iter().rev().nth(frame).cloned()) else {
+        return vec![];
+    };
+
+    let is_module = Arc::ptr_eq(&frame.locals, &frame.globals);
+    let mut bindings: Vec<(Symbol, KyaObjectRef)> = vec![];
+
+    match scope {
+        Scope::Locals if is_module => bindings.extend(module_globals(&frame.globals)),
+        Scope::Locals => {
+            for (name, value) in frame.varnames.iter().zip(&frame.fast_locals) {
+                if let Some(value) = value {
+                    bindings.push((*name, value.clone()));
+                }
+            }
+
+            for (name, value) in frame.locals.lock().unwrap().iter() {
+                bindings.push((*name, value.clone()));
+            }
+        }
+        Scope::Globals if is_module => {}
+        Scope::Globals => bindings.extend(module_globals(&frame.globals)),
+    }
+
+    bindings.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+    unseen(|| {
+        bindings
+            .into_iter()
+            .map(|(name, value)| Variable {
+                name: name.to_string(),
+                value: object_to_string_repr(&value)
+                    .unwrap_or_else(|_| format!("<{}>", kya_type_name(&value))),
+            })
+            .collect()
+    })
+}
+
+// The globals the program defined, leaving out the builtins it did not
+// rebind.
+fn module_globals(globals: &DictRef) -> Vec<(Symbol, KyaObjectRef)> {
+    let runtime = runtime::current();
+    let builtins = runtime.builtins.get();
+
+    globals
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(name, value)| {
+            let is_builtin = builtins
+                .and_then(|builtins| builtins.get(*name))
+                .is_some_and(|builtin| Gc::ptr_eq(builtin, value));
+
+            !is_builtin && !matches!(name.as_str(), "None" | "true" | "false")
+        })
+        .map(|(name, value)| (*name, value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stop_at_breakpoint_and_step() {
+        let source =
+            "def double(n)\n    m = n + n\n    return m\nend\n\nx = double(21)\nprint(x)\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let code = compiler.get_output();
+
+        assert_eq!(
+            statement_lines(&code).into_iter().collect::<Vec<_>>(),
+            [1, 2, 3, 6, 7]
+        );
+
+        let (sender, events) = mpsc::channel();
+        let debugger = Debugger::launch(Interpreter::new("."), code, &[3], false, move |event| {
+            sender.send(event).unwrap()
+        })
+        .unwrap();
+        let next_event = || events.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        assert_eq!(next_event(), Event::Stopped(StopReason::Breakpoint));
+        assert_eq!(
+            debugger.stack_trace(),
+            [
+                StackFrame {
+                    name: "double".to_string(),
+                    line: 3
+                },
+                StackFrame {
+                    name: "<main>".to_string(),
+                    line: 6
+                }
+            ]
+        );
+
+        let locals: Vec<(String, String)> = debugger
+            .variables(0, Scope::Locals)
+            .into_iter()
+            .map(|variable| (variable.name, variable.value))
+            .collect();
+
+        assert_eq!(
+            locals,
+            [
+                ("m".to_string(), "42".to_string()),
+                ("n".to_string(), "21".to_string())
+            ]
+        );
+
+        debugger.resume(Resume::StepOut);
+
+        assert_eq!(next_event(), Event::Stopped(StopReason::Step));
+        assert_eq!(debugger.stack_trace()[0].line, 7);
+        assert_eq!(debugger.variables(0, Scope::Locals)[0].name, "double");
+
+        debugger.resume(Resume::Continue);
+
+        assert_eq!(next_event(), Event::Exited(None));
+    }
+}
@@ -0,0 +1,237 @@
+//! Every error the lexer, parser and compiler report, with the text of its
+//! message and a stable code. Tools should match on the code, which does not
+//! change when a message is reworded.
+//!
+//! Codes are grouped by the stage that reports them:
+//!
+//! - `K01xx`: lexer
+//! - `K02xx`: parser
+//! - `K03xx`: compiler
+//! - `K04xx`: runtime, one code per kind of error (see `Error::code`)
+//!
+//! A code is never reused for a different error once it is removed.
+
+use crate::bytecode::WIDE_OPERAND_SIZE;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    InvalidSymbol(char),
+    UnterminatedString,
+    InvalidNumber,
+    InvalidEscape(String),
+
+    UnexpectedToken {
+        value: String,
+        line: usize,
+        column: usize,
+    },
+    ExpectedToken {
+        value: String,
+        line: usize,
+        column: usize,
+    },
+    UnexpectedEnd,
+    InvalidOperator(String),
+    InvalidNumberLiteral {
+        value: String,
+        line: usize,
+        column: usize,
+    },
+    UnclosedTry,
+
+    SlotsDeclaredTwice(String),
+    SlotsNotStrings(String),
+    DuplicateSlot(String, String),
+    TooManyArguments(usize),
+    InvalidAssignmentTarget,
+    InvalidParameter,
+    MissingComparisonOperator,
+    MissingBinaryOperator,
+    UnsupportedUnaryOperator(String),
+    OperandTooLarge(usize),
+    TooManyProbes(String),
+    BreakOutsideLoop,
+}
+
+pub const RUNTIME_ERROR: &str = "K0401";
+pub const TYPE_ERROR: &str = "K0402";
+pub const VALUE_ERROR: &str = "K0403";
+pub const UNDEFINED_VARIABLE: &str = "K0404";
+pub const BREAK_INTERRUPT: &str = "K0405";
+pub const NOT_IMPLEMENTED: &str = "K0406";
+pub const SYNTAX_ERROR: &str = "K0407";
+pub const UNCAUGHT_EXCEPTION: &str = "K0410";
+
+impl Diagnostic {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Diagnostic::InvalidSymbol(_) => "K0101",
+            Diagnostic::UnterminatedString => "K0102",
+            Diagnostic::InvalidNumber => "K0103",
+            Diagnostic::InvalidEscape(_) => "K0104",
+
+            Diagnostic::UnexpectedToken { .. } => "K0201",
+            Diagnostic::ExpectedToken { .. } => "K0202",
+            Diagnostic::UnexpectedEnd => "K0203",
+            Diagnostic::InvalidOperator(_) => "K0204",
+            Diagnostic::InvalidNumberLiteral { .. } => "K0205",
+            Diagnostic::UnclosedTry => "K0206",
+
+            Diagnostic::SlotsDeclaredTwice(_) => "K0301",
+            Diagnostic::SlotsNotStrings(_) => "K0302",
+            Diagnostic::DuplicateSlot(..) => "K0303",
+            Diagnostic::TooManyArguments(_) => "K0304",
+            Diagnostic::InvalidAssignmentTarget => "K0305",
+            Diagnostic::InvalidParameter => "K0306",
+            Diagnostic::MissingComparisonOperator => "K0307",
+            Diagnostic::MissingBinaryOperator => "K0308",
+            Diagnostic::UnsupportedUnaryOperator(_) => "K0309",
+            Diagnostic::OperandTooLarge(_) => "K0310",
+            Diagnostic::TooManyProbes(_) => "K0311",
+            Diagnostic::BreakOutsideLoop => "K0312",
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::InvalidSymbol(c) => write!(f, "Invalid symbol: {}", c),
+            Diagnostic::UnterminatedString => write!(f, "Unterminated string literal"),
+            Diagnostic::InvalidNumber => write!(f, "Invalid number literal"),
+            Diagnostic::InvalidEscape(sequence) => {
+                write!(f, "Invalid escape sequence: {}", sequence)
+            }
+
+            Diagnostic::UnexpectedToken {
+                value,
+                line,
+                column,
+            } => write!(
+                f,
+                "Unexpected token {} at line {}, column {}",
+                value, line, column
+            ),
+            Diagnostic::ExpectedToken {
+                value,
+                line,
+                column,
+            } => write!(
+                f,
+                "Expected token \"{}\" at line {}, column {}",
+                value, line, column
+            ),
+            Diagnostic::UnexpectedEnd => write!(f, "Unexpected token"),
+            Diagnostic::InvalidOperator(operator) => write!(f, "Invalid operator: {}", operator),
+            Diagnostic::InvalidNumberLiteral {
+                value,
+                line,
+                column,
+            } => write!(
+                f,
+                "Invalid number literal: {} at line {}, column {}",
+                value, line, column
+            ),
+            Diagnostic::UnclosedTry => write!(f, "Expected 'ensure' to close the try block"),
+
+            Diagnostic::SlotsDeclaredTwice(class) => {
+                write!(f, "Class '{}' declares slots more than once", class)
+            }
+            Diagnostic::SlotsNotStrings(class) => {
+                write!(f, "The slots of class '{}' must be string literals", class)
+            }
+            Diagnostic::DuplicateSlot(class, slot) => {
+                write!(f, "Class '{}' declares the slot '{}' twice", class, slot)
+            }
+            Diagnostic::TooManyArguments(count) => write!(
+                f,
+                "A call takes at most {} arguments, got {}",
+                u8::MAX,
+                count
+            ),
+            Diagnostic::InvalidAssignmentTarget => {
+                write!(f, "Assignment name must be an identifier")
+            }
+            Diagnostic::InvalidParameter => write!(f, "Method parameters must be identifiers"),
+            Diagnostic::MissingComparisonOperator => write!(f, "Comparison operator is missing"),
+            Diagnostic::MissingBinaryOperator => write!(f, "Binary operator is missing"),
+            Diagnostic::UnsupportedUnaryOperator(operator) => {
+                write!(f, "Unsupported unary operator: {}", operator)
+            }
+            Diagnostic::OperandTooLarge(value) => write!(
+                f,
+                "Operand {} does not fit in {} bytes; split the function into smaller ones",
+                value, WIDE_OPERAND_SIZE
+            ),
+            Diagnostic::TooManyProbes(scope) => {
+                write!(f, "Too many statements to instrument in {}", scope)
+            }
+            Diagnostic::BreakOutsideLoop => write!(f, "break outside loop"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique() {
+        let diagnostics = [
+            Diagnostic::InvalidSymbol('$'),
+            Diagnostic::UnterminatedString,
+            Diagnostic::InvalidNumber,
+            Diagnostic::InvalidEscape(String::new()),
+            Diagnostic::UnexpectedToken {
+                value: String::new(),
+                line: 0,
+                column: 0,
+            },
+            Diagnostic::ExpectedToken {
+                value: String::new(),
+                line: 0,
+                column: 0,
+            },
+            Diagnostic::UnexpectedEnd,
+            Diagnostic::InvalidOperator(String::new()),
+            Diagnostic::InvalidNumberLiteral {
+                value: String::new(),
+                line: 0,
+                column: 0,
+            },
+            Diagnostic::UnclosedTry,
+            Diagnostic::SlotsDeclaredTwice(String::new()),
+            Diagnostic::SlotsNotStrings(String::new()),
+            Diagnostic::DuplicateSlot(String::new(), String::new()),
+            Diagnostic::TooManyArguments(0),
+            Diagnostic::InvalidAssignmentTarget,
+            Diagnostic::InvalidParameter,
+            Diagnostic::MissingComparisonOperator,
+            Diagnostic::MissingBinaryOperator,
+            Diagnostic::UnsupportedUnaryOperator(String::new()),
+            Diagnostic::OperandTooLarge(0),
+            Diagnostic::TooManyProbes(String::new()),
+            Diagnostic::BreakOutsideLoop,
+        ];
+
+        let mut codes: Vec<&str> = diagnostics.iter().map(Diagnostic::code).collect();
+        codes.extend([
+            RUNTIME_ERROR,
+            TYPE_ERROR,
+            VALUE_ERROR,
+            UNDEFINED_VARIABLE,
+            BREAK_INTERRUPT,
+            NOT_IMPLEMENTED,
+            SYNTAX_ERROR,
+            UNCAUGHT_EXCEPTION,
+        ]);
+
+        let count = codes.len();
+        codes.sort();
+        codes.dedup();
+
+        assert_eq!(codes.len(), count);
+    }
+}
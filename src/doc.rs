@@ -0,0 +1,246 @@
+use std::path::{Path, PathBuf};
+
+use crate::ast;
+use crate::internal::source::read_source;
+use crate::interpreter::builtin_modules;
+use crate::lexer::Lexer;
+use crate::objects::base::KyaObject;
+use crate::parser::Parser;
+use crate::pkg::MODULES_DIR;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+struct DocEntry {
+    level: usize,
+    title: String,
+    body: Option<String>,
+}
+
+pub fn generate(path: &Path, format: DocFormat) -> Result<String, String> {
+    let mut entries = vec![DocEntry {
+        level: 1,
+        title: "API documentation".to_string(),
+        body: None,
+    }];
+
+    for file in source_files(path)? {
+        let input = read_source(&file)?;
+
+        let module = Parser::new(Lexer::new(input))
+            .parse()
+            .map_err(|e| format!("Error parsing file {}: {}", file.display(), e))?;
+
+        entries.push(DocEntry {
+            level: 2,
+            title: file.display().to_string(),
+            body: None,
+        });
+
+        document_module(&module, &mut entries);
+    }
+
+    document_builtin_modules(&mut entries);
+
+    Ok(match format {
+        DocFormat::Markdown => render_markdown(&entries),
+        DocFormat::Html => render_html(&entries),
+    })
+}
+
+pub fn source_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| format!("Error: Could not read directory {}: {}", path.display(), e))?;
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if name.starts_with('.') || name == MODULES_DIR {
+            continue;
+        }
+
+        if entry_path.is_dir() {
+            files.extend(source_files(&entry_path)?);
+        } else if entry_path.extension().is_some_and(|ext| ext == "k") {
+            files.push(entry_path);
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn method_signature(prefix: &str, method_def: &ast::MethodDef) -> String {
+    let parameters: Vec<String> = method_def
+        .parameters
+        .iter()
+        .filter_map(|param| match &**param {
+            ast::ASTNode::Identifier(identifier) => Some(identifier.name.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    format!("{}{}({})", prefix, method_def.name, parameters.join(", "))
+}
+
+fn block_statements(node: &ast::ASTNode) -> &[Box<ast::ASTNode>] {
+    match node {
+        ast::ASTNode::Block(block) => &block.statements,
+        _ => &[],
+    }
+}
+
+fn document_module(module: &ast::ASTNode, entries: &mut Vec<DocEntry>) {
+    let ast::ASTNode::Module(module) = module else {
+        return;
+    };
+
+    for statement in block_statements(&module.block) {
+        match &**statement {
+            ast::ASTNode::ClassDef(class_def) => {
+                entries.push(DocEntry {
+                    level: 3,
+                    title: format!("class {}", class_def.name),
+                    body: class_def.doc.clone(),
+                });
+
+                for member in block_statements(&class_def.body) {
+                    if let ast::ASTNode::MethodDef(method_def) = &**member {
+                        entries.push(DocEntry {
+                            level: 4,
+                            title: method_signature(&format!("{}.", class_def.name), method_def),
+                            body: method_def.doc.clone(),
+                        });
+                    }
+                }
+            }
+            ast::ASTNode::MethodDef(method_def) => entries.push(DocEntry {
+                level: 3,
+                title: format!("def {}", method_signature("", method_def)),
+                body: method_def.doc.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+fn document_builtin_modules(entries: &mut Vec<DocEntry>) {
+    entries.push(DocEntry {
+        level: 2,
+        title: "Builtin modules".to_string(),
+        body: None,
+    });
+
+    for (name, module) in builtin_modules() {
+        let dict = match &*module.lock().unwrap() {
+            KyaObject::ModuleObject(module_object) => module_object.dict.clone(),
+            _ => continue,
+        };
+
+        entries.push(DocEntry {
+            level: 3,
+            title: name.to_string(),
+            body: None,
+        });
+
+        let mut functions: Vec<(String, Option<&'static str>)> = dict
+            .lock()
+            .unwrap()
+            .iter()
+            .map(
+                |(function_name, function)| match &*function.lock().unwrap() {
+                    KyaObject::RsFunctionObject(rs_function) => {
+                        (function_name.to_string(), rs_function.doc)
+                    }
+                    KyaObject::HostFunctionObject(host_function) => {
+                        (function_name.to_string(), host_function.doc)
+                    }
+                    _ => (function_name.to_string(), None),
+                },
+            )
+            .collect();
+
+        functions.sort();
+
+        for (function_name, doc) in functions {
+            entries.push(DocEntry {
+                level: 4,
+                title: format!("{}.{}", name, function_name),
+                body: doc.map(|d| d.to_string()),
+            });
+        }
+    }
+}
+
+fn render_markdown(entries: &[DocEntry]) -> String {
+    let mut output = String::new();
+
+    for entry in entries {
+        if entry.level <= 2 {
+            output.push_str(&format!("{} {}\n\n", "#".repeat(entry.level), entry.title));
+        } else {
+            output.push_str(&format!(
+                "{} `{}`\n\n",
+                "#".repeat(entry.level),
+                entry.title
+            ));
+        }
+
+        if let Some(body) = &entry.body {
+            output.push_str(body);
+            output.push_str("\n\n");
+        }
+    }
+
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(entries: &[DocEntry]) -> String {
+    let mut output = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset='UTF-8'>\n<title>API documentation</title>\n</head>\n<body>\n",
+    );
+
+    for entry in entries {
+        if entry.level <= 2 {
+            output.push_str(&format!(
+                "<h{0}>{1}</h{0}>\n",
+                entry.level,
+                escape_html(&entry.title)
+            ));
+        } else {
+            output.push_str(&format!(
+                "<h{0}><code>{1}</code></h{0}>\n",
+                entry.level,
+                escape_html(&entry.title)
+            ));
+        }
+
+        if let Some(body) = &entry.body {
+            output.push_str(&format!(
+                "<p>{}</p>\n",
+                escape_html(body).replace('\n', "<br>\n")
+            ));
+        }
+    }
+
+    output.push_str("</body>\n</html>\n");
+
+    output
+}
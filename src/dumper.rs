@@ -6,6 +6,12 @@ pub struct ASTDumper {
     indent: i32,
 }
 
+impl Default for ASTDumper {
+    fn default() -> Self {
+        ASTDumper::new()
+    }
+}
+
 impl ASTDumper {
     pub fn new() -> Self {
         ASTDumper {
@@ -92,6 +98,25 @@ impl Visitor for ASTDumper {
         self.push(")");
     }
 
+    fn visit_slice(&mut self, slice: &ast::Slice) {
+        self.push("Slice(");
+        self.concat("value: ");
+        slice.value.accept(self);
+
+        for (label, bound) in [
+            ("start: ", &slice.start),
+            ("end: ", &slice.end),
+            ("step: ", &slice.step),
+        ] {
+            if let Some(bound) = bound {
+                self.concat(label);
+                bound.accept(self);
+            }
+        }
+
+        self.push(")");
+    }
+
     fn visit_compare(&mut self, compare: &ast::Compare) {
         self.push("Compare(");
         self.concat("left: ");
@@ -177,6 +202,297 @@ impl Visitor for ASTDumper {
         } else {
             self.push("None");
         }
+        if let Some(cause) = &raise.cause {
+            self.concat("cause: ");
+            cause.accept(self);
+        }
+        self.push(")");
+    }
+
+    fn visit_try(&mut self, try_node: &ast::Try) {
+        self.push("Try(");
+        self.concat("body: ");
+        try_node.body.accept(self);
+        self.concat("ensure: ");
+        try_node.ensure.accept(self);
         self.push(")");
     }
+
+    fn visit_with(&mut self, with: &ast::With) {
+        self.push("With(");
+        self.concat("value: ");
+        with.value.accept(self);
+        if let Some(name) = &with.name {
+            self.concat("name: ");
+            self.push(&format!("\"{}\"", name));
+        }
+        self.concat("body: ");
+        with.body.accept(self);
+        self.push(")");
+    }
+}
+
+/// Writes the AST as a Graphviz graph, for `--dump-format dot`. Each node is
+/// labelled with its kind and its name or operator, and each edge with the
+/// field of the parent it comes from.
+pub struct DotDumper {
+    pub output: String,
+    next_id: usize,
+    // The node whose children are being visited, and the field they are in.
+    parents: Vec<(usize, &'static str)>,
+}
+
+impl Default for DotDumper {
+    fn default() -> Self {
+        DotDumper::new()
+    }
+}
+
+impl DotDumper {
+    pub fn new() -> Self {
+        DotDumper {
+            output: String::new(),
+            next_id: 0,
+            parents: vec![],
+        }
+    }
+
+    // Adds a node, linked to the parent being visited, and returns its id.
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.output
+            .push_str(&format!("    n{} [label=\"{}\"];\n", id, escape(label)));
+
+        if let Some((parent, field)) = self.parents.last() {
+            if field.is_empty() {
+                self.output
+                    .push_str(&format!("    n{} -> n{};\n", parent, id));
+            } else {
+                self.output.push_str(&format!(
+                    "    n{} -> n{} [label=\"{}\"];\n",
+                    parent, id, field
+                ));
+            }
+        }
+
+        id
+    }
+
+    fn child(&mut self, parent: usize, field: &'static str, node: &ast::ASTNode) {
+        self.parents.push((parent, field));
+        node.accept(self);
+        self.parents.pop();
+    }
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Visitor for DotDumper {
+    fn visit_module(&mut self, module: &ast::Module) {
+        self.output
+            .push_str("digraph AST {\n    node [shape=box];\n");
+
+        let id = self.node("Module");
+        self.child(id, "", &module.block);
+
+        self.output.push_str("}\n");
+    }
+
+    fn visit_identifier(&mut self, identifier: &ast::Identifier) {
+        self.node(&format!("Identifier({})", identifier.name));
+    }
+
+    fn visit_method_call(&mut self, method_call: &ast::MethodCall) {
+        let id = self.node("MethodCall");
+
+        self.child(id, "name", &method_call.name);
+
+        for argument in &method_call.arguments {
+            self.child(id, "argument", argument);
+        }
+    }
+
+    fn visit_string_literal(&mut self, string_literal: &str) {
+        self.node(&format!("StringLiteral({:?})", string_literal));
+    }
+
+    fn visit_assignment(&mut self, assignment: &ast::Assignment) {
+        let id = self.node("Assignment");
+
+        self.child(id, "name", &assignment.name);
+        self.child(id, "value", &assignment.value);
+    }
+
+    fn visit_number_literal(&mut self, number_literal: &f64) {
+        self.node(&format!("NumberLiteral({})", number_literal));
+    }
+
+    fn visit_method_def(&mut self, method_def: &ast::MethodDef) {
+        let id = self.node(&format!("MethodDef({})", method_def.name));
+
+        for parameter in &method_def.parameters {
+            self.child(id, "parameter", parameter);
+        }
+
+        self.child(id, "body", &method_def.body);
+    }
+
+    fn visit_class_def(&mut self, class_def: &ast::ClassDef) {
+        let id = self.node(&format!("ClassDef({})", class_def.name));
+
+        self.child(id, "body", &class_def.body);
+    }
+
+    fn visit_attribute(&mut self, attribute: &ast::Attribute) {
+        let id = self.node(&format!("Attribute({})", attribute.value));
+
+        self.child(id, "name", &attribute.name);
+    }
+
+    fn visit_slice(&mut self, slice: &ast::Slice) {
+        let id = self.node("Slice");
+
+        self.child(id, "value", &slice.value);
+
+        for (field, bound) in [
+            ("start", &slice.start),
+            ("end", &slice.end),
+            ("step", &slice.step),
+        ] {
+            if let Some(bound) = bound {
+                self.child(id, field, bound);
+            }
+        }
+    }
+
+    fn visit_compare(&mut self, compare: &ast::Compare) {
+        let id = self.node(&format!("Compare({:?})", compare.operator));
+
+        self.child(id, "left", &compare.left);
+        self.child(id, "right", &compare.right);
+    }
+
+    fn visit_if(&mut self, if_statement: &ast::If) {
+        let id = self.node("If");
+
+        self.child(id, "test", &if_statement.test);
+        self.child(id, "body", &if_statement.body);
+    }
+
+    fn visit_import(&mut self, import: &ast::Import) {
+        self.node(&format!("Import({})", import.name));
+    }
+
+    fn visit_bin_op(&mut self, bin_op: &ast::BinOp) {
+        let id = self.node(&format!("BinOp({:?})", bin_op.operator));
+
+        self.child(id, "left", &bin_op.left);
+        self.child(id, "right", &bin_op.right);
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &ast::UnaryOp) {
+        let id = self.node(&format!("UnaryOp({:?})", unary_op.operator));
+
+        self.child(id, "operand", &unary_op.operand);
+    }
+
+    fn visit_while(&mut self, while_node: &ast::While) {
+        let id = self.node("While");
+
+        self.child(id, "condition", &while_node.condition);
+        self.child(id, "body", &while_node.body);
+    }
+
+    fn visit_break(&mut self) {
+        self.node("Break");
+    }
+
+    fn visit_block(&mut self, block: &ast::Block) {
+        let id = self.node("Block");
+
+        for statement in &block.statements {
+            self.child(id, "", statement);
+        }
+    }
+
+    fn visit_return(&mut self, return_node: &ast::Return) {
+        let id = self.node("Return");
+
+        if let Some(value) = &return_node.value {
+            self.child(id, "value", value);
+        }
+    }
+
+    fn visit_raise(&mut self, raise: &ast::Raise) {
+        let id = self.node("Raise");
+
+        if let Some(message) = &raise.message {
+            self.child(id, "message", message);
+        }
+
+        if let Some(cause) = &raise.cause {
+            self.child(id, "cause", cause);
+        }
+    }
+
+    fn visit_try(&mut self, try_node: &ast::Try) {
+        let id = self.node("Try");
+
+        self.child(id, "body", &try_node.body);
+        self.child(id, "ensure", &try_node.ensure);
+    }
+
+    fn visit_with(&mut self, with: &ast::With) {
+        let label = match &with.name {
+            Some(name) => format!("With({})", name),
+            None => "With".to_string(),
+        };
+        let id = self.node(&label);
+
+        self.child(id, "value", &with.value);
+        self.child(id, "body", &with.body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_dot_dumper() {
+        let ast = Parser::new(Lexer::new("x = \"a\"\n".to_string()))
+            .parse()
+            .unwrap();
+        let mut dumper = DotDumper::new();
+
+        ast.accept(&mut dumper);
+
+        assert_eq!(
+            dumper.output,
+            concat!(
+                "digraph AST {\n",
+                "    node [shape=box];\n",
+                "    n0 [label=\"Module\"];\n",
+                "    n1 [label=\"Block\"];\n",
+                "    n0 -> n1;\n",
+                "    n2 [label=\"Assignment\"];\n",
+                "    n1 -> n2;\n",
+                "    n3 [label=\"Identifier(x)\"];\n",
+                "    n2 -> n3 [label=\"name\"];\n",
+                "    n4 [label=\"StringLiteral(\\\"a\\\")\"];\n",
+                "    n2 -> n4 [label=\"value\"];\n",
+                "}\n"
+            )
+        );
+        assert_eq!(escape("say \"hi\"\n"), "say \\\"hi\\\"\\n");
+    }
 }
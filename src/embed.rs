@@ -0,0 +1,412 @@
+//! The API for Rust programs that embed the language. `Value` and
+//! `EvalError` own their data and do not expose the interpreter's object
+//! model, so they stay the same when the internals change.
+
+use crate::compiler::Compiler;
+use crate::errors::Error;
+use crate::gc::Gc;
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{KyaObject, KyaObjectRef};
+use crate::objects::bytes_object::bytes_new;
+use crate::objects::hash_object::hash_from_pairs;
+use crate::objects::host_function_object::{host_function_new, ArgType, HostFn, HostValue};
+use crate::objects::list_object::list_new;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::number_object::number_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::bool_to_bool_object;
+use crate::objects::utils::object_to_string_repr;
+use crate::parser::Parser;
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A value computed by a program, copied out of the interpreter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    None,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    /// The entries of a hash, in its iteration order.
+    Hash(Vec<(Value, Value)>),
+    /// Any other object, as its repr.
+    Object(String),
+}
+
+impl Value {
+    /// Copies `object` and everything it contains. Must be called with the
+    /// interpreter lock held, since other objects are represented by calling
+    /// their `__repr__`.
+    pub fn from_object(object: &KyaObjectRef) -> Result<Value, Error> {
+        Value::copy(object, &mut vec![])
+    }
+
+    /// Makes the object `self` was copied from, or an equal one. An `Object`
+    /// cannot be made back from its repr.
+    pub fn into_object(self) -> Result<KyaObjectRef, Error> {
+        Ok(match self {
+            Value::None => kya_none(),
+            Value::Bool(value) => bool_to_bool_object(value),
+            Value::Number(value) => number_new(value),
+            Value::String(value) => string_new(&value),
+            Value::Bytes(value) => bytes_new(value),
+            Value::List(items) => list_new(
+                items
+                    .into_iter()
+                    .map(Value::into_object)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Hash(entries) => hash_from_pairs(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((key.into_object()?, value.into_object()?)))
+                    .collect::<Result<_, Error>>()?,
+            )?,
+            Value::Object(repr) => {
+                return Err(Error::ValueError(format!(
+                    "Cannot make an object from its repr {}",
+                    repr
+                )))
+            }
+        })
+    }
+
+    fn copy(object: &KyaObjectRef, containers: &mut Vec<KyaObjectRef>) -> Result<Value, Error> {
+        if containers
+            .iter()
+            .any(|container| Gc::ptr_eq(container, object))
+        {
+            return Err(Error::ValueError(
+                "Cannot copy a value that contains itself".to_string(),
+            ));
+        }
+
+        let (items, entries) = match &*object.lock().unwrap() {
+            KyaObject::NoneObject(_) => return Ok(Value::None),
+            KyaObject::BoolObject(bool_object) => return Ok(Value::Bool(bool_object.value)),
+            KyaObject::NumberObject(number_object) => {
+                return Ok(Value::Number(number_object.value))
+            }
+            KyaObject::StringObject(string_object) => {
+                return Ok(Value::String(string_object.value.clone()))
+            }
+            KyaObject::BytesObject(bytes_object) => {
+                return Ok(Value::Bytes(bytes_object.value.clone()))
+            }
+            KyaObject::ByteViewObject(view) => return Ok(Value::Bytes(view.as_slice().to_vec())),
+            KyaObject::ListObject(list_object) => (
+                Some(list_object.items.iter().cloned().collect::<Vec<_>>()),
+                None,
+            ),
+            KyaObject::HashObject(hash_object) => (
+                None,
+                Some(
+                    hash_object
+                        .items
+                        .lock()
+                        .unwrap()
+                        .values()
+                        .map(|item| (item.key.clone(), item.value.clone()))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            _ => (None, None),
+        };
+
+        containers.push(object.clone());
+
+        let value = match (items, entries) {
+            (Some(items), _) => Value::List(
+                items
+                    .iter()
+                    .map(|item| Value::copy(item, containers))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (_, Some(entries)) => Value::Hash(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            Value::copy(key, containers)?,
+                            Value::copy(value, containers)?,
+                        ))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            _ => Value::Object(object_to_string_repr(object)?),
+        };
+
+        containers.pop();
+
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The source could not be read as a program.
+    Syntax,
+    /// The program could not be compiled.
+    Compilation,
+    /// The program raised while running.
+    Runtime,
+}
+
+/// Why a program failed. `code` is one of the codes in `diagnostics`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError {
+    kind: ErrorKind,
+    code: &'static str,
+    exception_type: Option<String>,
+    message: String,
+}
+
+impl EvalError {
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// The type of the exception the program raised, like "TimeoutError".
+    pub fn exception_type(&self) -> Option<&str> {
+        self.exception_type.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<Error> for EvalError {
+    fn from(error: Error) -> Self {
+        let code = error.code();
+
+        let (kind, exception_type, message) = match error {
+            Error::LexerError(lexer_error) => (
+                ErrorKind::Syntax,
+                None,
+                format!(
+                    "{} at line {}, column {}",
+                    lexer_error.diagnostic, lexer_error.line, lexer_error.column
+                ),
+            ),
+            Error::ParserError(diagnostic) => (ErrorKind::Syntax, None, diagnostic.to_string()),
+            Error::SyntaxError(message) => (ErrorKind::Syntax, None, message),
+            Error::CompilationError(diagnostic) => {
+                (ErrorKind::Compilation, None, diagnostic.to_string())
+            }
+            Error::Exception(exception_type, message, ..) => {
+                (ErrorKind::Runtime, Some(exception_type), message)
+            }
+            Error::RuntimeError(message)
+            | Error::UndefinedVariable(message)
+            | Error::TypeError(message)
+            | Error::ValueError(message)
+            | Error::BreakInterrupt(message)
+            | Error::NotImplemented(message) => (ErrorKind::Runtime, None, message),
+        };
+
+        EvalError {
+            kind,
+            code,
+            exception_type,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.exception_type {
+            Some(exception_type) => write!(f, "{}: {}", exception_type, self.message),
+            None => write!(f, "[{}] {}", self.code, self.message),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+enum Member {
+    Function {
+        params: Vec<ArgType>,
+        returns: ArgType,
+        function: HostFn,
+    },
+    Value(HostValue),
+}
+
+/// The members of a module registered with `Interpreter::register_module` or
+/// by a native extension. They are kept as plain data and only turned into
+/// objects by `build`, so that the objects of an extension's module are made
+/// by the interpreter rather than by the extension's copy of this crate.
+#[derive(Default)]
+pub struct ModuleBuilder {
+    members: Vec<(String, Member)>,
+}
+
+impl ModuleBuilder {
+    pub fn new() -> Self {
+        ModuleBuilder::default()
+    }
+
+    /// Adds a function, as `Interpreter::register_fn` does.
+    pub fn function(
+        &mut self,
+        name: &str,
+        params: &[ArgType],
+        returns: ArgType,
+        function: impl Fn(Vec<HostValue>) -> Result<HostValue, Error> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.members.push((
+            name.to_string(),
+            Member::Function {
+                params: params.to_vec(),
+                returns,
+                function: Arc::new(function),
+            },
+        ));
+        self
+    }
+
+    pub fn value(&mut self, name: &str, value: HostValue) -> &mut Self {
+        self.members.push((name.to_string(), Member::Value(value)));
+        self
+    }
+
+    pub fn build(self, name: &str) -> KyaObjectRef {
+        let members: Vec<(String, KyaObjectRef)> = self
+            .members
+            .into_iter()
+            .map(|(member_name, member)| {
+                let object = match member {
+                    Member::Function {
+                        params,
+                        returns,
+                        function,
+                    } => host_function_new(&member_name, &params, returns, move |args| {
+                        function(args)
+                    }),
+                    Member::Value(value) => value.into_object(),
+                };
+
+                (member_name, object)
+            })
+            .collect();
+
+        module_from_functions(
+            name,
+            members
+                .iter()
+                .map(|(member_name, object)| (member_name.as_str(), object.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Runs `source` in a new interpreter and returns the value of its last
+/// expression statement, or `Value::None`.
+///
+/// ```
+/// assert_eq!(kyanite::eval_str("1 + 2"), Ok(kyanite::Value::Number(3.0)));
+/// ```
+pub fn eval_str(source: &str) -> Result<Value, EvalError> {
+    let ast = Parser::new(Lexer::new(source.to_string())).parse()?;
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.keep_result();
+    compiler.compile()?;
+
+    let result = Interpreter::new(".").eval(&compiler.get_output())?;
+
+    kya_acquire_lock();
+    let value = Value::from_object(&result);
+    kya_release_lock();
+
+    Ok(value?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_str() {
+        assert_eq!(
+            eval_str("items = List()\nitems.append(\"a\")\nitems.append(None)\nitems"),
+            Ok(Value::List(vec![
+                Value::String("a".to_string()),
+                Value::None
+            ]))
+        );
+
+        let error = eval_str("print(1))").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntax);
+        assert_eq!(error.code(), "K0202");
+
+        let error = eval_str("1 +").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntax);
+        assert_eq!(error.code(), "K0203");
+
+        let error = eval_str("print(\"unterminated)").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntax);
+        assert_eq!(error.code(), "K0102");
+
+        let error = eval_str("raise TimeoutError(\"slow\")").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Runtime);
+        assert_eq!(error.exception_type(), Some("TimeoutError"));
+        assert_eq!(error.message(), "slow");
+
+        let error = eval_str("import json\njson.parse(\"[1,\")").unwrap_err();
+        assert_eq!(error.code(), "K0403");
+        assert_eq!(error.exception_type(), Some("ValueError"));
+        assert!(error.message().starts_with("Invalid JSON"));
+
+        let error = eval_str("break").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Compilation);
+        assert_eq!(error.code(), "K0312");
+
+        // There is no continue statement, so it is an undefined name.
+        let error = eval_str("continue").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Runtime);
+    }
+
+    #[test]
+    fn test_register_natives() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut interpreter = Interpreter::new(".");
+
+        interpreter.register_fn("double", &[ArgType::Number], ArgType::Number, |args| {
+            Ok(HostValue::Number(args[0].as_number()? * 2.0))
+        });
+
+        let counted = calls.clone();
+        interpreter.register_module("host", move |module| {
+            module.function("bump", &[], ArgType::None, move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+                Ok(HostValue::None)
+            });
+        });
+
+        let ast = Parser::new(Lexer::new(
+            "host.bump()\nhost.bump()\nassert(double(21) == 42)\n".to_string(),
+        ))
+        .parse()
+        .unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        assert!(interpreter.eval(&compiler.get_output()).is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,33 +1,36 @@
 use colored::Colorize;
 
+use crate::diagnostics::{self, Diagnostic};
 use crate::objects::base::KyaObjectRef;
 
 #[derive(Debug, Clone)]
 pub enum Error {
     RuntimeError(String),
-    ParserError(String),
+    ParserError(Diagnostic),
     UndefinedVariable(String),
     LexerError(LexerError),
     TypeError(String),
     ValueError(String),
     BreakInterrupt(String),
     NotImplemented(String),
-    CompilationError(String),
+    CompilationError(Diagnostic),
     SyntaxError(String),
-    Exception(String, String),
+    // The type, message and cause of an uncaught exception, and the code of
+    // the error it was raised for.
+    Exception(String, String, Option<Box<Error>>, &'static str),
 }
 
 #[derive(Debug, Clone)]
 pub struct LexerError {
-    pub message: String,
+    pub diagnostic: Diagnostic,
     pub line: usize,
     pub column: usize,
 }
 
 impl LexerError {
-    pub fn new(message: String, line: usize, column: usize) -> Self {
+    pub fn new(diagnostic: Diagnostic, line: usize, column: usize) -> Self {
         LexerError {
-            message,
+            diagnostic,
             line,
             column,
         }
@@ -38,50 +41,130 @@ impl std::fmt::Display for LexerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Invalid symbol at line {}, column {}: {}",
-            self.line, self.column, self.message
+            "Invalid symbol[{}] at line {}, column {}: {}",
+            self.diagnostic.code(),
+            self.line,
+            self.column,
+            self.diagnostic
         )
     }
 }
 
+impl Error {
+    /// An exception of `exception_type` raised by a builtin, with the code
+    /// that every exception raised by a program shares.
+    pub fn exception(exception_type: impl Into<String>, message: impl Into<String>) -> Self {
+        Error::Exception(
+            exception_type.into(),
+            message.into(),
+            None,
+            diagnostics::UNCAUGHT_EXCEPTION,
+        )
+    }
+
+    /// The stable code of the error, listed in `diagnostics`. Exceptions
+    /// raised by scripts share one code; their type tells them apart.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::RuntimeError(_) => diagnostics::RUNTIME_ERROR,
+            Error::ParserError(diagnostic) | Error::CompilationError(diagnostic) => {
+                diagnostic.code()
+            }
+            Error::UndefinedVariable(_) => diagnostics::UNDEFINED_VARIABLE,
+            Error::LexerError(lexer_error) => lexer_error.diagnostic.code(),
+            Error::TypeError(_) => diagnostics::TYPE_ERROR,
+            Error::ValueError(_) => diagnostics::VALUE_ERROR,
+            Error::BreakInterrupt(_) => diagnostics::BREAK_INTERRUPT,
+            Error::NotImplemented(_) => diagnostics::NOT_IMPLEMENTED,
+            Error::SyntaxError(_) => diagnostics::SYNTAX_ERROR,
+            Error::Exception(.., code) => code,
+        }
+    }
+
+    /// The message alone, without the kind of error and its code.
+    pub fn message(&self) -> String {
+        match self {
+            Error::RuntimeError(message)
+            | Error::UndefinedVariable(message)
+            | Error::TypeError(message)
+            | Error::ValueError(message)
+            | Error::BreakInterrupt(message)
+            | Error::NotImplemented(message)
+            | Error::SyntaxError(message)
+            | Error::Exception(_, message, ..) => message.clone(),
+            Error::ParserError(diagnostic) | Error::CompilationError(diagnostic) => {
+                diagnostic.to_string()
+            }
+            Error::LexerError(lexer_error) => format!(
+                "{} at line {}, column {}",
+                lexer_error.diagnostic, lexer_error.line, lexer_error.column
+            ),
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = self.code();
+
         match self {
-            Error::RuntimeError(msg) => write!(f, "{}", format_error("Runtime Error", msg)),
-            Error::ParserError(msg) => write!(f, "{}", format_error("Parser Error", msg)),
+            Error::RuntimeError(msg) => write!(f, "{}", format_error("Runtime Error", code, msg)),
+            Error::ParserError(diagnostic) => write!(
+                f,
+                "{}",
+                format_error("Parser Error", code, &diagnostic.to_string())
+            ),
             Error::UndefinedVariable(var) => write!(
                 f,
                 "{}: {}",
-                "Undefined Variable".purple().bold(),
+                format!("Undefined Variable[{}]", code).purple().bold(),
                 var.red().bold()
             ),
             Error::LexerError(lexer_error) => write!(f, "{}", lexer_error),
-            Error::TypeError(msg) => write!(f, "{}", format_error("Type Error", msg)),
-            Error::ValueError(msg) => write!(f, "{}", format_error("Value Error", msg)),
+            Error::TypeError(msg) => write!(f, "{}", format_error("Type Error", code, msg)),
+            Error::ValueError(msg) => write!(f, "{}", format_error("Value Error", code, msg)),
             Error::BreakInterrupt(msg) => write!(
                 f,
                 "{}: {}",
-                "Break Interrupt".purple().bold(),
+                format!("Break Interrupt[{}]", code).purple().bold(),
                 msg.red().bold()
             ),
             Error::NotImplemented(msg) => write!(
                 f,
                 "{}: {}",
-                "Not Implemented".purple().bold(),
+                format!("Not Implemented[{}]", code).purple().bold(),
                 msg.red().bold()
             ),
-            Error::CompilationError(msg) => write!(f, "{}", format_error("Compilation Error", msg)),
-            Error::SyntaxError(msg) => write!(f, "{}", format_error("Syntax Error", msg)),
-            Error::Exception(exception_type, message) => write!(
+            Error::CompilationError(diagnostic) => write!(
                 f,
-                "{}: {}",
-                exception_type.purple().bold(),
-                message.red().bold()
+                "{}",
+                format_error("Compilation Error", code, &diagnostic.to_string())
             ),
+            Error::SyntaxError(msg) => write!(f, "{}", format_error("Syntax Error", code, msg)),
+            Error::Exception(exception_type, message, cause, _) => {
+                if let Some(cause) = cause {
+                    write!(
+                        f,
+                        "{}\n\nThe above exception was the direct cause of the following exception:\n\n",
+                        cause
+                    )?;
+                }
+
+                write!(
+                    f,
+                    "{}: {}",
+                    format!("{}[{}]", exception_type, code).purple().bold(),
+                    message.red().bold()
+                )
+            }
         }
     }
 }
 
-fn format_error(error_type: &str, message: &str) -> String {
-    format!("{}: {}", error_type.purple().bold(), message.purple())
+fn format_error(error_type: &str, code: &str, message: &str) -> String {
+    format!(
+        "{}: {}",
+        format!("{}[{}]", error_type, code).purple().bold(),
+        message.purple()
+    )
 }
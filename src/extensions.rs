@@ -0,0 +1,156 @@
+//! Native extensions: shared libraries that add a module written in Rust.
+//! When there is no module of that name already, `import name` looks for
+//! `libname.so` (`libname.dylib` on macOS, `name.dll` on Windows) in the
+//! search paths, checks that it was built for this interpreter's
+//! `ABI_VERSION` and calls its `kya_module_init` to fill in the module.
+//!
+//! An extension is a `cdylib` built against the same version of this crate
+//! and with the same compiler as the interpreter that loads it. The
+//! `extension!` macro exports both entry points:
+//!
+//! ```ignore
+//! use kyanite::objects::host_function_object::{ArgType, HostValue};
+//!
+//! kyanite::extension!(|module| {
+//!     module.function("square", &[ArgType::Number], ArgType::Number, |args| {
+//!         Ok(HostValue::Number(args[0].as_number()?.powi(2)))
+//!     });
+//! });
+//! ```
+//!
+//! Libraries stay loaded until the process exits.
+
+use crate::capabilities::{self, Capability};
+use crate::embed::ModuleBuilder;
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+
+use libloading::{Library, Symbol};
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+
+/// The version of the interface between the interpreter and its extensions,
+/// raised whenever the entry points or `ModuleBuilder` change.
+pub const ABI_VERSION: u32 = 1;
+
+pub const VERSION_SYMBOL: &str = "kya_extension_abi_version";
+pub const INIT_SYMBOL: &str = "kya_module_init";
+
+/// Returns the `ABI_VERSION` the extension was built with.
+pub type AbiVersion = unsafe extern "C" fn() -> u32;
+
+/// Fills in the `ModuleBuilder` it is given a pointer to, and returns false
+/// if that panicked.
+pub type ModuleInit = unsafe extern "C" fn(module: *mut c_void) -> bool;
+
+/// Exports the entry points of an extension whose module is filled in by
+/// `$init`, a `fn(&mut ModuleBuilder)`.
+#[macro_export]
+macro_rules! extension {
+    ($init:expr) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn kya_extension_abi_version() -> u32 {
+            $crate::extensions::ABI_VERSION
+        }
+
+        /// # Safety
+        ///
+        /// `module` must point to a `ModuleBuilder` of the same version of
+        /// the crate, as the interpreter's loader guarantees.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn kya_module_init(module: *mut ::std::ffi::c_void) -> bool {
+            let init: fn(&mut $crate::embed::ModuleBuilder) = $init;
+            let module = unsafe { &mut *(module as *mut $crate::embed::ModuleBuilder) };
+
+            ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| init(module))).is_ok()
+        }
+    };
+}
+
+pub fn library_name(module: &str) -> String {
+    format!("{}{}{}", DLL_PREFIX, module, DLL_SUFFIX)
+}
+
+/// Loads the extension named `module` from the first of `search_paths` that
+/// has it, or returns `None` if none does.
+pub fn load(module: &str, search_paths: &[PathBuf]) -> Result<Option<KyaObjectRef>, Error> {
+    let Some(path) = search_paths
+        .iter()
+        .map(|directory| directory.join(library_name(module)))
+        .find(|path| path.is_file())
+    else {
+        return Ok(None);
+    };
+
+    capabilities::require(Capability::Extensions, &format!("import {}", module))?;
+
+    let mut builder = ModuleBuilder::new();
+
+    init(&path, &mut builder).map_err(|reason| {
+        Error::RuntimeError(format!(
+            "Cannot load the extension {}: {}",
+            path.display(),
+            reason
+        ))
+    })?;
+
+    Ok(Some(builder.build(module)))
+}
+
+fn init(path: &Path, builder: &mut ModuleBuilder) -> Result<(), String> {
+    // SAFETY: loading a library runs its initializers; extensions are trusted
+    // code, which the Extensions capability stands for.
+    let library = unsafe { Library::new(path) }.map_err(|error| error.to_string())?;
+
+    // SAFETY: the symbols are declared with these types by `extension!`, and
+    // the library outlives every use of them.
+    unsafe {
+        let version: Symbol<AbiVersion> = library
+            .get(VERSION_SYMBOL.as_bytes())
+            .map_err(|_| format!("it does not export {}", VERSION_SYMBOL))?;
+        let version = version();
+
+        if version != ABI_VERSION {
+            return Err(format!(
+                "it was built for extension ABI {}, this interpreter needs {}",
+                version, ABI_VERSION
+            ));
+        }
+
+        let init: Symbol<ModuleInit> = library
+            .get(INIT_SYMBOL.as_bytes())
+            .map_err(|_| format!("it does not export {}", INIT_SYMBOL))?;
+
+        if !init(builder as *mut ModuleBuilder as *mut c_void) {
+            return Err(format!("its {} panicked", INIT_SYMBOL));
+        }
+    }
+
+    // The module's functions are code of the library, so it is never closed.
+    std::mem::forget(library);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_extension() {
+        let root = std::env::temp_dir().join(format!("kyanite-ext-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join(library_name("broken")), "not a library").unwrap();
+
+        let search_paths = vec![root.clone()];
+
+        assert!(load("missing", &search_paths).unwrap().is_none());
+        assert!(matches!(
+            load("broken", &search_paths),
+            Err(Error::RuntimeError(message)) if message.starts_with("Cannot load the extension")
+        ));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,579 @@
+//! `kyanite fmt`: prints a program back from its AST in one canonical
+//! layout. Blocks are indented by four spaces, binary operators and `=` are
+//! surrounded by single spaces and arguments are separated by ", ". Comments
+//! are kept, above the statement they preceded or at the end of its line, and
+//! runs of blank lines between statements become a single one.
+//!
+//! The result is parsed again before it is returned, so a program is never
+//! rewritten into one that means something else.
+
+use crate::ast::{self, ASTNode, Operator};
+use crate::errors::Error;
+use crate::internal::number::{format_number, parse_number};
+use crate::lexer::{unescape_string_literal, Lexer, Token, TokenType};
+use crate::parser::Parser;
+
+use std::collections::HashMap;
+
+const INDENT: &str = "    ";
+
+pub fn format_source(source: &str) -> Result<String, Error> {
+    let mut lexer = Lexer::new(source.to_string());
+    let mut tokens = vec![];
+
+    // The tokens give the layout and comments that the AST leaves out.
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+
+    let ast = Parser::new(Lexer::new(source.to_string())).parse()?;
+
+    let mut formatter = Formatter {
+        output: String::new(),
+        indent: 0,
+        comments: lexer.comments().to_vec(),
+        next_comment: 0,
+        layout: Layout::new(&tokens),
+        spellings: string_spellings(source),
+        number_spellings: number_spellings(&tokens),
+        last_line: 0,
+        at_block_start: true,
+    };
+
+    if let Some(shebang) = source.lines().next().filter(|line| line.starts_with("#!")) {
+        formatter.output.push_str(shebang);
+        formatter.output.push('\n');
+        formatter.last_line = 1;
+    }
+
+    if let ASTNode::Module(module) = &ast
+        && let ASTNode::Block(block) = &*module.block
+    {
+        formatter.block(block, None);
+    }
+
+    formatter.comments_before(usize::MAX);
+
+    let output = formatter.output;
+
+    if Parser::new(Lexer::new(output.clone())).parse().ok() != Some(ast) {
+        return Err(Error::RuntimeError(
+            "Formatting would change the meaning of the program".to_string(),
+        ));
+    }
+
+    Ok(output)
+}
+
+// Where things are in the source that the AST does not record.
+struct Layout {
+    // The line of the `end` that closes the block opened on a line.
+    ends: HashMap<usize, usize>,
+    // The line of the `ensure` of the `try` on a line.
+    ensures: HashMap<usize, usize>,
+    // The last line of the statement that starts on a line.
+    last_lines: HashMap<usize, usize>,
+}
+
+impl Layout {
+    fn new(tokens: &[Token]) -> Self {
+        let mut layout = Layout {
+            ends: HashMap::new(),
+            ensures: HashMap::new(),
+            last_lines: HashMap::new(),
+        };
+
+        let mut blocks = vec![];
+        let mut start = None;
+        let mut last = 0;
+        let mut brackets = 0;
+
+        for token in tokens {
+            match token.kind {
+                TokenType::Def
+                | TokenType::Class
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Try
+                | TokenType::With => blocks.push(token.line),
+                TokenType::Ensure => {
+                    if let Some(opener) = blocks.last() {
+                        layout.ensures.insert(*opener, token.line);
+                    }
+                }
+                TokenType::End => {
+                    if let Some(opener) = blocks.pop() {
+                        layout.ends.insert(opener, token.line);
+                    }
+                }
+                TokenType::LeftParen | TokenType::LeftBracket => brackets += 1,
+                TokenType::RightParen | TokenType::RightBracket => brackets -= 1,
+                _ => {}
+            }
+
+            if token.kind == TokenType::Newline {
+                if brackets <= 0
+                    && let Some(start) = start.take()
+                {
+                    layout.last_lines.insert(start, last);
+                }
+
+                continue;
+            }
+
+            start.get_or_insert(token.line);
+            last = token.line;
+        }
+
+        if let Some(start) = start {
+            layout.last_lines.insert(start, last);
+        }
+
+        layout
+    }
+
+    fn last_line(&self, line: usize) -> usize {
+        self.last_lines.get(&line).copied().unwrap_or(line)
+    }
+}
+
+struct Formatter {
+    output: String,
+    indent: usize,
+    comments: Vec<Token>,
+    next_comment: usize,
+    layout: Layout,
+    // The last line of the source written so far.
+    last_line: usize,
+    at_block_start: bool,
+    // How each string in the source was written, by its value.
+    spellings: HashMap<String, String>,
+    // The same for the numbers written with an exponent or underscores, by
+    // the bits of their value.
+    number_spellings: HashMap<u64, String>,
+}
+
+impl Formatter {
+    // Starts a line for something that was on `line` of the source, keeping
+    // a blank line before it if the source had any.
+    fn start_line(&mut self, line: usize) {
+        if !self.at_block_start && line > self.last_line + 1 {
+            self.output.push('\n');
+        }
+
+        self.output.push_str(&INDENT.repeat(self.indent));
+        self.at_block_start = false;
+    }
+
+    fn end_line(&mut self, line: usize) {
+        if let Some(comment) = self.comments.get(self.next_comment)
+            && comment.line == line
+        {
+            self.output
+                .push_str(&format!("  #{}", comment.value.trim_end()));
+            self.next_comment += 1;
+        }
+
+        self.output.push('\n');
+        self.last_line = self.last_line.max(line);
+    }
+
+    // Writes the comments that come before `line` on lines of their own.
+    fn comments_before(&mut self, line: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line >= line {
+                break;
+            }
+
+            let comment = comment.clone();
+            self.next_comment += 1;
+
+            self.start_line(comment.line);
+            self.output
+                .push_str(&format!("#{}", comment.value.trim_end()));
+            self.output.push('\n');
+            self.last_line = comment.line;
+        }
+    }
+
+    // Writes the statements of `block`, and the comments in it before the
+    // line that closes it.
+    fn block(&mut self, block: &ast::Block, closed_on: Option<usize>) {
+        self.at_block_start = true;
+
+        for (index, statement) in block.statements.iter().enumerate() {
+            let line = block.line(index).unwrap_or(self.last_line);
+
+            self.statement(statement, line);
+        }
+
+        if let Some(line) = closed_on {
+            self.comments_before(line);
+        }
+    }
+
+    fn nested(&mut self, body: &ASTNode, closed_on: Option<usize>) {
+        self.indent += 1;
+
+        match body {
+            ASTNode::Block(block) => self.block(block, closed_on),
+            statement => self.statement(statement, self.last_line),
+        }
+
+        self.indent -= 1;
+    }
+
+    // Writes a line that opens or continues a block, such as `if x` or
+    // `ensure`.
+    fn header(&mut self, text: &str, line: usize) {
+        let last_line = self.layout.last_line(line);
+
+        self.comments_before(last_line);
+        self.start_line(line);
+        self.output.push_str(text);
+        self.end_line(last_line);
+    }
+
+    fn end(&mut self, opened_on: usize) {
+        let line = self.layout.ends.get(&opened_on).copied();
+
+        self.start_line(line.unwrap_or(self.last_line));
+        self.output.push_str("end");
+        self.end_line(line.unwrap_or(self.last_line));
+    }
+
+    fn statement(&mut self, statement: &ASTNode, line: usize) {
+        let ends = self.layout.ends.get(&line).copied();
+
+        match statement {
+            ASTNode::MethodDef(method_def) => {
+                let header = if method_def.parameters.is_empty() {
+                    format!("def {}", method_def.name)
+                } else {
+                    format!(
+                        "def {}({})",
+                        method_def.name,
+                        self.list(&method_def.parameters)
+                    )
+                };
+
+                self.header(&header, line);
+                self.nested(&method_def.body, ends);
+                self.end(line);
+            }
+            ASTNode::ClassDef(class_def) => {
+                self.header(&format!("class {}", class_def.name), line);
+                self.nested(&class_def.body, ends);
+                self.end(line);
+            }
+            ASTNode::If(if_node) => {
+                self.header(&format!("if {}", self.expression(&if_node.test)), line);
+                self.nested(&if_node.body, ends);
+                self.end(line);
+            }
+            ASTNode::While(while_node) => {
+                self.header(
+                    &format!("while {}", self.expression(&while_node.condition)),
+                    line,
+                );
+                self.nested(&while_node.body, ends);
+                self.end(line);
+            }
+            ASTNode::With(with) => {
+                let header = match &with.name {
+                    Some(name) => format!("with {} as {}", self.expression(&with.value), name),
+                    None => format!("with {}", self.expression(&with.value)),
+                };
+
+                self.header(&header, line);
+                self.nested(&with.body, ends);
+                self.end(line);
+            }
+            ASTNode::Try(try_node) => {
+                let ensure = self.layout.ensures.get(&line).copied();
+
+                self.header("try", line);
+                self.nested(&try_node.body, ensure);
+                self.header("ensure", ensure.unwrap_or(self.last_line));
+                self.nested(&try_node.ensure, ends);
+                self.end(line);
+            }
+            statement => {
+                let last_line = self.layout.last_line(line);
+
+                self.comments_before(last_line);
+                self.start_line(line);
+                self.output.push_str(&self.simple_statement(statement));
+                self.end_line(last_line);
+            }
+        }
+    }
+}
+
+impl Formatter {
+    fn simple_statement(&self, statement: &ASTNode) -> String {
+        match statement {
+            ASTNode::Break() => "break".to_string(),
+            ASTNode::Import(import) => format!("import {}", import.name),
+            ASTNode::Return(return_node) => match &return_node.value {
+                Some(value) => format!("return {}", self.expression(value)),
+                None => "return".to_string(),
+            },
+            ASTNode::Raise(raise) => match (&raise.message, &raise.cause) {
+                (Some(message), Some(cause)) => {
+                    format!(
+                        "raise {} from {}",
+                        self.expression(message),
+                        self.expression(cause)
+                    )
+                }
+                (Some(message), None) => format!("raise {}", self.expression(message)),
+                _ => "raise".to_string(),
+            },
+            expression_statement => self.expression(expression_statement),
+        }
+    }
+
+    fn list(&self, nodes: &[Box<ASTNode>]) -> String {
+        nodes
+            .iter()
+            .map(|node| self.expression(node))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // The grammar has no parentheses for grouping, so the AST of a parsed
+    // program always reads back the same way without adding any.
+    fn expression(&self, node: &ASTNode) -> String {
+        match node {
+            ASTNode::Identifier(identifier) => identifier.name.to_string(),
+            ASTNode::StringLiteral(value) => match self.spellings.get(value) {
+                Some(spelling) => spelling.clone(),
+                None => string_literal(value),
+            },
+            ASTNode::NumberLiteral(value) => match self.number_spellings.get(&value.to_bits()) {
+                Some(spelling) => spelling.clone(),
+                None => format_number(*value),
+            },
+            ASTNode::MethodCall(call) => {
+                format!(
+                    "{}({})",
+                    self.expression(&call.name),
+                    self.list(&call.arguments)
+                )
+            }
+            ASTNode::Assignment(assignment) => format!(
+                "{} = {}",
+                self.expression(&assignment.name),
+                self.expression(&assignment.value)
+            ),
+            ASTNode::Attribute(attribute) => {
+                format!("{}.{}", self.expression(&attribute.name), attribute.value)
+            }
+            ASTNode::Slice(slice) => {
+                let bound = |bound: &Option<Box<ASTNode>>| {
+                    bound
+                        .as_ref()
+                        .map(|bound| self.expression(bound))
+                        .unwrap_or_default()
+                };
+
+                let step = match &slice.step {
+                    Some(step) => format!(":{}", self.expression(step)),
+                    None => String::new(),
+                };
+
+                format!(
+                    "{}[{}..{}{}]",
+                    self.expression(&slice.value),
+                    bound(&slice.start),
+                    bound(&slice.end),
+                    step
+                )
+            }
+            ASTNode::Compare(compare) => format!(
+                "{} {} {}",
+                self.expression(&compare.left),
+                operator(&compare.operator),
+                self.expression(&compare.right)
+            ),
+            ASTNode::BinOp(bin_op) => format!(
+                "{} {} {}",
+                self.expression(&bin_op.left),
+                operator(&bin_op.operator),
+                self.expression(&bin_op.right)
+            ),
+            ASTNode::UnaryOp(unary_op) => {
+                let sign = if unary_op.operator == TokenType::Minus {
+                    "-"
+                } else {
+                    "+"
+                };
+
+                format!("{}{}", sign, self.expression(&unary_op.operand))
+            }
+            statement => self.simple_statement(statement),
+        }
+    }
+}
+
+fn operator(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Equal => "==",
+        Operator::Gt => ">",
+        Operator::Lt => "<",
+        Operator::Gte => ">=",
+        Operator::Lte => "<=",
+        Operator::Neq => "!=",
+        Operator::Is => "is",
+        Operator::IsNot => "is not",
+        Operator::Plus => "+",
+        Operator::Minus => "-",
+    }
+}
+
+// The string literals of `source` as they were written, quotes included,
+// by their value.
+fn string_spellings(source: &str) -> HashMap<String, String> {
+    let mut spellings = HashMap::new();
+    let mut chars = source.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '#' => {
+                chars.find(|(_, c)| *c == '\n');
+            }
+            '"' | '\'' => {
+                let mut end = None;
+
+                while let Some((index, next)) = chars.next() {
+                    if next == '\\' {
+                        chars.next();
+                    } else if next == c {
+                        end = Some(index);
+                        break;
+                    }
+                }
+
+                let Some(end) = end else {
+                    break;
+                };
+
+                if let Ok(value) = unescape_string_literal(&source[start + 1..end]) {
+                    spellings
+                        .entry(value)
+                        .or_insert_with(|| source[start..=end].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spellings
+}
+
+fn number_spellings(tokens: &[Token]) -> HashMap<u64, String> {
+    let mut spellings = HashMap::new();
+
+    for token in tokens {
+        if token.kind != TokenType::NumberLiteral || !token.value.contains(['_', 'e', 'E']) {
+            continue;
+        }
+
+        if let Ok(value) = parse_number(&token.value.replace('_', "")) {
+            spellings
+                .entry(value.to_bits())
+                .or_insert_with(|| token.value.clone());
+        }
+    }
+
+    spellings
+}
+
+// Double quotes are preferred, and single ones used when that saves
+// escaping the text.
+fn string_literal(value: &str) -> String {
+    let quote = if value.contains('"') && !value.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    let mut literal = String::from(quote);
+
+    for c in value.chars() {
+        match c {
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            '\\' => literal.push_str("\\\\"),
+            c if c == quote => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            c if c.is_ascii_control() => literal.push_str(&format!("\\x{:02x}", c as u8)),
+            c if c.is_control() => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+
+    literal.push(quote);
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_source() {
+        let source = concat!(
+            "# Adds things.\n",
+            "def add(a,b)\n",
+            "  total=a+b   # sum\n",
+            "\n",
+            "\n",
+            "  return total\n",
+            "end\n",
+            "try\n",
+            "        print(add(1,-2), 'say \"hi\"')\n",
+            "ensure\n",
+            "  # always\n",
+            "  x=items[1..:2]\n",
+            "end # done\n",
+            "if x   is not None\n",
+            "print(x,1_000,2.5e-3)\n",
+            "end\n",
+        );
+        let expected = concat!(
+            "# Adds things.\n",
+            "def add(a, b)\n",
+            "    total = a + b  # sum\n",
+            "\n",
+            "    return total\n",
+            "end\n",
+            "try\n",
+            "    print(add(1, -2), 'say \"hi\"')\n",
+            "ensure\n",
+            "    # always\n",
+            "    x = items[1..:2]\n",
+            "end  # done\n",
+            "if x is not None\n",
+            "    print(x, 1_000, 2.5e-3)\n",
+            "end\n",
+        );
+
+        assert_eq!(format_source(source).unwrap(), expected);
+        assert_eq!(format_source(expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_string_literal_round_trips() {
+        for value in ["plain", "say \"hi\"", "it's \"quoted\"", "a\\b\n\0\x07"] {
+            let literal = string_literal(value);
+            let content = &literal[1..literal.len() - 1];
+
+            assert_eq!(unescape_string_literal(content), Ok(value.to_string()));
+        }
+    }
+}
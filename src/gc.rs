@@ -0,0 +1,436 @@
+//! A cycle collector for the objects that hold other objects. Objects are
+//! reference counted, so anything that is not part of a cycle is freed as
+//! soon as its last reference goes away; what the collector adds is freeing
+//! groups of objects that only keep each other alive, such as an instance
+//! that stores one of its own bound methods.
+//!
+//! Containers are tracked when they are created. A collection subtracts the
+//! references that tracked objects hold to each other from their reference
+//! counts: whatever is left comes from outside, from frames, globals, types
+//! or Rust code, and makes the object reachable along with everything it
+//! refers to. The rest is garbage, and clearing the contents of its
+//! containers breaks the cycles so that reference counting frees it.
+//!
+//! Each runtime tracks and collects its own containers. Threads register
+//! the containers they create in batches, so a collection only sees those of
+//! other threads once their batch is handed over; until then they are left
+//! alone, along with anything they refer to.
+//!
+//! Objects whose type has a finalizer are never cleared, since the
+//! finalizer would see them emptied; cycles through them are kept alive.
+//!
+//! Objects live behind a `Gc` handle, the `KyaObjectRef` of the rest of the
+//! interpreter. Creating one is what puts an object on the heap, and the
+//! containers among them are tracked there and then.
+
+use crate::objects::base::{KyaObject, KyaObjectRef};
+use crate::objects::instance_object::Fields;
+use crate::runtime::{self, Runtime};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+// Collections happen at least this many allocations apart, and otherwise
+// once as many containers were created as survived the last one, so that
+// the work stays proportional to the allocations.
+const MIN_THRESHOLD: usize = 10_000;
+
+// How many containers a thread creates before handing them to its collector.
+const BATCH_SIZE: usize = 256;
+
+/// A handle to an object on the heap. Cloning it adds a reference to the
+/// object, which is freed once no handle is left or, when it is part of a
+/// cycle, once the collector finds it unreachable.
+pub struct Gc(Arc<Mutex<KyaObject>>);
+
+/// A handle that does not keep its object alive.
+pub struct WeakGc(Weak<Mutex<KyaObject>>);
+
+impl Gc {
+    pub fn new(object: KyaObject) -> Gc {
+        let tracked = is_container(&object);
+        let object = Gc(Arc::new(Mutex::new(object)));
+
+        if tracked {
+            track(&object);
+        }
+
+        object
+    }
+
+    /// Whether both handles point at the same object, which is what `is`
+    /// compares.
+    pub fn ptr_eq(this: &Gc, other: &Gc) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+
+    pub fn as_ptr(this: &Gc) -> *const Mutex<KyaObject> {
+        Arc::as_ptr(&this.0)
+    }
+
+    /// How many handles point at the object, this one included.
+    pub fn strong_count(this: &Gc) -> usize {
+        Arc::strong_count(&this.0)
+    }
+
+    pub fn downgrade(this: &Gc) -> WeakGc {
+        WeakGc(Arc::downgrade(&this.0))
+    }
+}
+
+impl Clone for Gc {
+    fn clone(&self) -> Self {
+        Gc(self.0.clone())
+    }
+}
+
+impl Deref for Gc {
+    type Target = Mutex<KyaObject>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl WeakGc {
+    pub fn upgrade(&self) -> Option<Gc> {
+        self.0.upgrade().map(Gc)
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.0.strong_count()
+    }
+}
+
+pub struct Collector {
+    tracked: Mutex<Vec<WeakGc>>,
+    allocations: AtomicUsize,
+    threshold: AtomicUsize,
+    enabled: AtomicBool,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Collector {
+            tracked: Mutex::new(Vec::new()),
+            allocations: AtomicUsize::new(0),
+            threshold: AtomicUsize::new(MIN_THRESHOLD),
+            enabled: AtomicBool::new(true),
+        }
+    }
+}
+
+// Only objects that can refer to other objects can be part of a cycle.
+fn is_container(object: &KyaObject) -> bool {
+    matches!(
+        object,
+        KyaObject::InstanceObject(_)
+            | KyaObject::MethodObject(_)
+            | KyaObject::ListObject(_)
+            | KyaObject::HashObject(_)
+            | KyaObject::ExceptionObject(_)
+    )
+}
+
+// The containers a thread created and has not registered yet, and the
+// runtime they belong to.
+#[derive(Default)]
+struct Batch {
+    runtime: Option<Arc<Runtime>>,
+    objects: Vec<WeakGc>,
+}
+
+impl Batch {
+    fn flush(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            let collector = &runtime.collector;
+
+            collector
+                .allocations
+                .fetch_add(self.objects.len(), Ordering::Relaxed);
+            collector.tracked.lock().unwrap().append(&mut self.objects);
+        }
+    }
+}
+
+// A thread that exits hands over what it has left.
+impl Drop for Batch {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+thread_local! {
+    static BATCH: RefCell<Batch> = RefCell::default();
+}
+
+// Registers a container so that the collector can find it in cycles.
+fn track(object: &Gc) {
+    let queued = BATCH.try_with(|batch| {
+        let mut batch = batch.borrow_mut();
+
+        batch.runtime.get_or_insert_with(runtime::current);
+        batch.objects.push(Gc::downgrade(object));
+
+        if batch.objects.len() >= BATCH_SIZE {
+            batch.flush();
+        }
+    });
+
+    if queued.is_err() {
+        let mut batch = Batch {
+            runtime: Some(runtime::current()),
+            objects: vec![Gc::downgrade(object)],
+        };
+
+        batch.flush();
+    }
+}
+
+/// Hands the containers this thread created over to the collector of the
+/// runtime they were created for. Called before the thread changes runtimes.
+pub fn flush() {
+    let _ = BATCH.try_with(|batch| batch.borrow_mut().flush());
+}
+
+pub fn set_enabled(enabled: bool) {
+    runtime::current()
+        .collector
+        .enabled
+        .store(enabled, Ordering::Relaxed);
+}
+
+/// Collects if enough containers were created since the last collection.
+/// Must be called with the interpreter lock held and no object locked.
+pub fn maybe_collect() {
+    let runtime = runtime::current();
+    let collector = &runtime.collector;
+
+    if collector.enabled.load(Ordering::Relaxed)
+        && collector.allocations.load(Ordering::Relaxed)
+            >= collector.threshold.load(Ordering::Relaxed)
+    {
+        collect();
+    }
+}
+
+// Calls `f` with each object `object` holds a reference to. Each call stands
+// for exactly one of the references counted by the target, so maps shared
+// with Rust code, whose references belong to whoever else holds the map, are
+// not looked into.
+fn visit_references(object: &KyaObject, f: &mut impl FnMut(&KyaObjectRef)) {
+    match object {
+        KyaObject::InstanceObject(instance) => match &instance.fields {
+            Fields::Dict(dict) if Arc::strong_count(dict) == 1 => {
+                if let Ok(dict) = dict.try_lock() {
+                    dict.values().for_each(f);
+                }
+            }
+            Fields::Dict(_) => {}
+            Fields::Slots(_, values) => values.iter().flatten().for_each(f),
+        },
+        KyaObject::ListObject(list) => list.items.iter().for_each(f),
+        KyaObject::HashObject(hash) if Arc::strong_count(&hash.items) == 1 => {
+            if let Ok(items) = hash.items.try_lock() {
+                for item in items.values() {
+                    f(&item.key);
+                    f(&item.value);
+                }
+            }
+        }
+        KyaObject::ExceptionObject(exception) => {
+            f(&exception.message);
+            exception.cause.iter().for_each(f);
+        }
+        KyaObject::MethodObject(method) => {
+            f(&method.function);
+            f(&method.instance_object);
+        }
+        _ => {}
+    }
+}
+
+// Takes the references out of a container, to be dropped once no object is
+// locked. Maps shared with Rust code are left alone, as when visiting. Bound methods cannot be emptied, but every cycle through one also
+// runs through the instance it is bound to.
+fn clear(object: &mut KyaObject) -> Option<Box<dyn Any>> {
+    match object {
+        KyaObject::InstanceObject(instance) => match &mut instance.fields {
+            Fields::Dict(dict) if Arc::strong_count(dict) == 1 => {
+                Some(Box::new(std::mem::take(&mut *dict.lock().unwrap())))
+            }
+            Fields::Dict(_) => None,
+            Fields::Slots(_, values) => Some(Box::new(
+                values.iter_mut().map(Option::take).collect::<Vec<_>>(),
+            )),
+        },
+        KyaObject::ListObject(list) => Some(Box::new(std::mem::take(&mut list.items))),
+        KyaObject::HashObject(hash) if Arc::strong_count(&hash.items) == 1 => {
+            Some(Box::new(std::mem::take(&mut *hash.items.lock().unwrap())))
+        }
+        KyaObject::ExceptionObject(exception) => Some(Box::new(exception.cause.take())),
+        _ => None,
+    }
+}
+
+fn has_finalizer(object: &KyaObject) -> bool {
+    object
+        .get_type()
+        .ok()
+        .and_then(|ob_type| ob_type.try_lock().ok().map(|ob_type| ob_type.tp_del))
+        .flatten()
+        .is_some()
+}
+
+/// Frees the tracked objects that are only reachable from each other and
+/// returns how many there were.
+pub fn collect() -> usize {
+    flush();
+
+    let runtime = runtime::current();
+    let collector = &runtime.collector;
+
+    let objects: Vec<KyaObjectRef> = {
+        let mut tracked = collector.tracked.lock().unwrap();
+
+        tracked.retain(|object| object.strong_count() > 0);
+        tracked.iter().filter_map(WeakGc::upgrade).collect()
+    };
+
+    let index: HashMap<*const Mutex<KyaObject>, usize> = objects
+        .iter()
+        .enumerate()
+        .map(|(i, object)| (Gc::as_ptr(object), i))
+        .collect();
+
+    // The reference held by `objects` is not one of the program's.
+    let mut external: Vec<usize> = objects
+        .iter()
+        .map(|object| Gc::strong_count(object) - 1)
+        .collect();
+    let mut references: Vec<Vec<usize>> = vec![vec![]; objects.len()];
+    let mut roots: Vec<usize> = vec![];
+
+    for (i, object) in objects.iter().enumerate() {
+        // An object locked elsewhere is in use, and so are its references.
+        let Ok(object) = object.try_lock() else {
+            roots.push(i);
+            continue;
+        };
+
+        if has_finalizer(&object) {
+            roots.push(i);
+        }
+
+        visit_references(&object, &mut |target| {
+            if let Some(&j) = index.get(&Gc::as_ptr(target)) {
+                external[j] = external[j].saturating_sub(1);
+                references[i].push(j);
+            }
+        });
+    }
+
+    let mut reachable = vec![false; objects.len()];
+    let mut pending: VecDeque<usize> = (0..objects.len())
+        .filter(|&i| external[i] > 0)
+        .chain(roots)
+        .collect();
+
+    while let Some(i) = pending.pop_front() {
+        if reachable[i] {
+            continue;
+        }
+
+        reachable[i] = true;
+        pending.extend(references[i].iter().filter(|&&j| !reachable[j]));
+    }
+
+    let mut garbage = vec![];
+
+    for (object, &reachable) in objects.iter().zip(&reachable) {
+        if reachable {
+            continue;
+        }
+
+        if let Ok(mut object) = object.try_lock() {
+            garbage.extend(clear(&mut object));
+        }
+    }
+
+    let collected = objects.len() - reachable.iter().filter(|&&r| r).count();
+    let survivors = objects.len() - collected;
+
+    collector.allocations.store(0, Ordering::Relaxed);
+    collector
+        .threshold
+        .store(survivors.max(MIN_THRESHOLD), Ordering::Relaxed);
+
+    drop(garbage);
+    drop(objects);
+
+    collected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::hash_object::{hash_new, HashItem, HashItems};
+    use crate::objects::list_object::list_new;
+
+    #[test]
+    fn test_collects_cycles() {
+        let list = list_new(vec![]);
+        let other = list_new(vec![list.clone()]);
+
+        if let KyaObject::ListObject(list_object) = &mut *list.lock().unwrap() {
+            list_object.items.push_back(other.clone());
+        }
+
+        let kept = list_new(vec![]);
+        let weak = Gc::downgrade(&list);
+
+        drop(other);
+        drop(list);
+
+        collect();
+
+        assert!(weak.upgrade().is_none());
+        assert_eq!(Gc::strong_count(&kept), 1);
+    }
+
+    #[test]
+    fn test_keeps_maps_shared_with_rust_code() {
+        let mut items = HashItems::default();
+
+        items.insert(
+            0,
+            HashItem {
+                key: list_new(vec![]),
+                value: list_new(vec![]),
+            },
+        );
+
+        let hash = hash_new(items);
+        let shared = match &*hash.lock().unwrap() {
+            KyaObject::HashObject(hash_object) => hash_object.items.clone(),
+            _ => unreachable!(),
+        };
+        let list = list_new(vec![hash]);
+        let other = list_new(vec![list.clone()]);
+
+        if let KyaObject::ListObject(list_object) = &mut *list.lock().unwrap() {
+            list_object.items.push_back(other.clone());
+        }
+
+        drop(other);
+        drop(list);
+
+        collect();
+
+        assert_eq!(shared.lock().unwrap().len(), 1);
+    }
+}
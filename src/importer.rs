@@ -0,0 +1,195 @@
+//! Modules written in Kyanite. `import utils` runs `utils.k` from the first
+//! search path that has it, in a main frame of its own, and binds a module
+//! whose attributes are the globals it defined. A file runs once per
+//! runtime: importing it again gives the same module, even from an import
+//! cycle while the file is still running.
+//!
+//! Dotted names map to directories: `import net.http` runs `net/http.k`. A
+//! directory without a source file of the same name imports as an empty
+//! module, so that `net.http` can be reached from `net`.
+
+use crate::bytecode::CodeObject;
+use crate::compiler::Compiler;
+use crate::errors::Error;
+use crate::internal::source::read_source;
+use crate::interpreter::{create_main_frame, eval_frame};
+use crate::lexer::Lexer;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::module_object::module_new;
+use crate::optimizer;
+use crate::parser::Parser;
+use crate::runtime;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const SOURCE_EXTENSION: &str = "k";
+
+/// The path of the module named `module` relative to a search path,
+/// without the extension.
+pub fn module_path(module: &str) -> PathBuf {
+    module.split('.').collect()
+}
+
+/// The source file of the module named `module` in the first of
+/// `search_paths` that has one.
+pub fn source_path(module: &str, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    let file_name = module_path(module).with_extension(SOURCE_EXTENSION);
+
+    search_paths
+        .iter()
+        .map(|directory| directory.join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// Runs the source file of the module named `module`, or returns `None` if
+/// no search path has one or a directory of that name.
+pub fn load(module: &str, search_paths: &[PathBuf]) -> Result<Option<KyaObjectRef>, Error> {
+    let Some(path) = source_path(module, search_paths) else {
+        return Ok(package(module, search_paths));
+    };
+    let path = path.canonicalize().unwrap_or(path);
+    let runtime = runtime::current();
+
+    if let Some(loaded) = runtime.module(&path) {
+        return Ok(Some(loaded));
+    }
+
+    let code = compile(&path).map_err(|e| {
+        Error::RuntimeError(format!(
+            "Cannot import '{}' from {}: {}",
+            module,
+            path.display(),
+            e
+        ))
+    })?;
+
+    let mut frame = create_main_frame(code);
+    let loaded = module_new(module, frame.globals.clone());
+
+    // Added before it runs, so that the modules it imports can import it.
+    runtime.add_module(path.clone(), loaded.clone());
+
+    if let Err(error) = eval_frame(&mut frame) {
+        runtime.remove_module(&path);
+
+        return Err(error);
+    }
+
+    Ok(Some(loaded))
+}
+
+/// The error for a module that none of `search_paths` has.
+pub fn not_found(module: &str, search_paths: &[PathBuf]) -> Error {
+    let searched: Vec<String> = search_paths
+        .iter()
+        .map(|directory| directory.display().to_string())
+        .collect();
+
+    Error::RuntimeError(format!(
+        "No module named '{}' (searched {})",
+        module,
+        searched.join(", ")
+    ))
+}
+
+// The module for a directory, which only holds the modules imported from it.
+fn package(module: &str, search_paths: &[PathBuf]) -> Option<KyaObjectRef> {
+    let relative = module_path(module);
+    let directory = search_paths
+        .iter()
+        .map(|directory| directory.join(&relative))
+        .find(|path| path.is_dir())?;
+    let directory = directory.canonicalize().unwrap_or(directory);
+    let runtime = runtime::current();
+
+    if let Some(loaded) = runtime.module(&directory) {
+        return Some(loaded);
+    }
+
+    let loaded = module_new(module, Arc::new(Mutex::new(HashMap::new())));
+    runtime.add_module(directory, loaded.clone());
+
+    Some(loaded)
+}
+
+fn compile(path: &Path) -> Result<CodeObject, Error> {
+    let source = read_source(path).map_err(Error::RuntimeError)?;
+
+    let ast = Parser::new(Lexer::new(source)).parse()?;
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.add_pass(optimizer::ConstantBranches::new());
+    compiler.compile()?;
+
+    Ok(compiler.get_output())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_import_source_module() {
+        let root = std::env::temp_dir().join(format!("kyanite-import-{}", std::process::id()));
+
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("shapes.k"),
+            "import units\n\ndef area(side)\n  return units.scale(side)\nend\n",
+        )
+        .unwrap();
+        // Imports the module that is importing it.
+        std::fs::write(
+            root.join("units.k"),
+            "import shapes\n\ndef scale(value)\n  return value + value\nend\n",
+        )
+        .unwrap();
+
+        let source = "import shapes\nimport units\n\nassert(shapes.area(3) == 6)\nassert(shapes.units is units)\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let result = Interpreter::new(root.to_str().unwrap()).eval(&compiler.get_output());
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+
+    #[test]
+    fn test_import_dotted_module() {
+        let root = std::env::temp_dir().join(format!("kyanite-dotted-{}", std::process::id()));
+
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        std::fs::write(
+            root.join("net").join("http.k"),
+            "def status\n  return 200\nend\n",
+        )
+        .unwrap();
+
+        let eval = |source: &str| {
+            let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+            let mut compiler = Compiler::new(Arc::new(ast));
+            compiler.compile().unwrap();
+
+            Interpreter::new(root.to_str().unwrap()).eval(&compiler.get_output())
+        };
+
+        let found = eval("import net.http\n\nassert(net.http.status() == 200)\n");
+        let missing = eval("import net.ftp\n");
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(found.is_ok(), "{:?}", found.err());
+        assert!(missing.err().unwrap().to_string().contains(&format!(
+            "No module named 'net.ftp' (searched {}",
+            root.display()
+        )));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::ast::ASTNode;
+use crate::bytecode::{CodeObject, Opcode};
+use crate::diagnostics::Diagnostic;
+use crate::errors::Error;
+use crate::visitor::CompilerPass;
+
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+/// Called with the id of every probe the interpreter runs into. It runs on
+/// the interpreter thread while holding the global lock, so it must not call
+/// back into `set_hook`.
+pub type ProbeHook = Box<dyn FnMut(usize) + Send>;
+
+// Labels of every probe emitted so far, indexed by probe id.
+static PROBES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(vec![]));
+
+static HOOK: Lazy<Mutex<Option<ProbeHook>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_hook(hook: Option<ProbeHook>) {
+    *HOOK.lock().unwrap() = hook;
+}
+
+pub fn probe_count() -> usize {
+    PROBES.lock().unwrap().len()
+}
+
+pub fn probe_label(probe: usize) -> Option<String> {
+    PROBES.lock().unwrap().get(probe).cloned()
+}
+
+pub fn fire(probe: usize) {
+    if let Some(hook) = HOOK.lock().unwrap().as_mut() {
+        hook(probe);
+    }
+}
+
+fn register(label: String) -> usize {
+    let mut probes = PROBES.lock().unwrap();
+
+    probes.push(label);
+    probes.len() - 1
+}
+
+fn describe(statement: &ASTNode) -> String {
+    match statement {
+        ASTNode::Assignment(assignment) => match &*assignment.name {
+            ASTNode::Identifier(identifier) => format!("assignment to {}", identifier.name),
+            ASTNode::Attribute(attribute) => format!("assignment to .{}", attribute.value),
+            _ => "assignment".to_string(),
+        },
+        ASTNode::MethodCall(_) => "call".to_string(),
+        ASTNode::MethodDef(method_def) => format!("def {}", method_def.name),
+        ASTNode::ClassDef(class_def) => format!("class {}", class_def.name),
+        ASTNode::If(_) => "if".to_string(),
+        ASTNode::While(_) => "while".to_string(),
+        ASTNode::Break() => "break".to_string(),
+        ASTNode::Return(_) => "return".to_string(),
+        ASTNode::Raise(_) => "raise".to_string(),
+        ASTNode::Try(_) => "try".to_string(),
+        ASTNode::With(_) => "with".to_string(),
+        ASTNode::Import(_) => "import".to_string(),
+        _ => "expression".to_string(),
+    }
+}
+
+/// Emits a `Probe` instruction at the start of every statement, labelled with
+/// the enclosing function or class and the statement's position in it.
+pub struct StatementProbes {
+    scopes: Vec<(String, usize)>,
+}
+
+impl Default for StatementProbes {
+    fn default() -> Self {
+        StatementProbes::new()
+    }
+}
+
+impl StatementProbes {
+    pub fn new() -> Self {
+        StatementProbes {
+            scopes: vec![("<main>".to_string(), 0)],
+        }
+    }
+}
+
+impl CompilerPass for StatementProbes {
+    fn before_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        match node {
+            ASTNode::MethodDef(method_def) => self.scopes.push((method_def.name.clone(), 0)),
+            ASTNode::ClassDef(class_def) => self.scopes.push((class_def.name.clone(), 0)),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn after_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        if let ASTNode::MethodDef(_) | ASTNode::ClassDef(_) = node {
+            self.scopes.pop();
+        }
+
+        Ok(())
+    }
+
+    fn before_statement(
+        &mut self,
+        statement: &ASTNode,
+        code: &mut CodeObject,
+    ) -> Result<(), Error> {
+        let (scope, count) = self.scopes.last_mut().unwrap();
+        *count += 1;
+
+        let probe = register(format!("{} #{} {}", scope, count, describe(statement)));
+        let index = code.add_probe(probe);
+
+        code.add_instruction(Opcode::Probe as u8);
+        code.add_wide_operand(index)
+            .map_err(|_| Error::CompilationError(Diagnostic::TooManyProbes(scope.clone())))
+    }
+}
+
+/// Counts how often each probe runs, for `--coverage`.
+pub struct Coverage {
+    hits: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Coverage {
+    pub fn start() -> Self {
+        let hits = Arc::new(Mutex::new(vec![]));
+        let recorder = hits.clone();
+
+        set_hook(Some(Box::new(move |probe| {
+            let mut hits = recorder.lock().unwrap();
+
+            if hits.len() <= probe {
+                hits.resize(probe + 1, 0);
+            }
+
+            hits[probe] += 1;
+        })));
+
+        Coverage { hits }
+    }
+
+    pub fn hits(&self, probe: usize) -> usize {
+        self.hits.lock().unwrap().get(probe).copied().unwrap_or(0)
+    }
+
+    pub fn report(&self) -> String {
+        let total = probe_count();
+        let missed: Vec<usize> = (0..total).filter(|&probe| self.hits(probe) == 0).collect();
+        let executed = total - missed.len();
+
+        let mut output = format!("Coverage: {} of {} statements executed", executed, total);
+
+        if total > 0 {
+            output.push_str(&format!(
+                " ({:.1}%)",
+                executed as f64 * 100.0 / total as f64
+            ));
+        }
+
+        for probe in missed {
+            output.push_str(&format!(
+                "\n  not executed: {}",
+                probe_label(probe).unwrap()
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_statement_probes_record_coverage() {
+        let source = "def f\n    return 1\n    x = 2\nend\nf()\nif false\n    y = 3\nend\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.add_pass(StatementProbes::new());
+        compiler.compile().unwrap();
+
+        let code = compiler.get_output();
+        let coverage = Coverage::start();
+
+        Interpreter::new(".").eval(&code).unwrap();
+        set_hook(None);
+
+        let probe = |index: usize| code.probes[index];
+        let labels: Vec<String> = (0..code.probes.len())
+            .map(|index| probe_label(probe(index)).unwrap())
+            .collect();
+
+        assert_eq!(
+            labels,
+            [
+                "<main> #1 def f",
+                "<main> #2 call",
+                "<main> #3 if",
+                "<main> #4 assignment to y"
+            ]
+        );
+        assert!(code.dis().contains("PROBE 1"));
+        assert_eq!(coverage.hits(probe(1)), 1);
+
+        let report = coverage.report();
+
+        assert!(report.contains("not executed: f #2 assignment to x"));
+        assert!(report.contains("not executed: <main> #4 assignment to y"));
+        assert!(!report.contains("f #1 return"));
+    }
+}
@@ -0,0 +1,318 @@
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A UTC calendar date and time with millisecond precision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millis: u32,
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since the Unix epoch to a (year, month, day) date and back, using
+// Howard Hinnant's algorithms for the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+impl DateTime {
+    pub fn new(
+        year: i64,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Result<Self, String> {
+        if !(1..=12).contains(&month) {
+            return Err(format!("Month {} is out of range", month));
+        }
+
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(format!("Day {} is out of range for month {}", day, month));
+        }
+
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(format!(
+                "Time {:02}:{:02}:{:02} is out of range",
+                hour, minute, second
+            ));
+        }
+
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            millis: 0,
+        })
+    }
+
+    pub fn from_timestamp(timestamp: f64) -> Self {
+        let millis = (timestamp * 1000.0).round() as i64;
+        let seconds = millis.div_euclid(1000);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        let time = seconds.rem_euclid(86400) as u32;
+
+        DateTime {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time % 3600 / 60,
+            second: time % 60,
+            millis: millis.rem_euclid(1000) as u32,
+        }
+    }
+
+    pub fn timestamp(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds =
+            days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+
+        seconds as f64 + self.millis as f64 / 1000.0
+    }
+
+    /// Day of the week, from 0 for Monday to 6 for Sunday.
+    pub fn weekday(&self) -> u32 {
+        (days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) as u32
+    }
+
+    /// Formats with strftime-style directives: %Y %m %d %H %M %S %L
+    /// (milliseconds) %y %j %a %A %b %B %Z (always "UTC") and %%.
+    pub fn format(&self, format: &str) -> Result<String, String> {
+        let mut output = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+
+            let weekday = WEEKDAYS[self.weekday() as usize];
+            let month = MONTHS[self.month as usize - 1];
+
+            match chars.next() {
+                Some('Y') => output.push_str(&format!("{:04}", self.year)),
+                Some('y') => output.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => output.push_str(&format!("{:02}", self.month)),
+                Some('d') => output.push_str(&format!("{:02}", self.day)),
+                Some('H') => output.push_str(&format!("{:02}", self.hour)),
+                Some('M') => output.push_str(&format!("{:02}", self.minute)),
+                Some('S') => output.push_str(&format!("{:02}", self.second)),
+                Some('L') => output.push_str(&format!("{:03}", self.millis)),
+                Some('j') => {
+                    let day_of_year = days_from_civil(self.year, self.month, self.day)
+                        - days_from_civil(self.year, 1, 1)
+                        + 1;
+
+                    output.push_str(&format!("{:03}", day_of_year));
+                }
+                Some('a') => output.push_str(&weekday[..3]),
+                Some('A') => output.push_str(weekday),
+                Some('b') => output.push_str(&month[..3]),
+                Some('B') => output.push_str(month),
+                Some('Z') => output.push_str("UTC"),
+                Some('%') => output.push('%'),
+                Some(other) => return Err(format!("Unknown format directive '%{}'", other)),
+                None => return Err("Format ends with a lone '%'".to_string()),
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Parses `input` according to `format`, accepting the same directives as
+    /// `format`. Weekday names are checked for shape but otherwise ignored.
+    pub fn parse(input: &str, format: &str) -> Result<Self, String> {
+        let mut rest = input;
+        let mut fields = (1970, 1, 1, 0, 0, 0, 0);
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                rest = rest
+                    .strip_prefix(c)
+                    .ok_or_else(|| format!("Expected '{}' in '{}'", c, input))?;
+                continue;
+            }
+
+            let directive = chars
+                .next()
+                .ok_or_else(|| "Format ends with a lone '%'".to_string())?;
+
+            match directive {
+                'Y' => fields.0 = take_number(&mut rest, 4)? as i64,
+                'y' => fields.0 = 2000 + take_number(&mut rest, 2)? as i64,
+                'm' => fields.1 = take_number(&mut rest, 2)?,
+                'd' => fields.2 = take_number(&mut rest, 2)?,
+                'H' => fields.3 = take_number(&mut rest, 2)?,
+                'M' => fields.4 = take_number(&mut rest, 2)?,
+                'S' => fields.5 = take_number(&mut rest, 2)?,
+                'L' => fields.6 = take_number(&mut rest, 3)?,
+                'a' | 'A' => {
+                    take_name(&mut rest, &WEEKDAYS, directive == 'a')?;
+                }
+                'b' | 'B' => fields.1 = take_name(&mut rest, &MONTHS, directive == 'b')? + 1,
+                'Z' => {
+                    rest = ["UTC", "GMT", "Z"]
+                        .iter()
+                        .find_map(|zone| rest.strip_prefix(zone))
+                        .ok_or_else(|| format!("Expected a UTC zone in '{}'", input))?;
+                }
+                '%' => {
+                    rest = rest
+                        .strip_prefix('%')
+                        .ok_or_else(|| format!("Expected '%' in '{}'", input))?;
+                }
+                other => return Err(format!("Unknown format directive '%{}'", other)),
+            }
+        }
+
+        if !rest.is_empty() {
+            return Err(format!("Unexpected trailing text '{}'", rest));
+        }
+
+        let (year, month, day, hour, minute, second, millis) = fields;
+        let mut date_time = DateTime::new(year, month, day, hour, minute, second)?;
+        date_time.millis = millis;
+
+        Ok(date_time)
+    }
+}
+
+fn take_number(rest: &mut &str, max_digits: usize) -> Result<u32, String> {
+    let digits = rest
+        .chars()
+        .take(max_digits)
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+
+    if digits == 0 {
+        return Err(format!("Expected a number at '{}'", rest));
+    }
+
+    let value = rest[..digits].parse::<u32>().map_err(|e| e.to_string())?;
+    *rest = &rest[digits..];
+
+    Ok(value)
+}
+
+fn take_name(rest: &mut &str, names: &[&str], abbreviated: bool) -> Result<u32, String> {
+    for (index, name) in names.iter().enumerate() {
+        let name = if abbreviated { &name[..3] } else { name };
+
+        if let Some(stripped) = rest.strip_prefix(name) {
+            *rest = stripped;
+            return Ok(index as u32);
+        }
+    }
+
+    Err(format!("Unexpected name at '{}'", rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let date_time = DateTime::from_timestamp(1_700_000_000.5);
+
+        assert_eq!(
+            (date_time.year, date_time.month, date_time.day),
+            (2023, 11, 14)
+        );
+        assert_eq!(
+            (date_time.hour, date_time.minute, date_time.second),
+            (22, 13, 20)
+        );
+        assert_eq!(date_time.millis, 500);
+        assert_eq!(date_time.weekday(), 1);
+        assert_eq!(date_time.timestamp(), 1_700_000_000.5);
+        assert_eq!(DateTime::from_timestamp(-1.0).year, 1969);
+    }
+
+    #[test]
+    fn test_format_and_parse() {
+        let date_time = DateTime::new(2024, 2, 29, 8, 5, 9).unwrap();
+
+        assert_eq!(
+            date_time.format(HTTP_DATE).unwrap(),
+            "Thu, 29 Feb 2024 08:05:09 GMT"
+        );
+        assert_eq!(date_time.format("%j %y %%").unwrap(), "060 24 %");
+        assert_eq!(
+            DateTime::parse("Thu, 29 Feb 2024 08:05:09 GMT", HTTP_DATE).unwrap(),
+            date_time
+        );
+        assert!(DateTime::parse("2023-02-29", "%Y-%m-%d").is_err());
+        assert!(DateTime::parse("2024-01-01 extra", "%Y-%m-%d").is_err());
+        assert!(date_time.format("%Q").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use std::env;
+
+fn home_dir() -> Result<String, String> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map_err(|_| "Cannot expand '~': the home directory is not set".to_string())
+}
+
+fn variable(name: &str) -> Result<String, String> {
+    env::var(name).map_err(|_| format!("Environment variable '{}' is not set", name))
+}
+
+/// Expands a leading `~`, or `~` and a separator, to the home directory and
+/// every `$VAR` or `${VAR}` to the value of that environment variable. A `$`
+/// that does not start a variable name is kept as is.
+pub fn expand_path(path: &str) -> Result<String, String> {
+    let mut output = String::new();
+    let mut rest = path;
+
+    if rest == "~"
+        || rest
+            .strip_prefix('~')
+            .is_some_and(|rest| rest.starts_with(std::path::is_separator))
+    {
+        output.push_str(&home_dir()?);
+        rest = &rest[1..];
+    }
+
+    while let Some(index) = rest.find('$') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| format!("Unterminated '${{' in path '{}'", path))?;
+
+            output.push_str(&variable(&braced[..end])?);
+            rest = &braced[end + 1..];
+            continue;
+        }
+
+        let length = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+
+        if length == 0 {
+            output.push('$');
+            continue;
+        }
+
+        output.push_str(&variable(&rest[..length])?);
+        rest = &rest[length..];
+    }
+
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_path() {
+        let home = home_dir().unwrap();
+
+        assert_eq!(
+            expand_path("$HOME/handlers.k").unwrap(),
+            format!("{}/handlers.k", home)
+        );
+        assert_eq!(expand_path("${HOME}_v2").unwrap(), format!("{}_v2", home));
+        assert_eq!(
+            expand_path("~/scripts").unwrap(),
+            format!("{}/scripts", home)
+        );
+        assert_eq!(
+            expand_path(&format!("~{}scripts", std::path::MAIN_SEPARATOR)).unwrap(),
+            format!("{}{}scripts", home, std::path::MAIN_SEPARATOR)
+        );
+        assert_eq!(expand_path("~scripts").unwrap(), "~scripts");
+        assert_eq!(expand_path("a/~/b$/c$").unwrap(), "a/~/b$/c$");
+        assert!(expand_path("$KYA_TEST_UNSET_VARIABLE/x").is_err());
+        assert!(expand_path("${HOME").is_err());
+    }
+}
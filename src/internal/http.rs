@@ -0,0 +1,233 @@
+use crate::internal::socket::{Connection, SocketError};
+
+const READ_CHUNK_SIZE: usize = 4096;
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case(name))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            status_reason(self.status)
+        );
+
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        if !self.has_header("Content-Length") {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+
+        if !self.has_header("Connection") {
+            head.push_str("Connection: close\r\n");
+        }
+
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(&self.body);
+
+        bytes
+    }
+}
+
+pub fn status_reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn find_head_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+pub fn parse_head(head: &str) -> Result<HttpRequest, String> {
+    let mut lines = head.split("\r\n");
+
+    let request_line = lines.next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+
+    let (method, path, version) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(path), Some(version), None) if version.starts_with("HTTP/") => {
+            (method, path, version)
+        }
+        _ => return Err(format!("Malformed request line '{}'", request_line)),
+    };
+
+    let mut headers = Vec::new();
+
+    for line in lines.filter(|line| !line.is_empty()) {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed header '{}'", line))?;
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("Malformed header '{}'", line));
+        }
+
+        headers.push((name.to_string(), value.trim().to_string()));
+    }
+
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        version: version.to_string(),
+        headers,
+        body: Vec::new(),
+    })
+}
+
+pub fn read_request(connection: &mut Connection) -> Result<HttpRequest, SocketError> {
+    let mut buffer = Vec::new();
+
+    let head_end = loop {
+        if let Some(position) = find_head_end(&buffer) {
+            break position;
+        }
+
+        if buffer.len() > MAX_HEAD_SIZE {
+            return Err(SocketError::ReadError(
+                "Request head is too large".to_string(),
+            ));
+        }
+
+        let chunk = connection.read(READ_CHUNK_SIZE)?;
+
+        if chunk.is_empty() {
+            return Err(SocketError::ReadError(
+                "Connection closed before the request was complete".to_string(),
+            ));
+        }
+
+        buffer.extend_from_slice(&chunk);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut request = parse_head(&head).map_err(SocketError::ReadError)?;
+
+    let content_length = match request.header("Content-Length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| SocketError::ReadError(format!("Invalid Content-Length '{}'", value)))?,
+        None => 0,
+    };
+
+    let mut body = buffer[head_end + 4..].to_vec();
+
+    while body.len() < content_length {
+        let chunk = connection.read(READ_CHUNK_SIZE)?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    body.truncate(content_length);
+    request.body = body;
+
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head() {
+        let request =
+            parse_head("GET /index.html HTTP/1.1\r\nHost: localhost\r\nX-Empty:\r\nAccept:  */* ")
+                .unwrap();
+
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/index.html");
+        assert_eq!(request.version, "HTTP/1.1");
+        assert_eq!(request.header("host"), Some("localhost"));
+        assert_eq!(request.header("x-empty"), Some(""));
+        assert_eq!(request.header("Accept"), Some("*/*"));
+        assert_eq!(request.header("Missing"), None);
+    }
+
+    #[test]
+    fn test_parse_head_errors() {
+        assert!(parse_head("").is_err());
+        assert!(parse_head("GET /").is_err());
+        assert!(parse_head("GET / FTP/1.0").is_err());
+        assert!(parse_head("GET / HTTP/1.1\r\nNoColon").is_err());
+        assert!(parse_head("GET / HTTP/1.1\r\nBad Name: x").is_err());
+    }
+
+    #[test]
+    fn test_response_to_bytes() {
+        let mut response = HttpResponse::new(404, b"missing".to_vec());
+        response.set_header("Content-Type", "text/plain");
+
+        assert_eq!(
+            String::from_utf8(response.to_bytes()).unwrap(),
+            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 7\r\nConnection: close\r\n\r\nmissing"
+        );
+    }
+}
@@ -1 +1,6 @@
+pub mod datetime;
+pub mod expand;
+pub mod http;
+pub mod number;
 pub mod socket;
+pub mod source;
@@ -0,0 +1,111 @@
+// Every number that reaches program output or is read from text goes through
+// this module. Rust never consults the system locale, so the decimal
+// separator is always '.', and the grammar below is the same on every
+// machine.
+
+pub const MAX_PRECISION: usize = 20;
+
+/// The shortest text that parses back to the same value, without a trailing
+/// ".0" for integral values.
+pub fn format_number(value: f64) -> String {
+    value.to_string()
+}
+
+/// Formats with exactly `precision` digits after the decimal point. Values
+/// that round to zero are never printed with a minus sign.
+pub fn format_fixed(value: f64, precision: usize) -> Result<String, String> {
+    if precision > MAX_PRECISION {
+        return Err(format!(
+            "Precision {} is out of range, the maximum is {}",
+            precision, MAX_PRECISION
+        ));
+    }
+
+    if !value.is_finite() {
+        return Ok(format_number(value));
+    }
+
+    let text = format!("{:.*}", precision, value);
+
+    match text.strip_prefix('-') {
+        Some(digits) if digits.bytes().all(|b| b == b'0' || b == b'.') => Ok(digits.to_string()),
+        _ => Ok(text),
+    }
+}
+
+/// Parses `[+-]digits[.digits][(e|E)[+-]digits]`, ignoring surrounding
+/// whitespace. Thousands separators, ',' decimals and spelled-out infinities
+/// are rejected.
+pub fn parse_number(text: &str) -> Result<f64, String> {
+    let trimmed = text.trim();
+    let bytes = trimmed.as_bytes();
+    let mut position = 0;
+
+    let skip_sign = |position: &mut usize| {
+        if matches!(bytes.get(*position), Some(b'+' | b'-')) {
+            *position += 1;
+        }
+    };
+
+    let skip_digits = |position: &mut usize| {
+        let start = *position;
+
+        while bytes.get(*position).is_some_and(u8::is_ascii_digit) {
+            *position += 1;
+        }
+
+        *position - start
+    };
+
+    skip_sign(&mut position);
+    let mut digits = skip_digits(&mut position);
+
+    if bytes.get(position) == Some(&b'.') {
+        position += 1;
+        digits += skip_digits(&mut position);
+    }
+
+    let mut valid = digits > 0;
+
+    if valid && matches!(bytes.get(position), Some(b'e' | b'E')) {
+        position += 1;
+        skip_sign(&mut position);
+        valid = skip_digits(&mut position) > 0;
+    }
+
+    if !valid || position != bytes.len() {
+        return Err(format!("Invalid number '{}'", text));
+    }
+
+    trimmed
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid number '{}'", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_fixed(2.0 / 3.0, 2).unwrap(), "0.67");
+        assert_eq!(format_fixed(-0.001, 2).unwrap(), "0.00");
+        assert_eq!(format_fixed(-1.5, 0).unwrap(), "-2");
+        assert_eq!(format_fixed(f64::INFINITY, 2).unwrap(), "inf");
+        assert!(format_fixed(1.0, MAX_PRECISION + 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(" 42 ").unwrap(), 42.0);
+        assert_eq!(parse_number("-.5").unwrap(), -0.5);
+        assert_eq!(parse_number("1.5e3").unwrap(), 1500.0);
+        assert_eq!(parse_number("2.").unwrap(), 2.0);
+
+        for text in ["", ".", "1,5", "1e", "inf", "NaN", "1_000", "0x10", "--1"] {
+            assert!(parse_number(text).is_err(), "{:?} should not parse", text);
+        }
+    }
+}
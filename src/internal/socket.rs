@@ -1,13 +1,29 @@
+use std::io::ErrorKind;
 use std::io::Read;
 use std::io::Write;
 use std::net::TcpListener;
-use std::time::Duration;
+use std::net::TcpStream;
+use std::net::UdpSocket as StdUdpSocket;
+use std::net::{SocketAddr, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, RawFd};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::replay;
 
 #[derive(Debug, Clone)]
 pub enum SocketError {
     BindError(String),
     AcceptError(String),
+    ConnectError(String),
     ReadError(String),
+    SendError(String),
+    Unsupported(String),
+    Timeout(String),
+    Closed,
 }
 
 impl std::fmt::Display for SocketError {
@@ -15,7 +31,148 @@ impl std::fmt::Display for SocketError {
         match self {
             SocketError::BindError(msg) => write!(f, "Bind Error: {}", msg),
             SocketError::AcceptError(msg) => write!(f, "Accept Error: {}", msg),
+            SocketError::ConnectError(msg) => write!(f, "Connect Error: {}", msg),
             SocketError::ReadError(msg) => write!(f, "Read Error: {}", msg),
+            SocketError::SendError(msg) => write!(f, "Send Error: {}", msg),
+            SocketError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+            SocketError::Timeout(msg) => write!(f, "Timeout: {}", msg),
+            SocketError::Closed => write!(f, "Socket is closed"),
+        }
+    }
+}
+
+// The replay log stores errors as text, so timeouts are recognised by their
+// prefix when a recorded read is played back.
+fn replayed(message: String) -> SocketError {
+    match message.strip_prefix("Timeout: ") {
+        Some(message) => SocketError::Timeout(message.to_string()),
+        None => SocketError::ReadError(message),
+    }
+}
+
+fn replay_message(error: SocketError) -> String {
+    match error {
+        SocketError::ReadError(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+// Reads that time out and operations that would block in non-blocking mode
+// both surface as WouldBlock (or TimedOut on some platforms).
+fn io_error(
+    error: std::io::Error,
+    operation: &str,
+    wrap: fn(String) -> SocketError,
+) -> SocketError {
+    match error.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+            SocketError::Timeout(format!("{} did not complete in time", operation))
+        }
+        _ => wrap(error.to_string()),
+    }
+}
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Listeners have no accept timeout of their own, so with a timeout they are
+// put in non-blocking mode and polled until the deadline passes.
+fn accept_within<T>(
+    options: SocketOptions,
+    mut accept: impl FnMut() -> std::io::Result<T>,
+) -> Result<T, SocketError> {
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        match accept() {
+            Ok(accepted) => return Ok(accepted),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if options.nonblocking || deadline.is_none_or(|deadline| Instant::now() >= deadline)
+                {
+                    return Err(io_error(e, "accept", SocketError::AcceptError));
+                }
+
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => return Err(io_error(e, "accept", SocketError::AcceptError)),
+        }
+    }
+}
+
+/// Blocking behaviour shared by sockets and the connections they create.
+/// Connections start with the options of the socket that accepted or opened
+/// them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SocketOptions {
+    pub timeout: Option<Duration>,
+    pub nonblocking: bool,
+    pub family: Family,
+    // Lets an IPv6 socket bound to "::" accept IPv4 clients too.
+    pub dual_stack: bool,
+    // None keeps the platform default: on for TCP outside Windows.
+    pub reuse_address: Option<bool>,
+    pub backlog: Option<u32>,
+    pub nodelay: bool,
+    pub keepalive: bool,
+}
+
+const DEFAULT_BACKLOG: u32 = 128;
+
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// An option set by name from scripts. `ReuseAddress` and `Backlog` only
+/// take effect when the socket is bound; the others also apply to the
+/// connections it opens or accepts afterwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SocketOption {
+    ReuseAddress(bool),
+    Backlog(u32),
+    NoDelay(bool),
+    KeepAlive(bool),
+}
+
+/// The address family host names are resolved to.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Family {
+    #[default]
+    Any,
+    V6,
+}
+
+impl std::fmt::Display for Family {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Family::Any => write!(f, "IP"),
+            Family::V6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    Inet(String, u16),
+    Unix(PathBuf),
+}
+
+impl From<std::net::SocketAddr> for Address {
+    fn from(address: std::net::SocketAddr) -> Self {
+        Address::Inet(address.ip().to_string(), address.port())
+    }
+}
+
+// Unnamed Unix sockets, like the client end of most connections, have an
+// empty path.
+#[cfg(unix)]
+impl From<std::os::unix::net::SocketAddr> for Address {
+    fn from(address: std::os::unix::net::SocketAddr) -> Self {
+        Address::Unix(address.as_pathname().map(PathBuf::from).unwrap_or_default())
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Inet(host, port) => write!(f, "{}:{}", host, port),
+            Address::Unix(path) => write!(f, "{}", path.display()),
         }
     }
 }
@@ -23,127 +180,1226 @@ impl std::fmt::Display for SocketError {
 #[derive(Debug)]
 pub enum Socket {
     Tcp(TcpSocket),
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixSocket),
 }
 
 impl Socket {
     pub fn as_socketable(&mut self) -> &mut dyn Socketable {
         match self {
             Socket::Tcp(tcp_socket) => tcp_socket,
+            Socket::Udp(udp_socket) => udp_socket,
+            #[cfg(unix)]
+            Socket::Unix(unix_socket) => unix_socket,
         }
     }
 
-    pub fn bind(&mut self, host: &str, port: u16) -> Result<(), SocketError> {
-        self.as_socketable().bind(host, port)
+    // Unix sockets are created for a path, which bind() and connect() fall
+    // back to when no address is given.
+    pub fn default_address(&self) -> Option<Address> {
+        match self {
+            #[cfg(unix)]
+            Socket::Unix(unix_socket) => Some(Address::Unix(unix_socket.path.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn bind(&mut self, address: &Address) -> Result<(), SocketError> {
+        self.as_socketable().bind(address)
     }
 
     pub fn accept(&mut self) -> Result<Connection, SocketError> {
         self.as_socketable().accept()
     }
+
+    pub fn connect(&mut self, address: &Address) -> Result<Connection, SocketError> {
+        self.as_socketable().connect(address)
+    }
+
+    pub fn send_to(&mut self, data: &[u8], host: &str, port: u16) -> Result<usize, SocketError> {
+        self.as_socketable().send_to(data, host, port)
+    }
+
+    pub fn recv_from(&mut self, buffer_size: usize) -> Result<(Vec<u8>, String), SocketError> {
+        replay::capture("socket.recv_from", || {
+            self.as_socketable()
+                .recv_from(buffer_size)
+                .map_err(replay_message)
+        })
+        .map_err(replayed)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), SocketError> {
+        let socket = self.as_socketable();
+        socket.options().timeout = timeout;
+        socket.apply_options()
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), SocketError> {
+        let socket = self.as_socketable();
+        socket.options().nonblocking = nonblocking;
+        socket.apply_options()
+    }
+
+    // The family and dual-stack mode are only read when binding or
+    // connecting.
+    pub fn set_family(&mut self, family: Family) {
+        self.as_socketable().options().family = family;
+    }
+
+    pub fn set_dual_stack(&mut self, dual_stack: bool) {
+        self.as_socketable().options().dual_stack = dual_stack;
+    }
+
+    // The descriptor to wait on for incoming data or connections, once the
+    // socket is bound.
+    #[cfg(unix)]
+    pub fn raw_fd(&self) -> Option<RawFd> {
+        match self {
+            Socket::Tcp(tcp_socket) => tcp_socket.listener.as_ref().map(|l| l.as_raw_fd()),
+            Socket::Udp(udp_socket) => udp_socket.socket.as_ref().map(|s| s.as_raw_fd()),
+            Socket::Unix(unix_socket) => unix_socket.listener.as_ref().map(|l| l.as_raw_fd()),
+        }
+    }
+
+    fn is_bound(&self) -> bool {
+        match self {
+            Socket::Tcp(tcp_socket) => tcp_socket.listener.is_some(),
+            Socket::Udp(udp_socket) => udp_socket.socket.is_some(),
+            #[cfg(unix)]
+            Socket::Unix(unix_socket) => unix_socket.listener.is_some(),
+        }
+    }
+
+    pub fn set_option(&mut self, option: SocketOption) -> Result<(), SocketError> {
+        let bind_time = matches!(
+            option,
+            SocketOption::ReuseAddress(_) | SocketOption::Backlog(_)
+        );
+
+        if bind_time && self.is_bound() {
+            return Err(SocketError::Unsupported(
+                "reuse_address and backlog must be set before bind()".to_string(),
+            ));
+        }
+
+        // On Windows SO_REUSEADDR lets another socket take over a port that is
+        // still in use, rather than rebind one left in TIME_WAIT.
+        #[cfg(windows)]
+        if let SocketOption::ReuseAddress(true) = option {
+            return Err(SocketError::Unsupported(
+                "reuse_address is not available on Windows".to_string(),
+            ));
+        }
+
+        let options = self.as_socketable().options();
+
+        match option {
+            SocketOption::ReuseAddress(reuse_address) => {
+                options.reuse_address = Some(reuse_address)
+            }
+            SocketOption::Backlog(backlog) => options.backlog = Some(backlog),
+            SocketOption::NoDelay(nodelay) => options.nodelay = nodelay,
+            SocketOption::KeepAlive(keepalive) => options.keepalive = keepalive,
+        }
+
+        Ok(())
+    }
+
+    pub fn close(&mut self) {
+        self.as_socketable().close()
+    }
 }
 
 pub trait Socketable {
-    fn bind(&mut self, host: &str, port: u16) -> Result<(), SocketError>;
+    fn bind(&mut self, address: &Address) -> Result<(), SocketError>;
     fn accept(&mut self) -> Result<Connection, SocketError>;
+    fn close(&mut self);
+    fn options(&mut self) -> &mut SocketOptions;
+
+    // Pushes the options down to the OS socket, if one is open yet.
+    fn apply_options(&mut self) -> Result<(), SocketError>;
+
+    fn connect(&mut self, _address: &Address) -> Result<Connection, SocketError> {
+        Err(SocketError::Unsupported(
+            "connect() needs a TCP or Unix socket".to_string(),
+        ))
+    }
+
+    fn send_to(&mut self, _data: &[u8], _host: &str, _port: u16) -> Result<usize, SocketError> {
+        Err(SocketError::Unsupported(
+            "send_to() needs a UDP socket".to_string(),
+        ))
+    }
+
+    fn recv_from(&mut self, _buffer_size: usize) -> Result<(Vec<u8>, String), SocketError> {
+        Err(SocketError::Unsupported(
+            "recv_from() needs a UDP socket".to_string(),
+        ))
+    }
+}
+
+// IPv6 literals may be written with or without brackets, "localhost" is the
+// loopback address of the family and other names resolve to their first
+// address of the family.
+fn resolve(host: &str, port: u16, family: Family) -> Result<SocketAddr, String> {
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+
+    let host = match (host, family) {
+        ("localhost", Family::V6) => "::1",
+        ("localhost", _) => "127.0.0.1",
+        _ => host,
+    };
+
+    (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve '{}': {}", host, e))?
+        .find(|address| match family {
+            Family::Any => true,
+            Family::V6 => address.is_ipv6(),
+        })
+        .ok_or_else(|| format!("No {} address found for '{}'", family, host))
+}
+
+fn inet_address(address: &Address, family: Family) -> Result<SocketAddr, String> {
+    match address {
+        Address::Inet(host, port) => resolve(host, *port, family),
+        Address::Unix(path) => Err(format!(
+            "Expected a host and port, got the path '{}'",
+            path.display()
+        )),
+    }
+}
+
+// Sockets are opened through socket2 so that IPv6 ones can be made
+// dual-stack before they are bound.
+fn bind_inet(
+    address: SocketAddr,
+    kind: socket2::Type,
+    options: SocketOptions,
+) -> std::io::Result<socket2::Socket> {
+    let socket = socket2::Socket::new(socket2::Domain::for_address(address), kind, None)?;
+
+    if address.is_ipv6() {
+        socket.set_only_v6(!options.dual_stack)?;
+    }
+
+    // By default do what std's TcpListener::bind does, so a restarted
+    // server can rebind while old connections linger in TIME_WAIT.
+    let reuse_address = options
+        .reuse_address
+        .unwrap_or(cfg!(not(windows)) && kind == socket2::Type::STREAM);
+
+    if reuse_address {
+        socket.set_reuse_address(true)?;
+    }
+
+    socket.bind(&address.into())?;
+
+    Ok(socket)
 }
 
 #[derive(Debug)]
 pub struct TcpSocket {
     pub listener: Option<TcpListener>,
+    pub options: SocketOptions,
+    pub closed: bool,
 }
 
 impl Socketable for TcpSocket {
-    fn bind(&mut self, host: &str, port: u16) -> Result<(), SocketError> {
-        let parsed_host = if host == "localhost" {
-            "127.0.0.1"
+    fn bind(&mut self, address: &Address) -> Result<(), SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        let address = inet_address(address, self.options.family).map_err(SocketError::BindError)?;
+
+        let listener = bind_inet(address, socket2::Type::STREAM, self.options).and_then(|socket| {
+            let backlog = self.options.backlog.unwrap_or(DEFAULT_BACKLOG);
+
+            socket.listen(backlog.min(i32::MAX as u32) as i32)?;
+
+            Ok(socket)
+        });
+
+        match listener {
+            Ok(listener) => {
+                self.listener = Some(TcpListener::from(listener));
+
+                self.apply_options()
+            }
+            Err(e) => Err(SocketError::BindError(e.to_string())),
+        }
+    }
+
+    fn accept(&mut self) -> Result<Connection, SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        if let Some(listener) = &self.listener {
+            let (stream, _) = accept_within(self.options, || listener.accept())?;
+
+            Connection::Tcp(TcpConnection {
+                stream: Some(stream),
+                options: self.options,
+            })
+            .with_options()
         } else {
-            host
+            Err(SocketError::AcceptError(
+                "Listener is not initialized".to_string(),
+            ))
+        }
+    }
+
+    fn connect(&mut self, address: &Address) -> Result<Connection, SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        let address =
+            inet_address(address, self.options.family).map_err(SocketError::ConnectError)?;
+
+        match TcpStream::connect(address) {
+            Ok(stream) => Connection::Tcp(TcpConnection {
+                stream: Some(stream),
+                options: self.options,
+            })
+            .with_options(),
+            Err(e) => Err(SocketError::ConnectError(e.to_string())),
+        }
+    }
+
+    fn close(&mut self) {
+        self.listener = None;
+        self.closed = true;
+    }
+
+    fn options(&mut self) -> &mut SocketOptions {
+        &mut self.options
+    }
+
+    fn apply_options(&mut self) -> Result<(), SocketError> {
+        let Some(listener) = &self.listener else {
+            return Ok(());
         };
 
-        let address = format!("{}:{}", parsed_host, port);
+        listener
+            .set_nonblocking(self.options.nonblocking || self.options.timeout.is_some())
+            .map_err(|e| SocketError::BindError(e.to_string()))
+    }
+}
+
+impl Drop for TcpSocket {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[derive(Debug)]
+pub struct UdpSocket {
+    pub socket: Option<StdUdpSocket>,
+    pub options: SocketOptions,
+    pub closed: bool,
+}
+
+impl UdpSocket {
+    fn socket(&mut self) -> Result<&StdUdpSocket, SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        self.socket
+            .as_ref()
+            .ok_or_else(|| SocketError::ReadError("Socket is not bound".to_string()))
+    }
+}
+
+impl Socketable for UdpSocket {
+    fn bind(&mut self, address: &Address) -> Result<(), SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
 
-        match TcpListener::bind(&address) {
+        let address = inet_address(address, self.options.family).map_err(SocketError::BindError)?;
+
+        match bind_inet(address, socket2::Type::DGRAM, self.options) {
+            Ok(socket) => {
+                self.socket = Some(StdUdpSocket::from(socket));
+
+                self.apply_options()
+            }
+            Err(e) => Err(SocketError::BindError(e.to_string())),
+        }
+    }
+
+    fn accept(&mut self) -> Result<Connection, SocketError> {
+        Err(SocketError::Unsupported(
+            "UDP sockets do not accept connections".to_string(),
+        ))
+    }
+
+    fn close(&mut self) {
+        self.socket = None;
+        self.closed = true;
+    }
+
+    fn options(&mut self) -> &mut SocketOptions {
+        &mut self.options
+    }
+
+    fn apply_options(&mut self) -> Result<(), SocketError> {
+        let Some(socket) = &self.socket else {
+            return Ok(());
+        };
+
+        socket
+            .set_nonblocking(self.options.nonblocking)
+            .and_then(|_| socket.set_read_timeout(self.options.timeout))
+            .and_then(|_| socket.set_write_timeout(self.options.timeout))
+            .map_err(|e| SocketError::BindError(e.to_string()))
+    }
+
+    // An unbound socket sends from an ephemeral port, like the OS would.
+    fn send_to(&mut self, data: &[u8], host: &str, port: u16) -> Result<usize, SocketError> {
+        let target = resolve(host, port, self.options.family).map_err(SocketError::SendError)?;
+
+        if !self.closed && self.socket.is_none() {
+            let any = if target.is_ipv6() { "::" } else { "0.0.0.0" };
+
+            self.bind(&Address::Inet(any.to_string(), 0))?;
+        }
+
+        self.socket()?
+            .send_to(data, target)
+            .map_err(|e| io_error(e, "send_to", SocketError::SendError))
+    }
+
+    fn recv_from(&mut self, buffer_size: usize) -> Result<(Vec<u8>, String), SocketError> {
+        // No datagram is larger, so a bigger buffer would only go unused.
+        let mut buffer = vec![0; buffer_size.min(MAX_DATAGRAM_SIZE)];
+
+        match self.socket()?.recv_from(&mut buffer) {
+            Ok((length, from)) => {
+                buffer.truncate(length);
+
+                Ok((buffer, from.to_string()))
+            }
+            Err(e) => Err(io_error(e, "recv_from", SocketError::ReadError)),
+        }
+    }
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct UnixSocket {
+    pub path: PathBuf,
+    pub listener: Option<UnixListener>,
+    pub options: SocketOptions,
+    pub closed: bool,
+}
+
+#[cfg(unix)]
+impl UnixSocket {
+    fn path<'a>(&self, address: &'a Address) -> Result<&'a PathBuf, String> {
+        match address {
+            Address::Unix(path) => Ok(path),
+            Address::Inet(host, port) => Err(format!(
+                "Expected a socket path, got the address '{}:{}'",
+                host, port
+            )),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Socketable for UnixSocket {
+    fn bind(&mut self, address: &Address) -> Result<(), SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        let path = self.path(address).map_err(SocketError::BindError)?.clone();
+
+        match UnixListener::bind(&path) {
             Ok(listener) => {
+                self.path = path;
                 self.listener = Some(listener);
 
-                Ok(())
+                self.apply_options()
             }
             Err(e) => Err(SocketError::BindError(e.to_string())),
         }
     }
 
     fn accept(&mut self) -> Result<Connection, SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
         if let Some(listener) = &self.listener {
-            match listener.accept() {
-                Ok((stream, _)) => Ok(Connection::Tcp(TcpConnection { stream })),
-                Err(e) => Err(SocketError::AcceptError(e.to_string())),
-            }
+            let (stream, _) = accept_within(self.options, || listener.accept())?;
+
+            Connection::Unix(UnixConnection {
+                stream: Some(stream),
+                options: self.options,
+            })
+            .with_options()
         } else {
             Err(SocketError::AcceptError(
                 "Listener is not initialized".to_string(),
             ))
         }
     }
+
+    fn connect(&mut self, address: &Address) -> Result<Connection, SocketError> {
+        if self.closed {
+            return Err(SocketError::Closed);
+        }
+
+        let path = self.path(address).map_err(SocketError::ConnectError)?;
+
+        match UnixStream::connect(path) {
+            Ok(stream) => Connection::Unix(UnixConnection {
+                stream: Some(stream),
+                options: self.options,
+            })
+            .with_options(),
+            Err(e) => Err(SocketError::ConnectError(e.to_string())),
+        }
+    }
+
+    // The socket file outlives the listener, so the side that bound it also
+    // removes it; otherwise the next bind to the same path fails.
+    fn close(&mut self) {
+        if self.listener.take().is_some() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+
+        self.closed = true;
+    }
+
+    fn options(&mut self) -> &mut SocketOptions {
+        &mut self.options
+    }
+
+    fn apply_options(&mut self) -> Result<(), SocketError> {
+        let Some(listener) = &self.listener else {
+            return Ok(());
+        };
+
+        listener
+            .set_nonblocking(self.options.nonblocking || self.options.timeout.is_some())
+            .map_err(|e| SocketError::BindError(e.to_string()))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        self.close();
+    }
 }
 
 pub enum Connection {
     Tcp(TcpConnection),
+    #[cfg(unix)]
+    Unix(UnixConnection),
 }
 
 impl Connection {
     pub fn as_connectionable(&mut self) -> &mut dyn Connectionable {
         match self {
             Connection::Tcp(tcp_connection) => tcp_connection,
+            #[cfg(unix)]
+            Connection::Unix(unix_connection) => unix_connection,
         }
     }
 
+    fn with_options(mut self) -> Result<Self, SocketError> {
+        self.as_connectionable().apply_options()?;
+
+        Ok(self)
+    }
+
     pub fn read(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
-        self.as_connectionable().read(buffer_size)
+        replay::capture("socket.read", || {
+            self.as_connectionable()
+                .read(buffer_size)
+                .map_err(replay_message)
+        })
+        .map_err(replayed)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), SocketError> {
+        let connection = self.as_connectionable();
+        connection.options().timeout = timeout;
+        connection.apply_options()
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), SocketError> {
+        let connection = self.as_connectionable();
+        connection.options().nonblocking = nonblocking;
+        connection.apply_options()
     }
 
     pub fn send(&mut self, data: Vec<u8>) -> Result<(), SocketError> {
         self.as_connectionable().send(data)
     }
 
+    pub fn peer_addr(&mut self) -> Result<Address, SocketError> {
+        self.as_connectionable().peer_addr()
+    }
+
+    pub fn peek(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
+        self.as_connectionable().peek(buffer_size)
+    }
+
+    /// How many bytes can be read right away, as reported by the OS.
+    #[cfg(unix)]
+    pub fn available(&self) -> Result<usize, SocketError> {
+        let fd = self.raw_fd().ok_or(SocketError::Closed)?;
+        let mut available: libc::c_int = 0;
+
+        // SAFETY: FIONREAD writes a single c_int through the pointer.
+        if unsafe { libc::ioctl(fd, libc::FIONREAD, &mut available) } < 0 {
+            return Err(SocketError::ReadError(
+                std::io::Error::last_os_error().to_string(),
+            ));
+        }
+
+        Ok(available as usize)
+    }
+
+    #[cfg(not(unix))]
+    pub fn available(&self) -> Result<usize, SocketError> {
+        Err(SocketError::Unsupported(
+            "available() is not supported on this platform".to_string(),
+        ))
+    }
+
+    #[cfg(unix)]
+    pub fn raw_fd(&self) -> Option<RawFd> {
+        match self {
+            Connection::Tcp(tcp_connection) => {
+                tcp_connection.stream.as_ref().map(|s| s.as_raw_fd())
+            }
+            Connection::Unix(unix_connection) => {
+                unix_connection.stream.as_ref().map(|s| s.as_raw_fd())
+            }
+        }
+    }
+
+    pub fn local_addr(&mut self) -> Result<Address, SocketError> {
+        self.as_connectionable().local_addr()
+    }
+
+    /// A second handle for sending on this connection while another thread
+    /// is blocked reading from it.
+    pub fn writer(&self) -> Result<ConnectionWriter, SocketError> {
+        let clone_error = |e: std::io::Error| SocketError::ReadError(e.to_string());
+
+        match self {
+            Connection::Tcp(tcp_connection) => {
+                let stream = tcp_connection.stream.as_ref().ok_or(SocketError::Closed)?;
+
+                Ok(ConnectionWriter::Tcp(
+                    stream.try_clone().map_err(clone_error)?,
+                ))
+            }
+            #[cfg(unix)]
+            Connection::Unix(unix_connection) => {
+                let stream = unix_connection.stream.as_ref().ok_or(SocketError::Closed)?;
+
+                Ok(ConnectionWriter::Unix(
+                    stream.try_clone().map_err(clone_error)?,
+                ))
+            }
+        }
+    }
+
     pub fn close(&mut self) -> Result<(), SocketError> {
         self.as_connectionable().close()
     }
 }
 
+// Unlike a Connection, dropping a writer leaves the connection open.
+pub enum ConnectionWriter {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl ConnectionWriter {
+    pub fn send(&mut self, data: &[u8]) -> Result<(), SocketError> {
+        let result = match self {
+            ConnectionWriter::Tcp(stream) => stream.write_all(data),
+            #[cfg(unix)]
+            ConnectionWriter::Unix(stream) => stream.write_all(data),
+        };
+
+        result.map_err(|e| io_error(e, "send", SocketError::SendError))
+    }
+}
+
 pub trait Connectionable {
     fn read(&mut self, buffer: usize) -> Result<Vec<u8>, SocketError>;
+    fn peek(&mut self, buffer: usize) -> Result<Vec<u8>, SocketError>;
     fn send(&mut self, data: Vec<u8>) -> Result<(), SocketError>;
     fn close(&mut self) -> Result<(), SocketError>;
+    fn options(&mut self) -> &mut SocketOptions;
+    fn apply_options(&mut self) -> Result<(), SocketError>;
+    fn peer_addr(&mut self) -> Result<Address, SocketError>;
+    fn local_addr(&mut self) -> Result<Address, SocketError>;
 }
 
 pub struct TcpConnection {
-    pub stream: std::net::TcpStream,
+    pub stream: Option<std::net::TcpStream>,
+    pub options: SocketOptions,
+}
+
+impl TcpConnection {
+    fn stream(&mut self) -> Result<&mut std::net::TcpStream, SocketError> {
+        self.stream.as_mut().ok_or(SocketError::Closed)
+    }
 }
 
 impl Connectionable for TcpConnection {
     fn read(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
         let mut buffer = vec![0; buffer_size];
 
-        match self.stream.read(&mut buffer) {
-            Ok(_) => Ok(buffer.into_iter().filter(|&b| b != 0).collect()),
-            Err(e) => Err(SocketError::ReadError(e.to_string())),
+        match self.stream()?.read(&mut buffer) {
+            Ok(length) => {
+                buffer.truncate(length);
+
+                Ok(buffer)
+            }
+            Err(e) => Err(io_error(e, "read", SocketError::ReadError)),
+        }
+    }
+
+    fn peek(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
+        let mut buffer = vec![0; buffer_size];
+
+        match self.stream()?.peek(&mut buffer) {
+            Ok(length) => {
+                buffer.truncate(length);
+
+                Ok(buffer)
+            }
+            Err(e) => Err(io_error(e, "peek", SocketError::ReadError)),
         }
     }
 
     fn send(&mut self, data: Vec<u8>) -> Result<(), SocketError> {
-        match self.stream.write_all(&data) {
+        match self.stream()?.write_all(&data) {
             Ok(_) => Ok(()),
+            Err(e) => Err(io_error(e, "send", SocketError::SendError)),
+        }
+    }
+
+    // Closing twice is a no-op, and a peer that already hung up is not an
+    // error: either way the descriptor is released when the stream drops.
+    fn close(&mut self) -> Result<(), SocketError> {
+        let Some(stream) = self.stream.take() else {
+            return Ok(());
+        };
+
+        match stream.shutdown(std::net::Shutdown::Both) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotConnected => Ok(()),
             Err(e) => Err(SocketError::ReadError(e.to_string())),
         }
     }
 
+    fn options(&mut self) -> &mut SocketOptions {
+        &mut self.options
+    }
+
+    fn apply_options(&mut self) -> Result<(), SocketError> {
+        let options = self.options;
+        let stream = self.stream()?;
+
+        stream
+            .set_nonblocking(options.nonblocking)
+            .and_then(|_| stream.set_read_timeout(options.timeout))
+            .and_then(|_| stream.set_write_timeout(options.timeout))
+            .and_then(|_| stream.set_nodelay(options.nodelay))
+            .and_then(|_| socket2::SockRef::from(&*stream).set_keepalive(options.keepalive))
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+
+    fn peer_addr(&mut self) -> Result<Address, SocketError> {
+        self.stream()?
+            .peer_addr()
+            .map(Address::from)
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+
+    fn local_addr(&mut self) -> Result<Address, SocketError> {
+        self.stream()?
+            .local_addr()
+            .map(Address::from)
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+}
+
+impl Drop for TcpConnection {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(unix)]
+pub struct UnixConnection {
+    pub stream: Option<UnixStream>,
+    pub options: SocketOptions,
+}
+
+#[cfg(unix)]
+impl UnixConnection {
+    fn stream(&mut self) -> Result<&mut UnixStream, SocketError> {
+        self.stream.as_mut().ok_or(SocketError::Closed)
+    }
+}
+
+#[cfg(unix)]
+impl Connectionable for UnixConnection {
+    fn read(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
+        let mut buffer = vec![0; buffer_size];
+
+        match self.stream()?.read(&mut buffer) {
+            Ok(length) => {
+                buffer.truncate(length);
+
+                Ok(buffer)
+            }
+            Err(e) => Err(io_error(e, "read", SocketError::ReadError)),
+        }
+    }
+
+    // UnixStream::peek is not stable yet, so go through socket2.
+    fn peek(&mut self, buffer_size: usize) -> Result<Vec<u8>, SocketError> {
+        let mut buffer = vec![std::mem::MaybeUninit::<u8>::uninit(); buffer_size];
+
+        match socket2::SockRef::from(&*self.stream()?).peek(&mut buffer) {
+            Ok(length) => Ok(buffer[..length]
+                .iter()
+                // SAFETY: peek initialised the first `length` bytes.
+                .map(|byte| unsafe { byte.assume_init() })
+                .collect()),
+            Err(e) => Err(io_error(e, "peek", SocketError::ReadError)),
+        }
+    }
+
+    fn send(&mut self, data: Vec<u8>) -> Result<(), SocketError> {
+        match self.stream()?.write_all(&data) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(io_error(e, "send", SocketError::SendError)),
+        }
+    }
+
     fn close(&mut self) -> Result<(), SocketError> {
-        match self.stream.shutdown(std::net::Shutdown::Both) {
+        let Some(stream) = self.stream.take() else {
+            return Ok(());
+        };
+
+        match stream.shutdown(std::net::Shutdown::Both) {
             Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotConnected => Ok(()),
             Err(e) => Err(SocketError::ReadError(e.to_string())),
         }
     }
+
+    fn options(&mut self) -> &mut SocketOptions {
+        &mut self.options
+    }
+
+    fn apply_options(&mut self) -> Result<(), SocketError> {
+        let options = self.options;
+        let stream = self.stream()?;
+
+        stream
+            .set_nonblocking(options.nonblocking)
+            .and_then(|_| stream.set_read_timeout(options.timeout))
+            .and_then(|_| stream.set_write_timeout(options.timeout))
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+
+    fn peer_addr(&mut self) -> Result<Address, SocketError> {
+        self.stream()?
+            .peer_addr()
+            .map(Address::from)
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+
+    fn local_addr(&mut self) -> Result<Address, SocketError> {
+        self.stream()?
+            .local_addr()
+            .map(Address::from)
+            .map_err(|e| SocketError::ReadError(e.to_string()))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixConnection {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+/// Waits until some of `fds` can be read from or accepted on, or until the
+/// timeout passes, and returns the positions of the ready ones. Hang-ups and
+/// errors count as ready so that the next read reports them.
+#[cfg(unix)]
+pub fn select(fds: &[RawFd], timeout: Option<Duration>) -> Result<Vec<usize>, SocketError> {
+    let mut pollfds: Vec<libc::pollfd> = fds
+        .iter()
+        .map(|&fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+
+    let timeout = timeout.map_or(-1, |timeout| {
+        timeout.as_millis().min(i32::MAX as u128) as i32
+    });
+
+    loop {
+        // SAFETY: `pollfds` is a valid array of `pollfds.len()` entries that
+        // outlives the call.
+        let result =
+            unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, timeout) };
+
+        if result >= 0 {
+            break;
+        }
+
+        let error = std::io::Error::last_os_error();
+
+        if error.kind() != ErrorKind::Interrupted {
+            return Err(SocketError::ReadError(error.to_string()));
+        }
+    }
+
+    Ok(pollfds
+        .iter()
+        .enumerate()
+        .filter(|(_, pollfd)| pollfd.revents != 0)
+        .map(|(index, _)| index)
+        .collect())
 }
 
 pub fn create_socket() -> Socket {
-    Socket::Tcp(TcpSocket { listener: None })
+    Socket::Tcp(TcpSocket {
+        listener: None,
+        options: SocketOptions::default(),
+        closed: false,
+    })
+}
+
+pub fn create_udp_socket() -> Socket {
+    Socket::Udp(UdpSocket {
+        socket: None,
+        options: SocketOptions::default(),
+        closed: false,
+    })
+}
+
+#[cfg(unix)]
+pub fn create_unix_socket(path: PathBuf) -> Socket {
+    Socket::Unix(UnixSocket {
+        path,
+        listener: None,
+        options: SocketOptions::default(),
+        closed: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_releases_socket_and_connection() {
+        let mut socket = create_socket();
+        socket
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        let Socket::Tcp(tcp_socket) = &socket else {
+            unreachable!()
+        };
+        let address = tcp_socket.listener.as_ref().unwrap().local_addr().unwrap();
+
+        let mut client = TcpStream::connect(address).unwrap();
+        let mut connection = socket.accept().unwrap();
+
+        connection.close().unwrap();
+        connection.close().unwrap();
+
+        assert_eq!(client.read(&mut [0; 8]).unwrap(), 0);
+        assert!(matches!(connection.read(8), Err(SocketError::ReadError(_))));
+        assert!(matches!(
+            connection.send(b"late".to_vec()),
+            Err(SocketError::Closed)
+        ));
+
+        socket.close();
+
+        assert!(matches!(socket.accept(), Err(SocketError::Closed)));
+        assert!(TcpStream::connect(address).is_err());
+    }
+
+    #[test]
+    fn test_send_to_a_closed_connection() {
+        let mut socket = create_socket();
+        socket
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        let Socket::Tcp(tcp_socket) = &socket else {
+            unreachable!()
+        };
+        let address = tcp_socket.listener.as_ref().unwrap().local_addr().unwrap();
+
+        let client = TcpStream::connect(address).unwrap();
+        let mut connection = socket.accept().unwrap();
+
+        drop(client);
+
+        // The first writes can still go out before the peer resets the
+        // connection.
+        let error = (0..100)
+            .find_map(|_| {
+                std::thread::sleep(Duration::from_millis(10));
+                connection.send(b"late".to_vec()).err()
+            })
+            .unwrap();
+
+        assert!(matches!(error, SocketError::SendError(_)));
+    }
+
+    #[test]
+    fn test_udp_send_to_and_recv_from() {
+        let mut server = create_udp_socket();
+        server
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        let Socket::Udp(udp_socket) = &server else {
+            unreachable!()
+        };
+        let port = udp_socket
+            .socket
+            .as_ref()
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let mut client = create_udp_socket();
+        assert!(matches!(
+            client.recv_from(8),
+            Err(SocketError::ReadError(_))
+        ));
+        assert_eq!(client.send_to(b"ping", "localhost", port).unwrap(), 4);
+
+        let (data, from) = server.recv_from(64).unwrap();
+        assert_eq!(data, b"ping");
+        assert!(from.starts_with("127.0.0.1:"));
+
+        assert!(matches!(server.accept(), Err(SocketError::Unsupported(_))));
+
+        client.close();
+        assert!(matches!(
+            client.send_to(b"late", "localhost", port),
+            Err(SocketError::Closed)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("kyanite-{}.sock", std::process::id()));
+        let address = Address::Unix(path.clone());
+
+        let mut server = create_unix_socket(path.clone());
+        server.bind(&address).unwrap();
+
+        let mut client = create_unix_socket(path.clone());
+        let mut outgoing = client.connect(&address).unwrap();
+        let mut incoming = server.accept().unwrap();
+
+        outgoing.writer().unwrap().send(b"pi").unwrap();
+        outgoing.send(b"ng".to_vec()).unwrap();
+        assert_eq!(incoming.peek(2).unwrap(), b"pi");
+        assert_eq!(incoming.available().unwrap(), 4);
+        assert_eq!(incoming.read(16).unwrap(), b"ping");
+        assert_eq!(incoming.available().unwrap(), 0);
+        assert_eq!(incoming.local_addr().unwrap(), address);
+        assert_eq!(outgoing.peer_addr().unwrap(), address);
+        assert_eq!(incoming.peer_addr().unwrap(), Address::Unix(PathBuf::new()));
+
+        assert!(matches!(
+            server.bind(&Address::Inet("localhost".to_string(), 0)),
+            Err(SocketError::BindError(_))
+        ));
+
+        server.close();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_timeouts_and_nonblocking_mode() {
+        let mut socket = create_socket();
+        socket
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+        socket.set_timeout(Some(Duration::from_millis(20))).unwrap();
+
+        assert!(matches!(socket.accept(), Err(SocketError::Timeout(_))));
+
+        let Socket::Tcp(tcp_socket) = &socket else {
+            unreachable!()
+        };
+        let address = tcp_socket.listener.as_ref().unwrap().local_addr().unwrap();
+
+        let _client = TcpStream::connect(address).unwrap();
+        let mut connection = socket.accept().unwrap();
+
+        assert!(matches!(connection.read(8), Err(SocketError::Timeout(_))));
+
+        connection.set_timeout(None).unwrap();
+        connection.set_nonblocking(true).unwrap();
+
+        assert!(matches!(connection.read(8), Err(SocketError::Timeout(_))));
+    }
+
+    fn listening_port(socket: &Socket) -> u16 {
+        let Socket::Tcp(tcp_socket) = socket else {
+            unreachable!()
+        };
+
+        tcp_socket
+            .listener
+            .as_ref()
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn test_options_apply_to_new_connections() {
+        let mut server = create_socket();
+        server.set_option(SocketOption::Backlog(4)).unwrap();
+        server.set_option(SocketOption::NoDelay(true)).unwrap();
+        server.set_option(SocketOption::KeepAlive(true)).unwrap();
+        server
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        assert!(matches!(
+            server.set_option(SocketOption::ReuseAddress(false)),
+            Err(SocketError::Unsupported(_))
+        ));
+
+        let _client = TcpStream::connect(("127.0.0.1", listening_port(&server))).unwrap();
+        let Connection::Tcp(connection) = server.accept().unwrap() else {
+            unreachable!()
+        };
+        let stream = connection.stream.as_ref().unwrap();
+
+        assert!(stream.nodelay().unwrap());
+        assert!(socket2::SockRef::from(stream).keepalive().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_select_reports_ready_descriptors() {
+        let mut server = create_socket();
+        server
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        let listener = server.raw_fd().unwrap();
+        let timeout = Some(Duration::from_millis(10));
+
+        assert_eq!(select(&[listener], timeout).unwrap(), Vec::<usize>::new());
+
+        let mut outgoing = create_socket()
+            .connect(&Address::Inet(
+                "localhost".to_string(),
+                listening_port(&server),
+            ))
+            .unwrap();
+
+        assert_eq!(select(&[listener], timeout).unwrap(), vec![0]);
+
+        let incoming = server.accept().unwrap();
+        let fds = [listener, incoming.raw_fd().unwrap()];
+
+        assert_eq!(select(&fds, timeout).unwrap(), Vec::<usize>::new());
+
+        outgoing.send(b"ping".to_vec()).unwrap();
+        assert_eq!(select(&fds, timeout).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve("[::1]", 80, Family::Any).unwrap(),
+            "[::1]:80".parse().unwrap()
+        );
+        assert_eq!(
+            resolve("localhost", 80, Family::V6).unwrap(),
+            "[::1]:80".parse().unwrap()
+        );
+        assert_eq!(
+            resolve("localhost", 80, Family::Any).unwrap(),
+            "127.0.0.1:80".parse().unwrap()
+        );
+        assert!(resolve("127.0.0.1", 80, Family::V6).is_err());
+    }
+
+    #[test]
+    fn test_ipv6_and_dual_stack() {
+        let mut server = create_socket();
+        server.set_family(Family::V6);
+        server
+            .bind(&Address::Inet("localhost".to_string(), 0))
+            .unwrap();
+
+        let port = listening_port(&server);
+        let mut outgoing = create_socket()
+            .connect(&Address::Inet("[::1]".to_string(), port))
+            .unwrap();
+        let mut incoming = server.accept().unwrap();
+
+        outgoing.send(b"ping".to_vec()).unwrap();
+        assert_eq!(incoming.read(4).unwrap(), b"ping");
+        assert_eq!(
+            incoming.local_addr().unwrap(),
+            Address::Inet("::1".to_string(), port)
+        );
+
+        let mut dual_stack = create_socket();
+        dual_stack.set_dual_stack(true);
+        dual_stack
+            .bind(&Address::Inet("::".to_string(), 0))
+            .unwrap();
+
+        let port = listening_port(&dual_stack);
+
+        create_socket()
+            .connect(&Address::Inet("127.0.0.1".to_string(), port))
+            .unwrap();
+        assert!(dual_stack.accept().is_ok());
+    }
 }
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+const BOM: char = '\u{feff}';
+
+/// Reads a source file, which must be UTF-8. A byte order mark at the start
+/// is dropped, and an encoding declared in a comment on one of the first two
+/// lines, as in `# encoding: utf-8`, must name UTF-8.
+pub fn read_source(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|_| format!("Error: Could not read file {}", path.display()))?;
+
+    decode(&bytes).map_err(|e| format!("Error: {}: {}", path.display(), e))
+}
+
+pub fn decode(bytes: &[u8]) -> Result<String, String> {
+    let source = std::str::from_utf8(bytes).map_err(|e| {
+        let offset = e.valid_up_to();
+        let line = bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+
+        format!(
+            "invalid UTF-8 at byte offset {} (line {}); source files must be UTF-8",
+            offset, line
+        )
+    })?;
+    let source = source.strip_prefix(BOM).unwrap_or(source);
+
+    let unsupported = declared_encoding(source)
+        .filter(|encoding| !matches!(encoding.to_ascii_lowercase().as_str(), "utf-8" | "utf8"));
+
+    if let Some(encoding) = unsupported {
+        return Err(format!(
+            "unsupported source encoding '{}'; source files must be UTF-8",
+            encoding
+        ));
+    }
+
+    Ok(source.to_string())
+}
+
+fn declared_encoding(source: &str) -> Option<&str> {
+    source.lines().take(2).find_map(|line| {
+        let comment = line.trim_start().strip_prefix('#')?;
+        let index = comment.find("coding")?;
+        let rest = comment[index + "coding".len()..].strip_prefix([':', '='])?;
+        let name = rest.trim_start();
+        let end = name
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+            .unwrap_or(name.len());
+
+        Some(&name[..end]).filter(|name| !name.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"\xef\xbb\xbfprint(1)\n").unwrap(), "print(1)\n");
+        assert_eq!(
+            decode(b"# encoding: UTF-8\nx = 1\n").unwrap(),
+            "# encoding: UTF-8\nx = 1\n"
+        );
+        assert_eq!(
+            decode(b"x = 1\ny = \"\xff\"\n").unwrap_err(),
+            "invalid UTF-8 at byte offset 11 (line 2); source files must be UTF-8"
+        );
+        assert!(decode(b"# -*- coding: latin-1 -*-\n").is_err());
+    }
+}
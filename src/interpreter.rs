@@ -1,59 +1,178 @@
-use crate::builtins::methods::kya_print;
-use crate::bytecode::CodeObject;
+use crate::builtins::methods::{
+    kya_assert, kya_assert_eq, kya_assert_raises, kya_print, kya_retry,
+};
+use crate::bytecode::{CodeObject, WIDE_OPERAND_SIZE};
+use crate::capabilities::Capabilities;
+use crate::debugger;
+use crate::embed::ModuleBuilder;
 use crate::errors::Error;
-use crate::lock::{kya_acquire_lock, kya_release_lock};
-use crate::objects::bool_object::bool_new;
+use crate::gc::{self, Gc};
+use crate::internal::expand::expand_path;
+use crate::limits::Limits;
+use crate::lock::{kya_acquire_lock, kya_release_lock, kya_yield_lock};
 use crate::objects::class_object::class_new;
-use crate::objects::exception_object::{exception_new, EXCEPTION_TYPE};
+use crate::objects::exception_object::{
+    ExceptionObject, EXCEPTION_TYPE, MEMORY_ERROR_TYPE, PERMISSION_ERROR_TYPE, TIMEOUT_ERROR_TYPE,
+    TYPE_ERROR_TYPE, VALUE_ERROR_TYPE,
+};
+use crate::objects::finalizers;
 use crate::objects::hash_object::HASH_TYPE;
+use crate::objects::host_function_object::{host_function_new, ArgType, HostValue};
 use crate::objects::list_object::LIST_TYPE;
-use crate::objects::modules::sockets::functions::kya_socket;
+use crate::objects::modules::base64::functions::base64_module_new;
+use crate::objects::modules::crypto::functions::crypto_module_new;
+use crate::objects::modules::datetime::functions::datetime_module_new;
+use crate::objects::modules::gc::functions::gc_module_new;
+use crate::objects::modules::http::functions::http_module_new;
+use crate::objects::modules::json::functions::json_module_new;
+use crate::objects::modules::limits::functions::limits_module_new;
+use crate::objects::modules::log::functions::log_module_new;
+use crate::objects::modules::path::functions::path_module_new;
+use crate::objects::modules::random::functions::random_module_new;
+use crate::objects::modules::sockets::functions::{kya_socket, sockets_module_new};
+use crate::objects::modules::structs::functions::structs_module_new;
+use crate::objects::modules::threads::functions::threads_module_new;
 use crate::objects::modules::threads::lock_object::LOCK_TYPE;
-use crate::objects::modules::threads::thread_object::THREAD_OBJECT;
-use crate::objects::none_object::none_new;
+use crate::objects::modules::threads::thread_object::{wait_for_threads, THREAD_OBJECT};
+use crate::objects::modules::time::functions::time_module_new;
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::{kya_false, kya_none, kya_true};
 use crate::objects::string_object::{string_new, STRING_TYPE};
 use crate::objects::url_object::URL_TYPE;
 use crate::objects::utils::object_to_string_repr;
 use crate::opcodes::OPCODE_HANDLERS;
+use crate::pkg::modules_dir;
+use crate::prelude;
+use crate::profiler;
+use crate::runtime::{self, Runtime};
+use crate::symbol::Symbol;
+use crate::trace;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::LazyLock as Lazy;
 use std::sync::{Arc, Mutex};
-use std::thread;
-
-pub static NONE_OBJECT: Lazy<KyaObjectRef> =
-    Lazy::new(|| none_new().expect("Failed to create None object"));
-pub static TRUE_OBJECT: Lazy<KyaObjectRef> = Lazy::new(|| bool_new(true));
-pub static FALSE_OBJECT: Lazy<KyaObjectRef> = Lazy::new(|| bool_new(false));
 
 use crate::objects::base::{default_repr, DictRef, KyaObject, KyaObjectRef, BASE_TYPE};
 
 pub struct Interpreter {
     root: PathBuf,
+    search_paths: Vec<PathBuf>,
+    runtime: Arc<Runtime>,
+    limits: Limits,
+    // Functions and modules registered by the host, added to the globals of
+    // each run.
+    natives: HashMap<Symbol, KyaObjectRef>,
 }
 
 pub struct Frame {
     pub locals: DictRef,
+    // The values of the function's local slots, see `CodeObject::varnames`.
+    pub fast_locals: Vec<Option<KyaObjectRef>>,
     pub globals: DictRef,
     pub code: Arc<CodeObject>,
     pub pc: usize,
     pub stack: Vec<KyaObjectRef>,
     pub return_value: Option<KyaObjectRef>,
     pub error: Option<KyaObjectRef>,
+    pub blocks: Vec<EnsureBlock>,
+    pub pending_error: Option<Error>,
+}
+
+// A `try` whose ensure handler is active: where to jump when the body raises
+// and how deep the value stack was when it was entered.
+pub struct EnsureBlock {
+    pub handler: usize,
+    pub stack_depth: usize,
+}
+
+// Function calls reuse the allocations of frames that already returned, so
+// that calling a small function in a loop does not allocate a new dict and
+// stacks each time. The pool holds them empty, so it is not tied to a runtime.
+const FRAME_POOL_SIZE: usize = 64;
+
+struct FrameParts {
+    locals: DictRef,
+    fast_locals: Vec<Option<KyaObjectRef>>,
+    stack: Vec<KyaObjectRef>,
+    blocks: Vec<EnsureBlock>,
+}
+
+thread_local! {
+    static FRAME_POOL: RefCell<Vec<FrameParts>> = const { RefCell::new(Vec::new()) };
 }
 
 impl Frame {
-    pub fn register_local(&mut self, name: &str, object: KyaObjectRef) {
-        self.locals.lock().unwrap().insert(name.to_string(), object);
+    /// A frame for a call of `code`, with its slots unassigned.
+    pub fn for_call(code: Arc<CodeObject>, globals: DictRef) -> Frame {
+        let parts = FRAME_POOL.with(|pool| pool.borrow_mut().pop());
+
+        let (locals, mut fast_locals, stack, blocks) = match parts {
+            Some(parts) => (parts.locals, parts.fast_locals, parts.stack, parts.blocks),
+            None => (Arc::new(Mutex::new(HashMap::new())), vec![], vec![], vec![]),
+        };
+
+        fast_locals.resize(code.varnames.len(), None);
+
+        Frame {
+            locals,
+            fast_locals,
+            globals,
+            code,
+            pc: 0,
+            stack,
+            return_value: None,
+            error: None,
+            blocks,
+            pending_error: None,
+        }
+    }
+
+    /// Hands the allocations of a frame made by `for_call` back to the pool.
+    /// Its locals are only reused if nothing else holds on to them.
+    pub fn release(self) {
+        let Frame {
+            locals,
+            mut fast_locals,
+            mut stack,
+            mut blocks,
+            ..
+        } = self;
+
+        if Arc::strong_count(&locals) > 1 {
+            return;
+        }
+
+        locals.lock().unwrap().clear();
+        fast_locals.clear();
+        stack.clear();
+        blocks.clear();
+
+        FRAME_POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+
+            if pool.len() < FRAME_POOL_SIZE {
+                pool.push(FrameParts {
+                    locals,
+                    fast_locals,
+                    stack,
+                    blocks,
+                });
+            }
+        });
+    }
+
+    pub fn register_local(&mut self, name: impl Into<Symbol>, object: KyaObjectRef) {
+        self.locals.lock().unwrap().insert(name.into(), object);
     }
 
-    pub fn resolve(&self, name: &str) -> Result<KyaObjectRef, Error> {
-        if let Some(object) = self.locals.lock().unwrap().get(name) {
+    pub fn resolve(&self, name: impl Into<Symbol>) -> Result<KyaObjectRef, Error> {
+        let name = name.into();
+
+        if let Some(object) = self.locals.lock().unwrap().get(&name) {
             return Ok(object.clone());
         }
 
-        if let Some(object) = self.globals.lock().unwrap().get(name) {
+        if let Some(object) = self.globals.lock().unwrap().get(&name) {
             return Ok(object.clone());
         }
 
@@ -63,6 +182,30 @@ impl Frame {
         )))
     }
 
+    pub fn load_fast(&self, index: usize) -> Result<KyaObjectRef, Error> {
+        match self.fast_locals.get(index) {
+            Some(Some(object)) => Ok(object.clone()),
+            // Not assigned yet in this call, so the name still refers to a
+            // global, as it would with `LoadName`.
+            Some(None) => self.resolve(self.code.varnames[index]),
+            None => Err(Error::RuntimeError(format!(
+                "Local at index {} not defined",
+                index
+            ))),
+        }
+    }
+
+    pub fn store_fast(&mut self, index: usize, object: KyaObjectRef) -> Result<(), Error> {
+        let slot = self
+            .fast_locals
+            .get_mut(index)
+            .ok_or_else(|| Error::RuntimeError(format!("Local at index {} not defined", index)))?;
+
+        *slot = Some(object);
+
+        Ok(())
+    }
+
     pub fn get_const(&self, index: usize) -> Option<KyaObjectRef> {
         if index < self.code.consts.len() {
             return Some(self.code.consts[index].clone());
@@ -71,12 +214,8 @@ impl Frame {
         None
     }
 
-    pub fn get_name(&self, index: usize) -> Option<String> {
-        if index < self.code.names.len() {
-            return Some(self.code.names[index].clone());
-        }
-
-        None
+    pub fn get_name(&self, index: usize) -> Option<Symbol> {
+        self.code.names.get(index).copied()
     }
 
     pub fn current_pc(&self) -> usize {
@@ -106,6 +245,14 @@ impl Frame {
         );
     }
 
+    pub fn next_wide_operand(&mut self) -> usize {
+        let value = self.code.wide_operand_at(self.pc);
+
+        self.pc += WIDE_OPERAND_SIZE;
+
+        value
+    }
+
     pub fn current_code_length(&self) -> usize {
         self.code.instructions_count()
     }
@@ -133,14 +280,38 @@ impl Frame {
     }
 }
 
+pub fn builtin_modules() -> Vec<(&'static str, KyaObjectRef)> {
+    vec![
+        ("base64", base64_module_new()),
+        ("crypto", crypto_module_new()),
+        ("datetime", datetime_module_new()),
+        ("gc", gc_module_new()),
+        ("http", http_module_new()),
+        ("json", json_module_new()),
+        ("limits", limits_module_new()),
+        ("log", log_module_new()),
+        ("path", path_module_new()),
+        ("random", random_module_new()),
+        ("sockets", sockets_module_new()),
+        ("struct", structs_module_new()),
+        ("threads", threads_module_new()),
+        ("time", time_module_new()),
+    ]
+}
+
 fn register_builtin_objects(frame: &mut Frame) {
     let print_rs_function_object = rs_function_new(kya_print);
 
     frame.register_local("print", print_rs_function_object);
-    frame.register_local("None", NONE_OBJECT.clone());
-    frame.register_local("true", TRUE_OBJECT.clone());
-    frame.register_local("false", FALSE_OBJECT.clone());
+    frame.register_local("assert", rs_function_new(kya_assert));
+    frame.register_local("assert_eq", rs_function_new(kya_assert_eq));
+    frame.register_local("assert_raises", rs_function_new(kya_assert_raises));
+    frame.register_local("retry", rs_function_new(kya_retry));
     frame.register_local("socket", rs_function_new(kya_socket));
+
+    for (name, module) in builtin_modules() {
+        frame.register_local(name, module);
+    }
 }
 
 fn register_builtin_types(frame: &mut Frame) {
@@ -150,7 +321,7 @@ fn register_builtin_types(frame: &mut Frame) {
         .dict
         .lock()
         .unwrap()
-        .insert("__repr__".to_string(), rs_function_new(default_repr));
+        .insert("__repr__".into(), rs_function_new(default_repr));
 
     let type_object = class_new(BASE_TYPE.clone());
     let string_class = class_new(STRING_TYPE.clone());
@@ -168,87 +339,310 @@ fn register_builtin_types(frame: &mut Frame) {
     frame.register_local("Lock", lock_class);
     frame.register_local("Hash", hash_class);
     frame.register_local("Exception", exception_class);
+    frame.register_local("TimeoutError", class_new(TIMEOUT_ERROR_TYPE.clone()));
+    frame.register_local("MemoryError", class_new(MEMORY_ERROR_TYPE.clone()));
+    frame.register_local("PermissionError", class_new(PERMISSION_ERROR_TYPE.clone()));
+    frame.register_local("TypeError", class_new(TYPE_ERROR_TYPE.clone()));
+    frame.register_local("ValueError", class_new(VALUE_ERROR_TYPE.clone()));
     frame.register_local("Url", url_class);
 
     // frame.register_local(RS_FUNCTION_TYPE, rs_function_type);
 }
 
-fn register_builtins(frame: &mut Frame) {
-    register_builtin_types(frame);
-    register_builtin_objects(frame);
+// The builtins of the current runtime. The objects are shared by its runs, so
+// a run that changes a module leaves it changed for later runs of the same
+// interpreter.
+fn builtins() -> HashMap<Symbol, KyaObjectRef> {
+    runtime::current()
+        .builtins
+        .get_or_init(|| {
+            let mut frame = bare_frame(CodeObject::new());
+
+            register_builtin_types(&mut frame);
+            register_builtin_objects(&mut frame);
+
+            // Its functions keep these globals, which have every builtin.
+            let mut prelude = frame_with_globals(prelude::code(), frame.globals.clone());
+            register_singletons(&mut prelude);
+
+            if let Err(error) = debugger::unseen(|| eval_frame(&mut prelude)) {
+                panic!("The prelude failed to run: {}", error);
+            }
+
+            let globals = frame.globals.lock().unwrap();
+
+            globals.clone()
+        })
+        .clone()
+}
+
+// Registered separately since they are created with the runtime itself.
+fn register_singletons(frame: &mut Frame) {
+    frame.register_local("None", kya_none());
+    frame.register_local("true", kya_true());
+    frame.register_local("false", kya_false());
+}
+
+/// The builtin functions, types and constants. Module names are left out:
+/// they make common variable names, like `path`.
+pub fn builtin_names() -> Vec<String> {
+    let modules: Vec<&str> = builtin_modules().iter().map(|(name, _)| *name).collect();
+
+    builtins()
+        .keys()
+        .map(|name| name.as_str())
+        .chain(["None", "true", "false"])
+        .filter(|name| !modules.contains(name))
+        .map(|name| name.to_string())
+        .collect()
 }
 
-fn create_main_frame(code: CodeObject) -> Frame {
-    let globals = Arc::new(Mutex::new(HashMap::new()));
-    let mut frame = Frame {
+fn bare_frame(code: CodeObject) -> Frame {
+    frame_with_globals(code, Arc::new(Mutex::new(HashMap::new())))
+}
+
+// A main frame whose globals are `globals`.
+fn frame_with_globals(code: CodeObject, globals: DictRef) -> Frame {
+    Frame {
         locals: globals.clone(),
+        fast_locals: vec![],
         globals,
         code: Arc::new(code),
         pc: 0,
         stack: vec![],
         return_value: None,
         error: None,
-    };
+        blocks: vec![],
+        pending_error: None,
+    }
+}
 
-    register_builtins(&mut frame);
+pub(crate) fn create_main_frame(code: CodeObject) -> Frame {
+    let mut frame = bare_frame(code);
+
+    frame.globals.lock().unwrap().clone_from(&builtins());
+    register_singletons(&mut frame);
 
     frame
 }
 
+/// Directories to import modules from after the root and its `kya_modules`,
+/// separated like `PATH`.
+pub const SEARCH_PATH_VAR: &str = "KYA_PATH";
+
 impl Interpreter {
     pub fn new(root: &str) -> Self {
         let root_path = PathBuf::from(root);
+        let mut search_paths = vec![root_path.clone(), modules_dir(&root_path)];
+
+        if let Some(entries) = std::env::var_os(SEARCH_PATH_VAR) {
+            search_paths.extend(std::env::split_paths(&entries));
+        }
+
+        Interpreter {
+            root: root_path,
+            search_paths,
+            runtime: Runtime::new(),
+            limits: Limits::default(),
+            natives: HashMap::new(),
+        }
+    }
+
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    // Entries may reference `~` and environment variables, e.g.
+    // `$APP_HOME/handlers`.
+    pub fn add_search_path(&mut self, entry: &str) -> Result<(), String> {
+        self.search_paths.push(PathBuf::from(expand_path(entry)?));
 
-        Interpreter { root: root_path }
+        Ok(())
     }
 
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.runtime.set_capabilities(capabilities);
+    }
+
+    /// Makes `function` a global of the programs this interpreter runs. Its
+    /// arguments and result are checked against `params` and `returns`.
+    pub fn register_fn(
+        &mut self,
+        name: &str,
+        params: &[ArgType],
+        returns: ArgType,
+        function: impl Fn(Vec<HostValue>) -> Result<HostValue, Error> + Send + Sync + 'static,
+    ) {
+        self.natives.insert(
+            name.into(),
+            host_function_new(name, params, returns, function),
+        );
+    }
+
+    /// Makes a module named `name` a global of the programs this interpreter
+    /// runs. `build` adds its members.
+    pub fn register_module(&mut self, name: &str, build: impl FnOnce(&mut ModuleBuilder)) {
+        let mut builder = ModuleBuilder::new();
+
+        build(&mut builder);
+
+        self.natives.insert(name.into(), builder.build(name));
+    }
+
+    /// Runs the program and then shuts down in order: once the main code
+    /// returns or raises, waits for every thread that is not a daemon, then
+    /// runs the finalizers that are still pending. Daemon threads are left
+    /// running and stop when the process exits.
     pub fn eval(&mut self, code_object: &CodeObject) -> Result<KyaObjectRef, Error> {
+        runtime::enter(self.runtime.clone());
+        self.runtime.budget.start(self.limits);
+        self.runtime.set_search_paths(self.search_paths.clone());
         kya_acquire_lock();
 
         let mut frame = create_main_frame(code_object.clone());
 
+        frame.globals.lock().unwrap().extend(self.natives.clone());
+
+        let result = eval_frame(&mut frame);
+
+        kya_release_lock();
+        wait_for_threads();
+        kya_acquire_lock();
+
+        drop(frame);
+        finalizers::run_pending();
+
+        kya_release_lock();
+
+        result
+    }
+}
+
+/// Runs pieces of code one after another in the same globals, so that each
+/// one sees what the earlier ones defined, as the REPL does. Unlike
+/// `Interpreter::eval`, a run does not wait for the threads it started.
+pub struct Session {
+    interpreter: Interpreter,
+    globals: DictRef,
+}
+
+impl Session {
+    pub fn new(interpreter: Interpreter) -> Self {
+        runtime::enter(interpreter.runtime.clone());
+        kya_acquire_lock();
+
+        let globals = create_main_frame(CodeObject::new()).globals;
+        globals.lock().unwrap().extend(interpreter.natives.clone());
+
+        kya_release_lock();
+
+        Session {
+            interpreter,
+            globals,
+        }
+    }
+
+    pub fn eval(&mut self, code_object: &CodeObject) -> Result<KyaObjectRef, Error> {
+        let runtime = self.interpreter.runtime.clone();
+
+        runtime::enter(runtime.clone());
+        runtime.budget.start(self.interpreter.limits);
+        runtime.set_search_paths(self.interpreter.search_paths.clone());
+        kya_acquire_lock();
+
+        let mut frame = frame_with_globals(code_object.clone(), self.globals.clone());
         let result = eval_frame(&mut frame);
 
+        drop(frame);
         kya_release_lock();
 
         result
     }
 }
 
+// Instructions run by the thread since its last safe point. Counted per
+// thread rather than per frame, so that short calls made in a loop reach the
+// safe point too.
+const SAFE_POINT_INTERVAL: u32 = 100;
+
+thread_local! {
+    static INSTRUCTIONS_PROCESSED: Cell<u32> = const { Cell::new(0) };
+}
+
+// Lets other threads run, runs pending finalizers and collections, and
+// checks the limits of the run.
+fn safe_point() -> Result<(), Error> {
+    kya_yield_lock();
+
+    finalizers::run_pending();
+    gc::maybe_collect();
+
+    runtime::current().budget.charge(SAFE_POINT_INTERVAL as u64)
+}
+
 pub fn eval_frame(frame: &mut Frame) -> Result<KyaObjectRef, Error> {
-    let mut instructions_processed = 0;
+    let profiling = profiler::is_enabled();
+    let tracing = trace::is_enabled();
+    let debugging = debugger::is_attached();
+    let _debugged = debugging.then(|| debugger::enter(frame));
 
     while frame.current_pc() < frame.current_code_length() {
-        if instructions_processed >= 100 {
-            instructions_processed = 0;
+        let instructions_processed = INSTRUCTIONS_PROCESSED.with(|count| count.get());
 
-            kya_release_lock();
-            thread::yield_now();
-            kya_acquire_lock();
-        }
+        let result = if instructions_processed >= SAFE_POINT_INTERVAL {
+            INSTRUCTIONS_PROCESSED.with(|count| count.set(0));
+
+            safe_point()
+        } else {
+            INSTRUCTIONS_PROCESSED.with(|count| count.set(instructions_processed + 1));
 
-        let opcode = frame.next_opcode();
+            let pc = frame.current_pc();
+
+            if debugging {
+                debugger::before_instruction(frame, pc);
+            }
 
-        let result = OPCODE_HANDLERS[opcode as usize](frame);
+            let opcode = frame.next_opcode();
 
-        if let Err(error) = result {
-            if let Error::Exception(_, _) = error {
-                return Err(error);
+            let result = if profiling {
+                profiler::time_opcode(opcode, || OPCODE_HANDLERS[opcode as usize](frame))
             } else {
-                let error_object = map_error_to_exception(error)?;
-                handle_exception(error_object.clone())?;
+                OPCODE_HANDLERS[opcode as usize](frame)
+            };
+
+            if tracing {
+                trace::trace(frame, pc);
             }
-        }
 
-        instructions_processed += 1;
+            result
+        };
+
+        let error = match result {
+            Err(error @ Error::Exception(..)) => error,
+            Err(error) => exception_to_error(&map_error_to_exception(error)?, &mut Vec::new())?,
+            Ok(()) => {
+                if let Some(return_value) = &frame.return_value {
+                    return Ok(return_value.clone());
+                }
+
+                match frame.error.take() {
+                    Some(error) => exception_to_error(&error, &mut Vec::new())?,
+                    None => continue,
+                }
+            }
+        };
 
-        if let Some(return_value) = &frame.return_value {
-            return Ok(return_value.clone());
-        }
+        let Some(block) = frame.blocks.pop() else {
+            return Err(error);
+        };
 
-        if let Some(error) = &frame.error {
-            handle_exception(error.clone())?;
-        }
+        frame.stack.truncate(block.stack_depth);
+        frame.pending_error = Some(error);
+        frame.set_pc(block.handler);
     }
 
     if let Some(object) = frame.stack.last() {
@@ -258,20 +652,30 @@ pub fn eval_frame(frame: &mut Frame) -> Result<KyaObjectRef, Error> {
     Ok(frame.resolve("None")?)
 }
 
+// The exception an error of the interpreter raises in the program, which
+// keeps the error's code for the report of an uncaught one.
 fn map_error_to_exception(error: Error) -> Result<KyaObjectRef, Error> {
-    let message = match error {
-        Error::RuntimeError(msg) => msg,
-        _ => error.to_string(),
+    let ob_type = match error {
+        Error::TypeError(_) => TYPE_ERROR_TYPE.clone(),
+        Error::ValueError(_) => VALUE_ERROR_TYPE.clone(),
+        _ => EXCEPTION_TYPE.clone(),
     };
 
-    let exception_object = exception_new(string_new(&message));
-
-    Ok(exception_object)
+    Ok(KyaObject::from_exception(ExceptionObject {
+        ob_type,
+        message: string_new(&error.message()),
+        cause: None,
+        code: error.code(),
+    }))
 }
 
-fn handle_exception(error: KyaObjectRef) -> Result<KyaObjectRef, Error> {
-    let message = match &*error.lock().unwrap() {
-        KyaObject::ExceptionObject(exception) => exception.message.clone(),
+fn exception_to_error(error: &KyaObjectRef, seen: &mut Vec<KyaObjectRef>) -> Result<Error, Error> {
+    let (message, cause, code) = match &*error.lock().unwrap() {
+        KyaObject::ExceptionObject(exception) => (
+            exception.message.clone(),
+            exception.cause.clone(),
+            exception.code,
+        ),
         _ => {
             return Err(Error::RuntimeError(
                 "Uncaught exception is not an ExceptionObject".to_string(),
@@ -288,8 +692,158 @@ fn handle_exception(error: KyaObjectRef) -> Result<KyaObjectRef, Error> {
         .name
         .clone();
 
-    Err(Error::Exception(
+    seen.push(error.clone());
+
+    // Causes can be chained into a cycle, so stop at the first repeat.
+    let cause = match cause {
+        Some(cause) if !seen.iter().any(|other| Gc::ptr_eq(other, &cause)) => {
+            Some(Box::new(exception_to_error(&cause, seen)?))
+        }
+        _ => None,
+    };
+
+    Ok(Error::Exception(
         ob_type_name,
         object_to_string_repr(&message)?,
+        cause,
+        code,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    // Runs `source` and returns the outcome together with the repr of its
+    // `log` global, which the programs below append to.
+    fn run(source: &str) -> (Result<KyaObjectRef, Error>, String) {
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        kya_acquire_lock();
+
+        let mut frame = create_main_frame(compiler.get_output());
+        let result = eval_frame(&mut frame);
+        let log = object_to_string_repr(&frame.resolve("log").unwrap()).unwrap();
+
+        kya_release_lock();
+
+        (result, log)
+    }
+
+    #[test]
+    fn test_ensure_on_fallthrough() {
+        let (result, log) = run("log = List()\n\
+             try\n    log.append(\"body\")\nensure\n    log.append(\"ensure\")\nend\n\
+             log.append(\"after\")\n");
+
+        assert!(result.is_ok());
+        assert_eq!(log, "[body, ensure, after]");
+    }
+
+    #[test]
+    fn test_ensure_on_return() {
+        let (result, log) = run("log = List()\n\
+             def f\n    try\n        try\n            return 1\n        ensure\n            \
+             log.append(\"inner\")\n        end\n    ensure\n        log.append(\"outer\")\n    \
+             end\n    log.append(\"unreachable\")\nend\n\
+             log.append(f())\n");
+
+        assert!(result.is_ok());
+        assert_eq!(log, "[inner, outer, 1]");
+    }
+
+    #[test]
+    fn test_ensure_on_break() {
+        let (result, log) = run("log = List()\n\
+             try\n    while true\n        try\n            break\n        ensure\n            \
+             log.append(\"loop\")\n        end\n    end\n    log.append(\"after loop\")\n\
+             ensure\n    log.append(\"outer\")\nend\n");
+
+        assert!(result.is_ok());
+        assert_eq!(log, "[loop, after loop, outer]");
+    }
+
+    #[test]
+    fn test_ensure_on_exception() {
+        let (result, log) = run("log = List()\n\
+             def fail\n    raise Exception(\"boom\")\nend\n\
+             try\n    try\n        fail()\n    ensure\n        log.append(\"inner\")\n    end\n\
+             ensure\n    log.append(\"outer\")\nend\n\
+             log.append(\"unreachable\")\n");
+
+        assert!(matches!(result, Err(Error::Exception(_, message, ..)) if message == "boom"));
+        assert_eq!(log, "[inner, outer]");
+    }
+
+    #[test]
+    fn test_with_exits_resource() {
+        let (result, log) = run("log = List()\n\
+             class Resource\n    def __exit__\n        log.append(\"exit\")\n    end\nend\n\
+             def f\n    with Resource() as r\n        return \"returned\"\n    end\nend\n\
+             log.append(f())\n\
+             with Resource()\n    raise Exception(\"boom\")\nend\n");
+
+        assert!(matches!(result, Err(Error::Exception(_, message, ..)) if message == "boom"));
+        assert_eq!(log, "[exit, returned, exit]");
+    }
+
+    #[test]
+    fn test_thread_stack_size() {
+        let (result, log) = run("log = List()\n\
+             def depth(n)\n    if n == 0\n        return 0\n    end\n    return depth(n - 1) + 1\nend\n\
+             def work(log)\n    log.append(depth(2000))\nend\n\
+             thread = Thread(work, log)\n\
+             thread.set_stack_size(268435456)\n\
+             thread.start()\n\
+             thread.join()\n");
+
+        assert!(result.is_ok());
+        assert_eq!(log, "[2000]");
+
+        let (result, _) = run("log = List()\n\
+             def work\nend\n\
+             thread = Thread(work)\n\
+             thread.start()\n\
+             thread.set_stack_size(1048576)\n");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_main_frames_share_builtins() {
+        kya_acquire_lock();
+
+        let mut first = create_main_frame(CodeObject::new());
+        let second = create_main_frame(CodeObject::new());
+
+        first.register_local("print", kya_none());
+
+        assert!(Gc::ptr_eq(
+            &first.resolve("json").unwrap(),
+            &second.resolve("json").unwrap()
+        ));
+        assert!(!Gc::ptr_eq(
+            &first.resolve("print").unwrap(),
+            &second.resolve("print").unwrap()
+        ));
+
+        kya_release_lock();
+    }
+
+    #[test]
+    fn test_calls_reuse_frames() {
+        let (result, log) = run("log = List()\n\
+             def count(n)\n    total = 0\n    while n > 0\n        total = total + n\n        n = n - 1\n    end\n    return total\nend\n\
+             log.append(count(3))\n\
+             log.append(count(0))\n");
+
+        assert!(result.is_ok());
+        assert_eq!(log, "[6, 0]");
+        assert!(FRAME_POOL.with(|pool| !pool.borrow().is_empty()));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::diagnostics::Diagnostic;
 use crate::errors::{Error, LexerError};
 use std::collections::HashMap;
 
@@ -31,6 +32,16 @@ pub enum TokenType {
     Return,
     Not,
     Raise,
+    From,
+    Try,
+    Ensure,
+    With,
+    As,
+    Is,
+    LeftBracket,
+    RightBracket,
+    DotDot,
+    Colon,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -48,12 +59,63 @@ pub struct Lexer {
     line: usize,
     column: usize,
     symbols: HashMap<String, TokenType>,
+    comments: Vec<Token>,
 }
 
-pub fn unescape_string_literal(s: &str) -> String {
-    s.replace("\\n", "\n")
-        .replace("\\t", "\t")
-        .replace("\\r", "\r")
+/// The value of the text between the quotes of a string literal.
+pub fn unescape_string_literal(s: &str) -> Result<String, Diagnostic> {
+    let mut value = String::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let invalid = |sequence: &str| Diagnostic::InvalidEscape(format!("\\{}", sequence));
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"' | '\'')) => c,
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+
+                u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|byte| digits.len() == 2 && byte.is_ascii())
+                    .map(char::from)
+                    .ok_or_else(|| invalid(&format!("x{}", digits)))?
+            }
+            Some('u') => {
+                let rest = chars.as_str();
+                let digits = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(digits, _)| digits)
+                    .ok_or_else(|| invalid("u"))?;
+
+                // Up to six digits, as many as the largest code point has.
+                let c = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .filter(|_| !digits.is_empty() && digits.len() <= 6)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(&format!("u{{{}}}", digits)))?;
+
+                chars = rest[digits.len() + 2..].chars();
+                c
+            }
+            Some(c) => return Err(invalid(&c.to_string())),
+            None => return Err(invalid("")),
+        };
+
+        value.push(escaped);
+    }
+
+    Ok(value)
 }
 
 fn is_newline(c: char) -> bool {
@@ -61,7 +123,7 @@ fn is_newline(c: char) -> bool {
 }
 
 fn is_whitespace(c: char) -> bool {
-    c == ' ' || c == '\t' || c == '\r'
+    c == ' ' || c == '\t'
 }
 
 fn is_identifier(c: char) -> bool {
@@ -105,6 +167,10 @@ fn symbols() -> HashMap<String, TokenType> {
     symbols.insert(",".to_string(), TokenType::Comma);
     symbols.insert("class".to_string(), TokenType::Class);
     symbols.insert(".".to_string(), TokenType::Dot);
+    symbols.insert("..".to_string(), TokenType::DotDot);
+    symbols.insert("[".to_string(), TokenType::LeftBracket);
+    symbols.insert("]".to_string(), TokenType::RightBracket);
+    symbols.insert(":".to_string(), TokenType::Colon);
     symbols.insert("if".to_string(), TokenType::If);
     symbols.insert("import".to_string(), TokenType::Import);
     symbols.insert("+".to_string(), TokenType::Plus);
@@ -114,6 +180,12 @@ fn symbols() -> HashMap<String, TokenType> {
     symbols.insert("return".to_string(), TokenType::Return);
     symbols.insert("!".to_string(), TokenType::Not);
     symbols.insert("raise".to_string(), TokenType::Raise);
+    symbols.insert("from".to_string(), TokenType::From);
+    symbols.insert("try".to_string(), TokenType::Try);
+    symbols.insert("ensure".to_string(), TokenType::Ensure);
+    symbols.insert("with".to_string(), TokenType::With);
+    symbols.insert("as".to_string(), TokenType::As);
+    symbols.insert("is".to_string(), TokenType::Is);
     symbols
 }
 
@@ -123,12 +195,27 @@ fn is_symbol(c: char) -> bool {
 
 impl Lexer {
     pub fn new(input: String) -> Self {
+        // Editors on Windows often start UTF-8 files with a byte order mark.
+        let input = match input.strip_prefix('\u{feff}') {
+            Some(rest) => rest.to_string(),
+            None => input,
+        };
+
+        // A `#!` line lets scripts be run directly on Unix. It is skipped
+        // rather than read as a comment, so it never becomes a doc comment.
+        let position = if input.starts_with("#!") {
+            input.find(['\n', '\r']).unwrap_or(input.len())
+        } else {
+            0
+        };
+
         Lexer {
             input,
-            position: 0,
+            position,
             line: 1,
             column: 1,
             symbols: symbols(),
+            comments: vec![],
         }
     }
 
@@ -146,7 +233,8 @@ impl Lexer {
             }
 
             if is_comment(c) {
-                self.read_comment();
+                let comment = self.read_comment();
+                self.comments.push(comment);
                 continue;
             }
 
@@ -167,7 +255,7 @@ impl Lexer {
             }
 
             return Err(Error::LexerError(LexerError::new(
-                format!("Invalid symbol: {}", c),
+                Diagnostic::InvalidSymbol(c),
                 self.line,
                 self.column,
             )));
@@ -176,6 +264,45 @@ impl Lexer {
         Ok(None)
     }
 
+    /// The comments read so far that were not taken as doc comments.
+    pub fn comments(&self) -> &[Token] {
+        &self.comments
+    }
+
+    /// Returns the block of comment lines ending right above `line`, which is
+    /// how doc comments are attached to the `def` or `class` that follows them.
+    pub fn take_doc_comment(&mut self, line: usize) -> Option<String> {
+        let mut lines = Vec::new();
+        let mut expected_line = line;
+
+        while let Some(comment) = self.comments.last() {
+            if comment.line + 1 != expected_line {
+                break;
+            }
+
+            let comment = self.comments.pop().unwrap();
+            expected_line = comment.line;
+
+            lines.push(
+                comment
+                    .value
+                    .strip_prefix(' ')
+                    .unwrap_or(&comment.value)
+                    .to_string(),
+            );
+        }
+
+        self.comments.clear();
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        lines.reverse();
+
+        Some(lines.join("\n"))
+    }
+
     fn advance(&mut self) {
         self.position += self.peek().unwrap().len_utf8();
         self.column += 1;
@@ -185,8 +312,19 @@ impl Lexer {
         self.input[self.position..].chars().next()
     }
 
+    fn peek_next(&self) -> Option<char> {
+        self.input[self.position..].chars().nth(1)
+    }
+
+    // A Windows "\r\n" ends a single line.
     fn read_newline(&mut self) -> Token {
         let c = self.peek().unwrap();
+        let value = if c == '\r' && self.peek_next() == Some('\n') {
+            self.advance();
+            "\r\n".to_string()
+        } else {
+            c.to_string()
+        };
 
         self.advance();
 
@@ -195,7 +333,7 @@ impl Lexer {
 
         Token {
             kind: TokenType::Newline,
-            value: c.to_string(),
+            value,
             line: self.line,
             column: self.column,
         }
@@ -237,30 +375,24 @@ impl Lexer {
         let column_start = self.column;
 
         while let Some(c) = self.peek() {
-            if is_identifier(c) {
-                identifier.push(c);
-
-                if is_keyword(&identifier) {
-                    self.advance();
-
-                    let kind = self.symbols.get(&identifier).unwrap().clone();
-
-                    return Token {
-                        kind,
-                        value: identifier,
-                        line: self.line,
-                        column: column_start,
-                    };
-                }
-
-                self.advance();
-            } else {
+            if !is_identifier(c) {
                 break;
             }
+
+            identifier.push(c);
+            self.advance();
         }
 
+        // Keywords are only recognized as whole words, so names such as
+        // `from_timestamp` or `define` stay identifiers.
+        let kind = if is_keyword(&identifier) {
+            self.symbols.get(&identifier).unwrap().clone()
+        } else {
+            TokenType::Identifier
+        };
+
         Token {
-            kind: TokenType::Identifier,
+            kind,
             value: identifier,
             line: self.line,
             column: column_start,
@@ -280,47 +412,93 @@ impl Lexer {
                 is_terminated = true;
                 self.advance();
                 break;
-            } else {
-                content.push(c);
+            }
+
+            content.push(c);
+            self.advance();
+
+            // The escaped character cannot end the string.
+            if c == '\\'
+                && let Some(escaped) = self.peek()
+            {
+                content.push(escaped);
                 self.advance();
             }
         }
 
         if !is_terminated {
             return Err(Error::LexerError(LexerError::new(
-                "Unterminated string literal".to_string(),
+                Diagnostic::UnterminatedString,
                 self.line,
                 column_start,
             )));
         }
 
+        let value = unescape_string_literal(&content).map_err(|diagnostic| {
+            Error::LexerError(LexerError::new(diagnostic, self.line, column_start))
+        })?;
+
         Ok(Some(Token {
             kind: TokenType::StringLiteral,
-            value: unescape_string_literal(&content),
+            value,
             line: self.line,
             column: column_start,
         }))
     }
 
+    // Digits may be grouped with underscores, as in `1_000_000`, and followed
+    // by a fractional part and an exponent, as in `1.5e-3`. The token keeps
+    // the underscores.
     fn read_number_literal(&mut self) -> Result<Option<Token>, Error> {
         let mut number = String::new();
         let column_start = self.column;
         let mut dot_seen = false;
+        let mut exponent_seen = false;
+
+        let invalid = |line| {
+            Err(Error::LexerError(LexerError::new(
+                Diagnostic::InvalidNumber,
+                line,
+                column_start,
+            )))
+        };
 
         while let Some(c) = self.peek() {
             if c.is_digit(10) {
                 number.push(c);
                 self.advance();
-            } else if c == '.' && !dot_seen {
+            } else if c == '_' {
+                let between_digits = number.ends_with(|c: char| c.is_ascii_digit())
+                    && self.peek_next().is_some_and(|c| c.is_ascii_digit());
+
+                if !between_digits {
+                    return invalid(self.line);
+                }
+
+                number.push(c);
+                self.advance();
+            } else if c == '.' && self.peek_next() == Some('.') {
+                // A range such as `1..3`, not a fractional part.
+                break;
+            } else if c == '.' && !dot_seen && !exponent_seen {
                 dot_seen = true;
                 number.push(c);
                 self.advance();
-            } else if c == '.' && dot_seen {
-                return Err(Error::LexerError(LexerError::new(
-                    "Invalid number literal".to_string(),
-                    self.line,
-                    column_start,
-                )));
+            } else if c == '.' {
+                return invalid(self.line);
+            } else if (c == 'e' || c == 'E') && !exponent_seen {
+                exponent_seen = true;
+                number.push(c);
+                self.advance();
+
+                if let Some(sign @ ('+' | '-')) = self.peek() {
+                    number.push(sign);
+                    self.advance();
+                }
+
+                if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    return invalid(self.line);
+                }
             } else {
                 break;
             }
@@ -396,6 +574,29 @@ mod tests {
         assert_eq!(token.column, 1);
     }
 
+    #[test]
+    fn test_crlf_newline() {
+        let mut lexer = Lexer::new("a \r\n# doc\r\nb\rc".to_string());
+
+        lexer.next_token().unwrap();
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.kind, TokenType::Newline);
+        assert_eq!(token.value, "\r\n");
+        assert_eq!(token.line, 2);
+
+        lexer.next_token().unwrap();
+        assert_eq!(lexer.take_doc_comment(3), Some("doc".to_string()));
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.value, "b");
+        assert_eq!(token.line, 3);
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.value, "\r");
+        assert_eq!(token.line, 4);
+    }
+
     #[test]
     fn test_identifier() {
         let mut lexer = Lexer::new("my_function".to_string());
@@ -444,6 +645,41 @@ mod tests {
         assert_eq!(token.column, 1);
     }
 
+    #[test]
+    fn test_string_literal_escapes() {
+        let mut lexer = Lexer::new(r#""say \"hi\"\t\\ \x41\u{e9}\0" 'it\'s'"#.to_string());
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.value, "say \"hi\"\t\\ A\u{e9}\0");
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.value, "it's");
+
+        for invalid in [
+            r#""\d""#,
+            r#""\x4""#,
+            r#""\xff""#,
+            r#""\u{110000}""#,
+            r#""\u41""#,
+            r#""\u{D800}""#,
+            r#""\u{}""#,
+        ] {
+            let error = Lexer::new(invalid.to_string()).next_token().unwrap_err();
+
+            assert!(
+                matches!(
+                    error,
+                    Error::LexerError(LexerError {
+                        diagnostic: Diagnostic::InvalidEscape(_),
+                        ..
+                    })
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
     #[test]
     fn test_unterminated_string_literal() {
         let mut lexer = Lexer::new("\"my string".to_string());
@@ -456,7 +692,8 @@ mod tests {
             Error::LexerError(err) => err,
             _ => panic!("Expected LexerError"),
         };
-        assert_eq!(lexer_error.message, "Unterminated string literal");
+        assert_eq!(lexer_error.diagnostic, Diagnostic::UnterminatedString);
+        assert_eq!(lexer_error.diagnostic.code(), "K0102");
     }
     #[test]
     fn test_symbols() {
@@ -541,6 +778,37 @@ mod tests {
         assert!(token.is_err());
     }
 
+    #[test]
+    fn test_number_literal_with_exponent_and_underscores() {
+        let mut lexer = Lexer::new("1.5e-3 1_000_000 2E+8 1e3..5".to_string());
+        let mut values = vec![];
+
+        while let Some(token) = lexer.next_token().unwrap() {
+            values.push(token.value);
+        }
+
+        assert_eq!(
+            values,
+            vec!["1.5e-3", "1_000_000", "2E+8", "1e3", "..", "5"]
+        );
+
+        for invalid in ["1__000", "1_", "1._5", "2e", "2e+", "1e3.5"] {
+            let result = Lexer::new(invalid.to_string()).next_token();
+
+            assert!(
+                matches!(
+                    result,
+                    Err(Error::LexerError(LexerError {
+                        diagnostic: Diagnostic::InvalidNumber,
+                        ..
+                    }))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
     #[test]
     fn test_def_keyword() {
         let mut lexer = Lexer::new("def my_method\nend\n".to_string());
@@ -626,6 +894,23 @@ mod tests {
         assert_eq!(token.column, 1);
     }
 
+    #[test]
+    fn test_shebang() {
+        let mut lexer = Lexer::new(
+            "#!/usr/bin/env kyanite
+def"
+            .to_string(),
+        );
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.kind, TokenType::Newline);
+
+        let token = lexer.next_token().unwrap().unwrap();
+        assert_eq!(token.kind, TokenType::Def);
+        assert_eq!(token.line, 2);
+        assert_eq!(lexer.take_doc_comment(2), None);
+    }
+
     #[test]
     fn test_if_keyword() {
         let mut lexer = Lexer::new("if condition\nend\n".to_string());
@@ -746,4 +1031,49 @@ mod tests {
         assert_eq!(token.line, 1);
         assert_eq!(token.column, 17);
     }
+
+    #[test]
+    fn test_slice_tokens() {
+        let mut lexer = Lexer::new("items[1..-2:2]\n".to_string());
+        let mut tokens = vec![];
+
+        while let Some(token) = lexer.next_token().unwrap() {
+            tokens.push((token.kind, token.value));
+        }
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenType::Identifier, "items".to_string()),
+                (TokenType::LeftBracket, "[".to_string()),
+                (TokenType::NumberLiteral, "1".to_string()),
+                (TokenType::DotDot, "..".to_string()),
+                (TokenType::Minus, "-".to_string()),
+                (TokenType::NumberLiteral, "2".to_string()),
+                (TokenType::Colon, ":".to_string()),
+                (TokenType::NumberLiteral, "2".to_string()),
+                (TokenType::RightBracket, "]".to_string()),
+                (TokenType::Newline, "\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyword_prefixed_identifiers() {
+        let mut lexer = Lexer::new("from_timestamp define endpoint from\n".to_string());
+        let tokens = [
+            lexer.next_token().unwrap().unwrap(),
+            lexer.next_token().unwrap().unwrap(),
+            lexer.next_token().unwrap().unwrap(),
+            lexer.next_token().unwrap().unwrap(),
+        ];
+
+        assert_eq!(tokens[0].kind, TokenType::Identifier);
+        assert_eq!(tokens[0].value, "from_timestamp");
+        assert_eq!(tokens[1].kind, TokenType::Identifier);
+        assert_eq!(tokens[1].value, "define");
+        assert_eq!(tokens[2].kind, TokenType::Identifier);
+        assert_eq!(tokens[2].value, "endpoint");
+        assert_eq!(tokens[3].kind, TokenType::From);
+    }
 }
@@ -0,0 +1,54 @@
+//! Kyanite as a library: the lexer, parser, compiler and interpreter the
+//! `kyanite` binary is built from, and `eval_str` for running a snippet.
+
+pub mod ast;
+pub mod bench;
+pub mod builtins;
+pub mod bytecode;
+pub mod capabilities;
+pub mod compiler;
+pub mod convert;
+pub mod dap;
+pub mod debugger;
+pub mod diagnostics;
+pub mod doc;
+pub mod dumper;
+pub mod embed;
+pub mod errors;
+pub mod extensions;
+pub mod formatter;
+pub mod gc;
+pub mod importer;
+pub mod instrument;
+pub mod internal;
+pub mod interpreter;
+pub mod lexer;
+pub mod limits;
+pub mod lock;
+pub mod lsp;
+pub mod manifest;
+pub mod objects;
+pub mod opcodes;
+pub mod optimizer;
+pub mod parser;
+pub mod pkg;
+pub mod prelude;
+pub mod profiler;
+pub mod repl;
+pub mod replay;
+pub mod runtime;
+pub mod scaffold;
+pub mod scoping;
+pub mod semantic;
+pub mod symbol;
+pub mod testing;
+pub mod trace;
+pub mod visitor;
+pub mod watch;
+
+pub use compiler::Compiler;
+pub use convert::{from_value, to_value};
+pub use embed::{eval_str, ErrorKind, EvalError, Value};
+pub use interpreter::Interpreter;
+pub use lexer::Lexer;
+pub use parser::Parser;
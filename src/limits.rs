@@ -0,0 +1,93 @@
+//! Limits on how much work a run may do, so that a script that is not
+//! trusted cannot keep the interpreter busy forever. They are checked at the
+//! interpreter's safe point, every few instructions, and a run over a limit
+//! raises a `TimeoutError`. Catching it does not help: the next check raises
+//! it again.
+
+use crate::errors::Error;
+use crate::objects::exception_object::TIMEOUT_ERROR_TYPE;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    pub max_instructions: Option<u64>,
+    pub timeout: Option<Duration>,
+}
+
+// What a runtime has spent of its limits since its run started. The
+// instructions of every thread count.
+#[derive(Default)]
+pub struct Budget {
+    limits: Mutex<Limits>,
+    deadline: Mutex<Option<Instant>>,
+    executed: AtomicU64,
+}
+
+impl Budget {
+    pub fn start(&self, limits: Limits) {
+        *self.limits.lock().unwrap() = limits;
+        *self.deadline.lock().unwrap() = limits.timeout.map(|timeout| Instant::now() + timeout);
+        self.executed.store(0, Ordering::Relaxed);
+    }
+
+    /// Counts `instructions` more and raises if the run is over a limit.
+    pub fn charge(&self, instructions: u64) -> Result<(), Error> {
+        let executed = self.executed.fetch_add(instructions, Ordering::Relaxed) + instructions;
+        let limits = *self.limits.lock().unwrap();
+
+        if let Some(max) = limits.max_instructions.filter(|max| executed > *max) {
+            return Err(timeout_error(format!(
+                "The run went over the limit of {} instructions",
+                max
+            )));
+        }
+
+        let expired = self
+            .deadline
+            .lock()
+            .unwrap()
+            .is_some_and(|deadline| Instant::now() >= deadline);
+
+        if let Some(timeout) = limits.timeout.filter(|_| expired) {
+            return Err(timeout_error(format!(
+                "The run went over the time limit of {} seconds",
+                timeout.as_secs_f64()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn timeout_error(message: String) -> Error {
+    Error::exception(TIMEOUT_ERROR_TYPE.lock().unwrap().name.clone(), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let budget = Budget::default();
+
+        budget.start(Limits {
+            max_instructions: Some(150),
+            timeout: None,
+        });
+        assert!(budget.charge(100).is_ok());
+        assert!(budget.charge(100).is_err());
+
+        budget.start(Limits {
+            max_instructions: None,
+            timeout: Some(Duration::ZERO),
+        });
+        assert!(budget.charge(100).is_err());
+
+        budget.start(Limits::default());
+        assert!(budget.charge(u64::MAX / 2).is_ok());
+    }
+}
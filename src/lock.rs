@@ -1,23 +1,61 @@
-use once_cell::sync::Lazy;
+use crate::runtime::{self, Runtime};
+
 use std::cell::RefCell;
-use std::sync::Mutex;
-use std::sync::MutexGuard;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
 
-static GIL: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+// The interpreter lock of a runtime. It is taken and released in different
+// functions, so it is a flag rather than a guard.
+#[derive(Default)]
+pub struct Gil {
+    locked: Mutex<bool>,
+    unlocked: Condvar,
+    waiting: AtomicUsize,
+}
 
 thread_local! {
-    static GIL_GUARD: RefCell<Option<MutexGuard<'static, ()>>> = RefCell::new(None);
+    // The runtime whose lock the thread holds, which is the one to release
+    // even if the thread entered another one since.
+    static HELD: RefCell<Option<Arc<Runtime>>> = const { RefCell::new(None) };
 }
 
 pub fn kya_acquire_lock() {
-    GIL_GUARD.with(|cell| {
-        let guard = GIL.lock().unwrap();
-        *cell.borrow_mut() = Some(guard);
-    });
+    let runtime = runtime::current();
+
+    {
+        let mut locked = runtime.gil.locked.lock().unwrap();
+
+        runtime.gil.waiting.fetch_add(1, Ordering::Relaxed);
+
+        while *locked {
+            locked = runtime.gil.unlocked.wait(locked).unwrap();
+        }
+
+        runtime.gil.waiting.fetch_sub(1, Ordering::Relaxed);
+
+        *locked = true;
+    }
+
+    HELD.with(|cell| *cell.borrow_mut() = Some(runtime));
 }
 
 pub fn kya_release_lock() {
-    GIL_GUARD.with(|cell| {
-        *cell.borrow_mut() = None;
-    });
+    let Some(runtime) = HELD.with(|cell| cell.borrow_mut().take()) else {
+        return;
+    };
+
+    *runtime.gil.locked.lock().unwrap() = false;
+    runtime.gil.unlocked.notify_one();
+}
+
+/// Hands the lock over to the threads waiting for it, if there are any.
+pub fn kya_yield_lock() {
+    if runtime::current().gil.waiting.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    kya_release_lock();
+    thread::yield_now();
+    kya_acquire_lock();
 }
@@ -0,0 +1,437 @@
+//! A Language Server Protocol server over stdin and stdout, for `kyanite
+//! lsp`. Editors send whole documents on every change; the server reports
+//! the errors of lexing, parsing and compiling them, lists their classes and
+//! functions, jumps to where a name is defined and shows the parameters of a
+//! function on hover. Names are looked up with `semantic`.
+
+use crate::compiler::Compiler;
+use crate::dap::read_message;
+use crate::diagnostics::Diagnostic;
+use crate::errors::Error;
+use crate::lexer::{Lexer, Token, TokenType};
+use crate::optimizer;
+use crate::parser::Parser;
+use crate::semantic::{self, Analysis, Definition, DefinitionKind};
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufReader, Write};
+use std::sync::Arc;
+
+// From the specification.
+const METHOD_NOT_FOUND: i64 = -32601;
+const FULL_SYNC: u8 = 1;
+const SEVERITY_ERROR: u8 = 1;
+
+pub fn serve() -> Result<(), String> {
+    let mut reader = BufReader::new(std::io::stdin());
+    let mut server = Server::default();
+
+    while let Some(message) = read_message(&mut reader)? {
+        let (response, notifications) = server.handle(&message);
+
+        for notification in notifications {
+            send(&notification)?;
+        }
+
+        if let Some(response) = response {
+            send(&response)?;
+        }
+
+        if server.exited {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn send(message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    let mut stdout = std::io::stdout().lock();
+
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| stdout.flush())
+        .map_err(|e| format!("Error: Could not write to the client: {}", e))
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, String>,
+    exited: bool,
+}
+
+impl Server {
+    // Answers a message, returning the response to a request and the
+    // notifications to send before it.
+    fn handle(&mut self, message: &Value) -> (Option<Value>, Vec<Value>) {
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        let mut notifications = vec![];
+
+        let result = match message["method"].as_str().unwrap_or("") {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": FULL_SYNC,
+                    "documentSymbolProvider": true,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                },
+                "serverInfo": { "name": "kyanite" },
+            })),
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+
+                self.documents.insert(uri.clone(), text.to_string());
+                notifications.push(publish_diagnostics(&uri, text));
+
+                Ok(Value::Null)
+            }
+            "textDocument/didChange" => {
+                // With full sync, the last change holds the whole document.
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.clone(), text.to_string());
+                    notifications.push(publish_diagnostics(&uri, text));
+                }
+
+                Ok(Value::Null)
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                notifications.push(publish_diagnostics(&uri, ""));
+
+                Ok(Value::Null)
+            }
+            "textDocument/documentSymbol" => Ok(self
+                .document(&uri)
+                .map(|document| json!(document_symbols(&document)))
+                .unwrap_or(Value::Null)),
+            "textDocument/definition" => Ok(self
+                .document(&uri)
+                .and_then(|document| {
+                    let (definition, _) = document.definition_at(&params["position"])?;
+
+                    Some(json!({ "uri": uri, "range": document.name_range(definition) }))
+                })
+                .unwrap_or(Value::Null)),
+            "textDocument/hover" => Ok(self
+                .document(&uri)
+                .and_then(|document| {
+                    let (definition, token) = document.definition_at(&params["position"])?;
+
+                    Some(json!({
+                        "contents": { "kind": "markdown", "value": hover(definition) },
+                        "range": range(token),
+                    }))
+                })
+                .unwrap_or(Value::Null)),
+            "shutdown" => Ok(Value::Null),
+            "exit" => {
+                self.exited = true;
+
+                Ok(Value::Null)
+            }
+            method => Err(format!("Unsupported method '{}'", method)),
+        };
+
+        // Notifications have no id and get no response.
+        let response = message.get("id").map(|id| match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": METHOD_NOT_FOUND, "message": message },
+            }),
+        });
+
+        (response, notifications)
+    }
+
+    fn document(&self, uri: &str) -> Option<Document> {
+        self.documents.get(uri).map(|text| Document::new(text))
+    }
+}
+
+// An open document, as far as it could be lexed and parsed.
+struct Document {
+    tokens: Vec<Token>,
+    analysis: Analysis,
+}
+
+impl Document {
+    fn new(text: &str) -> Self {
+        let mut lexer = Lexer::new(text.to_string());
+        let mut tokens = vec![];
+
+        while let Ok(Some(token)) = lexer.next_token() {
+            tokens.push(token);
+        }
+
+        let analysis = Parser::new(Lexer::new(text.to_string()))
+            .parse()
+            .map(|ast| semantic::analyze(&ast))
+            .unwrap_or_default();
+
+        Document { tokens, analysis }
+    }
+
+    // The definition of the name at an LSP position, with its token.
+    fn definition_at(&self, position: &Value) -> Option<(&Definition, &Token)> {
+        let line = position["line"].as_u64()? as usize + 1;
+        let column = position["character"].as_u64()? as usize + 1;
+
+        let token = self.tokens.iter().find(|token| {
+            token.kind == TokenType::Identifier
+                && token.line == line
+                && token.column <= column
+                && column <= token.column + token.value.chars().count()
+        })?;
+
+        Some((self.analysis.resolve(&token.value, line)?, token))
+    }
+
+    // Where the name of `definition` is written, or its whole line.
+    fn name_range(&self, definition: &Definition) -> Value {
+        match self
+            .tokens
+            .iter()
+            .find(|token| token.line == definition.line && token.value == definition.name)
+        {
+            Some(token) => range(token),
+            None => json!({
+                "start": { "line": definition.line - 1, "character": 0 },
+                "end": { "line": definition.line, "character": 0 },
+            }),
+        }
+    }
+}
+
+fn range(token: &Token) -> Value {
+    let start = token.column - 1;
+
+    json!({
+        "start": { "line": token.line - 1, "character": start },
+        "end": { "line": token.line - 1, "character": start + token.value.chars().count() },
+    })
+}
+
+fn publish_diagnostics(uri: &str, text: &str) -> Value {
+    let diagnostics: Vec<Value> = check(text)
+        .err()
+        .map(|error| {
+            let (line, column) = error_position(&error).unwrap_or((1, 1));
+
+            json!({
+                "range": {
+                    "start": { "line": line - 1, "character": column - 1 },
+                    "end": { "line": line - 1, "character": column },
+                },
+                "severity": SEVERITY_ERROR,
+                "code": error.code(),
+                "source": "kyanite",
+                "message": error_message(&error),
+            })
+        })
+        .into_iter()
+        .collect();
+
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn check(text: &str) -> Result<(), Error> {
+    let ast = Parser::new(Lexer::new(text.to_string())).parse()?;
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.add_pass(optimizer::ConstantBranches::new());
+
+    compiler.compile()
+}
+
+// Where the error is, for the errors that know it.
+fn error_position(error: &Error) -> Option<(usize, usize)> {
+    match error {
+        Error::LexerError(error) => Some((error.line, error.column)),
+        Error::ParserError(
+            Diagnostic::UnexpectedToken { line, column, .. }
+            | Diagnostic::ExpectedToken { line, column, .. }
+            | Diagnostic::InvalidNumberLiteral { line, column, .. },
+        ) => Some((*line, *column)),
+        _ => None,
+    }
+}
+
+// The message without the colors and the name of the stage.
+fn error_message(error: &Error) -> String {
+    match error {
+        Error::LexerError(error) => error.diagnostic.to_string(),
+        Error::ParserError(diagnostic) | Error::CompilationError(diagnostic) => {
+            diagnostic.to_string()
+        }
+        error => error.to_string(),
+    }
+}
+
+// The DocumentSymbol kinds of the specification.
+fn symbol_kind(kind: DefinitionKind) -> u8 {
+    match kind {
+        DefinitionKind::Class => 5,
+        DefinitionKind::Method => 6,
+        DefinitionKind::Function => 12,
+        DefinitionKind::Variable | DefinitionKind::Parameter => 13,
+    }
+}
+
+fn document_symbols(document: &Document) -> Vec<Value> {
+    symbols_in(document, 0)
+}
+
+fn symbols_in(document: &Document, scope: usize) -> Vec<Value> {
+    let analysis = &document.analysis;
+
+    analysis
+        .declarations(scope)
+        .map(|definition| {
+            let body = definition.body.unwrap_or(scope);
+            let last_line = analysis.scopes[body].last_line;
+
+            json!({
+                "name": definition.name,
+                "detail": signature(definition),
+                "kind": symbol_kind(definition.kind),
+                "range": {
+                    "start": { "line": definition.line - 1, "character": 0 },
+                    "end": { "line": last_line, "character": 0 },
+                },
+                "selectionRange": document.name_range(definition),
+                "children": symbols_in(document, body),
+            })
+        })
+        .collect()
+}
+
+fn signature(definition: &Definition) -> String {
+    match definition.kind {
+        DefinitionKind::Class => format!("class {}", definition.name),
+        DefinitionKind::Function | DefinitionKind::Method => format!(
+            "def {}({})",
+            definition.name,
+            definition.parameters.join(", ")
+        ),
+        DefinitionKind::Variable => format!("{} (variable)", definition.name),
+        DefinitionKind::Parameter => format!("{} (parameter)", definition.name),
+    }
+}
+
+fn hover(definition: &Definition) -> String {
+    let mut text = format!("```kyanite\n{}\n```", signature(definition));
+
+    if let DefinitionKind::Function | DefinitionKind::Method = definition.kind {
+        let arity = definition.parameters.len();
+
+        text.push_str(&format!(
+            "\n\nTakes {} argument{}.",
+            arity,
+            if arity == 1 { "" } else { "s" }
+        ));
+    }
+
+    if let Some(doc) = &definition.doc {
+        text.push_str(&format!("\n\n{}", doc));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(server: &mut Server, text: &str) -> Vec<Value> {
+        let (_, notifications) = server.handle(&json!({
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": "file:///a.k", "text": text } },
+        }));
+
+        notifications
+    }
+
+    fn request(server: &mut Server, method: &str, line: u64, character: u64) -> Value {
+        let (response, _) = server.handle(&json!({
+            "id": 1,
+            "method": method,
+            "params": {
+                "textDocument": { "uri": "file:///a.k" },
+                "position": { "line": line, "character": character },
+            },
+        }));
+
+        response.unwrap()["result"].clone()
+    }
+
+    #[test]
+    fn test_language_features() {
+        let mut server = Server::default();
+
+        let notifications = open(&mut server, "x = (\n");
+        let diagnostics = &notifications[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+
+        open(
+            &mut server,
+            "class Greeter\n  def greet(name)\n    print(name)\n  end\nend\n\nGreeter().greet('you')\n",
+        );
+
+        let symbols = request(&mut server, "textDocument/documentSymbol", 0, 0);
+        assert_eq!(symbols[0]["name"], "Greeter");
+        assert_eq!(symbols[0]["children"][0]["detail"], "def greet(name)");
+
+        let definition = request(&mut server, "textDocument/definition", 6, 11);
+        assert_eq!(
+            definition["range"]["start"],
+            json!({ "line": 1, "character": 6 })
+        );
+
+        let hover = request(&mut server, "textDocument/hover", 2, 11);
+        assert!(hover["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("name (parameter)"));
+
+        let hover = request(&mut server, "textDocument/hover", 6, 11);
+        assert!(hover["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("Takes 1 argument."));
+    }
+
+    #[test]
+    fn test_requests_on_a_document_that_does_not_parse() {
+        let mut server = Server::default();
+
+        open(
+            &mut server,
+            "x = 1
+print(x
+",
+        );
+
+        assert_eq!(
+            request(&mut server, "textDocument/definition", 1, 7),
+            Value::Null
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 1, 7),
+            Value::Null
+        );
+    }
+}
@@ -1,125 +1,566 @@
-mod ast;
-mod builtins;
-mod bytecode;
-mod compiler;
-mod dumper;
-mod errors;
-mod internal;
-mod interpreter;
-mod lexer;
-mod lock;
-mod objects;
-mod opcodes;
-mod parser;
-mod visitor;
+use kyanite::{
+    bench, capabilities, compiler, dap, doc, dumper, formatter, instrument, internal, interpreter,
+    lexer, limits, lsp, objects, optimizer, parser, pkg, profiler, repl, replay, scaffold, scoping,
+    testing, trace, watch,
+};
 
 use clap::Parser;
 use std::sync::Arc;
 
-use dumper::ASTDumper;
+use dumper::{ASTDumper, DotDumper};
 
-fn dump(input: &str) {
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum DumpFormat {
+    Text,
+    Dot,
+}
+
+fn dump(input: &str, format: DumpFormat) {
     let lexer = lexer::Lexer::new(input.to_string());
     let mut parser = parser::Parser::new(lexer);
 
     match parser.parse() {
-        Ok(module) => {
-            let mut dumper = ASTDumper::new();
-            module.accept(&mut dumper);
-            println!("{}", dumper.output);
-        }
+        Ok(module) => match format {
+            DumpFormat::Text => {
+                let mut dumper = ASTDumper::new();
+                module.accept(&mut dumper);
+                println!("{}", dumper.output);
+            }
+            DumpFormat::Dot => {
+                let mut dumper = DotDumper::new();
+                module.accept(&mut dumper);
+                print!("{}", dumper.output);
+            }
+        },
         Err(e) => {
             eprintln!("{}", e);
         }
     }
 }
 
-fn interpret(filename: &str) -> Result<(), String> {
-    let input = std::fs::read_to_string(filename)
-        .map_err(|_| format!("Error: Could not read file {}", filename))?;
+// The program to run and where it came from: a file, the `-e` option or
+// stdin. Imports are looked up next to a file, and in the current directory
+// otherwise.
+struct Program {
+    name: String,
+    input: String,
+    root: String,
+}
+
+impl Program {
+    fn from_file(filename: &str) -> Result<Self, String> {
+        let input = internal::source::read_source(std::path::Path::new(filename))?;
+
+        let root = std::path::Path::new(filename)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(std::path::Path::new("."))
+            .to_str()
+            .unwrap_or(".");
+
+        Ok(Program {
+            name: filename.to_string(),
+            input,
+            root: root.to_string(),
+        })
+    }
+
+    fn from_stdin() -> Result<Self, String> {
+        let mut bytes = vec![];
 
-    let _root_dir = std::path::Path::new(filename)
-        .parent()
-        .unwrap_or(std::path::Path::new("."))
-        .to_str()
-        .unwrap_or(".");
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut bytes)
+            .map_err(|e| format!("Error: Could not read stdin: {}", e))?;
 
-    let mut parser = parser::Parser::new(lexer::Lexer::new(input.clone()));
+        Ok(Program {
+            name: "<stdin>".to_string(),
+            input: internal::source::decode(&bytes)
+                .map_err(|e| format!("Error: <stdin>: {}", e))?,
+            root: ".".to_string(),
+        })
+    }
+
+    fn from_code(code: String) -> Self {
+        Program {
+            name: "<string>".to_string(),
+            input: code,
+            root: ".".to_string(),
+        }
+    }
+}
+
+fn interpret(
+    program: &Program,
+    coverage: bool,
+    profile: bool,
+    limits: limits::Limits,
+    capabilities: capabilities::Capabilities,
+    paths: &[String],
+) -> Result<(), String> {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(program.input.clone()));
     let ast = Arc::new(parser.parse().unwrap_or_else(|e| {
-        eprintln!("Error parsing file {}: {}", filename, e);
+        eprintln!("Error parsing file {}: {}", program.name, e);
 
         std::process::exit(1);
     }));
 
     let mut compiler = compiler::Compiler::new(ast);
+    compiler.add_pass(optimizer::ConstantBranches::new());
+
+    if coverage {
+        compiler.add_pass(instrument::StatementProbes::new());
+    }
+
+    let scope_warnings = scoping::ScopeWarnings::new(interpreter::builtin_names());
+    let warnings = scope_warnings.warnings();
+    compiler.add_pass(scope_warnings);
+
     let _ = compiler.compile().unwrap_or_else(|e| {
         eprintln!("{}", e.to_string());
 
         std::process::exit(1);
     });
 
-    let mut interpreter = interpreter::Interpreter::new(".");
+    for warning in warnings.borrow().iter() {
+        eprintln!("warning: {}", warning);
+    }
 
-    let _ = interpreter
-        .eval(&compiler.get_output())
-        .unwrap_or_else(|e| {
-            eprintln!("{}", e.to_string());
+    let coverage = coverage.then(instrument::Coverage::start);
+    let profile = profile.then(profiler::Profile::start);
+    let mut interpreter = interpreter::Interpreter::new(&program.root);
+    interpreter.set_limits(limits);
+    interpreter.set_capabilities(capabilities);
 
+    for path in paths {
+        interpreter.add_search_path(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
             std::process::exit(1);
         });
+    }
+
+    let result = interpreter.eval(&compiler.get_output());
+
+    if let Some(coverage) = coverage {
+        eprintln!("{}", coverage.report());
+    }
+
+    if let Some(profile) = profile {
+        eprintln!("{}", profile.report());
+    }
+
+    let _ = result.unwrap_or_else(|e| {
+        eprintln!("{}", e.to_string());
+
+        std::process::exit(1);
+    });
 
     Ok(())
 }
 
-fn disassemble(filename: &str) -> Result<(), String> {
-    let input = std::fs::read_to_string(filename)
-        .map_err(|_| format!("Error: Could not read file {}", filename))?;
+// Parses and compiles the program without running it, printing its errors
+// and warnings. Returns whether it compiled.
+fn check(program: &Program) -> bool {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(program.input.clone()));
+    let ast = match parser.parse() {
+        Ok(ast) => Arc::new(ast),
+        Err(e) => {
+            eprintln!("{}: {}", program.name, e);
+            return false;
+        }
+    };
+
+    let mut compiler = compiler::Compiler::new(ast);
+    compiler.add_pass(optimizer::ConstantBranches::new());
+
+    let scope_warnings = scoping::ScopeWarnings::new(interpreter::builtin_names());
+    let warnings = scope_warnings.warnings();
+    compiler.add_pass(scope_warnings);
+
+    let result = compiler.compile();
+
+    for warning in warnings.borrow().iter() {
+        eprintln!("{}: warning: {}", program.name, warning);
+    }
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", program.name, e);
+        return false;
+    }
+
+    true
+}
 
-    let mut parser = parser::Parser::new(lexer::Lexer::new(input));
+fn disassemble(program: &Program) -> Result<(), String> {
+    let mut parser = parser::Parser::new(lexer::Lexer::new(program.input.clone()));
     let ast = Arc::new(parser.parse().unwrap_or_else(|e| {
-        eprintln!("Error parsing file {}: {}", filename, e);
+        eprintln!("Error parsing file {}: {}", program.name, e);
         std::process::exit(1);
     }));
 
     let mut compiler = compiler::Compiler::new(ast);
+    compiler.add_pass(optimizer::ConstantBranches::new());
+
     let _ = compiler.compile().unwrap_or_else(|e| {
-        eprintln!("Error compiling file {}: {}", filename, e);
+        eprintln!("Error compiling file {}: {}", program.name, e);
 
         std::process::exit(1);
     });
 
-    println!("{}", compiler.get_output().dis());
+    println!("{}", compiler.get_output().dis_with_source(&program.input));
 
     Ok(())
 }
 
+fn parse_capability(name: &str) -> Result<capabilities::Capability, String> {
+    capabilities::Capability::from_name(name).ok_or_else(|| {
+        format!(
+            "unknown capability '{}', expected network, filesystem, process or extensions",
+            name
+        )
+    })
+}
+
+fn parse_timeout(seconds: &str) -> Result<std::time::Duration, String> {
+    seconds
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("'{}' is not a non-negative number of seconds", seconds))
+}
+
 #[derive(Parser)]
 struct Cli {
-    #[arg(required = true)]
-    file: String,
+    /// The script to run, or - to read it from stdin; without one,
+    /// statements are read interactively
+    file: Option<String>,
+
+    /// Run this code instead of a script
+    #[clap(short, long, value_name = "CODE", conflicts_with = "file")]
+    eval: Option<String>,
 
     /// Dump the AST
     #[clap(short, long)]
     dump: bool,
 
+    /// How to dump the AST: as text, or as a Graphviz graph
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "text",
+        requires = "dump"
+    )]
+    dump_format: DumpFormat,
+
     /// Disassemble the bytecode
     #[clap(long)]
     disassemble: bool,
+
+    /// Parse and compile the script without running it, and exit with 1 if
+    /// that fails
+    #[clap(long)]
+    check: bool,
+
+    /// Report which statements ran once the program finishes
+    #[clap(long)]
+    coverage: bool,
+
+    /// Print every instruction as it runs, with the top of the stack after it
+    #[clap(long)]
+    trace: bool,
+
+    /// Report the time spent in each opcode and function once the program
+    /// finishes
+    #[clap(long)]
+    profile: bool,
+
+    /// Run the script again whenever a source file next to it changes
+    #[clap(long, conflicts_with = "eval")]
+    watch: bool,
+
+    /// Stack size in bytes for threads that do not set their own
+    #[clap(long, value_name = "BYTES")]
+    thread_stack_size: Option<usize>,
+
+    /// Record time, random and socket reads to a replay log
+    #[clap(long, value_name = "LOG", conflicts_with = "replay")]
+    record: Option<String>,
+
+    /// Feed the results stored in a replay log back to the program
+    #[clap(long, value_name = "LOG")]
+    replay: Option<String>,
+
+    /// Hash objects by the order they are first hashed instead of their
+    /// address, so that hashes iterate in the same order on every run
+    #[clap(long)]
+    deterministic_hashing: bool,
+
+    /// Stop the program with a TimeoutError after it runs this many
+    /// instructions
+    #[clap(long, value_name = "COUNT")]
+    max_instructions: Option<u64>,
+
+    /// Stop the program with a TimeoutError after it runs for this long
+    #[clap(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<std::time::Duration>,
+
+    /// Deny the program the network, the filesystem, starting processes and
+    /// native extensions
+    #[clap(long)]
+    sandbox: bool,
+
+    /// Grant a capability back to a sandboxed program: network, filesystem,
+    /// process or extensions
+    #[clap(long, value_name = "CAPABILITY", requires = "sandbox", value_parser = parse_capability)]
+    allow: Vec<capabilities::Capability>,
+
+    /// Also import modules from this directory; may be given more than once
+    #[clap(short = 'I', long = "path", value_name = "DIR")]
+    paths: Vec<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Generate API documentation for a project
+    Doc {
+        #[arg(default_value = ".")]
+        path: String,
+
+        /// Emit HTML instead of Markdown
+        #[arg(long)]
+        html: bool,
+
+        /// Write the documentation to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Create a new project with a starter layout
+    New { path: String },
+    /// Print a script in the canonical layout
+    Fmt {
+        file: String,
+
+        /// Rewrite the file instead of printing it
+        #[arg(long)]
+        write: bool,
+    },
+    /// Run the tests in `*_test.k` files
+    Test {
+        #[arg(default_value = ".")]
+        path: String,
+    },
+    /// Time the `bench_*` functions of a script
+    Bench { file: String },
+    /// Serve an editor over the Language Server Protocol on stdin and stdout
+    Lsp,
+    /// Serve a debugger client over the Debug Adapter Protocol
+    Dap {
+        /// The local port to wait for the client on
+        #[arg(long, default_value_t = 4711)]
+        port: u16,
+    },
+    /// Manage project dependencies
+    Pkg {
+        #[command(subcommand)]
+        action: PkgAction,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum PkgAction {
+    /// Clone a package into kya_modules and record it in the manifest
+    Add { url: String },
+    /// Install every package listed in the manifest
+    Install,
+}
+
+fn generate_doc(path: &str, html: bool, output: Option<String>) -> Result<(), String> {
+    let format = if html {
+        doc::DocFormat::Html
+    } else {
+        doc::DocFormat::Markdown
+    };
+
+    let contents = doc::generate(std::path::Path::new(path), format)?;
+
+    match output {
+        Some(output) => std::fs::write(&output, contents)
+            .map_err(|e| format!("Error: Could not write {}: {}", output, e)),
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+fn format_file(file: &str, write: bool) -> Result<(), String> {
+    let path = std::path::Path::new(file);
+    let source = internal::source::read_source(path)?;
+    let formatted = formatter::format_source(&source).map_err(|e| format!("{}: {}", file, e))?;
+
+    if !write {
+        print!("{}", formatted);
+        return Ok(());
+    }
+
+    if formatted != source {
+        std::fs::write(path, formatted)
+            .map_err(|e| format!("Error: Could not write {}: {}", file, e))?;
+    }
+
+    Ok(())
+}
+
+fn run_tests(path: &str) -> Result<(), String> {
+    let summary = testing::run(std::path::Path::new(path))?;
+
+    println!("{}", summary);
+
+    if summary.failed > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn run_command(command: Command) -> Result<(), String> {
+    let root = std::path::Path::new(".");
+
+    match command {
+        Command::Doc { path, html, output } => generate_doc(&path, html, output),
+        Command::New { path } => scaffold::new_project(std::path::Path::new(&path)),
+        Command::Fmt { file, write } => format_file(&file, write),
+        Command::Test { path } => run_tests(&path),
+        Command::Bench { file } => bench::run(std::path::Path::new(&file)).map(|_| ()),
+        Command::Lsp => lsp::serve(),
+        Command::Dap { port } => dap::serve(port),
+        Command::Pkg { action } => match action {
+            PkgAction::Add { url } => pkg::add(root, &url),
+            PkgAction::Install => pkg::install(root),
+        },
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    let input = std::fs::read_to_string(&cli.file).unwrap_or_else(|_| {
-        eprintln!("Error: Could not read file {}", cli.file);
+    if let Some(command) = cli.command {
+        run_command(command).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+
+        return;
+    }
+
+    let limits = limits::Limits {
+        max_instructions: cli.max_instructions,
+        timeout: cli.timeout,
+    };
+
+    let capabilities = if cli.sandbox {
+        capabilities::Capabilities::none().with(&cli.allow)
+    } else {
+        capabilities::Capabilities::all()
+    };
+
+    // These hold for the whole process, so they apply to the REPL as well.
+    // A watched script gets them in the child that runs it.
+    let watched = cli.watch && cli.file.as_deref().is_some_and(|file| file != "-");
+
+    if !watched {
+        let replay_setup = match (&cli.record, &cli.replay) {
+            (Some(log), _) => replay::start_recording(std::path::Path::new(log)),
+            (_, Some(log)) => replay::start_replay(std::path::Path::new(log)),
+            _ => Ok(()),
+        };
+
+        replay_setup.unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+
+        // A replayed run has to visit hashes in the order they were recorded.
+        if cli.deterministic_hashing || cli.record.is_some() || cli.replay.is_some() {
+            objects::base::set_deterministic_hashing(true);
+        }
+
+        trace::set_enabled(cli.trace);
+
+        if let Some(bytes) = cli.thread_stack_size {
+            objects::modules::threads::thread_object::set_default_stack_size(bytes);
+        }
+    }
+
+    let program = match (cli.eval, cli.file) {
+        (Some(code), _) => Ok(Program::from_code(code)),
+        (None, Some(file)) if file == "-" => Program::from_stdin(),
+        (None, Some(file)) => {
+            let file = internal::expand::expand_path(&file).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+
+            if cli.watch {
+                let arguments = std::env::args()
+                    .skip(1)
+                    .filter(|argument| argument != "--watch")
+                    .collect();
+
+                watch::run(&file, &cli.paths, arguments).unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                });
+
+                return;
+            }
+
+            Program::from_file(&file)
+        }
+        (None, None) => {
+            let mut interpreter = interpreter::Interpreter::new(".");
+            interpreter.set_limits(limits);
+            interpreter.set_capabilities(capabilities);
+
+            for path in &cli.paths {
+                interpreter.add_search_path(path).unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                });
+            }
+
+            repl::start(interpreter).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+
+            return;
+        }
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("{}", e);
         std::process::exit(1);
     });
 
-    if cli.dump {
-        dump(&input);
+    if cli.check {
+        if !check(&program) {
+            std::process::exit(1);
+        }
+    } else if cli.dump {
+        dump(&program.input, cli.dump_format);
     } else if cli.disassemble {
-        disassemble(&cli.file).unwrap();
+        disassemble(&program).unwrap();
     } else {
-        interpret(&cli.file).unwrap()
+        interpret(
+            &program,
+            cli.coverage,
+            cli.profile,
+            limits,
+            capabilities,
+            &cli.paths,
+        )
+        .unwrap()
     }
 }
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "kyanite.toml";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<(String, String)>,
+}
+
+impl Manifest {
+    pub fn new(name: &str) -> Self {
+        Manifest {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            dependencies: vec![],
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let input = std::fs::read_to_string(path)
+            .map_err(|_| format!("Error: Could not read manifest {}", path.display()))?;
+
+        Manifest::parse(&input)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_source())
+            .map_err(|e| format!("Error: Could not write manifest {}: {}", path.display(), e))
+    }
+
+    /// Parses the small TOML subset used by kyanite manifests: `[section]`
+    /// headers followed by `key = "value"` pairs.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut manifest = Manifest::new("");
+        let mut section = String::new();
+
+        for (index, raw_line) in input.lines().enumerate() {
+            let line = raw_line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            let (key, value) = parse_pair(line)
+                .ok_or_else(|| format!("Invalid manifest entry at line {}: {}", index + 1, line))?;
+
+            match (section.as_str(), key.as_str()) {
+                ("package", "name") => manifest.name = value,
+                ("package", "version") => manifest.version = value,
+                ("dependencies", _) => manifest.add_dependency(&key, &value),
+                _ => {
+                    return Err(format!(
+                        "Unknown manifest key '{}' in section '{}' at line {}",
+                        key,
+                        section,
+                        index + 1
+                    ));
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn to_source(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("[package]\n");
+        output.push_str(&format!("name = \"{}\"\n", self.name));
+        output.push_str(&format!("version = \"{}\"\n", self.version));
+        output.push_str("\n[dependencies]\n");
+
+        for (name, url) in &self.dependencies {
+            output.push_str(&format!("{} = \"{}\"\n", name, url));
+        }
+
+        output
+    }
+
+    pub fn add_dependency(&mut self, name: &str, url: &str) {
+        if let Some(entry) = self.dependencies.iter_mut().find(|(n, _)| n == name) {
+            entry.1 = url.to_string();
+        } else {
+            self.dependencies.push((name.to_string(), url.to_string()));
+        }
+    }
+}
+
+fn parse_pair(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    let value = value.trim();
+
+    if key.is_empty() || value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return None;
+    }
+
+    Some((key.to_string(), value[1..value.len() - 1].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let input = "[package]\nname = \"app\"\nversion = \"1.2.0\"\n\n[dependencies]\nhttp = \"https://example.com/http.git\"\n";
+
+        let manifest = Manifest::parse(input).unwrap();
+
+        assert_eq!(manifest.name, "app");
+        assert_eq!(manifest.version, "1.2.0");
+        assert_eq!(
+            manifest.dependencies,
+            vec![(
+                "http".to_string(),
+                "https://example.com/http.git".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let mut manifest = Manifest::new("app");
+        manifest.add_dependency("http", "https://example.com/http.git");
+        manifest.add_dependency("http", "https://example.com/http2.git");
+
+        let parsed = Manifest::parse(&manifest.to_source()).unwrap();
+
+        assert_eq!(parsed, manifest);
+        assert_eq!(parsed.dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_invalid_entry() {
+        assert!(Manifest::parse("[package]\nname app\n").is_err());
+    }
+}
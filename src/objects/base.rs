@@ -1,35 +1,51 @@
 use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
-use crate::interpreter::{FALSE_OBJECT, NONE_OBJECT, TRUE_OBJECT};
+use crate::gc::{Gc, WeakGc};
 use crate::objects::bool_object::BoolObject;
+use crate::objects::byte_view_object::ByteViewObject;
 use crate::objects::bytes_object::BytesObject;
 use crate::objects::class_object::{
     class_nb_bool, class_tp_call, class_tp_init, class_tp_new, class_tp_repr, ClassObject,
 };
 use crate::objects::code_object::CodeObject;
 use crate::objects::exception_object::ExceptionObject;
+use crate::objects::finalizers;
 use crate::objects::function_object::FunctionObject;
 use crate::objects::hash_object::HashObject;
+use crate::objects::host_function_object::HostFunctionObject;
 use crate::objects::instance_object::InstanceObject;
 use crate::objects::list_object::ListObject;
 use crate::objects::method_object::{MethodObject, METHOD_TYPE};
+use crate::objects::module_object::ModuleObject;
+use crate::objects::modules::datetime::datetime_object::DateTimeObject;
+use crate::objects::modules::http::request_object::RequestObject;
+use crate::objects::modules::http::response_object::ResponseObject;
 use crate::objects::modules::sockets::connection_object::ConnectionObject;
+use crate::objects::modules::sockets::connection_set_object::ConnectionSetObject;
 use crate::objects::modules::sockets::socket_object::SocketObject;
+use crate::objects::modules::threads::channel_object::ChannelObject;
 use crate::objects::modules::threads::lock_object::LockObject;
+use crate::objects::modules::threads::queue_object::QueueObject;
+use crate::objects::modules::threads::semaphore_object::SemaphoreObject;
 use crate::objects::modules::threads::thread_object::ThreadObject;
-use crate::objects::none_object::NoneObject;
+use crate::objects::none_object::{NoneObject, NONE_TYPE};
 use crate::objects::number_object::NumberObject;
 use crate::objects::rs_function_object::RsFunctionObject;
 use crate::objects::string_object::StringObject;
 use crate::objects::url_object::UrlObject;
-use crate::objects::utils::parse_receiver;
+use crate::objects::utils::{bool_to_bool_object, parse_receiver};
+use crate::symbol::Symbol;
 
-pub type KyaObjectRef = Arc<Mutex<KyaObject>>;
+pub type KyaObjectRef = Gc;
 pub type TypeRef = Arc<Mutex<Type>>;
-pub type DictRef = Arc<Mutex<std::collections::HashMap<String, KyaObjectRef>>>;
+pub type DictRef = Arc<Mutex<std::collections::HashMap<Symbol, KyaObjectRef>>>;
 pub type TypeDictRef = Arc<Mutex<std::collections::HashMap<String, TypeRef>>>;
 pub type CallableFunctionPtr = fn(
     callable: KyaObjectRef,
@@ -42,7 +58,9 @@ pub type TypeFunctionPtr = fn(
     receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error>;
 pub type GetAttrFunctionPtr =
-    fn(obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error>;
+    fn(obj: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error>;
+pub type GetMethodFunctionPtr =
+    fn(obj: KyaObjectRef, attr_name: Symbol) -> Result<(KyaObjectRef, Option<KyaObjectRef>), Error>;
 pub type NumberCheckFunctionPtr = fn(obj: KyaObjectRef) -> Result<f64, Error>;
 pub type LenFunctionPtr = fn(obj: KyaObjectRef) -> Result<usize, Error>;
 pub type CompareFunctionPtr = fn(
@@ -52,9 +70,13 @@ pub type CompareFunctionPtr = fn(
 ) -> Result<KyaObjectRef, Error>;
 pub type HashFunctionPtr = fn(obj: KyaObjectRef) -> Result<usize, Error>;
 pub type SetAttrFunctionPtr =
-    fn(obj: KyaObjectRef, attr_name: String, value: KyaObjectRef) -> Result<(), Error>;
+    fn(obj: KyaObjectRef, attr_name: Symbol, value: KyaObjectRef) -> Result<(), Error>;
 pub type BinaryFunctionPtr =
     fn(obj1: KyaObjectRef, obj2: KyaObjectRef) -> Result<KyaObjectRef, Error>;
+pub type ExitFunctionPtr = fn(obj: KyaObjectRef) -> Result<(), Error>;
+pub type DelFunctionPtr = fn(obj: KyaObjectRef) -> Result<(), Error>;
+pub type SliceFunctionPtr =
+    fn(obj: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error>;
 
 pub enum KyaObject {
     NoneObject(NoneObject),
@@ -67,21 +89,83 @@ pub enum KyaObject {
     MethodObject(MethodObject),
     SocketObject(SocketObject),
     ConnectionObject(ConnectionObject),
+    ConnectionSetObject(ConnectionSetObject),
     BytesObject(BytesObject),
+    ByteViewObject(ByteViewObject),
     BoolObject(BoolObject),
     CodeObject(CodeObject),
     ThreadObject(ThreadObject),
     LockObject(LockObject),
+    ChannelObject(ChannelObject),
+    QueueObject(QueueObject),
+    SemaphoreObject(SemaphoreObject),
     ListObject(ListObject),
     HashObject(HashObject),
     ExceptionObject(ExceptionObject),
     UrlObject(UrlObject),
+    ModuleObject(ModuleObject),
+    RequestObject(RequestObject),
+    ResponseObject(ResponseObject),
+    HostFunctionObject(HostFunctionObject),
+    DateTimeObject(DateTimeObject),
 }
 
 pub trait KyaObjectTrait {
     fn get_type(&self) -> TypeRef;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliceRange {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub step: i64,
+}
+
+impl SliceRange {
+    pub fn new(start: Option<i64>, end: Option<i64>, step: i64) -> Result<Self, Error> {
+        if step == 0 {
+            return Err(Error::ValueError("Slice step cannot be zero".to_string()));
+        }
+
+        Ok(SliceRange { start, end, step })
+    }
+
+    /// Resolves the range against a sequence of `len` items. Negative bounds
+    /// count from the end and out of range bounds are clamped, so the result
+    /// only ever contains valid positions.
+    pub fn indices(&self, len: usize) -> Vec<usize> {
+        let len = len as i64;
+        let resolve = |bound: i64, low: i64, high: i64| {
+            let bound = if bound < 0 { bound + len } else { bound };
+            bound.clamp(low, high)
+        };
+
+        let mut indices = Vec::new();
+
+        if self.step > 0 {
+            let mut index = self.start.map_or(0, |start| resolve(start, 0, len));
+            let end = self.end.map_or(len, |end| resolve(end, 0, len));
+
+            while index < end {
+                indices.push(index as usize);
+                index += self.step;
+            }
+        } else {
+            let mut index = self
+                .start
+                .map_or(len - 1, |start| resolve(start, -1, len - 1));
+            let end = self.end.map_or(-1, |end| resolve(end, -1, len - 1));
+
+            while index > end {
+                indices.push(index as usize);
+                index += self.step;
+            }
+        }
+
+        indices
+    }
+}
+
 pub struct Type {
     pub ob_type: Option<TypeRef>,
     pub name: String,
@@ -91,13 +175,23 @@ pub struct Type {
     pub tp_new: Option<TypeFunctionPtr>,
     pub tp_init: Option<CallableFunctionPtr>,
     pub tp_get_attr: Option<GetAttrFunctionPtr>,
+    // Looks an attribute up for a call: a function comes back unbound, with
+    // the object to call it on. Types without one go through `tp_get_attr`.
+    pub tp_get_method: Option<GetMethodFunctionPtr>,
     pub nb_bool: Option<NumberCheckFunctionPtr>,
     pub sq_len: Option<LenFunctionPtr>,
     pub tp_compare: Option<CompareFunctionPtr>,
     pub tp_hash: Option<HashFunctionPtr>,
     pub tp_add: Option<BinaryFunctionPtr>,
     pub tp_sub: Option<BinaryFunctionPtr>,
+    pub sq_slice: Option<SliceFunctionPtr>,
+    pub tp_exit: Option<ExitFunctionPtr>,
+    pub tp_del: Option<DelFunctionPtr>,
     pub dict: DictRef,
+    // Set on classes declared with `slots(...)`, whose instances keep their
+    // attributes in an array instead of a dict.
+    pub slots: Option<Arc<[Symbol]>>,
+    pub instance_size: usize,
 }
 
 impl Type {
@@ -129,6 +223,10 @@ impl Type {
             self.tp_get_attr = parent_type.tp_get_attr.clone();
         }
 
+        if self.tp_get_method.is_none() {
+            self.tp_get_method = parent_type.tp_get_method;
+        }
+
         if self.tp_set_attr.is_none() {
             self.tp_set_attr = parent_type.tp_set_attr.clone();
         }
@@ -141,6 +239,18 @@ impl Type {
             self.sq_len = parent_type.sq_len.clone();
         }
 
+        if self.sq_slice.is_none() {
+            self.sq_slice = parent_type.sq_slice;
+        }
+
+        if self.tp_exit.is_none() {
+            self.tp_exit = parent_type.tp_exit;
+        }
+
+        if self.tp_del.is_none() {
+            self.tp_del = parent_type.tp_del;
+        }
+
         if self.tp_compare.is_none() {
             self.tp_compare = parent_type.tp_compare.clone();
         }
@@ -222,7 +332,7 @@ impl Type {
         }
     }
 
-    pub fn get_attr(&self, obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error> {
+    pub fn get_attr(&self, obj: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
         if let Some(get_attr_fn) = self.tp_get_attr {
             get_attr_fn(obj, attr_name)
         } else {
@@ -236,7 +346,7 @@ impl Type {
     pub fn set_attr(
         &self,
         obj: KyaObjectRef,
-        attr_name: String,
+        attr_name: Symbol,
         value: KyaObjectRef,
     ) -> Result<(), Error> {
         if let Some(set_attr_fn) = self.tp_set_attr {
@@ -269,6 +379,38 @@ impl Type {
     }
 }
 
+impl Drop for KyaObject {
+    fn drop(&mut self) {
+        // A class object shares its type with the instances it creates but
+        // owns none of their resources.
+        if finalizers::take_finalized() || matches!(self, KyaObject::ClassObject(_)) {
+            return;
+        }
+
+        let Ok(ob_type) = self.get_type() else {
+            return;
+        };
+
+        // The type may be locked further up this thread's stack, in which
+        // case the object is released without its finalizer.
+        let tp_del = match ob_type.try_lock() {
+            Ok(ob_type) => ob_type.tp_del,
+            Err(_) => None,
+        };
+
+        if let Some(tp_del) = tp_del {
+            let object = std::mem::replace(
+                self,
+                KyaObject::NoneObject(NoneObject {
+                    ob_type: NONE_TYPE.clone(),
+                }),
+            );
+
+            finalizers::schedule(tp_del, object);
+        }
+    }
+}
+
 impl KyaObject {
     pub fn as_object_ref(&self) -> Option<&dyn KyaObjectTrait> {
         match self {
@@ -282,15 +424,25 @@ impl KyaObject {
             KyaObject::MethodObject(obj) => Some(obj),
             KyaObject::SocketObject(obj) => Some(obj),
             KyaObject::ConnectionObject(obj) => Some(obj),
+            KyaObject::ConnectionSetObject(obj) => Some(obj),
             KyaObject::BytesObject(obj) => Some(obj),
+            KyaObject::ByteViewObject(obj) => Some(obj),
             KyaObject::BoolObject(obj) => Some(obj),
             KyaObject::CodeObject(obj) => Some(obj),
             KyaObject::ThreadObject(obj) => Some(obj),
             KyaObject::LockObject(obj) => Some(obj),
+            KyaObject::ChannelObject(obj) => Some(obj),
+            KyaObject::QueueObject(obj) => Some(obj),
+            KyaObject::SemaphoreObject(obj) => Some(obj),
             KyaObject::ListObject(obj) => Some(obj),
             KyaObject::HashObject(obj) => Some(obj),
             KyaObject::ExceptionObject(obj) => Some(obj),
             KyaObject::UrlObject(obj) => Some(obj),
+            KyaObject::ModuleObject(obj) => Some(obj),
+            KyaObject::RequestObject(obj) => Some(obj),
+            KyaObject::ResponseObject(obj) => Some(obj),
+            KyaObject::HostFunctionObject(obj) => Some(obj),
+            KyaObject::DateTimeObject(obj) => Some(obj),
             _ => None,
         }
     }
@@ -331,7 +483,7 @@ impl KyaObject {
     }
 
     pub fn as_ref(object: KyaObject) -> KyaObjectRef {
-        Arc::new(Mutex::new(object))
+        Gc::new(object)
     }
 
     pub fn from_none_object(none_object: NoneObject) -> KyaObjectRef {
@@ -374,10 +526,18 @@ impl KyaObject {
         KyaObject::as_ref(KyaObject::ConnectionObject(connection_object))
     }
 
+    pub fn from_connection_set_object(connection_set_object: ConnectionSetObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::ConnectionSetObject(connection_set_object))
+    }
+
     pub fn from_bytes_object(bytes_object: BytesObject) -> KyaObjectRef {
         KyaObject::as_ref(KyaObject::BytesObject(bytes_object))
     }
 
+    pub fn from_byte_view_object(byte_view_object: ByteViewObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::ByteViewObject(byte_view_object))
+    }
+
     pub fn from_bool_object(bool_object: BoolObject) -> KyaObjectRef {
         KyaObject::as_ref(KyaObject::BoolObject(bool_object))
     }
@@ -394,6 +554,18 @@ impl KyaObject {
         KyaObject::as_ref(KyaObject::LockObject(lock_object))
     }
 
+    pub fn from_channel_object(channel_object: ChannelObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::ChannelObject(channel_object))
+    }
+
+    pub fn from_queue_object(queue_object: QueueObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::QueueObject(queue_object))
+    }
+
+    pub fn from_semaphore_object(semaphore_object: SemaphoreObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::SemaphoreObject(semaphore_object))
+    }
+
     pub fn from_list_object(list_object: ListObject) -> KyaObjectRef {
         KyaObject::as_ref(KyaObject::ListObject(list_object))
     }
@@ -409,6 +581,26 @@ impl KyaObject {
     pub fn from_url_object(url_object: UrlObject) -> KyaObjectRef {
         KyaObject::as_ref(KyaObject::UrlObject(url_object))
     }
+
+    pub fn from_module_object(module_object: ModuleObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::ModuleObject(module_object))
+    }
+
+    pub fn from_request_object(request_object: RequestObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::RequestObject(request_object))
+    }
+
+    pub fn from_response_object(response_object: ResponseObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::ResponseObject(response_object))
+    }
+
+    pub fn from_host_function_object(host_function_object: HostFunctionObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::HostFunctionObject(host_function_object))
+    }
+
+    pub fn from_datetime_object(datetime_object: DateTimeObject) -> KyaObjectRef {
+        KyaObject::as_ref(KyaObject::DateTimeObject(datetime_object))
+    }
 }
 
 impl Default for Type {
@@ -421,39 +613,71 @@ impl Default for Type {
             tp_new: Some(class_tp_new),
             tp_init: Some(class_tp_init),
             tp_get_attr: Some(generic_get_attr),
-            tp_set_attr: Some(generic_set_attr),
+            tp_get_method: Some(generic_get_method),
+            tp_set_attr: None,
             nb_bool: Some(class_nb_bool),
             sq_len: None,
             tp_compare: Some(generic_tp_compare),
             tp_hash: Some(generic_tp_hash),
             tp_add: None,
             tp_sub: None,
+            sq_slice: None,
+            tp_exit: None,
+            tp_del: None,
             dict: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            slots: None,
+            instance_size: 0,
         }
     }
 }
 
-pub fn generic_get_attr(obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error> {
-    let found_object = get_attr_helper(obj.clone(), attr_name.clone())?;
+pub fn generic_get_attr(obj: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
+    let (found_object, receiver) = generic_get_method(obj, attr_name)?;
 
-    if let KyaObject::FunctionObject(_) = &*found_object.lock().unwrap() {
-        return Ok(KyaObject::from_method_object(MethodObject {
-            ob_type: METHOD_TYPE.clone(),
-            instance_object: obj.clone(),
-            function: found_object.clone(),
-        }));
-    } else if let KyaObject::RsFunctionObject(_) = &*found_object.lock().unwrap() {
-        return Ok(KyaObject::from_method_object(MethodObject {
-            ob_type: METHOD_TYPE.clone(),
-            instance_object: obj.clone(),
-            function: found_object.clone(),
-        }));
+    Ok(bind_method(found_object, receiver))
+}
+
+pub fn generic_get_method(
+    obj: KyaObjectRef,
+    attr_name: Symbol,
+) -> Result<(KyaObjectRef, Option<KyaObjectRef>), Error> {
+    let found_object = get_attr_helper(obj.clone(), attr_name)?;
+
+    Ok(with_receiver(found_object, obj))
+}
+
+/// Pairs `found_object` with `obj` when it is a function, which is called
+/// with `obj` as its receiver.
+pub fn with_receiver(
+    found_object: KyaObjectRef,
+    obj: KyaObjectRef,
+) -> (KyaObjectRef, Option<KyaObjectRef>) {
+    let is_function = matches!(
+        &*found_object.lock().unwrap(),
+        KyaObject::FunctionObject(_) | KyaObject::RsFunctionObject(_)
+    );
+
+    if is_function {
+        (found_object, Some(obj))
+    } else {
+        (found_object, None)
     }
+}
 
-    Ok(found_object)
+/// The value of a plain attribute access: a function paired with a receiver
+/// becomes a method object.
+pub fn bind_method(found_object: KyaObjectRef, receiver: Option<KyaObjectRef>) -> KyaObjectRef {
+    match receiver {
+        Some(instance_object) => KyaObject::from_method_object(MethodObject {
+            ob_type: METHOD_TYPE.clone(),
+            instance_object,
+            function: found_object,
+        }),
+        None => found_object,
+    }
 }
 
-fn get_attr_helper(object: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error> {
+fn get_attr_helper(object: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
     let ob_type = object.lock().unwrap().get_type()?;
 
     if let Some(attr) = ob_type.lock().unwrap().dict.lock().unwrap().get(&attr_name) {
@@ -497,31 +721,77 @@ pub fn generic_tp_compare(
     operator: ComparisonOperator,
 ) -> Result<KyaObjectRef, Error> {
     match operator {
-        ComparisonOperator::Equal => {
-            if Arc::ptr_eq(&obj1, &obj2) {
-                return Ok(TRUE_OBJECT.clone());
-            } else {
-                return Ok(FALSE_OBJECT.clone());
-            }
-        }
-        _ => {
-            return Err(Error::RuntimeError(format!(
-                "Comparison operator '{:?}' is not supported",
-                operator
-            )));
-        }
+        ComparisonOperator::Equal => Ok(bool_to_bool_object(Gc::ptr_eq(&obj1, &obj2))),
+        ComparisonOperator::Neq => Ok(bool_to_bool_object(!Gc::ptr_eq(&obj1, &obj2))),
+        _ => Err(Error::TypeError(format!(
+            "'{}' is not supported between instances of '{}' and '{}'",
+            operator.symbol(),
+            kya_type_name(&obj1),
+            kya_type_name(&obj2)
+        ))),
+    }
+}
+
+/// The name to show for the type of `obj` in messages. Instances are named
+/// after their class.
+pub fn kya_type_name(obj: &KyaObjectRef) -> String {
+    let Ok(ob_type) = obj.lock().unwrap().get_type() else {
+        return "Unknown".to_string();
+    };
+
+    let is_instance = matches!(&*obj.lock().unwrap(), KyaObject::InstanceObject(_));
+    let ob_type = ob_type.lock().unwrap();
+
+    match (is_instance, &ob_type.ob_type) {
+        (true, Some(class_type)) => class_type.lock().unwrap().name.clone(),
+        _ => ob_type.name.clone(),
     }
 }
 
+static DETERMINISTIC_HASHING: AtomicBool = AtomicBool::new(false);
+
+// The ids `generic_tp_hash` handed out in deterministic mode, by address.
+// An entry whose object was dropped is replaced when the address is reused.
+type ObjectIds = HashMap<usize, (WeakGc, usize)>;
+
+static OBJECT_IDS: Lazy<Mutex<ObjectIds>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Makes objects without a hash of their own hash to an id counted from the
+/// start of the program instead of their address, so that hashes and the
+/// order of hash items are the same on every run.
+pub fn set_deterministic_hashing(enabled: bool) {
+    DETERMINISTIC_HASHING.store(enabled, Ordering::Relaxed);
+}
+
 pub fn generic_tp_hash(obj: KyaObjectRef) -> Result<usize, Error> {
-    let hash: usize = Arc::as_ptr(&obj) as usize;
+    let address = Gc::as_ptr(&obj) as usize;
+
+    if !DETERMINISTIC_HASHING.load(Ordering::Relaxed) {
+        return Ok(address);
+    }
 
-    Ok(hash)
+    let mut ids = OBJECT_IDS.lock().unwrap();
+    let next_id = ids.len();
+
+    let id = match ids.get(&address) {
+        Some((object, id)) if object.strong_count() > 0 => *id,
+        _ => {
+            ids.insert(address, (Gc::downgrade(&obj), next_id));
+            next_id
+        }
+    };
+
+    // Spread the ids so that they do not collide with small content hashes
+    // such as those of booleans.
+    let mut hasher = DefaultHasher::new();
+    ("object", id).hash(&mut hasher);
+
+    Ok(hasher.finish() as usize)
 }
 
 pub fn generic_set_attr(
     obj: KyaObjectRef,
-    attr_name: String,
+    attr_name: Symbol,
     value: KyaObjectRef,
 ) -> Result<(), Error> {
     let ob_type = obj.lock().unwrap().get_type()?;
@@ -630,6 +900,38 @@ pub fn kya_sq_len(obj: KyaObjectRef) -> Result<usize, Error> {
     sq_len_fn(obj)
 }
 
+pub fn kya_sq_slice(obj: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    let ob_type = obj.lock().unwrap().get_type()?;
+    let ob_name = ob_type.lock().unwrap().name.clone();
+    let sq_slice_fn = match ob_type.lock().unwrap().sq_slice {
+        Some(slice_fn) => Ok(slice_fn),
+        None => Err(Error::TypeError(format!(
+            "The object '{}' does not support slicing",
+            ob_name
+        ))),
+    }?;
+
+    drop(ob_type);
+
+    sq_slice_fn(obj, range)
+}
+
+pub fn kya_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    let ob_type = obj.lock().unwrap().get_type()?;
+    let ob_name = ob_type.lock().unwrap().name.clone();
+    let tp_exit_fn = match ob_type.lock().unwrap().tp_exit {
+        Some(exit_fn) => Ok(exit_fn),
+        None => Err(Error::TypeError(format!(
+            "The object '{}' cannot be used in a with statement",
+            ob_name
+        ))),
+    }?;
+
+    drop(ob_type);
+
+    tp_exit_fn(obj)
+}
+
 pub fn kya_repr(
     obj: KyaObjectRef,
     args: &mut Vec<KyaObjectRef>,
@@ -670,7 +972,11 @@ pub fn kya_init(
     tp_init(obj, args, receiver)
 }
 
-pub fn kya_get_attr(obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error> {
+pub fn kya_get_attr(
+    obj: KyaObjectRef,
+    attr_name: impl Into<Symbol>,
+) -> Result<KyaObjectRef, Error> {
+    let attr_name = attr_name.into();
     let ob_type = obj.lock().unwrap().get_type()?;
     let ob_name = ob_type.lock().unwrap().name.clone();
     let get_attr_fn = match ob_type.lock().unwrap().tp_get_attr {
@@ -686,11 +992,31 @@ pub fn kya_get_attr(obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef
     get_attr_fn(obj, attr_name)
 }
 
+/// Looks `attr_name` up to be called right away, returning the callable and
+/// the receiver to call it with. Unlike `kya_get_attr`, methods are not
+/// wrapped in a method object.
+pub fn kya_get_method(
+    obj: KyaObjectRef,
+    attr_name: impl Into<Symbol>,
+) -> Result<(KyaObjectRef, Option<KyaObjectRef>), Error> {
+    let attr_name = attr_name.into();
+    let ob_type = obj.lock().unwrap().get_type()?;
+    let get_method_fn = ob_type.lock().unwrap().tp_get_method;
+
+    drop(ob_type);
+
+    match get_method_fn {
+        Some(get_method_fn) => get_method_fn(obj, attr_name),
+        None => Ok((kya_get_attr(obj, attr_name)?, None)),
+    }
+}
+
 pub fn kya_set_attr(
     obj: KyaObjectRef,
-    attr_name: String,
+    attr_name: impl Into<Symbol>,
     value: KyaObjectRef,
 ) -> Result<(), Error> {
+    let attr_name = attr_name.into();
     let ob_type = obj.lock().unwrap().get_type()?;
     let ob_name = ob_type.lock().unwrap().name.clone();
     let tp_set_attr = match ob_type.lock().unwrap().tp_set_attr {
@@ -2,6 +2,7 @@ use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::string_object::{StringObject, STRING_TYPE};
+use crate::objects::utils::bool_to_bool_object;
 
 use once_cell::sync::Lazy;
 use std::sync::Arc;
@@ -54,6 +55,43 @@ pub fn bool_nb_bool(object: KyaObjectRef) -> Result<f64, Error> {
     }
 }
 
+pub fn bool_tp_compare(
+    obj1: KyaObjectRef,
+    obj2: KyaObjectRef,
+    operator: ComparisonOperator,
+) -> Result<KyaObjectRef, Error> {
+    let a = match &*obj1.lock().unwrap() {
+        KyaObject::BoolObject(obj) => obj.value,
+        _ => {
+            return Err(Error::RuntimeError(
+                "The first object is not a bool".to_string(),
+            ))
+        }
+    };
+
+    let b = match &*obj2.lock().unwrap() {
+        KyaObject::BoolObject(obj) => Some(obj.value),
+        _ => None,
+    };
+
+    match operator {
+        ComparisonOperator::Equal => Ok(bool_to_bool_object(b == Some(a))),
+        ComparisonOperator::Neq => Ok(bool_to_bool_object(b != Some(a))),
+        _ => Err(Error::RuntimeError(format!(
+            "Comparison operator '{:?}' is not supported",
+            operator
+        ))),
+    }
+}
+
+pub fn bool_tp_hash(obj: KyaObjectRef) -> Result<usize, Error> {
+    if let KyaObject::BoolObject(bool_object) = &*obj.lock().unwrap() {
+        Ok(bool_object.value as usize + 1)
+    } else {
+        Err(Error::RuntimeError("The object is not a bool".to_string()))
+    }
+}
+
 pub fn bool_new(value: bool) -> KyaObjectRef {
     KyaObject::from_bool_object(BoolObject {
         ob_type: BOOL_TYPE.clone(),
@@ -67,6 +105,8 @@ pub static BOOL_TYPE: Lazy<TypeRef> = Lazy::new(|| {
         name: "Bool".to_string(),
         tp_repr: Some(bool_tp_repr),
         nb_bool: Some(bool_nb_bool),
+        tp_compare: Some(bool_tp_compare),
+        tp_hash: Some(bool_tp_hash),
         ..Default::default()
     })
 });
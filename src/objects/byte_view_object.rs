@@ -0,0 +1,232 @@
+use crate::errors::Error;
+use crate::objects::base::{
+    KyaObject, KyaObjectRef, KyaObjectTrait, SliceRange, Type, TypeRef, BASE_TYPE,
+};
+use crate::objects::bytes_object::{bytes_new, bytes_tp_add};
+use crate::objects::modules::limits::functions::{check, Limit};
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{number_object_to_float, parse_arg, parse_receiver};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A window into a shared buffer, as `recv` returns. Slicing a view with a
+/// step of 1 returns another view of the same buffer instead of copying it,
+/// and functions that take bytes take views too.
+pub struct ByteViewObject {
+    pub ob_type: TypeRef,
+    pub data: Arc<[u8]>,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl ByteViewObject {
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[self.start..self.start + self.len]
+    }
+}
+
+impl KyaObjectTrait for ByteViewObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+pub fn byte_view_new(data: Arc<[u8]>, start: usize, len: usize) -> KyaObjectRef {
+    KyaObject::from_byte_view_object(ByteViewObject {
+        ob_type: BYTE_VIEW_TYPE.clone(),
+        data,
+        start,
+        len,
+    })
+}
+
+fn with_view<T>(object: &KyaObjectRef, f: impl FnOnce(&ByteViewObject) -> T) -> Result<T, Error> {
+    match &*object.lock().unwrap() {
+        KyaObject::ByteViewObject(view) => Ok(f(view)),
+        _ => Err(Error::RuntimeError(
+            "Expected a ByteView object".to_string(),
+        )),
+    }
+}
+
+pub fn byte_view_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let repr = with_view(&callable, |view| {
+        format!("view(b'{}')", String::from_utf8_lossy(view.as_slice()))
+    })?;
+
+    Ok(string_new(&repr))
+}
+
+pub fn byte_view_sq_len(object: KyaObjectRef) -> Result<usize, Error> {
+    with_view(&object, |view| view.len)
+}
+
+pub fn byte_view_sq_slice(object: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    with_view(&object, |view| {
+        let indices = range.indices(view.len);
+
+        if range.step == 1 {
+            let start = indices.first().map_or(0, |index| view.start + index);
+
+            return byte_view_new(view.data.clone(), start, indices.len());
+        }
+
+        let bytes = view.as_slice();
+        let copied: Vec<u8> = indices.into_iter().map(|index| bytes[index]).collect();
+        let len = copied.len();
+
+        byte_view_new(Arc::from(copied), 0, len)
+    })
+}
+
+pub fn byte_view_length(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let len = with_view(&parse_receiver(&receiver)?, |view| view.len)?;
+
+    Ok(number_new(len as f64))
+}
+
+// Returns the position of the first occurrence of needle at or after start,
+// or None if there is none.
+pub fn byte_view_find(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let needle_arg = parse_arg(args, 0, 1)?;
+    let from = match args.get(1) {
+        Some(arg) => number_object_to_float(arg)?.max(0.0) as usize,
+        None => 0,
+    };
+
+    let needle = match &*needle_arg.lock().unwrap() {
+        KyaObject::BytesObject(bytes_object) => bytes_object.value.clone(),
+        KyaObject::StringObject(string_object) => string_object.value.as_bytes().to_vec(),
+        KyaObject::ByteViewObject(view) => view.as_slice().to_vec(),
+        _ => {
+            return Err(Error::TypeError(
+                "ByteView.find() expects bytes, a string or a ByteView".to_string(),
+            ))
+        }
+    };
+
+    let position = with_view(&instance, |view| {
+        let haystack = view.as_slice().get(from..)?;
+
+        if needle.is_empty() {
+            return Some(from);
+        }
+
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle.as_slice())
+            .map(|position| from + position)
+    })?;
+
+    Ok(position.map_or_else(kya_none, |position| number_new(position as f64)))
+}
+
+pub fn byte_view_to_bytes(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let bytes = with_view(&parse_receiver(&receiver)?, |view| view.as_slice().to_vec())?;
+
+    Ok(bytes_new(bytes))
+}
+
+pub fn byte_view_decode(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let instance = parse_receiver(&receiver)?;
+    let decoded = with_view(&instance, |view| {
+        check(Limit::String, view.len)?;
+
+        Ok::<_, Error>(String::from_utf8_lossy(view.as_slice()).to_string())
+    })??;
+
+    Ok(string_new(&decoded))
+}
+
+pub static BYTE_VIEW_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("length".into(), rs_function_new(byte_view_length));
+
+    dict.lock()
+        .unwrap()
+        .insert("find".into(), rs_function_new(byte_view_find));
+
+    dict.lock()
+        .unwrap()
+        .insert("to_bytes".into(), rs_function_new(byte_view_to_bytes));
+
+    dict.lock()
+        .unwrap()
+        .insert("decode".into(), rs_function_new(byte_view_decode));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "ByteView".to_string(),
+        tp_repr: Some(byte_view_tp_repr),
+        sq_len: Some(byte_view_sq_len),
+        sq_slice: Some(byte_view_sq_slice),
+        tp_add: Some(bytes_tp_add),
+        dict,
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slices_share_the_buffer() {
+        let data: Arc<[u8]> = Arc::from(b"GET /index HTTP/1.1".as_slice());
+        let view = byte_view_new(data.clone(), 0, data.len());
+
+        let path =
+            byte_view_sq_slice(view, &SliceRange::new(Some(4), Some(10), 1).unwrap()).unwrap();
+
+        with_view(&path, |path| {
+            assert!(Arc::ptr_eq(&path.data, &data));
+            assert_eq!(path.as_slice(), b"/index");
+        })
+        .unwrap();
+
+        let found = byte_view_find(
+            path.clone(),
+            &mut vec![string_new("dex")],
+            Some(path.clone()),
+        )
+        .unwrap();
+        assert_eq!(number_object_to_float(&found).unwrap(), 3.0);
+
+        let missing = byte_view_find(path.clone(), &mut vec![string_new("?")], Some(path));
+        assert!(matches!(
+            &*missing.unwrap().lock().unwrap(),
+            KyaObject::NoneObject(_)
+        ));
+    }
+}
@@ -1,11 +1,13 @@
 use crate::errors::Error;
 use crate::objects::base::{
-    kya_sq_len, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
+    kya_sq_len, KyaObject, KyaObjectRef, KyaObjectTrait, SliceRange, Type, TypeRef, BASE_TYPE,
 };
+use crate::objects::byte_view_object::byte_view_new;
+use crate::objects::modules::limits::functions::{check, Limit};
 use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
 use crate::objects::string_object::string_new;
-use crate::objects::utils::parse_receiver;
+use crate::objects::utils::{parse_arg, parse_receiver};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -58,6 +60,48 @@ pub fn bytes_sq_len(object: KyaObjectRef) -> Result<usize, Error> {
     }
 }
 
+pub fn bytes_sq_slice(object: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::BytesObject(obj) = &*object.lock().unwrap() {
+        Ok(bytes_new(
+            range
+                .indices(obj.value.len())
+                .into_iter()
+                .map(|index| obj.value[index])
+                .collect(),
+        ))
+    } else {
+        Err(Error::RuntimeError(format!(
+            "The object '{}' is not a bytes object.",
+            object.lock().unwrap().get_type()?.lock().unwrap().name
+        )))
+    }
+}
+
+// Views concatenate like the bytes they show.
+fn bytes_value(object: &KyaObjectRef) -> Option<Vec<u8>> {
+    match &*object.lock().unwrap() {
+        KyaObject::BytesObject(obj) => Some(obj.value.clone()),
+        KyaObject::ByteViewObject(view) => Some(view.as_slice().to_vec()),
+        _ => None,
+    }
+}
+
+pub fn bytes_tp_add(obj1: KyaObjectRef, obj2: KyaObjectRef) -> Result<KyaObjectRef, Error> {
+    if let (Some(mut value), Some(other)) = (bytes_value(&obj1), bytes_value(&obj2)) {
+        check(Limit::Bytes, value.len() + other.len())?;
+
+        value.extend(other);
+
+        return Ok(bytes_new(value));
+    }
+
+    Err(Error::RuntimeError(format!(
+        "Unsupported operand type(s) for +: '{}' and '{}'",
+        obj1.lock().unwrap().get_type()?.lock().unwrap().name,
+        obj2.lock().unwrap().get_type()?.lock().unwrap().name
+    )))
+}
+
 pub fn bytes_length(
     _callable: KyaObjectRef,
     _args: &mut Vec<KyaObjectRef>,
@@ -85,6 +129,8 @@ pub fn bytes_decode(
     let instance = parse_receiver(&receiver)?;
 
     if let KyaObject::BytesObject(obj) = &*instance.lock().unwrap() {
+        check(Limit::String, obj.value.len())?;
+
         let decoded_string = String::from_utf8_lossy(&obj.value).to_string();
         Ok(string_new(decoded_string.as_str()))
     } else {
@@ -95,22 +141,51 @@ pub fn bytes_decode(
     }
 }
 
+// Copies the bytes once into a buffer that slices of the view then share.
+pub fn bytes_view(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::BytesObject(obj) = &*instance.lock().unwrap() {
+        Ok(byte_view_new(
+            Arc::from(obj.value.as_slice()),
+            0,
+            obj.value.len(),
+        ))
+    } else {
+        Err(Error::RuntimeError(format!(
+            "The object '{}' is not a bytes object.",
+            instance.lock().unwrap().get_type()?.lock().unwrap().name
+        )))
+    }
+}
+
 pub static BYTES_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     let dict = Arc::new(Mutex::new(HashMap::new()));
 
     dict.lock()
         .unwrap()
-        .insert("length".to_string(), rs_function_new(bytes_length));
+        .insert("length".into(), rs_function_new(bytes_length));
+
+    dict.lock()
+        .unwrap()
+        .insert("decode".into(), rs_function_new(bytes_decode));
 
     dict.lock()
         .unwrap()
-        .insert("decode".to_string(), rs_function_new(bytes_decode));
+        .insert("view".into(), rs_function_new(bytes_view));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "Bytes".to_string(),
         tp_repr: Some(bytes_tp_repr),
         sq_len: Some(bytes_sq_len),
+        sq_slice: Some(bytes_sq_slice),
+        tp_add: Some(bytes_tp_add),
         dict: dict.clone(),
         ..Default::default()
     })
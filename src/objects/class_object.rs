@@ -1,12 +1,9 @@
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::objects::base::{kya_init, kya_new, KyaObject, KyaObjectRef, KyaObjectTrait, TypeRef};
-use crate::objects::instance_object::{instance_type_new, InstanceObject};
+use crate::objects::instance_object::{instance_type_new, Fields, InstanceObject};
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
 
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-
 pub struct ClassObject {
     pub ob_type: TypeRef,
 }
@@ -36,9 +33,11 @@ pub fn class_tp_new(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
+    let fields = Fields::new(&ob_type.lock().unwrap());
+
     Ok(KyaObject::from_instance_object(InstanceObject {
         ob_type: instance_type_new(ob_type),
-        dict: Arc::new(Mutex::new(HashMap::new())),
+        fields,
     }))
 }
 
@@ -47,7 +46,7 @@ pub fn class_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn class_tp_repr(
@@ -1,5 +1,6 @@
 use crate::bytecode;
 use crate::errors::Error;
+use crate::gc::Gc;
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::string_object::string_new;
 
@@ -29,7 +30,10 @@ pub fn none_repr(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(string_new(&format!("<Code object at {:p}>", _callable)))
+    Ok(string_new(&format!(
+        "<Code object at {:p}>",
+        Gc::as_ptr(&_callable)
+    )))
 }
 
 pub static CODE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
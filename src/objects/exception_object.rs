@@ -1,10 +1,11 @@
 use crate::bytecode::ComparisonOperator;
+use crate::diagnostics::UNCAUGHT_EXCEPTION;
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::list_object::list_new;
 use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::utils::{bool_to_bool_object, parse_arg, parse_receiver};
 use once_cell::sync::Lazy;
 use std::collections::hash_map::DefaultHasher;
@@ -15,6 +16,10 @@ use std::sync::{Arc, Mutex};
 pub struct ExceptionObject {
     pub ob_type: TypeRef,
     pub message: KyaObjectRef,
+    pub cause: Option<KyaObjectRef>,
+    // The code of the error of the interpreter it was raised for, or the
+    // one every exception raised by a program shares.
+    pub code: &'static str,
 }
 
 impl KyaObjectTrait for ExceptionObject {
@@ -27,17 +32,24 @@ pub fn exception_new(message: KyaObjectRef) -> KyaObjectRef {
     KyaObject::from_exception(ExceptionObject {
         ob_type: EXCEPTION_TYPE.clone(),
         message,
+        cause: None,
+        code: UNCAUGHT_EXCEPTION,
     })
 }
 
 pub fn exception_tp_new(
-    _ob_type: TypeRef,
+    ob_type: TypeRef,
     args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
     let arg = parse_arg(args, 0, 1)?;
 
-    Ok(exception_new(arg))
+    Ok(KyaObject::from_exception(ExceptionObject {
+        ob_type,
+        message: arg,
+        cause: None,
+        code: UNCAUGHT_EXCEPTION,
+    }))
 }
 
 pub fn exception_tp_init(
@@ -45,15 +57,104 @@ pub fn exception_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
+}
+
+pub fn exception_message(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::ExceptionObject(exception) = &*instance.lock().unwrap() {
+        Ok(exception.message.clone())
+    } else {
+        Err(Error::TypeError("Expected an Exception object".to_string()))
+    }
+}
+
+pub fn exception_cause(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::ExceptionObject(exception) = &*instance.lock().unwrap() {
+        Ok(exception.cause.clone().unwrap_or_else(kya_none))
+    } else {
+        Err(Error::TypeError("Expected an Exception object".to_string()))
+    }
 }
 
 pub static EXCEPTION_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("message".into(), rs_function_new(exception_message));
+
+    dict.lock()
+        .unwrap()
+        .insert("cause".into(), rs_function_new(exception_cause));
+
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "Exception".to_string(),
         tp_new: Some(exception_tp_new),
         tp_init: Some(exception_tp_init),
+        dict,
+        ..Default::default()
+    })
+});
+
+pub static MEMORY_ERROR_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(EXCEPTION_TYPE.clone()),
+        name: "MemoryError".to_string(),
+        tp_new: Some(exception_tp_new),
+        tp_init: Some(exception_tp_init),
+        ..Default::default()
+    })
+});
+
+pub static PERMISSION_ERROR_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(EXCEPTION_TYPE.clone()),
+        name: "PermissionError".to_string(),
+        tp_new: Some(exception_tp_new),
+        tp_init: Some(exception_tp_init),
+        ..Default::default()
+    })
+});
+
+pub static TIMEOUT_ERROR_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(EXCEPTION_TYPE.clone()),
+        name: "TimeoutError".to_string(),
+        tp_new: Some(exception_tp_new),
+        tp_init: Some(exception_tp_init),
+        ..Default::default()
+    })
+});
+
+pub static TYPE_ERROR_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(EXCEPTION_TYPE.clone()),
+        name: "TypeError".to_string(),
+        tp_new: Some(exception_tp_new),
+        tp_init: Some(exception_tp_init),
+        ..Default::default()
+    })
+});
+
+pub static VALUE_ERROR_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(EXCEPTION_TYPE.clone()),
+        name: "ValueError".to_string(),
+        tp_new: Some(exception_tp_new),
+        tp_init: Some(exception_tp_init),
         ..Default::default()
     })
 });
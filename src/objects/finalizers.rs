@@ -0,0 +1,89 @@
+use std::cell::Cell;
+use std::sync::Mutex;
+
+use crate::gc::Gc;
+use crate::objects::base::{DelFunctionPtr, KyaObject};
+use crate::runtime;
+
+// Objects can be dropped anywhere, including while other objects or types
+// are locked, so finalizers are queued on drop and only run by the
+// interpreter loop at points where no object locks are held.
+pub type PendingFinalizers = Mutex<Vec<(DelFunctionPtr, KyaObject)>>;
+
+thread_local! {
+    static FINALIZED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn schedule(tp_del: DelFunctionPtr, object: KyaObject) {
+    runtime::current()
+        .finalizers
+        .lock()
+        .unwrap()
+        .push((tp_del, object));
+}
+
+// Consumed by the drop of an object whose finalizer already ran so that it
+// is not queued a second time.
+pub fn take_finalized() -> bool {
+    FINALIZED.with(|finalized| finalized.replace(false))
+}
+
+pub fn run_pending() {
+    let pending = std::mem::take(&mut *runtime::current().finalizers.lock().unwrap());
+
+    for (tp_del, object) in pending {
+        let object = KyaObject::as_ref(object);
+
+        if let Err(error) = tp_del(object.clone()) {
+            eprintln!("Exception ignored in finalizer: {}", error);
+        }
+
+        // A finalizer that stored the object somewhere resurrects it, and it
+        // is finalized again once those references go away.
+        if Gc::strong_count(&object) == 1 {
+            FINALIZED.with(|finalized| finalized.set(true));
+        }
+
+        drop(object);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Error;
+    use crate::objects::base::{KyaObjectRef, Type, BASE_TYPE};
+    use crate::objects::none_object::NoneObject;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_del(_obj: KyaObjectRef) -> Result<(), Error> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_finalizer_runs_once_after_last_reference() {
+        let ob_type = Type::as_ref(Type {
+            ob_type: Some(BASE_TYPE.clone()),
+            name: "Finalized".to_string(),
+            tp_del: Some(count_del),
+            ..Default::default()
+        });
+        let object = KyaObject::as_ref(KyaObject::NoneObject(NoneObject { ob_type }));
+        let other = object.clone();
+
+        drop(object);
+        run_pending();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+        drop(other);
+        run_pending();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        run_pending();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,7 +1,7 @@
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::thread;
+use std::time::Instant;
 
 use crate::bytecode::CodeObject;
 use crate::errors::Error;
@@ -10,6 +10,8 @@ use crate::objects::base::{
     DictRef, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
 };
 use crate::objects::string_object::{StringObject, STRING_TYPE};
+use crate::profiler;
+use crate::symbol::Symbol;
 
 pub struct FunctionObject {
     pub ob_type: TypeRef,
@@ -77,27 +79,29 @@ pub fn function_call(
         )));
     }
 
-    let mut locals = HashMap::new();
+    let mut frame_ref = Frame::for_call(code.clone(), globals);
 
-    if let Some(receiver_obj) = receiver {
-        locals.insert("self".to_string(), receiver_obj);
+    // The parameters take the first slots, in order.
+    let self_index = code.varnames.iter().position(|name| *name == Symbol::SELF);
+
+    if let (Some(receiver_obj), Some(index)) = (receiver, self_index) {
+        frame_ref.fast_locals[index] = Some(receiver_obj);
     }
 
-    for (i, arg) in code.args.iter().enumerate() {
-        locals.insert(arg.clone(), args[i].clone());
+    for (slot, arg) in frame_ref.fast_locals.iter_mut().zip(args.iter()) {
+        *slot = Some(arg.clone());
     }
 
-    let mut frame_ref = Frame {
-        locals: Arc::new(Mutex::new(locals)),
-        globals: globals.clone(),
-        code: code.clone(),
-        pc: 0,
-        stack: vec![],
-        return_value: None,
-        error: None,
-    };
-
-    eval_frame(&mut frame_ref)
+    let started = profiler::is_enabled().then(Instant::now);
+    let result = eval_frame(&mut frame_ref);
+
+    if let Some(started) = started {
+        profiler::record_call(&name, started.elapsed());
+    }
+
+    frame_ref.release();
+
+    result
 }
 
 pub fn function_new(name: String, code: Arc<CodeObject>, globals: DictRef) -> KyaObjectRef {
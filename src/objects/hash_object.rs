@@ -1,26 +1,35 @@
 use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::objects::base::{
-    kya_compare, kya_hash, kya_init, kya_repr, KyaObject, KyaObjectRef, KyaObjectTrait, Type,
-    TypeRef, BASE_TYPE,
+    kya_call, kya_compare, kya_hash, kya_init, kya_repr, KyaObject, KyaObjectRef, KyaObjectTrait,
+    SliceRange, Type, TypeRef, BASE_TYPE,
 };
+use crate::objects::list_object::list_new;
+use crate::objects::modules::limits::functions::{check, Limit};
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
 use crate::objects::utils::{kya_is_true, parse_arg, parse_receiver, string_object_to_string};
 use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
 use std::sync::{Arc, Mutex};
 
+// Keyed with a fixed hasher, so that the items of hashes built the same way
+// come out in the same order on every run.
+pub type HashItems = HashMap<usize, HashItem, BuildHasherDefault<DefaultHasher>>;
+
 pub struct HashObject {
     pub ob_type: TypeRef,
-    pub items: Arc<Mutex<HashMap<usize, HashItem>>>,
+    pub items: Arc<Mutex<HashItems>>,
+    pub default: Option<KyaObjectRef>,
 }
 
 #[derive(Clone)]
-struct HashItem {
-    key: KyaObjectRef,
-    value: KyaObjectRef,
+pub struct HashItem {
+    pub key: KyaObjectRef,
+    pub value: KyaObjectRef,
 }
 
 impl KyaObjectTrait for HashObject {
@@ -29,19 +38,46 @@ impl KyaObjectTrait for HashObject {
     }
 }
 
-pub fn hash_new(items: HashMap<usize, HashItem>) -> KyaObjectRef {
+pub fn hash_new(items: HashItems) -> KyaObjectRef {
     KyaObject::from_hash_object(HashObject {
         ob_type: HASH_TYPE.clone(),
         items: Arc::new(Mutex::new(items)),
+        default: None,
     })
 }
 
+pub fn hash_from_pairs(pairs: Vec<(KyaObjectRef, KyaObjectRef)>) -> Result<KyaObjectRef, Error> {
+    let mut items = HashItems::default();
+
+    for (key, value) in pairs {
+        items.insert(kya_hash(key.clone())?, HashItem { key, value });
+    }
+
+    Ok(hash_new(items))
+}
+
+pub fn hash_pairs(obj: &KyaObjectRef) -> Result<Vec<(KyaObjectRef, KyaObjectRef)>, Error> {
+    let items = match &*obj.lock().unwrap() {
+        KyaObject::HashObject(hash) => hash.items.clone(),
+        _ => return Err(Error::RuntimeError("Expected a Hash".to_string())),
+    };
+
+    let pairs = items
+        .lock()
+        .unwrap()
+        .values()
+        .map(|item| (item.key.clone(), item.value.clone()))
+        .collect();
+
+    Ok(pairs)
+}
+
 pub fn hash_tp_new(
     _ob_type: TypeRef,
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    let obj = hash_new(HashMap::new());
+    let obj = hash_new(HashItems::default());
 
     kya_init(obj.clone(), _args, _receiver)?;
 
@@ -53,7 +89,7 @@ pub fn hash_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn hash_tp_repr(
@@ -96,8 +132,8 @@ pub fn hash_get(
 ) -> Result<KyaObjectRef, Error> {
     let key = parse_arg(args, 0, 1)?;
     let instance = parse_receiver(&receiver)?;
-    let items = match &*instance.lock().unwrap() {
-        KyaObject::HashObject(hash) => hash.items.clone(),
+    let (items, default) = match &*instance.lock().unwrap() {
+        KyaObject::HashObject(hash) => (hash.items.clone(), hash.default.clone()),
         _ => {
             return Err(Error::RuntimeError(format!(
                 "The object '{}' is not a hash",
@@ -109,14 +145,72 @@ pub fn hash_get(
     let item = items.lock().unwrap().get(&key_hash).cloned();
 
     if let Some(item) = item {
-        let compare_result = kya_compare(item.key.clone(), key, ComparisonOperator::Equal)?;
+        let compare_result = kya_compare(item.key.clone(), key.clone(), ComparisonOperator::Equal)?;
 
         if kya_is_true(compare_result)? {
             return Ok(item.value);
         }
     }
 
-    Ok(NONE_OBJECT.clone())
+    let Some(default) = default else {
+        return Ok(kya_none());
+    };
+
+    // Missing keys are filled in, so defaults such as `List` can be mutated
+    // in place by the caller and still be there on the next lookup.
+    check(Limit::Hash, items.lock().unwrap().len() + 1)?;
+
+    let value = default_value(default)?;
+
+    items.lock().unwrap().insert(
+        key_hash,
+        HashItem {
+            key,
+            value: value.clone(),
+        },
+    );
+
+    Ok(value)
+}
+
+fn default_value(default: KyaObjectRef) -> Result<KyaObjectRef, Error> {
+    let is_factory = matches!(
+        &*default.lock().unwrap(),
+        KyaObject::FunctionObject(_)
+            | KyaObject::RsFunctionObject(_)
+            | KyaObject::HostFunctionObject(_)
+            | KyaObject::MethodObject(_)
+            | KyaObject::ClassObject(_)
+    );
+
+    if is_factory {
+        kya_call(default, &mut vec![], None)
+    } else {
+        Ok(default)
+    }
+}
+
+pub fn hash_with_default(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let default = parse_arg(args, 0, 1)?;
+    let instance = parse_receiver(&receiver)?;
+
+    // Called on the Hash class this builds a new hash, called on a hash it
+    // sets the default of that hash.
+    let hash = if matches!(&*instance.lock().unwrap(), KyaObject::HashObject(_)) {
+        instance
+    } else {
+        hash_new(HashItems::default())
+    };
+
+    if let KyaObject::HashObject(hash_object) = &mut *hash.lock().unwrap() {
+        hash_object.default = Some(default);
+    }
+
+    Ok(hash)
 }
 
 pub fn hash_insert(
@@ -137,8 +231,13 @@ pub fn hash_insert(
         }
     };
     let key_hash = kya_hash(key.clone())?;
+    let mut items = items.lock().unwrap();
 
-    items.lock().unwrap().insert(
+    if !items.contains_key(&key_hash) {
+        check(Limit::Hash, items.len() + 1)?;
+    }
+
+    items.insert(
         key_hash,
         HashItem {
             key: key.clone(),
@@ -146,7 +245,38 @@ pub fn hash_insert(
         },
     );
 
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
+}
+
+// Hashes have no positional order of their own, so slicing works on the keys
+// ordered by their hash and returns them as a list.
+pub fn hash_sq_slice(obj: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    let items = match &*obj.lock().unwrap() {
+        KyaObject::HashObject(hash) => hash.items.clone(),
+        _ => {
+            return Err(Error::RuntimeError(format!(
+                "The object '{}' is not a hash",
+                obj.lock().unwrap().get_type()?.lock().unwrap().name
+            )))
+        }
+    };
+
+    let mut entries: Vec<(usize, KyaObjectRef)> = items
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(key_hash, item)| (*key_hash, item.key.clone()))
+        .collect();
+
+    entries.sort_by_key(|(key_hash, _)| *key_hash);
+
+    let keys = range
+        .indices(entries.len())
+        .into_iter()
+        .map(|index| entries[index].1.clone())
+        .collect();
+
+    Ok(list_new(keys))
 }
 
 pub static HASH_TYPE: Lazy<TypeRef> = Lazy::new(|| {
@@ -154,11 +284,15 @@ pub static HASH_TYPE: Lazy<TypeRef> = Lazy::new(|| {
 
     dict.lock()
         .unwrap()
-        .insert("get".to_string(), rs_function_new(hash_get));
+        .insert("get".into(), rs_function_new(hash_get));
 
     dict.lock()
         .unwrap()
-        .insert("insert".to_string(), rs_function_new(hash_insert));
+        .insert("insert".into(), rs_function_new(hash_insert));
+
+    dict.lock()
+        .unwrap()
+        .insert("with_default".into(), rs_function_new(hash_with_default));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -166,7 +300,54 @@ pub static HASH_TYPE: Lazy<TypeRef> = Lazy::new(|| {
         tp_new: Some(hash_tp_new),
         tp_init: Some(hash_tp_init),
         tp_repr: Some(hash_tp_repr),
+        sq_slice: Some(hash_sq_slice),
         dict,
         ..Default::default()
     })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::{generic_tp_hash, set_deterministic_hashing};
+    use crate::objects::number_object::number_new;
+    use crate::objects::utils::number_object_to_float;
+
+    #[test]
+    fn test_hash_with_default() {
+        let hash = hash_new(HashItems::default());
+        hash_with_default(hash.clone(), &mut vec![number_new(0.0)], Some(hash.clone())).unwrap();
+
+        let get = |key: &str| {
+            let value = hash_get(hash.clone(), &mut vec![string_new(key)], Some(hash.clone()));
+            number_object_to_float(&value.unwrap()).unwrap()
+        };
+
+        assert_eq!(get("missing"), 0.0);
+
+        hash_insert(
+            hash.clone(),
+            &mut vec![string_new("word"), number_new(2.0)],
+            Some(hash.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(get("word"), 2.0);
+        assert_eq!(hash_pairs(&hash).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_deterministic_hashing() {
+        set_deterministic_hashing(true);
+
+        let object = list_new(vec![]);
+        let hash = generic_tp_hash(object.clone()).unwrap();
+
+        assert_eq!(generic_tp_hash(object).unwrap(), hash);
+        assert_ne!(generic_tp_hash(list_new(vec![])).unwrap(), hash);
+        assert_eq!(
+            kya_hash(number_new(0.0)).unwrap(),
+            kya_hash(number_new(-0.0)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use crate::errors::Error;
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::bytes_object::bytes_new;
+use crate::objects::list_object::list_new;
+use crate::objects::number_object::number_new;
+use crate::objects::singletons::{canonicalize, kya_none};
+use crate::objects::string_object::string_new;
+use crate::objects::utils::bool_to_bool_object;
+
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType {
+    Any,
+    None,
+    Bool,
+    Number,
+    String,
+    Bytes,
+    List,
+}
+
+impl ArgType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgType::Any => "Any",
+            ArgType::None => "None",
+            ArgType::Bool => "Bool",
+            ArgType::Number => "Number",
+            ArgType::String => "String",
+            ArgType::Bytes => "Bytes",
+            ArgType::List => "List",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum HostValue {
+    None,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<KyaObjectRef>),
+    Object(KyaObjectRef),
+}
+
+impl HostValue {
+    fn arg_type(&self) -> ArgType {
+        match self {
+            HostValue::None => ArgType::None,
+            HostValue::Bool(_) => ArgType::Bool,
+            HostValue::Number(_) => ArgType::Number,
+            HostValue::String(_) => ArgType::String,
+            HostValue::Bytes(_) => ArgType::Bytes,
+            HostValue::List(_) => ArgType::List,
+            HostValue::Object(_) => ArgType::Any,
+        }
+    }
+
+    fn mismatch(&self, expected: ArgType) -> Error {
+        Error::TypeError(format!(
+            "Expected a {}, got {}",
+            expected.name(),
+            self.arg_type().name()
+        ))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        match self {
+            HostValue::Bool(value) => Ok(*value),
+            other => Err(other.mismatch(ArgType::Bool)),
+        }
+    }
+
+    pub fn as_number(&self) -> Result<f64, Error> {
+        match self {
+            HostValue::Number(value) => Ok(*value),
+            other => Err(other.mismatch(ArgType::Number)),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            HostValue::String(value) => Ok(value),
+            other => Err(other.mismatch(ArgType::String)),
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<&[u8], Error> {
+        match self {
+            HostValue::Bytes(value) => Ok(value),
+            other => Err(other.mismatch(ArgType::Bytes)),
+        }
+    }
+
+    pub fn from_object(obj: &KyaObjectRef, expected: ArgType) -> Option<HostValue> {
+        let value = match (&*obj.lock().unwrap(), expected) {
+            (_, ArgType::Any) => HostValue::Object(obj.clone()),
+            (KyaObject::NoneObject(_), ArgType::None) => HostValue::None,
+            (KyaObject::BoolObject(bool_object), ArgType::Bool) => {
+                HostValue::Bool(bool_object.value)
+            }
+            (KyaObject::NumberObject(number_object), ArgType::Number) => {
+                HostValue::Number(number_object.value)
+            }
+            (KyaObject::StringObject(string_object), ArgType::String) => {
+                HostValue::String(string_object.value.clone())
+            }
+            (KyaObject::BytesObject(bytes_object), ArgType::Bytes) => {
+                HostValue::Bytes(bytes_object.value.clone())
+            }
+            (KyaObject::ByteViewObject(view), ArgType::Bytes) => {
+                HostValue::Bytes(view.as_slice().to_vec())
+            }
+            (KyaObject::ListObject(list_object), ArgType::List) => {
+                HostValue::List(list_object.items.iter().cloned().collect())
+            }
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    pub fn into_object(self) -> KyaObjectRef {
+        match self {
+            HostValue::None => kya_none(),
+            HostValue::Bool(value) => bool_to_bool_object(value),
+            HostValue::Number(value) => number_new(value),
+            HostValue::String(value) => string_new(&value),
+            HostValue::Bytes(value) => bytes_new(value),
+            HostValue::List(items) => list_new(items),
+            HostValue::Object(obj) => canonicalize(obj),
+        }
+    }
+}
+
+pub type HostFn = Arc<dyn Fn(Vec<HostValue>) -> Result<HostValue, Error> + Send + Sync>;
+
+pub struct HostFunctionObject {
+    pub ob_type: TypeRef,
+    pub name: String,
+    pub params: Vec<ArgType>,
+    pub returns: ArgType,
+    pub function: HostFn,
+    pub doc: Option<&'static str>,
+}
+
+impl KyaObjectTrait for HostFunctionObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+fn type_name_of(obj: &KyaObjectRef) -> Result<String, Error> {
+    Ok(obj.lock().unwrap().get_type()?.lock().unwrap().name.clone())
+}
+
+pub fn host_function_tp_call(
+    callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let (name, params, returns, function) =
+        if let KyaObject::HostFunctionObject(host) = &*callable.lock().unwrap() {
+            (
+                host.name.clone(),
+                host.params.clone(),
+                host.returns,
+                host.function.clone(),
+            )
+        } else {
+            return Err(Error::TypeError("Expected a host function".to_string()));
+        };
+
+    if args.len() != params.len() {
+        return Err(Error::TypeError(format!(
+            "{}() takes {} argument(s) but {} were given",
+            name,
+            params.len(),
+            args.len()
+        )));
+    }
+
+    let mut values = Vec::with_capacity(args.len());
+
+    for (index, (arg, expected)) in args.iter().zip(params.iter()).enumerate() {
+        let value = HostValue::from_object(arg, *expected).ok_or_else(|| {
+            Error::TypeError(format!(
+                "{}() argument {} must be {}, not {}",
+                name,
+                index + 1,
+                expected.name(),
+                type_name_of(arg).unwrap_or_default()
+            ))
+        })?;
+
+        values.push(value);
+    }
+
+    let result = function(values)?;
+
+    if returns != ArgType::Any && result.arg_type() != returns {
+        let actual = match &result {
+            HostValue::Object(obj) => type_name_of(obj)?,
+            other => other.arg_type().name().to_string(),
+        };
+
+        return Err(Error::TypeError(format!(
+            "{}() returned {}, expected {}",
+            name,
+            actual,
+            returns.name()
+        )));
+    }
+
+    Ok(result.into_object())
+}
+
+pub fn host_function_new(
+    name: &str,
+    params: &[ArgType],
+    returns: ArgType,
+    function: impl Fn(Vec<HostValue>) -> Result<HostValue, Error> + Send + Sync + 'static,
+) -> KyaObjectRef {
+    KyaObject::from_host_function_object(HostFunctionObject {
+        ob_type: HOST_FUNCTION_TYPE.clone(),
+        name: name.to_string(),
+        params: params.to_vec(),
+        returns,
+        function: Arc::new(function),
+        doc: None,
+    })
+}
+
+pub fn host_function_with_doc(
+    name: &str,
+    params: &[ArgType],
+    returns: ArgType,
+    doc: &'static str,
+    function: impl Fn(Vec<HostValue>) -> Result<HostValue, Error> + Send + Sync + 'static,
+) -> KyaObjectRef {
+    let object = host_function_new(name, params, returns, function);
+
+    if let KyaObject::HostFunctionObject(host) = &mut *object.lock().unwrap() {
+        host.doc = Some(doc);
+    }
+
+    object
+}
+
+pub static HOST_FUNCTION_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "HostFunction".to_string(),
+        tp_call: Some(host_function_tp_call),
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::kya_call;
+    use crate::objects::utils::{number_object_to_float, string_object_to_string};
+
+    fn add() -> KyaObjectRef {
+        host_function_new(
+            "add",
+            &[ArgType::Number, ArgType::Number],
+            ArgType::Number,
+            |args| {
+                Ok(HostValue::Number(
+                    args[0].as_number()? + args[1].as_number()?,
+                ))
+            },
+        )
+    }
+
+    fn error_message(result: Result<KyaObjectRef, Error>) -> String {
+        match result {
+            Err(Error::TypeError(message)) => message,
+            _ => panic!("expected a TypeError"),
+        }
+    }
+
+    #[test]
+    fn test_host_function_call() {
+        let result = kya_call(add(), &mut vec![number_new(1.0), number_new(2.0)], None).unwrap();
+
+        assert_eq!(number_object_to_float(&result).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_host_function_validates_arguments() {
+        assert_eq!(
+            error_message(kya_call(add(), &mut vec![number_new(1.0)], None)),
+            "add() takes 2 argument(s) but 1 were given"
+        );
+        assert_eq!(
+            error_message(kya_call(
+                add(),
+                &mut vec![number_new(1.0), string_new("2")],
+                None
+            )),
+            "add() argument 2 must be Number, not String"
+        );
+    }
+
+    #[test]
+    fn test_host_function_validates_return() {
+        let greet = host_function_new("greet", &[ArgType::String], ArgType::String, |args| {
+            Ok(HostValue::String(format!("hello {}", args[0].as_str()?)))
+        });
+        let broken = host_function_new("broken", &[], ArgType::String, |_| {
+            Ok(HostValue::Number(1.0))
+        });
+
+        let result = kya_call(greet, &mut vec![string_new("kya")], None).unwrap();
+
+        assert_eq!(string_object_to_string(&result).unwrap(), "hello kya");
+        assert_eq!(
+            error_message(kya_call(broken, &mut vec![], None)),
+            "broken() returned Number, expected String"
+        );
+    }
+}
@@ -1,15 +1,90 @@
+use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
+use crate::gc::Gc;
 use crate::objects::base::{
-    kya_call, kya_get_attr, DictRef, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef,
-    BASE_TYPE,
+    bind_method, generic_tp_compare, kya_call, kya_get_attr, kya_repr, kya_type_name,
+    with_receiver, DictRef, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
 };
-use crate::objects::method_object::{MethodObject, METHOD_TYPE};
-use crate::objects::string_object::{StringObject, STRING_TYPE};
-use std::sync::Arc;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{bool_to_bool_object, kya_is_true, string_object_to_string};
+use crate::symbol::Symbol;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// The default repr lists at most this many fields, and stops expanding
+// instances nested deeper than this, which also keeps an instance that
+// refers to itself from recursing forever.
+const REPR_MAX_FIELDS: usize = 8;
+const REPR_MAX_DEPTH: usize = 3;
+
+thread_local! {
+    static REPR_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
 
 pub struct InstanceObject {
     pub ob_type: TypeRef,
-    pub dict: DictRef,
+    pub fields: Fields,
+}
+
+/// Where an instance keeps its attributes.
+pub enum Fields {
+    Dict(DictRef),
+    // The slot names of the class and the value at the same index, if set.
+    Slots(Arc<[Symbol]>, Vec<Option<KyaObjectRef>>),
+}
+
+impl Fields {
+    pub fn new(class_type: &Type) -> Self {
+        match &class_type.slots {
+            Some(names) => Fields::Slots(names.clone(), vec![None; names.len()]),
+            None => Fields::Dict(Arc::new(Mutex::new(HashMap::with_capacity(
+                class_type.instance_size,
+            )))),
+        }
+    }
+
+    fn get(&self, name: &Symbol) -> Option<KyaObjectRef> {
+        match self {
+            Fields::Dict(dict) => dict.lock().unwrap().get(name).cloned(),
+            Fields::Slots(names, values) => names
+                .iter()
+                .position(|slot| slot == name)
+                .and_then(|index| values[index].clone()),
+        }
+    }
+
+    // Fails with the name when the class has no slot for it.
+    fn set(&mut self, name: Symbol, value: KyaObjectRef) -> Result<(), Symbol> {
+        match self {
+            Fields::Dict(dict) => {
+                dict.lock().unwrap().insert(name, value);
+            }
+            Fields::Slots(names, values) => {
+                let index = names.iter().position(|slot| *slot == name).ok_or(name)?;
+
+                values[index] = Some(value);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Vec<(String, KyaObjectRef)> {
+        match self {
+            Fields::Dict(dict) => dict
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.clone()))
+                .collect(),
+            Fields::Slots(names, values) => names
+                .iter()
+                .zip(values)
+                .filter_map(|(name, value)| Some((name.to_string(), value.clone()?)))
+                .collect(),
+        }
+    }
 }
 
 impl KyaObjectTrait for InstanceObject {
@@ -42,7 +117,7 @@ pub fn instance_tp_init(
         .dict
         .lock()
         .unwrap()
-        .get("constructor")
+        .get(&Symbol::CONSTRUCTOR)
         .cloned();
 
     if let Some(init) = constructor {
@@ -67,101 +142,256 @@ pub fn instance_tp_repr(
     args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    let repr = kya_get_attr(callable.clone(), "__repr__".to_string());
+    match kya_get_attr(callable.clone(), Symbol::REPR) {
+        Ok(repr) if !is_default_repr(&repr) => kya_call(repr, args, Some(callable.clone())),
+        _ => instance_default_repr(callable, args, None),
+    }
+}
 
-    if repr.is_ok() {
-        kya_call(repr.unwrap(), args, Some(callable.clone()))
-    } else {
-        instance_default_repr(callable, args, None)
+// The `__repr__` every object inherits from the base type calls back into
+// kya_repr, so only one defined by the class is used here.
+fn is_default_repr(repr: &KyaObjectRef) -> bool {
+    let default_repr = BASE_TYPE
+        .lock()
+        .unwrap()
+        .dict
+        .lock()
+        .unwrap()
+        .get(&Symbol::REPR)
+        .cloned();
+
+    match (&*repr.lock().unwrap(), default_repr) {
+        (KyaObject::MethodObject(method), Some(default_repr)) => {
+            Gc::ptr_eq(&method.function, &default_repr)
+        }
+        _ => false,
+    }
+}
+
+pub fn instance_tp_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    let exit = kya_get_attr(obj.clone(), Symbol::EXIT).map_err(|_| {
+        Error::TypeError(format!(
+            "The object '{}' cannot be used in a with statement, it has no __exit__ method",
+            obj.lock()
+                .unwrap()
+                .get_type()
+                .map(|ob_type| ob_type.lock().unwrap().name.clone())
+                .unwrap_or_default()
+        ))
+    })?;
+
+    kya_call(exit, &mut vec![], Some(obj))?;
+
+    Ok(())
+}
+
+// Calls `obj.<name>(other)` if `obj` is an instance whose class defines it.
+fn call_dunder(
+    obj: &KyaObjectRef,
+    name: Symbol,
+    other: &KyaObjectRef,
+) -> Result<Option<KyaObjectRef>, Error> {
+    if !matches!(&*obj.lock().unwrap(), KyaObject::InstanceObject(_)) {
+        return Ok(None);
+    }
+
+    match kya_get_attr(obj.clone(), name) {
+        Ok(method) => Ok(Some(kya_call(method, &mut vec![other.clone()], None)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn negate(result: Option<KyaObjectRef>) -> Result<Option<KyaObjectRef>, Error> {
+    match result {
+        Some(result) => Ok(Some(bool_to_bool_object(!kya_is_true(result)?))),
+        None => Ok(None),
+    }
+}
+
+// Tries `a.<name>(b)`, then the reflected `b.<reflected>(a)`.
+fn call_ordering(
+    a: &KyaObjectRef,
+    b: &KyaObjectRef,
+    name: Symbol,
+    reflected: Symbol,
+) -> Result<Option<KyaObjectRef>, Error> {
+    match call_dunder(a, name, b)? {
+        Some(result) => Ok(Some(result)),
+        None => call_dunder(b, reflected, a),
     }
 }
 
+/// Compares through the `__eq__`, `__ne__`, `__lt__`, `__gt__`, `__le__` and
+/// `__ge__` methods. `!=` falls back to negating `__eq__`, and `<=` and `>=`
+/// to negating `__lt__`, so defining `__lt__` is enough to sort instances.
+pub fn instance_tp_compare(
+    obj1: KyaObjectRef,
+    obj2: KyaObjectRef,
+    operator: ComparisonOperator,
+) -> Result<KyaObjectRef, Error> {
+    let (a, b) = (&obj1, &obj2);
+
+    let result = match operator {
+        ComparisonOperator::Equal => call_dunder(a, Symbol::EQ, b)?,
+        ComparisonOperator::Neq => match call_dunder(a, Symbol::NE, b)? {
+            Some(result) => Some(result),
+            None => negate(call_dunder(a, Symbol::EQ, b)?)?,
+        },
+        ComparisonOperator::Lt => call_ordering(a, b, Symbol::LT, Symbol::GT)?,
+        ComparisonOperator::Gt => call_ordering(a, b, Symbol::GT, Symbol::LT)?,
+        ComparisonOperator::Lte => match call_ordering(a, b, Symbol::LE, Symbol::GE)? {
+            Some(result) => Some(result),
+            None => negate(call_ordering(b, a, Symbol::LT, Symbol::GT)?)?,
+        },
+        ComparisonOperator::Gte => match call_ordering(a, b, Symbol::GE, Symbol::LE)? {
+            Some(result) => Some(result),
+            None => negate(call_ordering(a, b, Symbol::LT, Symbol::GT)?)?,
+        },
+    };
+
+    match result {
+        Some(result) => Ok(result),
+        None => generic_tp_compare(obj1, obj2, operator),
+    }
+}
+
+/// Shows the instance's fields in name order, e.g. `<Point x=1, y=2>`.
 pub fn instance_default_repr(
     callable: KyaObjectRef,
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    let object = callable.lock().unwrap();
-
-    if let KyaObject::InstanceObject(_) = &*object {
-        Ok(KyaObject::from_string_object(StringObject {
-            ob_type: STRING_TYPE.clone(),
-            value: format!(
-                "<instance {} at {:p}>",
-                object.get_type()?.lock().unwrap().name,
-                &*object as *const KyaObject
-            ),
-        }))
-    } else {
-        Err(Error::RuntimeError(format!(
-            "The object '{}' is not a instance",
-            object.get_type()?.lock().unwrap().name
-        )))
+    let mut fields = match &*callable.lock().unwrap() {
+        KyaObject::InstanceObject(instance) => instance.fields.entries(),
+        object => {
+            return Err(Error::RuntimeError(format!(
+                "The object '{}' is not a instance",
+                object.get_type()?.lock().unwrap().name
+            )))
+        }
+    };
+
+    let name = kya_type_name(&callable);
+
+    if fields.is_empty() {
+        return Ok(string_new(&format!(
+            "<{} at {:p}>",
+            name,
+            Gc::as_ptr(&callable)
+        )));
+    }
+
+    if REPR_DEPTH.with(|depth| depth.get()) >= REPR_MAX_DEPTH {
+        return Ok(string_new(&format!("<{} ...>", name)));
+    }
+
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+    REPR_DEPTH.with(|depth| depth.set(depth.get() + 1));
+
+    let reprs: Result<Vec<String>, Error> = fields
+        .iter()
+        .take(REPR_MAX_FIELDS)
+        .map(|(field, value)| {
+            let repr = kya_repr(value.clone(), &mut vec![], None)?;
+
+            Ok(format!("{}={}", field, string_object_to_string(&repr)?))
+        })
+        .collect();
+
+    REPR_DEPTH.with(|depth| depth.set(depth.get() - 1));
+
+    let mut reprs = reprs?;
+
+    if fields.len() > REPR_MAX_FIELDS {
+        reprs.push("...".to_string());
     }
+
+    Ok(string_new(&format!("<{} {}>", name, reprs.join(", "))))
+}
+
+/// The value in slot `index` of `object`, if it is an instance whose class
+/// keeps `name` there and the slot is set.
+pub fn get_slot(object: &KyaObjectRef, index: usize, name: Symbol) -> Option<KyaObjectRef> {
+    let value = match &*object.lock().unwrap() {
+        KyaObject::InstanceObject(InstanceObject {
+            fields: Fields::Slots(names, values),
+            ..
+        }) if names.get(index) == Some(&name) => values[index].clone()?,
+        _ => return None,
+    };
+    let (value, receiver) = with_receiver(value, object.clone());
+
+    Some(bind_method(value, receiver))
 }
 
-pub fn instance_tp_get_attr(obj: KyaObjectRef, attr_name: String) -> Result<KyaObjectRef, Error> {
-    let dict_ref;
+/// Stores `value` in slot `index` of `object` if it is an instance whose
+/// class keeps `name` there, and returns whether it did.
+pub fn set_slot(object: &KyaObjectRef, index: usize, name: Symbol, value: KyaObjectRef) -> bool {
+    match &mut *object.lock().unwrap() {
+        KyaObject::InstanceObject(InstanceObject {
+            fields: Fields::Slots(names, values),
+            ..
+        }) if names.get(index) == Some(&name) => {
+            values[index] = Some(value);
 
-    if let KyaObject::InstanceObject(obj_instance) = &*obj.lock().unwrap() {
-        dict_ref = obj_instance.dict.clone();
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn instance_tp_get_attr(obj: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
+    let (found_object, receiver) = instance_tp_get_method(obj, attr_name)?;
+
+    Ok(bind_method(found_object, receiver))
+}
+
+pub fn instance_tp_get_method(
+    obj: KyaObjectRef,
+    attr_name: Symbol,
+) -> Result<(KyaObjectRef, Option<KyaObjectRef>), Error> {
+    let field = if let KyaObject::InstanceObject(obj_instance) = &*obj.lock().unwrap() {
+        obj_instance.fields.get(&attr_name)
     } else {
         return Err(Error::RuntimeError(format!(
             "The object '{}' is not a instance",
             obj.lock().unwrap().get_type()?.lock().unwrap().name
         )));
-    }
+    };
 
-    let found_object = get_attr(obj.clone(), dict_ref, attr_name.clone())?;
+    let found_object = match field {
+        Some(field) => field,
+        None => get_class_attr(obj.clone(), attr_name)?,
+    };
 
-    if let KyaObject::FunctionObject(_) = &*found_object.lock().unwrap() {
-        return Ok(KyaObject::from_method_object(MethodObject {
-            ob_type: METHOD_TYPE.clone(),
-            instance_object: obj.clone(),
-            function: found_object.clone(),
-        }));
-    } else if let KyaObject::RsFunctionObject(_) = &*found_object.lock().unwrap() {
-        return Ok(KyaObject::from_method_object(MethodObject {
-            ob_type: METHOD_TYPE.clone(),
-            instance_object: obj.clone(),
-            function: found_object.clone(),
-        }));
-    }
-
-    Ok(found_object)
+    Ok(with_receiver(found_object, obj))
 }
 
-pub fn get_attr(
-    object: KyaObjectRef,
-    dict: DictRef,
-    attr_name: String,
-) -> Result<KyaObjectRef, Error> {
-    if let Some(attr) = dict.lock().unwrap().get(&attr_name) {
-        return Ok(attr.clone());
-    } else {
-        let mut root_type = object.lock().unwrap().get_type()?;
-        let mut parent_type = root_type.lock().unwrap().parent()?;
+// Looks `attr_name` up in the class of `object` and then in its parents.
+fn get_class_attr(object: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
+    let mut root_type = object.lock().unwrap().get_type()?;
+    let mut parent_type = root_type.lock().unwrap().parent()?;
 
-        loop {
-            if let Some(attr) = root_type
-                .lock()
-                .unwrap()
-                .dict
-                .lock()
-                .unwrap()
-                .get(&attr_name)
-            {
-                return Ok(attr.clone());
-            } else if Arc::ptr_eq(&root_type, &BASE_TYPE) {
-                break;
-            }
+    loop {
+        if let Some(attr) = root_type
+            .lock()
+            .unwrap()
+            .dict
+            .lock()
+            .unwrap()
+            .get(&attr_name)
+        {
+            return Ok(attr.clone());
+        } else if Arc::ptr_eq(&root_type, &BASE_TYPE) {
+            break;
+        }
 
-            root_type = parent_type.clone();
+        root_type = parent_type.clone();
 
-            let new_parent_type = root_type.lock().unwrap().parent()?;
+        let new_parent_type = root_type.lock().unwrap().parent()?;
 
-            parent_type = new_parent_type;
-        }
+        parent_type = new_parent_type;
     }
 
     Err(Error::RuntimeError(format!(
@@ -173,14 +403,16 @@ pub fn get_attr(
 
 pub fn instance_tp_set_attr(
     obj: KyaObjectRef,
-    attr_name: String,
+    attr_name: Symbol,
     value: KyaObjectRef,
 ) -> Result<(), Error> {
-    let object = obj.lock().unwrap();
+    let name = kya_type_name(&obj);
+    let mut object = obj.lock().unwrap();
 
-    if let KyaObject::InstanceObject(obj) = &*object {
-        obj.dict.lock().unwrap().insert(attr_name, value);
-        Ok(())
+    if let KyaObject::InstanceObject(obj) = &mut *object {
+        obj.fields.set(attr_name, value).map_err(|attr_name| {
+            Error::RuntimeError(format!("The object '{}' has no slot '{}'", name, attr_name))
+        })
     } else {
         Err(Error::RuntimeError(format!(
             "The object '{}' is not a instance",
@@ -196,7 +428,10 @@ pub fn instance_type_new(ob_type: TypeRef) -> TypeRef {
         tp_init: Some(instance_tp_init),
         tp_repr: Some(instance_tp_repr),
         tp_get_attr: Some(instance_tp_get_attr),
+        tp_get_method: Some(instance_tp_get_method),
         tp_set_attr: Some(instance_tp_set_attr),
+        tp_exit: Some(instance_tp_exit),
+        tp_compare: Some(instance_tp_compare),
         ..Default::default()
     })
 }
@@ -1,21 +1,26 @@
 use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::objects::base::{
-    kya_compare, kya_init, kya_repr, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef,
-    BASE_TYPE,
+    kya_compare, kya_init, kya_repr, KyaObject, KyaObjectRef, KyaObjectTrait, SliceRange, Type,
+    TypeRef, BASE_TYPE,
 };
+use crate::objects::modules::limits::functions::{check, Limit};
 use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
-use crate::objects::utils::{kya_is_true, parse_arg, parse_receiver, string_object_to_string};
+use crate::objects::utils::{
+    kya_is_true, number_object_to_float, parse_arg, parse_receiver, string_object_to_string,
+};
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 
+// Items live in a VecDeque so lists can double as queues: shift and unshift
+// work on the front without moving the rest of the items.
 pub struct ListObject {
     pub ob_type: TypeRef,
-    pub items: Vec<KyaObjectRef>,
+    pub items: VecDeque<KyaObjectRef>,
 }
 
 impl KyaObjectTrait for ListObject {
@@ -27,7 +32,7 @@ impl KyaObjectTrait for ListObject {
 pub fn list_new(items: Vec<KyaObjectRef>) -> KyaObjectRef {
     KyaObject::from_list_object(ListObject {
         ob_type: LIST_TYPE.clone(),
-        items: items,
+        items: VecDeque::from(items),
     })
 }
 
@@ -48,7 +53,7 @@ pub fn list_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn list_tp_repr(
@@ -94,7 +99,9 @@ pub fn list_append(
     let arg = parse_arg(&args, 0, 1)?;
 
     if let KyaObject::ListObject(ref mut list_object) = *instance.lock().unwrap() {
-        list_object.items.push(arg.clone());
+        check(Limit::List, list_object.items.len() + 1)?;
+
+        list_object.items.push_back(arg.clone());
 
         Ok(instance.clone())
     } else {
@@ -138,7 +145,7 @@ pub fn list_remove(
         }
     }
 
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn list_at(
@@ -169,6 +176,264 @@ pub fn list_at(
     }
 }
 
+fn list_items_mut<T>(
+    instance: &KyaObjectRef,
+    f: impl FnOnce(&mut VecDeque<KyaObjectRef>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    if let KyaObject::ListObject(ref mut list_object) = *instance.lock().unwrap() {
+        return f(&mut list_object.items);
+    }
+
+    Err(Error::RuntimeError(format!(
+        "The object '{}' is not a list",
+        instance.lock().unwrap().get_type()?.lock().unwrap().name
+    )))
+}
+
+pub fn list_pop(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let index = match args.first() {
+        Some(index) => Some(number_object_to_float(index)? as i64),
+        None => None,
+    };
+
+    list_items_mut(&instance, |items| {
+        if items.is_empty() {
+            return Err(Error::RuntimeError("Pop from an empty list".to_string()));
+        }
+
+        let len = items.len() as i64;
+        let position = match index {
+            Some(index) if index < 0 => index + len,
+            Some(index) => index,
+            None => len - 1,
+        };
+
+        if position < 0 || position >= len {
+            return Err(Error::RuntimeError(format!(
+                "Index out of range: {}",
+                index.unwrap_or(position)
+            )));
+        }
+
+        Ok(items.remove(position as usize).unwrap())
+    })
+}
+
+pub fn list_shift(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    list_items_mut(&instance, |items| {
+        items
+            .pop_front()
+            .ok_or_else(|| Error::RuntimeError("Shift from an empty list".to_string()))
+    })
+}
+
+pub fn list_unshift(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let arg = parse_arg(args, 0, 1)?;
+
+    list_items_mut(&instance, |items| {
+        check(Limit::List, items.len() + 1)?;
+
+        items.push_front(arg);
+        Ok(())
+    })?;
+
+    Ok(instance)
+}
+
+fn list_item(instance: &KyaObjectRef, index: usize) -> Result<KyaObjectRef, Error> {
+    list_items_mut(instance, |items| {
+        items
+            .get(index)
+            .cloned()
+            .ok_or_else(|| Error::RuntimeError(format!("Index out of range: {}", index)))
+    })
+}
+
+fn is_less(a: KyaObjectRef, b: KyaObjectRef) -> Result<bool, Error> {
+    kya_is_true(kya_compare(a, b, ComparisonOperator::Lt)?)
+}
+
+// Binary searches a list assumed to be sorted for the first position where
+// `goes_before` stops holding. The list is only locked while reading single
+// items, since comparisons may run user code.
+fn partition_point(
+    instance: &KyaObjectRef,
+    goes_before: impl Fn(KyaObjectRef) -> Result<bool, Error>,
+) -> Result<usize, Error> {
+    let mut low = 0;
+    let mut high = list_items_mut(instance, |items| Ok(items.len()))?;
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        if goes_before(list_item(instance, mid)?)? {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low)
+}
+
+pub fn list_bsearch(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let value = parse_arg(args, 0, 1)?;
+
+    let index = partition_point(&instance, |item| is_less(item, value.clone()))?;
+
+    let found = match list_items_mut(&instance, |items| Ok(items.get(index).cloned()))? {
+        Some(item) => kya_is_true(kya_compare(item, value, ComparisonOperator::Equal)?)?,
+        None => false,
+    };
+
+    if found {
+        Ok(number_new(index as f64))
+    } else {
+        Ok(kya_none())
+    }
+}
+
+pub fn list_insert_sorted(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let value = parse_arg(args, 0, 1)?;
+
+    // Equal items keep their insertion order, so the new one goes after them.
+    let index = partition_point(&instance, |item| Ok(!is_less(value.clone(), item)?))?;
+
+    list_items_mut(&instance, |items| {
+        check(Limit::List, items.len() + 1)?;
+
+        items.insert(index.min(items.len()), value);
+        Ok(())
+    })?;
+
+    Ok(number_new(index as f64))
+}
+
+// A stable merge sort that stops at the first comparison that fails.
+fn merge_sort(mut items: Vec<KyaObjectRef>) -> Result<Vec<KyaObjectRef>, Error> {
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+
+    let right = merge_sort(items.split_off(items.len() / 2))?;
+    let left = merge_sort(items)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        // Only a strictly smaller item overtakes, so equal items keep their
+        // order.
+        if is_less(b.clone(), a.clone())? {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+// Sorts in place with `<`, which instances can define through `__lt__`.
+pub fn list_sort(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    let items = list_items_mut(&instance, |items| Ok(Vec::from(items.clone())))?;
+    let sorted = merge_sort(items)?;
+
+    list_items_mut(&instance, |items| {
+        *items = VecDeque::from(sorted);
+        Ok(())
+    })?;
+
+    Ok(kya_none())
+}
+
+// Returns the first item that no other item goes before (for min) or after
+// (for max).
+fn list_extreme(
+    receiver: Option<KyaObjectRef>,
+    name: &str,
+    max: bool,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let items = list_items_mut(&instance, |items| Ok(Vec::from(items.clone())))?;
+    let mut items = items.into_iter();
+
+    let mut best = items
+        .next()
+        .ok_or_else(|| Error::ValueError(format!("{}() of an empty list", name)))?;
+
+    for item in items {
+        let better = if max {
+            is_less(best.clone(), item.clone())?
+        } else {
+            is_less(item.clone(), best.clone())?
+        };
+
+        if better {
+            best = item;
+        }
+    }
+
+    Ok(best)
+}
+
+pub fn list_min(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    list_extreme(receiver, "min", false)
+}
+
+pub fn list_max(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    list_extreme(receiver, "max", true)
+}
+
 pub fn list_length(
     _callable: KyaObjectRef,
     _args: &mut Vec<KyaObjectRef>,
@@ -191,23 +456,51 @@ pub static LIST_TYPE: Lazy<TypeRef> = Lazy::new(|| {
 
     dict.lock()
         .unwrap()
-        .insert("append".to_string(), rs_function_new(list_append));
+        .insert("append".into(), rs_function_new(list_append));
 
     dict.lock()
         .unwrap()
-        .insert("remove".to_string(), rs_function_new(list_remove));
+        .insert("remove".into(), rs_function_new(list_remove));
 
     dict.lock()
         .unwrap()
-        .insert("at".to_string(), rs_function_new(list_at));
+        .insert("at".into(), rs_function_new(list_at));
 
     dict.lock()
         .unwrap()
-        .insert("length".to_string(), rs_function_new(list_length));
+        .insert("length".into(), rs_function_new(list_length));
 
     dict.lock()
         .unwrap()
-        .insert("slice".to_string(), rs_function_new(list_slice));
+        .insert("pop".into(), rs_function_new(list_pop));
+
+    dict.lock()
+        .unwrap()
+        .insert("shift".into(), rs_function_new(list_shift));
+
+    dict.lock()
+        .unwrap()
+        .insert("unshift".into(), rs_function_new(list_unshift));
+
+    dict.lock()
+        .unwrap()
+        .insert("bsearch".into(), rs_function_new(list_bsearch));
+
+    dict.lock()
+        .unwrap()
+        .insert("insert_sorted".into(), rs_function_new(list_insert_sorted));
+
+    dict.lock()
+        .unwrap()
+        .insert("sort".into(), rs_function_new(list_sort));
+
+    dict.lock()
+        .unwrap()
+        .insert("min".into(), rs_function_new(list_min));
+
+    dict.lock()
+        .unwrap()
+        .insert("max".into(), rs_function_new(list_max));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -215,46 +508,26 @@ pub static LIST_TYPE: Lazy<TypeRef> = Lazy::new(|| {
         tp_new: Some(list_tp_new),
         tp_init: Some(list_tp_init),
         tp_repr: Some(list_tp_repr),
+        sq_slice: Some(list_sq_slice),
         dict,
         ..Default::default()
     })
 });
 
-pub fn list_slice(
-    _callable: KyaObjectRef,
-    args: &mut Vec<KyaObjectRef>,
-    receiver: Option<KyaObjectRef>,
-) -> Result<KyaObjectRef, Error> {
-    let instance = parse_receiver(&receiver)?;
-    let start = parse_arg(&args, 0, 1)?;
-    let end = parse_arg(&args, 1, 2)?;
+pub fn list_sq_slice(obj: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::ListObject(list_object) = &*obj.lock().unwrap() {
+        let items = range
+            .indices(list_object.items.len())
+            .into_iter()
+            .map(|index| list_object.items[index].clone())
+            .collect();
 
-    if let KyaObject::ListObject(list_object) = &*instance.lock().unwrap() {
-        if let (KyaObject::NumberObject(start_num), KyaObject::NumberObject(end_num)) =
-            (&*start.lock().unwrap(), &*end.lock().unwrap())
-        {
-            let start_idx = start_num.value as usize;
-            let end_idx = end_num.value as usize;
-
-            if start_idx < list_object.items.len() && end_idx <= list_object.items.len() {
-                let slice_items = list_object.items[start_idx..end_idx].to_vec();
-                return Ok(list_new(slice_items));
-            } else {
-                return Err(Error::RuntimeError(format!(
-                    "Slice indices out of range: {} to {}",
-                    start_idx, end_idx
-                )));
-            }
-        } else {
-            return Err(Error::TypeError(
-                "Start and end must be numbers".to_string(),
-            ));
-        }
+        Ok(list_new(items))
     } else {
-        return Err(Error::RuntimeError(format!(
+        Err(Error::RuntimeError(format!(
             "The object '{}' is not a list",
-            instance.lock().unwrap().get_type()?.lock().unwrap().name
-        )));
+            obj.lock().unwrap().get_type()?.lock().unwrap().name
+        )))
     }
 }
 
@@ -320,4 +593,92 @@ mod tests {
             panic!("Expected a NumberObject");
         }
     }
+
+    #[test]
+    fn test_list_pop_shift_unshift() {
+        let list = list_new(vec![number_new(1.0), number_new(2.0), number_new(3.0)]);
+        let value = |item: KyaObjectRef| number_object_to_float(&item).unwrap();
+
+        let last = list_pop(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+        assert_eq!(value(last), 3.0);
+
+        list_unshift(list.clone(), &mut vec![number_new(0.0)], Some(list.clone())).unwrap();
+
+        let second = list_pop(
+            list.clone(),
+            &mut vec![number_new(-2.0)],
+            Some(list.clone()),
+        )
+        .unwrap();
+        assert_eq!(value(second), 1.0);
+
+        let first = list_shift(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+        assert_eq!(value(first), 0.0);
+
+        assert!(list_pop(list.clone(), &mut vec![number_new(5.0)], Some(list.clone())).is_err());
+
+        list_shift(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+        assert!(list_shift(list.clone(), &mut vec![], Some(list.clone())).is_err());
+    }
+
+    #[test]
+    fn test_list_bsearch_and_insert_sorted() {
+        let list = list_new(vec![]);
+        let value = |item: KyaObjectRef| number_object_to_float(&item).unwrap();
+
+        for number in [5.0, 1.0, 3.0, 3.0, 9.0] {
+            list_insert_sorted(
+                list.clone(),
+                &mut vec![number_new(number)],
+                Some(list.clone()),
+            )
+            .unwrap();
+        }
+
+        if let KyaObject::ListObject(list_object) = &*list.lock().unwrap() {
+            let values: Vec<f64> = list_object.items.iter().cloned().map(value).collect();
+            assert_eq!(values, vec![1.0, 3.0, 3.0, 5.0, 9.0]);
+        } else {
+            panic!("Expected a ListObject");
+        }
+
+        let found = list_bsearch(list.clone(), &mut vec![number_new(3.0)], Some(list.clone()));
+        assert_eq!(value(found.unwrap()), 1.0);
+
+        let missing = list_bsearch(list.clone(), &mut vec![number_new(4.0)], Some(list.clone()));
+        assert!(matches!(
+            &*missing.unwrap().lock().unwrap(),
+            KyaObject::NoneObject(_)
+        ));
+    }
+
+    #[test]
+    fn test_list_sort_min_max() {
+        let list = list_new(vec![
+            number_new(5.0),
+            number_new(1.0),
+            number_new(3.0),
+            number_new(9.0),
+            number_new(3.0),
+        ]);
+        let value = |item: KyaObjectRef| number_object_to_float(&item).unwrap();
+
+        let min = list_min(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+        assert_eq!(value(min), 1.0);
+
+        let max = list_max(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+        assert_eq!(value(max), 9.0);
+
+        list_sort(list.clone(), &mut vec![], Some(list.clone())).unwrap();
+
+        if let KyaObject::ListObject(list_object) = &*list.lock().unwrap() {
+            let values: Vec<f64> = list_object.items.iter().cloned().map(value).collect();
+            assert_eq!(values, vec![1.0, 3.0, 3.0, 5.0, 9.0]);
+        } else {
+            panic!("Expected a ListObject");
+        }
+
+        let empty = list_new(vec![]);
+        assert!(list_min(empty.clone(), &mut vec![], Some(empty.clone())).is_err());
+    }
 }
@@ -1,18 +1,23 @@
 pub mod base;
 pub mod bool_object;
+pub mod byte_view_object;
 pub mod bytes_object;
 pub mod class_object;
 pub mod code_object;
 pub mod exception_object;
+pub mod finalizers;
 pub mod function_object;
 pub mod hash_object;
+pub mod host_function_object;
 pub mod instance_object;
 pub mod list_object;
 pub mod method_object;
+pub mod module_object;
 pub mod modules;
 pub mod none_object;
 pub mod number_object;
 pub mod rs_function_object;
+pub mod singletons;
 pub mod string_object;
 pub mod url_object;
 pub mod utils;
@@ -0,0 +1,97 @@
+use crate::errors::Error;
+use crate::objects::base::{
+    DictRef, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
+};
+use crate::objects::string_object::string_new;
+use crate::symbol::Symbol;
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub struct ModuleObject {
+    pub ob_type: TypeRef,
+    pub name: String,
+    pub dict: DictRef,
+}
+
+impl KyaObjectTrait for ModuleObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+pub fn module_new(name: &str, dict: DictRef) -> KyaObjectRef {
+    KyaObject::from_module_object(ModuleObject {
+        ob_type: MODULE_TYPE.clone(),
+        name: name.to_string(),
+        dict,
+    })
+}
+
+pub fn module_from_functions(name: &str, functions: Vec<(&str, KyaObjectRef)>) -> KyaObjectRef {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    for (function_name, function) in functions {
+        dict.lock().unwrap().insert(function_name.into(), function);
+    }
+
+    module_new(name, dict)
+}
+
+pub fn module_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::ModuleObject(module) = &*callable.lock().unwrap() {
+        Ok(string_new(&format!("<module '{}'>", module.name)))
+    } else {
+        Err(Error::RuntimeError("Expected a module object".to_string()))
+    }
+}
+
+pub fn module_tp_get_attr(obj: KyaObjectRef, attr_name: Symbol) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::ModuleObject(module) = &*obj.lock().unwrap() {
+        module
+            .dict
+            .lock()
+            .unwrap()
+            .get(&attr_name)
+            .cloned()
+            .ok_or_else(|| {
+                Error::RuntimeError(format!(
+                    "module '{}' has no attribute '{}'",
+                    module.name, attr_name
+                ))
+            })
+    } else {
+        Err(Error::RuntimeError("Expected a module object".to_string()))
+    }
+}
+
+pub fn module_tp_set_attr(
+    obj: KyaObjectRef,
+    attr_name: Symbol,
+    value: KyaObjectRef,
+) -> Result<(), Error> {
+    if let KyaObject::ModuleObject(module) = &*obj.lock().unwrap() {
+        module.dict.lock().unwrap().insert(attr_name, value);
+
+        Ok(())
+    } else {
+        Err(Error::RuntimeError("Expected a module object".to_string()))
+    }
+}
+
+pub static MODULE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "Module".to_string(),
+        tp_repr: Some(module_tp_repr),
+        tp_get_attr: Some(module_tp_get_attr),
+        tp_get_method: None,
+        tp_set_attr: Some(module_tp_set_attr),
+        ..Default::default()
+    })
+});
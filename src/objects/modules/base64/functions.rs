@@ -0,0 +1,133 @@
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encode(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+fn decode_char(c: u8, position: usize) -> Result<u32, Error> {
+    ALPHABET
+        .iter()
+        .position(|&a| a == c)
+        .map(|value| value as u32)
+        .ok_or_else(|| {
+            Error::ValueError(format!(
+                "Invalid base64 character '{}' at position {}",
+                c as char, position
+            ))
+        })
+}
+
+pub fn decode(input: &str) -> Result<Vec<u8>, Error> {
+    let input = input.as_bytes();
+
+    if !input.len().is_multiple_of(4) {
+        return Err(Error::ValueError(
+            "Invalid base64 input: length must be a multiple of 4".to_string(),
+        ));
+    }
+
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+
+    for (index, chunk) in input.chunks(4).enumerate() {
+        let is_last = (index + 1) * 4 == input.len();
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(Error::ValueError(
+                "Invalid base64 input: misplaced padding".to_string(),
+            ));
+        }
+
+        let mut n = 0;
+
+        for (i, &c) in chunk[..4 - padding].iter().enumerate() {
+            n |= decode_char(c, index * 4 + i)? << (18 - 6 * i);
+        }
+
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+
+        output.extend_from_slice(&bytes[..3 - padding]);
+    }
+
+    Ok(output)
+}
+
+pub fn base64_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "base64",
+        vec![
+            (
+                "encode",
+                host_function_with_doc(
+                    "encode",
+                    &[ArgType::Bytes],
+                    ArgType::String,
+                    "encode(bytes): returns the standard base64 encoding of bytes as a String.",
+                    |args| Ok(HostValue::String(encode(args[0].as_bytes()?))),
+                ),
+            ),
+            (
+                "decode",
+                host_function_with_doc(
+                    "decode",
+                    &[ArgType::String],
+                    ArgType::Bytes,
+                    "decode(string): decodes a padded base64 String back into Bytes.",
+                    |args| Ok(HostValue::Bytes(decode(args[0].as_str()?)?)),
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        let cases: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (&[0, 255, 128, 1], "AP+AAQ=="),
+        ];
+
+        for (raw, encoded) in cases {
+            assert_eq!(encode(raw), encoded);
+            assert_eq!(decode(encoded).unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn test_base64_decode_errors() {
+        for input in ["Zg=", "Zg=a", "Z===", "Zg==Zg==", "Zm9*"] {
+            assert!(decode(input).is_err(), "expected error for {:?}", input);
+        }
+    }
+}
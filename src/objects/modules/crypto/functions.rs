@@ -0,0 +1,185 @@
+use hmac::{Hmac, KeyInit, Mac};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::errors::Error;
+use crate::objects::base::{KyaObject, KyaObjectRef};
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+
+fn data_bytes(function: &str, value: &HostValue) -> Result<Vec<u8>, Error> {
+    if let HostValue::Object(obj) = value {
+        match &*obj.lock().unwrap() {
+            KyaObject::StringObject(string_object) => {
+                return Ok(string_object.value.as_bytes().to_vec());
+            }
+            KyaObject::BytesObject(bytes_object) => return Ok(bytes_object.value.clone()),
+            KyaObject::ByteViewObject(view) => return Ok(view.as_slice().to_vec()),
+            _ => {}
+        }
+    }
+
+    Err(Error::TypeError(format!(
+        "{}() expects Bytes or String data",
+        function
+    )))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn digest(algorithm: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+    match algorithm {
+        "md5" => Ok(Md5::digest(data).to_vec()),
+        "sha1" => Ok(Sha1::digest(data).to_vec()),
+        "sha256" => Ok(Sha256::digest(data).to_vec()),
+        _ => Err(Error::ValueError(format!(
+            "Unsupported hash algorithm '{}'",
+            algorithm
+        ))),
+    }
+}
+
+fn mac_bytes<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, so this never fails.
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts any key length");
+
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub fn hmac(algorithm: &str, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    match algorithm {
+        "md5" => Ok(mac_bytes::<Hmac<Md5>>(key, data)),
+        "sha1" => Ok(mac_bytes::<Hmac<Sha1>>(key, data)),
+        "sha256" => Ok(mac_bytes::<Hmac<Sha256>>(key, data)),
+        _ => Err(Error::ValueError(format!(
+            "Unsupported hash algorithm '{}'",
+            algorithm
+        ))),
+    }
+}
+
+fn hex_digest_function(algorithm: &'static str, doc: &'static str) -> (&'static str, KyaObjectRef) {
+    (
+        algorithm,
+        host_function_with_doc(
+            algorithm,
+            &[ArgType::Any],
+            ArgType::String,
+            doc,
+            move |args| {
+                let data = data_bytes(algorithm, &args[0])?;
+
+                Ok(HostValue::String(to_hex(&digest(algorithm, &data)?)))
+            },
+        ),
+    )
+}
+
+pub fn crypto_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "crypto",
+        vec![
+            hex_digest_function(
+                "md5",
+                "md5(data): returns the MD5 digest of Bytes or String data as a hex String.",
+            ),
+            hex_digest_function(
+                "sha1",
+                "sha1(data): returns the SHA-1 digest of Bytes or String data as a hex String.",
+            ),
+            hex_digest_function(
+                "sha256",
+                "sha256(data): returns the SHA-256 digest of Bytes or String data as a hex String.",
+            ),
+            (
+                "digest",
+                host_function_with_doc(
+                    "digest",
+                    &[ArgType::String, ArgType::Any],
+                    ArgType::Bytes,
+                    "digest(algorithm, data): returns the raw digest as Bytes. algorithm is one of \"md5\", \"sha1\" or \"sha256\".",
+                    |args| {
+                        let data = data_bytes("digest", &args[1])?;
+
+                        Ok(HostValue::Bytes(digest(args[0].as_str()?, &data)?))
+                    },
+                ),
+            ),
+            (
+                "hmac",
+                host_function_with_doc(
+                    "hmac",
+                    &[ArgType::String, ArgType::Any, ArgType::Any],
+                    ArgType::String,
+                    "hmac(algorithm, key, data): returns the HMAC of data under key as a hex String.",
+                    |args| {
+                        let key = data_bytes("hmac", &args[1])?;
+                        let data = data_bytes("hmac", &args[2])?;
+
+                        Ok(HostValue::String(to_hex(&hmac(
+                            args[0].as_str()?,
+                            &key,
+                            &data,
+                        )?)))
+                    },
+                ),
+            ),
+            (
+                "hmac_digest",
+                host_function_with_doc(
+                    "hmac_digest",
+                    &[ArgType::String, ArgType::Any, ArgType::Any],
+                    ArgType::Bytes,
+                    "hmac_digest(algorithm, key, data): returns the raw HMAC of data under key as Bytes.",
+                    |args| {
+                        let key = data_bytes("hmac_digest", &args[1])?;
+                        let data = data_bytes("hmac_digest", &args[2])?;
+
+                        Ok(HostValue::Bytes(hmac(args[0].as_str()?, &key, &data)?))
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(
+            to_hex(&digest("md5", b"abc").unwrap()),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            to_hex(&digest("sha1", b"abc").unwrap()),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            to_hex(&digest("sha256", b"abc").unwrap()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(digest("sha512", b"abc").is_err());
+    }
+
+    #[test]
+    fn test_hmac() {
+        assert_eq!(
+            to_hex(
+                &hmac(
+                    "sha256",
+                    b"key",
+                    b"The quick brown fox jumps over the lazy dog"
+                )
+                .unwrap()
+            ),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
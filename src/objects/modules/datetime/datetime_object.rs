@@ -0,0 +1,285 @@
+use crate::bytecode::ComparisonOperator;
+use crate::errors::Error;
+use crate::internal::datetime::DateTime;
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{
+    bool_to_bool_object, number_object_to_float, parse_arg, parse_receiver, string_object_to_string,
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub struct DateTimeObject {
+    pub ob_type: TypeRef,
+    pub value: DateTime,
+}
+
+impl KyaObjectTrait for DateTimeObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+pub fn datetime_new(value: DateTime) -> KyaObjectRef {
+    KyaObject::from_datetime_object(DateTimeObject {
+        ob_type: DATETIME_TYPE.clone(),
+        value,
+    })
+}
+
+fn datetime_value(obj: &KyaObjectRef) -> Option<DateTime> {
+    match &*obj.lock().unwrap() {
+        KyaObject::DateTimeObject(datetime_object) => Some(datetime_object.value),
+        _ => None,
+    }
+}
+
+fn with_datetime<T>(
+    receiver: &Option<KyaObjectRef>,
+    f: impl FnOnce(&DateTime) -> T,
+) -> Result<T, Error> {
+    let instance = parse_receiver(receiver)?;
+
+    datetime_value(&instance)
+        .map(|value| f(&value))
+        .ok_or_else(|| Error::TypeError("Expected a DateTime object".to_string()))
+}
+
+fn component_arg(args: &[KyaObjectRef], index: usize, name: &str) -> Result<u32, Error> {
+    let Some(arg) = args.get(index) else {
+        return Ok(0);
+    };
+
+    let value = number_object_to_float(arg)
+        .map_err(|_| Error::TypeError(format!("The '{}' argument must be a number", name)))?;
+
+    if value.fract() != 0.0 || value < 0.0 {
+        return Err(Error::ValueError(format!("Invalid {} {}", name, value)));
+    }
+
+    Ok(value as u32)
+}
+
+pub fn datetime_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.len() < 3 || args.len() > 6 {
+        return Err(Error::TypeError(format!(
+            "DateTime() takes 3 to 6 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let year = number_object_to_float(&args[0])
+        .map_err(|_| Error::TypeError("The 'year' argument must be a number".to_string()))?;
+
+    let value = DateTime::new(
+        year as i64,
+        component_arg(args, 1, "month")?,
+        component_arg(args, 2, "day")?,
+        component_arg(args, 3, "hour")?,
+        component_arg(args, 4, "minute")?,
+        component_arg(args, 5, "second")?,
+    )
+    .map_err(Error::ValueError)?;
+
+    Ok(KyaObject::from_datetime_object(DateTimeObject {
+        ob_type,
+        value,
+    }))
+}
+
+pub fn datetime_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn datetime_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let value = datetime_value(&callable)
+        .ok_or_else(|| Error::TypeError("Expected a DateTime object".to_string()))?;
+    let text = value
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .map_err(Error::RuntimeError)?;
+
+    Ok(string_new(&format!("<DateTime {}>", text)))
+}
+
+pub fn datetime_tp_compare(
+    obj1: KyaObjectRef,
+    obj2: KyaObjectRef,
+    operator: ComparisonOperator,
+) -> Result<KyaObjectRef, Error> {
+    let a = datetime_value(&obj1).map(|value| value.timestamp());
+    let b = datetime_value(&obj2).map(|value| value.timestamp());
+
+    let (a, b) = match (a, b, operator) {
+        (Some(a), Some(b), _) => (a, b),
+        (_, _, ComparisonOperator::Equal) => return Ok(bool_to_bool_object(false)),
+        (_, _, ComparisonOperator::Neq) => return Ok(bool_to_bool_object(true)),
+        _ => {
+            return Err(Error::TypeError(
+                "DateTime can only be ordered against another DateTime".to_string(),
+            ))
+        }
+    };
+
+    Ok(bool_to_bool_object(match operator {
+        ComparisonOperator::Equal => a == b,
+        ComparisonOperator::Neq => a != b,
+        ComparisonOperator::Gt => a > b,
+        ComparisonOperator::Lt => a < b,
+        ComparisonOperator::Gte => a >= b,
+        ComparisonOperator::Lte => a <= b,
+    }))
+}
+
+pub fn datetime_tp_hash(obj: KyaObjectRef) -> Result<usize, Error> {
+    let value = datetime_value(&obj)
+        .ok_or_else(|| Error::TypeError("Expected a DateTime object".to_string()))?;
+
+    Ok(value.timestamp().to_bits() as usize)
+}
+
+pub fn datetime_year(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.year as f64))
+}
+
+pub fn datetime_month(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.month as f64))
+}
+
+pub fn datetime_day(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.day as f64))
+}
+
+pub fn datetime_hour(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.hour as f64))
+}
+
+pub fn datetime_minute(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.minute as f64))
+}
+
+pub fn datetime_second(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.second as f64))
+}
+
+pub fn datetime_weekday(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.weekday() as f64))
+}
+
+pub fn datetime_timestamp(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_datetime(&receiver, |value| number_new(value.timestamp()))
+}
+
+pub fn datetime_format(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let arg = parse_arg(args, 0, 1)?;
+    let format = string_object_to_string(&arg)
+        .map_err(|_| Error::TypeError("DateTime.format() expects a string".to_string()))?;
+
+    let text =
+        with_datetime(&receiver, |value| value.format(&format))?.map_err(Error::ValueError)?;
+
+    Ok(string_new(&text))
+}
+
+pub static DATETIME_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("year".into(), rs_function_new(datetime_year));
+
+    dict.lock()
+        .unwrap()
+        .insert("month".into(), rs_function_new(datetime_month));
+
+    dict.lock()
+        .unwrap()
+        .insert("day".into(), rs_function_new(datetime_day));
+
+    dict.lock()
+        .unwrap()
+        .insert("hour".into(), rs_function_new(datetime_hour));
+
+    dict.lock()
+        .unwrap()
+        .insert("minute".into(), rs_function_new(datetime_minute));
+
+    dict.lock()
+        .unwrap()
+        .insert("second".into(), rs_function_new(datetime_second));
+
+    dict.lock()
+        .unwrap()
+        .insert("weekday".into(), rs_function_new(datetime_weekday));
+
+    dict.lock()
+        .unwrap()
+        .insert("timestamp".into(), rs_function_new(datetime_timestamp));
+
+    dict.lock()
+        .unwrap()
+        .insert("format".into(), rs_function_new(datetime_format));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "datetime.DateTime".to_string(),
+        tp_new: Some(datetime_tp_new),
+        tp_init: Some(datetime_tp_init),
+        tp_repr: Some(datetime_tp_repr),
+        tp_compare: Some(datetime_tp_compare),
+        tp_hash: Some(datetime_tp_hash),
+        dict,
+        ..Default::default()
+    })
+});
@@ -0,0 +1,60 @@
+use crate::errors::Error;
+use crate::internal::datetime::DateTime;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::class_object::class_new;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::datetime::datetime_object::{datetime_new, DATETIME_TYPE};
+use crate::objects::modules::time::functions::now_seconds;
+
+pub fn datetime_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "datetime",
+        vec![
+            (
+                "now",
+                host_function_with_doc(
+                    "now",
+                    &[],
+                    ArgType::Any,
+                    "now(): returns the current UTC time as a DateTime.",
+                    |_| {
+                        Ok(HostValue::Object(datetime_new(DateTime::from_timestamp(
+                            now_seconds()?,
+                        ))))
+                    },
+                ),
+            ),
+            (
+                "from_timestamp",
+                host_function_with_doc(
+                    "from_timestamp",
+                    &[ArgType::Number],
+                    ArgType::Any,
+                    "from_timestamp(seconds): returns the UTC DateTime for seconds since the Unix epoch.",
+                    |args| {
+                        Ok(HostValue::Object(datetime_new(DateTime::from_timestamp(
+                            args[0].as_number()?,
+                        ))))
+                    },
+                ),
+            ),
+            (
+                "parse",
+                host_function_with_doc(
+                    "parse",
+                    &[ArgType::String, ArgType::String],
+                    ArgType::Any,
+                    "parse(text, format): parses text with strftime-style directives into a DateTime.",
+                    |args| {
+                        let value = DateTime::parse(args[0].as_str()?, args[1].as_str()?)
+                            .map_err(Error::ValueError)?;
+
+                        Ok(HostValue::Object(datetime_new(value)))
+                    },
+                ),
+            ),
+            ("DateTime", class_new(DATETIME_TYPE.clone())),
+        ],
+    )
+}
@@ -0,0 +1,2 @@
+pub mod datetime_object;
+pub mod functions;
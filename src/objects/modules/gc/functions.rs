@@ -0,0 +1,71 @@
+use crate::errors::Error;
+use crate::gc;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::singletons::kya_none;
+use crate::objects::utils::parse_arg;
+
+pub fn kya_gc_collect(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    Ok(number_new(gc::collect() as f64))
+}
+
+pub fn kya_gc_enable(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    gc::set_enabled(true);
+
+    Ok(kya_none())
+}
+
+pub fn kya_gc_disable(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    gc::set_enabled(false);
+
+    Ok(kya_none())
+}
+
+pub fn gc_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "gc",
+        vec![
+            (
+                "collect",
+                rs_function_with_doc(
+                    kya_gc_collect,
+                    "collect(): frees the objects only reachable from each other and returns how many there were.",
+                ),
+            ),
+            (
+                "enable",
+                rs_function_with_doc(
+                    kya_gc_enable,
+                    "enable(): turns automatic collection back on.",
+                ),
+            ),
+            (
+                "disable",
+                rs_function_with_doc(
+                    kya_gc_disable,
+                    "disable(): stops automatic collection; collect() still works.",
+                ),
+            ),
+        ],
+    )
+}
@@ -0,0 +1,117 @@
+use crate::capabilities::{self, Capability};
+use crate::errors::Error;
+use crate::internal::http::{read_request, HttpRequest, HttpResponse};
+use crate::internal::socket::{create_socket, Address};
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{kya_call, KyaObject, KyaObjectRef};
+use crate::objects::class_object::class_new;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::http::request_object::{request_new, REQUEST_TYPE};
+use crate::objects::modules::http::response_object::{body_to_bytes, RESPONSE_TYPE};
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::utils::{number_object_to_float, parse_arg, string_object_to_string};
+
+fn response_from_object(obj: &KyaObjectRef) -> Result<HttpResponse, Error> {
+    let content_type = match &*obj.lock().unwrap() {
+        KyaObject::ResponseObject(response_object) => {
+            return Ok(response_object.response.clone());
+        }
+        KyaObject::StringObject(_) => "text/plain; charset=UTF-8",
+        KyaObject::BytesObject(_) | KyaObject::ByteViewObject(_) => "application/octet-stream",
+        _ => {
+            return Err(Error::TypeError(
+                "HTTP handlers must return a Response, String or Bytes".to_string(),
+            ));
+        }
+    };
+
+    let mut response = HttpResponse::new(200, body_to_bytes(obj)?);
+    response.set_header("Content-Type", content_type);
+
+    Ok(response)
+}
+
+fn dispatch(handler: &KyaObjectRef, request: HttpRequest) -> HttpResponse {
+    let result = kya_call(handler.clone(), &mut vec![request_new(request)], None)
+        .and_then(|obj| response_from_object(&obj));
+
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+
+        HttpResponse::new(500, b"Internal Server Error".to_vec())
+    })
+}
+
+pub fn kya_http_serve(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let host = string_object_to_string(&parse_arg(args, 0, 3)?)
+        .map_err(|_| Error::TypeError("The 'host' argument must be a string".to_string()))?;
+    let port = number_object_to_float(&parse_arg(args, 1, 3)?)
+        .map_err(|_| Error::TypeError("The 'port' argument must be a number".to_string()))?;
+    let handler = parse_arg(args, 2, 3)?;
+
+    capabilities::require(Capability::Network, "serve()")?;
+
+    let mut socket = create_socket();
+
+    let address = Address::Inet(host.clone(), port as u16);
+
+    socket.bind(&address).map_err(|e| {
+        Error::RuntimeError(format!(
+            "Failed to bind socket to {}:{}. Error: {}",
+            host, port, e
+        ))
+    })?;
+
+    loop {
+        kya_release_lock();
+
+        let mut connection = match socket.accept() {
+            Ok(connection) => connection,
+            Err(e) => {
+                kya_acquire_lock();
+
+                return Err(Error::RuntimeError(format!(
+                    "Failed to accept connection: {}",
+                    e
+                )));
+            }
+        };
+
+        let request = read_request(&mut connection);
+
+        kya_acquire_lock();
+
+        let response = match request {
+            Ok(request) => dispatch(&handler, request),
+            Err(e) => HttpResponse::new(400, e.to_string().into_bytes()),
+        };
+
+        kya_release_lock();
+
+        let _ = connection.send(response.to_bytes());
+        let _ = connection.close();
+
+        kya_acquire_lock();
+    }
+}
+
+pub fn http_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "http",
+        vec![
+            (
+                "serve",
+                rs_function_with_doc(
+                    kya_http_serve,
+                    "serve(host, port, handler): accepts connections forever, calling handler(request) for each one. The handler returns a Response, or a String/Bytes body sent with status 200.",
+                ),
+            ),
+            ("Request", class_new(REQUEST_TYPE.clone())),
+            ("Response", class_new(RESPONSE_TYPE.clone())),
+        ],
+    )
+}
@@ -0,0 +1,3 @@
+pub mod functions;
+pub mod request_object;
+pub mod response_object;
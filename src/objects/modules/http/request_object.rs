@@ -0,0 +1,170 @@
+use crate::errors::Error;
+use crate::internal::http::HttpRequest;
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::bytes_object::bytes_new;
+use crate::objects::hash_object::hash_from_pairs;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{parse_arg, parse_receiver, string_object_to_string};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub struct RequestObject {
+    pub ob_type: TypeRef,
+    pub request: HttpRequest,
+}
+
+impl KyaObjectTrait for RequestObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+pub fn request_new(request: HttpRequest) -> KyaObjectRef {
+    KyaObject::from_request_object(RequestObject {
+        ob_type: REQUEST_TYPE.clone(),
+        request,
+    })
+}
+
+fn with_request<T>(
+    receiver: &Option<KyaObjectRef>,
+    f: impl FnOnce(&HttpRequest) -> T,
+) -> Result<T, Error> {
+    let instance = parse_receiver(receiver)?;
+
+    if let KyaObject::RequestObject(obj) = &*instance.lock().unwrap() {
+        Ok(f(&obj.request))
+    } else {
+        Err(Error::TypeError("Expected a Request object".to_string()))
+    }
+}
+
+pub fn request_tp_new(
+    _ob_type: TypeRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Err(Error::TypeError(
+        "Request object cannot be instantiated directly".to_string(),
+    ))
+}
+
+pub fn request_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::RequestObject(obj) = &*callable.lock().unwrap() {
+        Ok(string_new(&format!(
+            "<Request {} {}>",
+            obj.request.method, obj.request.path
+        )))
+    } else {
+        Err(Error::TypeError("Expected a Request object".to_string()))
+    }
+}
+
+pub fn request_method(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_request(&receiver, |request| string_new(&request.method))
+}
+
+pub fn request_path(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_request(&receiver, |request| string_new(&request.path))
+}
+
+pub fn request_version(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_request(&receiver, |request| string_new(&request.version))
+}
+
+pub fn request_headers(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let headers = with_request(&receiver, |request| request.headers.clone())?;
+
+    hash_from_pairs(
+        headers
+            .iter()
+            .map(|(name, value)| (string_new(&name.to_lowercase()), string_new(value)))
+            .collect(),
+    )
+}
+
+pub fn request_header(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let arg = parse_arg(args, 0, 1)?;
+    let name = string_object_to_string(&arg)
+        .map_err(|_| Error::TypeError("Request.header() expects a string".to_string()))?;
+
+    let value = with_request(&receiver, |request| {
+        request.header(&name).map(|value| value.to_string())
+    })?;
+
+    Ok(value
+        .map(|value| string_new(&value))
+        .unwrap_or_else(kya_none))
+}
+
+pub fn request_body(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_request(&receiver, |request| bytes_new(request.body.clone()))
+}
+
+pub static REQUEST_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("method".into(), rs_function_new(request_method));
+
+    dict.lock()
+        .unwrap()
+        .insert("path".into(), rs_function_new(request_path));
+
+    dict.lock()
+        .unwrap()
+        .insert("version".into(), rs_function_new(request_version));
+
+    dict.lock()
+        .unwrap()
+        .insert("headers".into(), rs_function_new(request_headers));
+
+    dict.lock()
+        .unwrap()
+        .insert("header".into(), rs_function_new(request_header));
+
+    dict.lock()
+        .unwrap()
+        .insert("body".into(), rs_function_new(request_body));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "http.Request".to_string(),
+        tp_new: Some(request_tp_new),
+        tp_repr: Some(request_tp_repr),
+        dict,
+        ..Default::default()
+    })
+});
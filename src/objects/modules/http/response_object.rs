@@ -0,0 +1,229 @@
+use crate::errors::Error;
+use crate::internal::http::{status_reason, HttpResponse};
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::bytes_object::bytes_new;
+use crate::objects::hash_object::{hash_from_pairs, hash_pairs};
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{
+    number_object_to_float, parse_arg, parse_receiver, string_object_to_string,
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub struct ResponseObject {
+    pub ob_type: TypeRef,
+    pub response: HttpResponse,
+}
+
+impl KyaObjectTrait for ResponseObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+pub fn response_new(response: HttpResponse) -> KyaObjectRef {
+    KyaObject::from_response_object(ResponseObject {
+        ob_type: RESPONSE_TYPE.clone(),
+        response,
+    })
+}
+
+pub fn body_to_bytes(body: &KyaObjectRef) -> Result<Vec<u8>, Error> {
+    match &*body.lock().unwrap() {
+        KyaObject::StringObject(obj) => Ok(obj.value.as_bytes().to_vec()),
+        KyaObject::BytesObject(obj) => Ok(obj.value.clone()),
+        KyaObject::ByteViewObject(view) => Ok(view.as_slice().to_vec()),
+        KyaObject::NoneObject(_) => Ok(Vec::new()),
+        _ => Err(Error::TypeError(
+            "Response body must be a String or Bytes".to_string(),
+        )),
+    }
+}
+
+fn header_to_string(value: &KyaObjectRef) -> Result<String, Error> {
+    string_object_to_string(value)
+        .map_err(|_| Error::TypeError("Header names and values must be strings".to_string()))
+}
+
+fn validate_header(name: &str, value: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ':') {
+        return Err(Error::ValueError(format!("Invalid header name '{}'", name)));
+    }
+
+    if value.contains(['\r', '\n']) {
+        return Err(Error::ValueError(
+            "Header values cannot contain line breaks".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn response_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.is_empty() || args.len() > 3 {
+        return Err(Error::TypeError(format!(
+            "Response() takes 1 to 3 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let status = number_object_to_float(&args[0])
+        .map_err(|_| Error::TypeError("The 'status' argument must be a number".to_string()))?;
+
+    if status.fract() != 0.0 || !(100.0..=999.0).contains(&status) {
+        return Err(Error::ValueError(format!("Invalid status code {}", status)));
+    }
+
+    let body = match args.get(1) {
+        Some(body) => body_to_bytes(body)?,
+        None => Vec::new(),
+    };
+
+    let mut response = HttpResponse::new(status as u16, body);
+
+    if let Some(headers) = args.get(2) {
+        for (name, value) in hash_pairs(headers)? {
+            let name = header_to_string(&name)?;
+            let value = header_to_string(&value)?;
+
+            validate_header(&name, &value)?;
+            response.set_header(&name, &value);
+        }
+    }
+
+    Ok(KyaObject::from_response_object(ResponseObject {
+        ob_type,
+        response,
+    }))
+}
+
+pub fn response_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn response_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::ResponseObject(obj) = &*callable.lock().unwrap() {
+        Ok(string_new(&format!(
+            "<Response {} {}>",
+            obj.response.status,
+            status_reason(obj.response.status)
+        )))
+    } else {
+        Err(Error::TypeError("Expected a Response object".to_string()))
+    }
+}
+
+pub fn response_status(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::ResponseObject(obj) = &*instance.lock().unwrap() {
+        Ok(number_new(obj.response.status as f64))
+    } else {
+        Err(Error::TypeError("Expected a Response object".to_string()))
+    }
+}
+
+pub fn response_body(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::ResponseObject(obj) = &*instance.lock().unwrap() {
+        Ok(bytes_new(obj.response.body.clone()))
+    } else {
+        Err(Error::TypeError("Expected a Response object".to_string()))
+    }
+}
+
+pub fn response_headers(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    let headers = if let KyaObject::ResponseObject(obj) = &*instance.lock().unwrap() {
+        obj.response.headers.clone()
+    } else {
+        return Err(Error::TypeError("Expected a Response object".to_string()));
+    };
+
+    hash_from_pairs(
+        headers
+            .iter()
+            .map(|(name, value)| (string_new(name), string_new(value)))
+            .collect(),
+    )
+}
+
+pub fn response_set_header(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let name = header_to_string(&parse_arg(args, 0, 2)?)?;
+    let value = header_to_string(&parse_arg(args, 1, 2)?)?;
+
+    validate_header(&name, &value)?;
+
+    if let KyaObject::ResponseObject(ref mut obj) = *instance.lock().unwrap() {
+        obj.response.set_header(&name, &value);
+
+        Ok(kya_none())
+    } else {
+        Err(Error::TypeError("Expected a Response object".to_string()))
+    }
+}
+
+pub static RESPONSE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("status".into(), rs_function_new(response_status));
+
+    dict.lock()
+        .unwrap()
+        .insert("body".into(), rs_function_new(response_body));
+
+    dict.lock()
+        .unwrap()
+        .insert("headers".into(), rs_function_new(response_headers));
+
+    dict.lock()
+        .unwrap()
+        .insert("set_header".into(), rs_function_new(response_set_header));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "http.Response".to_string(),
+        tp_new: Some(response_tp_new),
+        tp_init: Some(response_tp_init),
+        tp_repr: Some(response_tp_repr),
+        dict,
+        ..Default::default()
+    })
+});
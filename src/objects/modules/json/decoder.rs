@@ -0,0 +1,280 @@
+use crate::errors::Error;
+use crate::internal::number::parse_number;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::hash_object::hash_from_pairs;
+use crate::objects::list_object::list_new;
+use crate::objects::number_object::number_new;
+use crate::objects::singletons::{kya_false, kya_none, kya_true};
+use crate::objects::string_object::string_new;
+
+const MAX_DEPTH: usize = 512;
+
+pub struct Decoder<'a> {
+    input: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Decoder {
+            input: input.as_bytes(),
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn decode(&mut self) -> Result<KyaObjectRef, Error> {
+        let value = self.parse_value()?;
+
+        self.skip_whitespace();
+
+        if self.position < self.input.len() {
+            return Err(self.error("Unexpected trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::ValueError(format!(
+            "Invalid JSON at position {}: {}",
+            self.position, message
+        ))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == b' ' || c == b'\t' || c == b'\n' || c == b'\r' {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), Error> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected as char)))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<KyaObjectRef, Error> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => Ok(string_new(&self.parse_string()?)),
+            Some(b't') => self.parse_keyword("true", kya_true()),
+            Some(b'f') => self.parse_keyword("false", kya_false()),
+            Some(b'n') => self.parse_keyword("null", kya_none()),
+            Some(c) if c == b'-' || c.is_ascii_digit() => self.parse_number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: KyaObjectRef) -> Result<KyaObjectRef, Error> {
+        if self.input[self.position..].starts_with(keyword.as_bytes()) {
+            self.position += keyword.len();
+            Ok(value)
+        } else {
+            Err(self.error("Unexpected character"))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("Maximum nesting depth exceeded"));
+        }
+
+        Ok(())
+    }
+
+    fn parse_object(&mut self) -> Result<KyaObjectRef, Error> {
+        self.enter()?;
+        self.expect(b'{')?;
+
+        let mut pairs = Vec::new();
+
+        self.skip_whitespace();
+
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+
+                if self.peek() != Some(b'"') {
+                    return Err(self.error("Expected a string key"));
+                }
+
+                let key = self.parse_string()?;
+
+                self.skip_whitespace();
+                self.expect(b':')?;
+
+                let value = self.parse_value()?;
+
+                pairs.push((string_new(&key), value));
+
+                self.skip_whitespace();
+
+                match self.peek() {
+                    Some(b',') => self.position += 1,
+                    Some(b'}') => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.error("Expected ',' or '}'")),
+                }
+            }
+        }
+
+        self.depth -= 1;
+
+        hash_from_pairs(pairs)
+    }
+
+    fn parse_array(&mut self) -> Result<KyaObjectRef, Error> {
+        self.enter()?;
+        self.expect(b'[')?;
+
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+
+        if self.peek() == Some(b']') {
+            self.position += 1;
+        } else {
+            loop {
+                items.push(self.parse_value()?);
+
+                self.skip_whitespace();
+
+                match self.peek() {
+                    Some(b',') => self.position += 1,
+                    Some(b']') => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.error("Expected ',' or ']'")),
+                }
+            }
+        }
+
+        self.depth -= 1;
+
+        Ok(list_new(items))
+    }
+
+    fn parse_number(&mut self) -> Result<KyaObjectRef, Error> {
+        let start = self.position;
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E') {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.input[start..self.position])
+            .map_err(|_| self.error("Invalid number"))?;
+
+        parse_number(text)
+            .map(number_new)
+            .map_err(|message| self.error(&message))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+
+        let value =
+            u32::from_str_radix(digits, 16).map_err(|_| self.error("Invalid unicode escape"))?;
+
+        self.position += 4;
+
+        Ok(value)
+    }
+
+    fn parse_string(&mut self) -> Result<String, Error> {
+        self.expect(b'"')?;
+
+        let mut bytes = Vec::new();
+
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error("Unterminated string"))?;
+
+            self.position += 1;
+
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("Unterminated string"))?;
+
+                    self.position += 1;
+
+                    let decoded = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.parse_hex4()?;
+
+                            if (0xD800..0xDC00).contains(&code) {
+                                if !self.input[self.position..].starts_with(b"\\u") {
+                                    return Err(self.error("Unpaired surrogate"));
+                                }
+
+                                self.position += 2;
+
+                                let low = self.parse_hex4()?;
+
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("Invalid surrogate pair"));
+                                }
+
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+
+                            char::from_u32(code)
+                                .ok_or_else(|| self.error("Invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    };
+
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(decoded.encode_utf8(&mut buffer).as_bytes());
+                }
+                c if c < 0x20 => return Err(self.error("Control character in string")),
+                c => bytes.push(c),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.error("Invalid UTF-8 in string"))
+    }
+}
@@ -0,0 +1,115 @@
+use crate::errors::Error;
+use crate::internal::number::format_number;
+use crate::objects::base::{KyaObject, KyaObjectRef};
+use crate::objects::hash_object::hash_pairs;
+
+const MAX_DEPTH: usize = 512;
+
+pub fn encode(obj: &KyaObjectRef) -> Result<String, Error> {
+    let mut output = String::new();
+
+    encode_value(obj, &mut output, 0)?;
+
+    Ok(output)
+}
+
+fn encode_value(obj: &KyaObjectRef, output: &mut String, depth: usize) -> Result<(), Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::ValueError(
+            "Maximum nesting depth exceeded while encoding JSON".to_string(),
+        ));
+    }
+
+    let items = match &*obj.lock().unwrap() {
+        KyaObject::NoneObject(_) => {
+            output.push_str("null");
+            return Ok(());
+        }
+        KyaObject::BoolObject(bool_object) => {
+            output.push_str(if bool_object.value { "true" } else { "false" });
+            return Ok(());
+        }
+        KyaObject::NumberObject(number_object) => {
+            if !number_object.value.is_finite() {
+                return Err(Error::ValueError(format!(
+                    "Cannot encode {} as JSON",
+                    number_object.value
+                )));
+            }
+
+            output.push_str(&format_number(number_object.value));
+            return Ok(());
+        }
+        KyaObject::StringObject(string_object) => {
+            encode_string(&string_object.value, output);
+            return Ok(());
+        }
+        KyaObject::ListObject(list_object) => Some(list_object.items.clone()),
+        KyaObject::HashObject(_) => None,
+        other => {
+            return Err(Error::TypeError(format!(
+                "Object of type '{}' is not JSON serializable",
+                other.get_type()?.lock().unwrap().name
+            )));
+        }
+    };
+
+    if let Some(items) = items {
+        output.push('[');
+
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+
+            encode_value(item, output, depth + 1)?;
+        }
+
+        output.push(']');
+
+        return Ok(());
+    }
+
+    output.push('{');
+
+    for (i, (key, value)) in hash_pairs(obj)?.iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+
+        match &*key.lock().unwrap() {
+            KyaObject::StringObject(string_object) => encode_string(&string_object.value, output),
+            _ => {
+                return Err(Error::TypeError(
+                    "JSON object keys must be strings".to_string(),
+                ));
+            }
+        }
+
+        output.push(':');
+
+        encode_value(value, output, depth + 1)?;
+    }
+
+    output.push('}');
+
+    Ok(())
+}
+
+fn encode_string(value: &str, output: &mut String) {
+    output.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+}
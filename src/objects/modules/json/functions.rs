@@ -0,0 +1,99 @@
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::json::decoder::Decoder;
+use crate::objects::modules::json::encoder::encode;
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{parse_arg, string_object_to_string};
+
+pub fn kya_json_parse(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let arg = parse_arg(args, 0, 1)?;
+    let input = string_object_to_string(&arg)
+        .map_err(|_| Error::TypeError("json.parse() expects a string".to_string()))?;
+
+    Decoder::new(&input).decode()
+}
+
+pub fn kya_json_dump(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let arg = parse_arg(args, 0, 1)?;
+
+    Ok(string_new(&encode(&arg)?))
+}
+
+pub fn json_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "json",
+        vec![
+            (
+                "parse",
+                rs_function_with_doc(
+                    kya_json_parse,
+                    "parse(string): decodes a JSON document into Hash, List, Number, String, Bool and None values.",
+                ),
+            ),
+            (
+                "dump",
+                rs_function_with_doc(
+                    kya_json_dump,
+                    "dump(value): encodes a value as a compact JSON string.",
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::KyaObject;
+
+    fn round_trip(input: &str) -> String {
+        let parsed = kya_json_parse(string_new(""), &mut vec![string_new(input)], None).unwrap();
+        let dumped = kya_json_dump(string_new(""), &mut vec![parsed], None).unwrap();
+
+        string_object_to_string(&dumped).unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        assert_eq!(
+            round_trip("[1, 2.5, \"a\", true, null]"),
+            "[1,2.5,\"a\",true,null]"
+        );
+        assert_eq!(
+            round_trip("{\"key\": [\"x\\ny\"]}"),
+            "{\"key\":[\"x\\ny\"]}"
+        );
+        assert_eq!(round_trip("\"\\u00e9\\ud83d\\ude00\""), "\"é😀\"");
+    }
+
+    #[test]
+    fn test_json_parse_object() {
+        let parsed = kya_json_parse(
+            string_new(""),
+            &mut vec![string_new("{\"a\": {\"b\": 1}}")],
+            None,
+        )
+        .unwrap();
+
+        assert!(matches!(&*parsed.lock().unwrap(), KyaObject::HashObject(_)));
+    }
+
+    #[test]
+    fn test_json_parse_errors() {
+        for input in ["", "[1,", "{\"a\" 1}", "tru", "[1] x", "\"\\q\""] {
+            let result = kya_json_parse(string_new(""), &mut vec![string_new(input)], None);
+
+            assert!(result.is_err(), "expected error for {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod decoder;
+pub mod encoder;
+pub mod functions;
@@ -0,0 +1,155 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::exception_object::MEMORY_ERROR_TYPE;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    String,
+    Bytes,
+    List,
+    Hash,
+}
+
+impl Limit {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(Limit::String),
+            "bytes" => Some(Limit::Bytes),
+            "list" => Some(Limit::List),
+            "hash" => Some(Limit::Hash),
+            _ => None,
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            Limit::String | Limit::Bytes => "bytes",
+            Limit::List | Limit::Hash => "items",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Limit::String => "string",
+            Limit::Bytes => "bytes",
+            Limit::List => "list",
+            Limit::Hash => "hash",
+        }
+    }
+}
+
+// Zero means no limit, which is the default.
+static MAXIMUMS: [AtomicUsize; 4] = [const { AtomicUsize::new(0) }; 4];
+
+pub fn max(limit: Limit) -> Option<usize> {
+    match MAXIMUMS[limit as usize].load(Ordering::Relaxed) {
+        0 => None,
+        max => Some(max),
+    }
+}
+
+pub fn set_max(limit: Limit, max: Option<usize>) {
+    MAXIMUMS[limit as usize].store(max.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Raises a MemoryError when something of `size` would be over the maximum
+/// set for `limit`. Called before allocating, so that a length read from a
+/// socket cannot make the interpreter reserve an arbitrary amount of memory.
+pub fn check(limit: Limit, size: usize) -> Result<(), Error> {
+    match max(limit) {
+        Some(max) if size > max => Err(Error::exception(
+            MEMORY_ERROR_TYPE.lock().unwrap().name.clone(),
+            format!(
+                "A {} of {} {} is over the limit of {}",
+                limit.name(),
+                size,
+                limit.unit(),
+                max
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn parse_limit(value: &HostValue) -> Result<Limit, Error> {
+    let name = value.as_str()?;
+
+    Limit::from_name(name).ok_or_else(|| {
+        Error::ValueError(format!(
+            "Unknown limit '{}', expected string, bytes, list or hash",
+            name
+        ))
+    })
+}
+
+pub fn limits_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "limits",
+        vec![
+            (
+                "max",
+                host_function_with_doc(
+                    "max",
+                    &[ArgType::String],
+                    ArgType::Number,
+                    "max(kind): returns the maximum size for kind (string, bytes, list or hash), or 0 if there is none.",
+                    |args| {
+                        let limit = parse_limit(&args[0])?;
+
+                        Ok(HostValue::Number(max(limit).unwrap_or(0) as f64))
+                    },
+                ),
+            ),
+            (
+                "set_max",
+                host_function_with_doc(
+                    "set_max",
+                    &[ArgType::String, ArgType::Number],
+                    ArgType::None,
+                    "set_max(kind, size): raises MemoryError when a string or bytes grows past size bytes, or a list or hash past size items. 0 removes the limit.",
+                    |args| {
+                        let limit = parse_limit(&args[0])?;
+                        let size = args[1].as_number()?;
+
+                        if size.fract() != 0.0 || size < 0.0 {
+                            return Err(Error::ValueError(format!(
+                                "The maximum size must be a non-negative integer, got {}",
+                                size
+                            )));
+                        }
+
+                        set_max(limit, Some(size as usize).filter(|size| *size > 0));
+
+                        Ok(HostValue::None)
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        // Large enough that no other test running alongside gets near it.
+        set_max(Limit::Hash, Some(1_000_000));
+
+        assert!(check(Limit::Hash, 1_000_000).is_ok());
+        assert!(matches!(
+            check(Limit::Hash, 1_000_001),
+            Err(Error::Exception(name, _, None, _)) if name == "MemoryError"
+        ));
+
+        set_max(Limit::Hash, None);
+
+        assert!(check(Limit::Hash, usize::MAX).is_ok());
+        assert_eq!(Limit::from_name("bytes"), Some(Limit::Bytes));
+    }
+}
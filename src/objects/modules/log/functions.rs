@@ -0,0 +1,223 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+use crate::capabilities::{self, Capability};
+use crate::errors::Error;
+use crate::internal::datetime::DateTime;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::threads::functions::current_thread_name;
+use crate::objects::utils::object_to_string_repr;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+enum Target {
+    Stderr,
+    File(File),
+}
+
+struct Logger {
+    level: Level,
+    target: Target,
+}
+
+static LOGGER: Lazy<Mutex<Logger>> = Lazy::new(|| {
+    Mutex::new(Logger {
+        level: Level::Info,
+        target: Target::Stderr,
+    })
+});
+
+fn format_timestamp(millis: i64) -> String {
+    DateTime::from_timestamp(millis as f64 / 1000.0)
+        .format("%Y-%m-%dT%H:%M:%S.%LZ")
+        .unwrap_or_default()
+}
+
+pub fn format_line(millis: i64, level: Level, thread: &str, message: &str) -> String {
+    format!(
+        "{} {:<5} [thread {}] {}\n",
+        format_timestamp(millis),
+        level.name().to_uppercase(),
+        thread,
+        message
+    )
+}
+
+fn write_log(level: Level, message: &str) -> Result<(), Error> {
+    let mut logger = LOGGER.lock().unwrap();
+
+    if level < logger.level {
+        return Ok(());
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default();
+    let line = format_line(millis, level, &current_thread_name(), message);
+
+    let result = match &mut logger.target {
+        Target::Stderr => std::io::stderr().write_all(line.as_bytes()),
+        Target::File(file) => file.write_all(line.as_bytes()),
+    };
+
+    result.map_err(|e| Error::RuntimeError(format!("Failed to write log: {}", e)))
+}
+
+fn log_function(level: Level, doc: &'static str) -> KyaObjectRef {
+    host_function_with_doc(
+        level.name(),
+        &[ArgType::Any],
+        ArgType::None,
+        doc,
+        move |args| {
+            let message = match &args[0] {
+                HostValue::Object(obj) => object_to_string_repr(obj)?,
+                _ => unreachable!(),
+            };
+
+            write_log(level, &message)?;
+
+            Ok(HostValue::None)
+        },
+    )
+}
+
+pub fn log_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "log",
+        vec![
+            (
+                "debug",
+                log_function(Level::Debug, "debug(message): logs message at debug level."),
+            ),
+            (
+                "info",
+                log_function(Level::Info, "info(message): logs message at info level."),
+            ),
+            (
+                "warn",
+                log_function(Level::Warn, "warn(message): logs message at warn level."),
+            ),
+            (
+                "error",
+                log_function(Level::Error, "error(message): logs message at error level."),
+            ),
+            (
+                "level",
+                host_function_with_doc(
+                    "level",
+                    &[],
+                    ArgType::String,
+                    "level(): returns the current minimum level.",
+                    |_| {
+                        Ok(HostValue::String(
+                            LOGGER.lock().unwrap().level.name().to_string(),
+                        ))
+                    },
+                ),
+            ),
+            (
+                "set_level",
+                host_function_with_doc(
+                    "set_level",
+                    &[ArgType::String],
+                    ArgType::None,
+                    "set_level(name): drops messages below name (debug, info, warn or error).",
+                    |args| {
+                        let name = args[0].as_str()?;
+                        let level = Level::from_name(name).ok_or_else(|| {
+                            Error::ValueError(format!("Unknown log level '{}'", name))
+                        })?;
+
+                        LOGGER.lock().unwrap().level = level;
+
+                        Ok(HostValue::None)
+                    },
+                ),
+            ),
+            (
+                "set_target",
+                host_function_with_doc(
+                    "set_target",
+                    &[ArgType::String],
+                    ArgType::None,
+                    "set_target(target): writes to \"stderr\" or appends to the file at target.",
+                    |args| {
+                        let target = match args[0].as_str()? {
+                            "stderr" => Target::Stderr,
+                            path => {
+                                capabilities::require(Capability::Filesystem, "set_target()")?;
+
+                                Target::File(
+                                    OpenOptions::new()
+                                        .create(true)
+                                        .append(true)
+                                        .open(path)
+                                        .map_err(|e| {
+                                            Error::RuntimeError(format!(
+                                                "Could not open log file {}: {}",
+                                                path, e
+                                            ))
+                                        })?,
+                                )
+                            }
+                        };
+
+                        LOGGER.lock().unwrap().target = target;
+
+                        Ok(HostValue::None)
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line(1_700_000_000_123, Level::Warn, "2", "disk almost full"),
+            "2023-11-14T22:13:20.123Z WARN  [thread 2] disk almost full\n"
+        );
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert!(Level::Debug < Level::Error);
+    }
+}
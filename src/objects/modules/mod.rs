@@ -1,2 +1,14 @@
+pub mod base64;
+pub mod crypto;
+pub mod datetime;
+pub mod gc;
+pub mod http;
+pub mod json;
+pub mod limits;
+pub mod log;
+pub mod path;
+pub mod random;
 pub mod sockets;
+pub mod structs;
 pub mod threads;
+pub mod time;
@@ -0,0 +1,319 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::capabilities::{self, Capability};
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::string_object_to_string;
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn strings_to_list(strings: Vec<String>) -> HostValue {
+    HostValue::List(strings.iter().map(|s| string_new(s)).collect())
+}
+
+pub fn kya_path_join(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.is_empty() {
+        return Err(Error::TypeError(
+            "join() takes at least 1 argument".to_string(),
+        ));
+    }
+
+    let mut path = PathBuf::new();
+
+    for arg in args.iter() {
+        let part = string_object_to_string(arg)
+            .map_err(|_| Error::TypeError("join() arguments must be strings".to_string()))?;
+
+        path.push(part);
+    }
+
+    Ok(string_new(&path_to_string(&path)))
+}
+
+fn list_dir(path: &str) -> Result<Vec<String>, Error> {
+    capabilities::require(Capability::Filesystem, "list_dir()")?;
+
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| Error::RuntimeError(format!("Could not list directory {}: {}", path, e)))?;
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    names.sort();
+
+    Ok(names)
+}
+
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            wildcard_match(&pattern[1..], name)
+                || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+fn glob_walk(base: &Path, components: &[&str], matches: &mut Vec<PathBuf>) {
+    let Some((component, rest)) = components.split_first() else {
+        matches.push(base.to_path_buf());
+        return;
+    };
+
+    let dir = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        base
+    };
+
+    if *component == "**" {
+        glob_walk(base, rest, matches);
+
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    glob_walk(&base.join(entry.file_name()), components, matches);
+                }
+            }
+        }
+
+        return;
+    }
+
+    if !component.contains(['*', '?']) {
+        let next = base.join(component);
+
+        if next.exists() {
+            glob_walk(&next, rest, matches);
+        }
+
+        return;
+    }
+
+    let pattern: Vec<char> = component.chars().collect();
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if name.starts_with('.') && !component.starts_with('.') {
+                continue;
+            }
+
+            if wildcard_match(&pattern, &name.chars().collect::<Vec<_>>()) {
+                glob_walk(&base.join(&name), rest, matches);
+            }
+        }
+    }
+}
+
+// Patterns use the platform's separators, so on Windows both `\` and `/`
+// split components and a drive prefix like `C:\` is part of the base.
+pub fn glob(pattern: &str) -> Vec<String> {
+    let mut base = PathBuf::new();
+    let mut components = Vec::new();
+
+    for component in Path::new(pattern).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => base.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => components.push(".."),
+            Component::Normal(name) => components.push(name.to_str().unwrap_or_default()),
+        }
+    }
+
+    let mut matches = Vec::new();
+
+    glob_walk(&base, &components, &mut matches);
+
+    let mut results: Vec<String> = matches.iter().map(|path| path_to_string(path)).collect();
+
+    results.sort();
+    results.dedup();
+
+    results
+}
+
+pub fn path_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "path",
+        vec![
+            (
+                "join",
+                rs_function_with_doc(
+                    kya_path_join,
+                    "join(part, ...): joins path components with the platform separator.",
+                ),
+            ),
+            (
+                "dirname",
+                host_function_with_doc(
+                    "dirname",
+                    &[ArgType::String],
+                    ArgType::String,
+                    "dirname(path): returns the parent directory of path, or \"\" if it has none.",
+                    |args| {
+                        let parent = Path::new(args[0].as_str()?).parent();
+
+                        Ok(HostValue::String(
+                            parent.map(path_to_string).unwrap_or_default(),
+                        ))
+                    },
+                ),
+            ),
+            (
+                "basename",
+                host_function_with_doc(
+                    "basename",
+                    &[ArgType::String],
+                    ArgType::String,
+                    "basename(path): returns the final component of path.",
+                    |args| {
+                        let name = Path::new(args[0].as_str()?).file_name();
+
+                        Ok(HostValue::String(
+                            name.map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default(),
+                        ))
+                    },
+                ),
+            ),
+            (
+                "extension",
+                host_function_with_doc(
+                    "extension",
+                    &[ArgType::String],
+                    ArgType::String,
+                    "extension(path): returns the extension of path without the dot, or \"\".",
+                    |args| {
+                        let extension = Path::new(args[0].as_str()?).extension();
+
+                        Ok(HostValue::String(
+                            extension
+                                .map(|e| e.to_string_lossy().to_string())
+                                .unwrap_or_default(),
+                        ))
+                    },
+                ),
+            ),
+            (
+                "exists",
+                host_function_with_doc(
+                    "exists",
+                    &[ArgType::String],
+                    ArgType::Bool,
+                    "exists(path): returns true if path exists.",
+                    |args| {
+                        capabilities::require(Capability::Filesystem, "exists()")?;
+
+                        Ok(HostValue::Bool(Path::new(args[0].as_str()?).exists()))
+                    },
+                ),
+            ),
+            (
+                "is_dir",
+                host_function_with_doc(
+                    "is_dir",
+                    &[ArgType::String],
+                    ArgType::Bool,
+                    "is_dir(path): returns true if path is a directory.",
+                    |args| {
+                        capabilities::require(Capability::Filesystem, "is_dir()")?;
+
+                        Ok(HostValue::Bool(Path::new(args[0].as_str()?).is_dir()))
+                    },
+                ),
+            ),
+            (
+                "list_dir",
+                host_function_with_doc(
+                    "list_dir",
+                    &[ArgType::String],
+                    ArgType::List,
+                    "list_dir(path): returns the sorted names of the entries in a directory.",
+                    |args| Ok(strings_to_list(list_dir(args[0].as_str()?)?)),
+                ),
+            ),
+            (
+                "glob",
+                host_function_with_doc(
+                    "glob",
+                    &[ArgType::String],
+                    ArgType::List,
+                    "glob(pattern): returns the sorted paths matching a pattern. Supports *, ? and ** for any number of directories.",
+                    |args| {
+                        capabilities::require(Capability::Filesystem, "glob()")?;
+
+                        Ok(strings_to_list(glob(args[0].as_str()?)))
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        wildcard_match(
+            &pattern.chars().collect::<Vec<_>>(),
+            &name.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(matches("*.k", "main.k"));
+        assert!(matches("ma?n.*", "main.k"));
+        assert!(matches("*", ""));
+        assert!(!matches("*.k", "main.rs"));
+        assert!(!matches("?", ""));
+    }
+
+    #[test]
+    fn test_glob() {
+        let root = std::env::temp_dir().join(format!("kyanite-glob-{}", std::process::id()));
+
+        std::fs::create_dir_all(root.join("lib/nested")).unwrap();
+        std::fs::write(root.join("main.k"), "").unwrap();
+        std::fs::write(root.join("lib/util.k"), "").unwrap();
+        std::fs::write(root.join("lib/nested/deep.k"), "").unwrap();
+        std::fs::write(root.join("lib/notes.txt"), "").unwrap();
+
+        let found = glob(&path_to_string(&root.join("**").join("*.k")));
+
+        assert_eq!(
+            found,
+            vec![
+                path_to_string(&root.join("lib").join("nested").join("deep.k")),
+                path_to_string(&root.join("lib").join("util.k")),
+                path_to_string(&root.join("main.k")),
+            ]
+        );
+
+        #[cfg(windows)]
+        assert_eq!(
+            glob(&format!("{}/lib/*.k", path_to_string(&root))),
+            vec![path_to_string(&root.join("lib").join("util.k"))]
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
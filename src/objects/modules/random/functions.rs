@@ -0,0 +1,80 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+use crate::replay;
+
+static STATE: Lazy<Mutex<u64>> = Lazy::new(|| {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+
+    Mutex::new(seed | 1)
+});
+
+fn next_float() -> f64 {
+    let mut state = STATE.lock().unwrap();
+
+    // xorshift64*
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+
+    let value = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn random_float() -> Result<f64, Error> {
+    replay::capture("random", || Ok(next_float())).map_err(Error::RuntimeError)
+}
+
+fn random_randint(args: Vec<HostValue>) -> Result<HostValue, Error> {
+    let low = args[0].as_number()?.ceil();
+    let high = args[1].as_number()?.floor();
+
+    if low > high {
+        return Err(Error::ValueError(format!(
+            "randint() range is empty: {} > {}",
+            low, high
+        )));
+    }
+
+    let value = low + (random_float()? * (high - low + 1.0)).floor();
+
+    Ok(HostValue::Number(value.min(high)))
+}
+
+pub fn random_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "random",
+        vec![
+            (
+                "random",
+                host_function_with_doc(
+                    "random",
+                    &[],
+                    ArgType::Number,
+                    "random(): returns a pseudo-random Number in the range [0, 1).",
+                    |_| Ok(HostValue::Number(random_float()?)),
+                ),
+            ),
+            (
+                "randint",
+                host_function_with_doc(
+                    "randint",
+                    &[ArgType::Number, ArgType::Number],
+                    ArgType::Number,
+                    "randint(low, high): returns a pseudo-random integer between low and high, inclusive.",
+                    random_randint,
+                ),
+            ),
+        ],
+    )
+}
@@ -1,19 +1,33 @@
 use std::collections::HashMap;
 
 use crate::errors::Error;
-use crate::internal::socket::Connection;
-use crate::interpreter::NONE_OBJECT;
+use crate::internal::socket::{Address, Connection, ConnectionWriter};
 use crate::lock::{kya_acquire_lock, kya_release_lock};
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::byte_view_object::byte_view_new;
 use crate::objects::bytes_object::bytes_new;
+use crate::objects::list_object::list_new;
+use crate::objects::modules::limits::functions::{check, Limit};
+use crate::objects::modules::sockets::socket_object::{parse_timeout, socket_error};
+use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
-use crate::objects::utils::{number_object_to_float, parse_arg, parse_receiver};
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{kya_is_true, number_object_to_float, parse_arg, parse_receiver};
 use once_cell::sync::Lazy;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// How much `recv_until` asks the socket for at a time, and how much it keeps
+// buffered before giving up on finding the delimiter.
+const READ_CHUNK_SIZE: usize = 4096;
+const MAX_BUFFERED: usize = 1024 * 1024;
 
 pub struct ConnectionObject {
     ob_type: TypeRef,
     connection: Connection,
+    // Data read past the last delimiter, handed out by the next read.
+    buffer: Vec<u8>,
 }
 
 impl ConnectionObject {
@@ -21,22 +35,139 @@ impl ConnectionObject {
         Self {
             ob_type,
             connection,
+            buffer: Vec::new(),
         }
     }
 
     pub fn read(&mut self, buffer_size: usize) -> Result<Vec<u8>, Error> {
-        self.connection.read(buffer_size).map_err(|e| {
-            Error::RuntimeError(format!("Failed to read from connection: {}", e.to_string()))
-        })
+        if !self.buffer.is_empty() {
+            let size = buffer_size.min(self.buffer.len());
+
+            return Ok(self.buffer.drain(..size).collect());
+        }
+
+        self.connection
+            .read(buffer_size)
+            .map_err(|e| socket_error("Failed to read from connection", e))
+    }
+
+    /// Like `read`, but leaves the data in place for the next read.
+    pub fn peek(&mut self, buffer_size: usize) -> Result<Vec<u8>, Error> {
+        if !self.buffer.is_empty() {
+            let size = buffer_size.min(self.buffer.len());
+
+            return Ok(self.buffer[..size].to_vec());
+        }
+
+        self.connection
+            .peek(buffer_size)
+            .map_err(|e| socket_error("Failed to peek at connection", e))
+    }
+
+    /// Bytes that can be read without blocking, buffered ones included.
+    pub fn available(&self) -> Result<usize, Error> {
+        let pending = self
+            .connection
+            .available()
+            .map_err(|e| socket_error("Failed to query the connection", e))?;
+
+        Ok(self.buffer.len() + pending)
+    }
+
+    /// Reads up to and including `delimiter`. Returns whatever is left when
+    /// the peer closes the connection first, or None if nothing is left.
+    pub fn read_until(&mut self, delimiter: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        if delimiter.is_empty() {
+            return Err(Error::ValueError(
+                "The delimiter cannot be empty".to_string(),
+            ));
+        }
+
+        let mut searched = 0;
+
+        loop {
+            if let Some(position) = self.buffer[searched..]
+                .windows(delimiter.len())
+                .position(|window| window == delimiter)
+            {
+                let end = searched + position + delimiter.len();
+
+                return Ok(Some(self.buffer.drain(..end).collect()));
+            }
+
+            check(Limit::Bytes, self.buffer.len())?;
+
+            if self.buffer.len() >= MAX_BUFFERED {
+                return Err(Error::ValueError(format!(
+                    "Delimiter not found within {} bytes",
+                    MAX_BUFFERED
+                )));
+            }
+
+            searched = self.buffer.len().saturating_sub(delimiter.len() - 1);
+
+            let chunk = self
+                .connection
+                .read(READ_CHUNK_SIZE)
+                .map_err(|e| socket_error("Failed to read from connection", e))?;
+
+            if chunk.is_empty() {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+
+                return Ok(Some(std::mem::take(&mut self.buffer)));
+            }
+
+            self.buffer.extend(chunk);
+        }
     }
 
     pub fn send(&mut self, data: Vec<u8>) -> Result<(), Error> {
-        self.connection.send(data).map_err(|e| {
-            Error::RuntimeError(format!(
-                "Failed to send data on connection: {}",
-                e.to_string()
-            ))
-        })
+        self.connection
+            .send(data)
+            .map_err(|e| socket_error("Failed to send data on connection", e))
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.connection
+            .set_timeout(timeout)
+            .map_err(|e| socket_error("Failed to set the connection timeout", e))
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), Error> {
+        self.connection
+            .set_nonblocking(nonblocking)
+            .map_err(|e| socket_error("Failed to set the connection blocking mode", e))
+    }
+
+    pub fn peer_addr(&mut self) -> Result<Address, Error> {
+        self.connection
+            .peer_addr()
+            .map_err(|e| socket_error("Failed to get the peer address", e))
+    }
+
+    pub fn local_addr(&mut self) -> Result<Address, Error> {
+        self.connection
+            .local_addr()
+            .map_err(|e| socket_error("Failed to get the local address", e))
+    }
+
+    // Data left over from read_line() or recv_until() can be read without
+    // waiting on the socket.
+    pub fn has_buffered(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    pub fn writer(&self) -> Result<ConnectionWriter, Error> {
+        self.connection
+            .writer()
+            .map_err(|e| socket_error("Failed to share the connection", e))
+    }
+
+    #[cfg(unix)]
+    pub fn raw_fd(&self) -> Option<std::os::fd::RawFd> {
+        self.connection.raw_fd()
     }
 
     pub fn close(&mut self) -> Result<(), Error> {
@@ -56,18 +187,22 @@ pub fn connection_new(connection: Connection) -> KyaObjectRef {
     KyaObject::from_connection_object(ConnectionObject {
         ob_type: SOCKETS_CONNECTION_TYPE.clone(),
         connection,
+        buffer: Vec::new(),
     })
 }
 
+// Returns a ByteView, whose slices share the received data.
 pub fn connection_read(
     _callable: KyaObjectRef,
     args: &mut Vec<KyaObjectRef>,
     receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
     let instance = parse_receiver(&receiver)?;
-    let arg = parse_arg(&args, 0, 1)?;
+    let arg = parse_arg(args, 0, 1)?;
     let buffer_size = number_object_to_float(&arg)? as usize;
 
+    check(Limit::Bytes, buffer_size)?;
+
     if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
         kya_release_lock();
 
@@ -75,14 +210,124 @@ pub fn connection_read(
 
         kya_acquire_lock();
 
-        if let Err(e) = data {
-            return Err(Error::RuntimeError(format!(
-                "Failed to read from connection: {}",
-                e.to_string()
-            )));
+        let data: Arc<[u8]> = Arc::from(data?);
+        let len = data.len();
+
+        Ok(byte_view_new(data, 0, len))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_peek(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let arg = parse_arg(args, 0, 1)?;
+    let buffer_size = number_object_to_float(&arg)? as usize;
+
+    check(Limit::Bytes, buffer_size)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        kya_release_lock();
+
+        let data = connection_obj.peek(buffer_size);
+
+        kya_acquire_lock();
+
+        Ok(bytes_new(data?))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_available(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    if let KyaObject::ConnectionObject(ref connection_obj) = *instance.lock().unwrap() {
+        Ok(number_new(connection_obj.available()? as f64))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_recv_until(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let arg = parse_arg(args, 0, 1)?;
+
+    let delimiter = match &*arg.lock().unwrap() {
+        KyaObject::BytesObject(bytes_object) => bytes_object.value.clone(),
+        KyaObject::StringObject(string_object) => string_object.value.as_bytes().to_vec(),
+        KyaObject::ByteViewObject(view) => view.as_slice().to_vec(),
+        _ => {
+            return Err(Error::TypeError(
+                "The delimiter must be bytes or a string".to_string(),
+            ))
         }
+    };
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        kya_release_lock();
+
+        let data = connection_obj.read_until(&delimiter);
 
-        Ok(bytes_new(data.unwrap()))
+        kya_acquire_lock();
+
+        Ok(bytes_new(data?.unwrap_or_default()))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+// Returns the next line without its "\n" or "\r\n" terminator, or None once
+// the peer has closed the connection and every line has been read.
+pub fn connection_read_line(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        kya_release_lock();
+
+        let data = connection_obj.read_until(b"\n");
+
+        kya_acquire_lock();
+
+        let Some(mut line) = data? else {
+            return Ok(kya_none());
+        };
+
+        if line.ends_with(b"\n") {
+            line.pop();
+
+            if line.ends_with(b"\r") {
+                line.pop();
+            }
+        }
+
+        Ok(string_new(&String::from_utf8_lossy(&line)))
     } else {
         Err(Error::RuntimeError(
             "Expected a Connection object".to_string(),
@@ -101,6 +346,7 @@ pub fn connection_send(
     if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
         let data = match *arg.lock().unwrap() {
             KyaObject::BytesObject(ref bytes_obj) => bytes_obj.value.clone(),
+            KyaObject::ByteViewObject(ref view) => view.as_slice().to_vec(),
             _ => return Err(Error::RuntimeError("Expected bytes data".to_string())),
         };
 
@@ -110,14 +356,9 @@ pub fn connection_send(
 
         kya_acquire_lock();
 
-        if let Err(e) = result {
-            return Err(Error::RuntimeError(format!(
-                "Failed to send data on connection: {}",
-                e.to_string()
-            )));
-        }
+        result?;
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError(
             "Expected a Connection object".to_string(),
@@ -147,7 +388,7 @@ pub fn connection_close(
             )));
         }
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError(
             "Expected a Connection object".to_string(),
@@ -155,25 +396,316 @@ pub fn connection_close(
     }
 }
 
+// Inet addresses become [host, port] and Unix ones [path, None].
+fn address_to_list(address: Address) -> KyaObjectRef {
+    match address {
+        Address::Inet(host, port) => list_new(vec![string_new(&host), number_new(port as f64)]),
+        Address::Unix(path) => list_new(vec![string_new(&path.to_string_lossy()), kya_none()]),
+    }
+}
+
+pub fn connection_peer_addr(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        Ok(address_to_list(connection_obj.peer_addr()?))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_local_addr(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        Ok(address_to_list(connection_obj.local_addr()?))
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_set_timeout(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let timeout = parse_timeout(&parse_arg(args, 0, 1)?)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        connection_obj.set_timeout(timeout)?;
+
+        Ok(kya_none())
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_set_nonblocking(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let nonblocking = kya_is_true(parse_arg(args, 0, 1)?)?;
+
+    if let KyaObject::ConnectionObject(ref mut connection_obj) = *instance.lock().unwrap() {
+        connection_obj.set_nonblocking(nonblocking)?;
+
+        Ok(kya_none())
+    } else {
+        Err(Error::RuntimeError(
+            "Expected a Connection object".to_string(),
+        ))
+    }
+}
+
+pub fn connection_tp_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    connection_close(obj.clone(), &mut vec![], Some(obj))?;
+
+    Ok(())
+}
+
 pub static SOCKETS_CONNECTION_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     let dict = Arc::new(Mutex::new(HashMap::new()));
 
     dict.lock()
         .unwrap()
-        .insert("recv".to_string(), rs_function_new(connection_read));
+        .insert("recv".into(), rs_function_new(connection_read));
+
+    dict.lock()
+        .unwrap()
+        .insert("send".into(), rs_function_new(connection_send));
 
     dict.lock()
         .unwrap()
-        .insert("send".to_string(), rs_function_new(connection_send));
+        .insert("peek".into(), rs_function_new(connection_peek));
 
     dict.lock()
         .unwrap()
-        .insert("close".to_string(), rs_function_new(connection_close));
+        .insert("available".into(), rs_function_new(connection_available));
+
+    dict.lock()
+        .unwrap()
+        .insert("read_line".into(), rs_function_new(connection_read_line));
+
+    dict.lock()
+        .unwrap()
+        .insert("recv_until".into(), rs_function_new(connection_recv_until));
+
+    dict.lock()
+        .unwrap()
+        .insert("close".into(), rs_function_new(connection_close));
+
+    dict.lock()
+        .unwrap()
+        .insert("peer_addr".into(), rs_function_new(connection_peer_addr));
+
+    dict.lock()
+        .unwrap()
+        .insert("local_addr".into(), rs_function_new(connection_local_addr));
+
+    dict.lock().unwrap().insert(
+        "set_timeout".into(),
+        rs_function_new(connection_set_timeout),
+    );
+
+    dict.lock().unwrap().insert(
+        "set_nonblocking".into(),
+        rs_function_new(connection_set_nonblocking),
+    );
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "sockets.Connection".to_string(),
+        tp_exit: Some(connection_tp_exit),
+        tp_del: Some(connection_tp_exit),
         dict,
         ..Default::default()
     })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::socket::{SocketOptions, TcpConnection};
+    use crate::objects::base::{CallableFunctionPtr, SliceRange};
+    use crate::objects::byte_view_object::byte_view_sq_slice;
+    use crate::objects::utils::string_object_to_string;
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+
+    // A Connection for one end of a loopback TCP connection, and the stream
+    // of the other end.
+    fn loopback_pair() -> (KyaObjectRef, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let peer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let connection = Connection::Tcp(TcpConnection {
+            stream: Some(stream),
+            options: SocketOptions::default(),
+        });
+
+        (connection_new(connection), peer)
+    }
+
+    fn call(
+        method: CallableFunctionPtr,
+        connection: &KyaObjectRef,
+        mut args: Vec<KyaObjectRef>,
+    ) -> Result<KyaObjectRef, Error> {
+        kya_acquire_lock();
+        let result = method(kya_none(), &mut args, Some(connection.clone()));
+        kya_release_lock();
+
+        result
+    }
+
+    fn bytes_of(object: &KyaObjectRef) -> Vec<u8> {
+        match &*object.lock().unwrap() {
+            KyaObject::BytesObject(bytes_object) => bytes_object.value.clone(),
+            KyaObject::ByteViewObject(view) => view.as_slice().to_vec(),
+            _ => panic!("expected bytes"),
+        }
+    }
+
+    fn read_line(connection: &KyaObjectRef) -> Option<String> {
+        let line = call(connection_read_line, connection, vec![]).unwrap();
+
+        if let KyaObject::NoneObject(_) = &*line.lock().unwrap() {
+            return None;
+        }
+
+        Some(string_object_to_string(&line).unwrap())
+    }
+
+    #[test]
+    fn test_read_line_and_recv_until() {
+        let (connection, mut peer) = loopback_pair();
+
+        peer.write_all(b"one\r\ntwo\nthree|rest").unwrap();
+        drop(peer);
+
+        assert_eq!(read_line(&connection).as_deref(), Some("one"));
+        assert_eq!(read_line(&connection).as_deref(), Some("two"));
+
+        let until = call(connection_recv_until, &connection, vec![string_new("|")]).unwrap();
+        assert_eq!(bytes_of(&until), b"three|");
+
+        assert!(matches!(
+            call(connection_recv_until, &connection, vec![string_new("")]),
+            Err(Error::ValueError(_))
+        ));
+
+        // What is left when the peer closes comes back without a terminator.
+        assert_eq!(read_line(&connection).as_deref(), Some("rest"));
+        assert_eq!(read_line(&connection), None);
+    }
+
+    fn address_of(address: &KyaObjectRef) -> (String, u16) {
+        let KyaObject::ListObject(list) = &*address.lock().unwrap() else {
+            panic!("expected a list");
+        };
+
+        (
+            string_object_to_string(&list.items[0]).unwrap(),
+            number_object_to_float(&list.items[1]).unwrap() as u16,
+        )
+    }
+
+    #[test]
+    fn test_peer_and_local_addr() {
+        let (connection, peer) = loopback_pair();
+
+        let peer_addr = address_of(&call(connection_peer_addr, &connection, vec![]).unwrap());
+        let local_addr = address_of(&call(connection_local_addr, &connection, vec![]).unwrap());
+
+        assert_eq!(
+            peer_addr,
+            ("127.0.0.1".to_string(), peer.local_addr().unwrap().port())
+        );
+        assert_eq!(
+            local_addr,
+            ("127.0.0.1".to_string(), peer.peer_addr().unwrap().port())
+        );
+    }
+
+    fn available(connection: &KyaObjectRef) -> usize {
+        let available = call(connection_available, connection, vec![]).unwrap();
+
+        number_object_to_float(&available).unwrap() as usize
+    }
+
+    #[test]
+    fn test_peek_and_available() {
+        let (connection, mut peer) = loopback_pair();
+
+        peer.write_all(b"hello\ntwo\n").unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+
+        while available(&connection) < 10 {
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let peeked = call(connection_peek, &connection, vec![number_new(5.0)]).unwrap();
+        assert_eq!(bytes_of(&peeked), b"hello");
+        assert_eq!(available(&connection), 10);
+
+        // Reading a line buffers the rest, which still counts as available
+        // and is what a peek sees next.
+        assert_eq!(read_line(&connection).as_deref(), Some("hello"));
+        assert_eq!(available(&connection), 4);
+
+        let peeked = call(connection_peek, &connection, vec![number_new(8.0)]).unwrap();
+        assert_eq!(bytes_of(&peeked), b"two\n");
+        assert_eq!(available(&connection), 4);
+    }
+
+    #[test]
+    fn test_recv_returns_a_view() {
+        let (connection, mut peer) = loopback_pair();
+
+        peer.write_all(b"GET /index").unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+
+        while available(&connection) < 10 {
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let received = call(connection_read, &connection, vec![number_new(64.0)]).unwrap();
+        let path = byte_view_sq_slice(
+            received.clone(),
+            &SliceRange::new(Some(4), None, 1).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(bytes_of(&path), b"/index");
+
+        match (&*received.lock().unwrap(), &*path.lock().unwrap()) {
+            (KyaObject::ByteViewObject(received), KyaObject::ByteViewObject(path)) => {
+                assert!(Arc::ptr_eq(&received.data, &path.data))
+            }
+            _ => panic!("expected views"),
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::gc::Gc;
+use crate::internal::socket::ConnectionWriter;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{
+    kya_call, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
+};
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{parse_arg, parse_receiver};
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+struct Member {
+    connection: KyaObjectRef,
+    // Sends go through a handle of their own so that a thread blocked in
+    // connection.recv() does not hold up a broadcast.
+    writer: Arc<Mutex<ConnectionWriter>>,
+}
+
+/// A set of connections that can be written to from any thread. The set is
+/// only ever locked long enough to copy its members, never while sending or
+/// running a callback.
+pub struct ConnectionSetObject {
+    ob_type: TypeRef,
+    members: Vec<Member>,
+}
+
+impl KyaObjectTrait for ConnectionSetObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+fn with_set<T>(
+    instance: &KyaObjectRef,
+    f: impl FnOnce(&mut ConnectionSetObject) -> T,
+) -> Result<T, Error> {
+    match &mut *instance.lock().unwrap() {
+        KyaObject::ConnectionSetObject(set) => Ok(f(set)),
+        _ => Err(Error::RuntimeError(
+            "Expected a ConnectionSet object".to_string(),
+        )),
+    }
+}
+
+fn remove_members(instance: &KyaObjectRef, connections: &[KyaObjectRef]) -> Result<(), Error> {
+    with_set(instance, |set| {
+        set.members.retain(|member| {
+            !connections
+                .iter()
+                .any(|connection| Gc::ptr_eq(connection, &member.connection))
+        })
+    })
+}
+
+pub fn connection_set_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    Ok(KyaObject::from_connection_set_object(ConnectionSetObject {
+        ob_type,
+        members: Vec::new(),
+    }))
+}
+
+pub fn connection_set_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn connection_set_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let count = with_set(&callable, |set| set.members.len())?;
+
+    Ok(string_new(&format!(
+        "<sockets.ConnectionSet with {} connections>",
+        count
+    )))
+}
+
+pub fn connection_set_sq_len(object: KyaObjectRef) -> Result<usize, Error> {
+    with_set(&object, |set| set.members.len())
+}
+
+// Adding a connection that another thread is reading from waits for that
+// read to finish, so add connections before handing them to a worker.
+pub fn connection_set_add(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let connection = parse_arg(args, 0, 1)?;
+
+    if with_set(&instance, |set| {
+        set.members
+            .iter()
+            .any(|member| Gc::ptr_eq(&member.connection, &connection))
+    })? {
+        return Ok(kya_none());
+    }
+
+    kya_release_lock();
+
+    let writer = match &*connection.lock().unwrap() {
+        KyaObject::ConnectionObject(connection_obj) => connection_obj.writer(),
+        _ => Err(Error::TypeError(
+            "ConnectionSet.add() expects a Connection".to_string(),
+        )),
+    };
+
+    kya_acquire_lock();
+
+    let member = Member {
+        connection,
+        writer: Arc::new(Mutex::new(writer?)),
+    };
+
+    // Another thread may have added it while the lock was released.
+    with_set(&instance, |set| {
+        if !set
+            .members
+            .iter()
+            .any(|existing| Gc::ptr_eq(&existing.connection, &member.connection))
+        {
+            set.members.push(member);
+        }
+    })?;
+
+    Ok(kya_none())
+}
+
+pub fn connection_set_remove(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let connection = parse_arg(args, 0, 1)?;
+
+    remove_members(&instance, &[connection])?;
+
+    Ok(kya_none())
+}
+
+// Sends to every connection and returns how many were reached. Connections
+// that fail to take the data are dropped from the set.
+pub fn connection_set_broadcast(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let arg = parse_arg(args, 0, 1)?;
+
+    let data = match &*arg.lock().unwrap() {
+        KyaObject::BytesObject(bytes_object) => bytes_object.value.clone(),
+        KyaObject::StringObject(string_object) => string_object.value.as_bytes().to_vec(),
+        KyaObject::ByteViewObject(view) => view.as_slice().to_vec(),
+        _ => {
+            return Err(Error::TypeError(
+                "ConnectionSet.broadcast() expects bytes or a string".to_string(),
+            ))
+        }
+    };
+
+    let members = with_set(&instance, |set| set.members.clone())?;
+
+    kya_release_lock();
+
+    let failed: Vec<KyaObjectRef> = members
+        .iter()
+        .filter(|member| member.writer.lock().unwrap().send(&data).is_err())
+        .map(|member| member.connection.clone())
+        .collect();
+
+    kya_acquire_lock();
+
+    remove_members(&instance, &failed)?;
+
+    Ok(number_new((members.len() - failed.len()) as f64))
+}
+
+pub fn connection_set_each(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let function = parse_arg(args, 0, 1)?;
+
+    let connections: Vec<KyaObjectRef> = with_set(&instance, |set| {
+        set.members
+            .iter()
+            .map(|member| member.connection.clone())
+            .collect()
+    })?;
+
+    for connection in connections {
+        kya_call(function.clone(), &mut vec![connection], None)?;
+    }
+
+    Ok(kya_none())
+}
+
+pub static CONNECTION_SET_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("add".into(), rs_function_new(connection_set_add));
+
+    dict.lock()
+        .unwrap()
+        .insert("remove".into(), rs_function_new(connection_set_remove));
+
+    dict.lock().unwrap().insert(
+        "broadcast".into(),
+        rs_function_new(connection_set_broadcast),
+    );
+
+    dict.lock()
+        .unwrap()
+        .insert("each".into(), rs_function_new(connection_set_each));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "sockets.ConnectionSet".to_string(),
+        tp_repr: Some(connection_set_tp_repr),
+        tp_new: Some(connection_set_tp_new),
+        tp_init: Some(connection_set_tp_init),
+        sq_len: Some(connection_set_sq_len),
+        dict,
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::socket::{Connection, SocketOptions, TcpConnection};
+    use crate::objects::base::CallableFunctionPtr;
+    use crate::objects::modules::sockets::connection_object::{connection_close, connection_new};
+    use crate::objects::utils::number_object_to_float;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    // A Connection for one end of a loopback TCP connection, and the stream
+    // of the other end.
+    fn loopback_pair() -> (KyaObjectRef, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let peer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let connection = Connection::Tcp(TcpConnection {
+            stream: Some(stream),
+            options: SocketOptions::default(),
+        });
+
+        (connection_new(connection), peer)
+    }
+
+    fn call(
+        method: CallableFunctionPtr,
+        receiver: &KyaObjectRef,
+        mut args: Vec<KyaObjectRef>,
+    ) -> Result<KyaObjectRef, Error> {
+        kya_acquire_lock();
+        let result = method(kya_none(), &mut args, Some(receiver.clone()));
+        kya_release_lock();
+
+        result
+    }
+
+    fn broadcast(set: &KyaObjectRef, message: &str) -> usize {
+        let reached = call(connection_set_broadcast, set, vec![string_new(message)]).unwrap();
+
+        number_object_to_float(&reached).unwrap() as usize
+    }
+
+    #[test]
+    fn test_broadcast_add_and_remove() {
+        let set = connection_set_tp_new(CONNECTION_SET_TYPE.clone(), &mut vec![], None).unwrap();
+        let (first, mut first_peer) = loopback_pair();
+        let (second, mut second_peer) = loopback_pair();
+
+        call(connection_set_add, &set, vec![first.clone()]).unwrap();
+        call(connection_set_add, &set, vec![second.clone()]).unwrap();
+        call(connection_set_add, &set, vec![first.clone()]).unwrap();
+        assert_eq!(connection_set_sq_len(set.clone()).unwrap(), 2);
+
+        assert_eq!(broadcast(&set, "hi"), 2);
+
+        for peer in [&mut first_peer, &mut second_peer] {
+            let mut received = [0; 2];
+            peer.read_exact(&mut received).unwrap();
+            assert_eq!(&received, b"hi");
+        }
+
+        call(connection_set_remove, &set, vec![first.clone()]).unwrap();
+        assert_eq!(connection_set_sq_len(set.clone()).unwrap(), 1);
+
+        // A connection that can no longer be written to leaves the set.
+        call(connection_close, &second, vec![]).unwrap();
+        assert_eq!(broadcast(&set, "bye"), 0);
+        assert_eq!(connection_set_sq_len(set.clone()).unwrap(), 0);
+    }
+}
@@ -1,14 +1,234 @@
+use crate::capabilities::{self, Capability};
 use crate::errors::Error;
-use crate::objects::base::{kya_call, KyaObjectRef};
+use crate::internal::socket::create_udp_socket;
+#[cfg(unix)]
+use crate::internal::socket::create_unix_socket;
+#[cfg(unix)]
+use crate::internal::socket::select;
+#[cfg(unix)]
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{kya_call, KyaObject, KyaObjectRef};
 use crate::objects::class_object::class_new;
-use crate::objects::modules::sockets::socket_object::SOCKET_TYPE;
+#[cfg(unix)]
+use crate::objects::list_object::list_new;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::sockets::connection_set_object::CONNECTION_SET_TYPE;
+#[cfg(unix)]
+use crate::objects::modules::sockets::socket_object::socket_error;
+use crate::objects::modules::sockets::socket_object::{SocketObject, SOCKET_TYPE};
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::utils::{number_object_to_float, parse_arg, string_object_to_string};
+use std::time::Duration;
 
 pub fn kya_socket(
     _callable: KyaObjectRef,
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
+    capabilities::require(Capability::Network, "tcp()")?;
+
     let socket_class = class_new(SOCKET_TYPE.clone());
 
     kya_call(socket_class, &mut vec![], None)
 }
+
+pub fn kya_udp(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    capabilities::require(Capability::Network, "udp()")?;
+
+    Ok(KyaObject::from_socket_object(SocketObject::new(
+        SOCKET_TYPE.clone(),
+        create_udp_socket(),
+    )))
+}
+
+pub fn kya_unix(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let path = string_object_to_string(&parse_arg(args, 0, 1)?)
+        .map_err(|_| Error::TypeError("The 'path' argument must be a string".to_string()))?;
+
+    capabilities::require(Capability::Network, "unix()")?;
+
+    #[cfg(unix)]
+    return Ok(KyaObject::from_socket_object(SocketObject::new(
+        SOCKET_TYPE.clone(),
+        create_unix_socket(path.into()),
+    )));
+
+    #[cfg(not(unix))]
+    Err(Error::RuntimeError(format!(
+        "Unix sockets are not supported on this platform: {}",
+        path
+    )))
+}
+
+fn select_timeout(arg: &KyaObjectRef) -> Result<Option<Duration>, Error> {
+    if let KyaObject::NoneObject(_) = &*arg.lock().unwrap() {
+        return Ok(None);
+    }
+
+    let seconds = number_object_to_float(arg)
+        .map_err(|_| Error::TypeError("The timeout must be a number or None".to_string()))?;
+
+    Duration::try_from_secs_f64(seconds).map(Some).map_err(|_| {
+        Error::ValueError(format!(
+            "The timeout must be a non-negative number of seconds, got {}",
+            seconds
+        ))
+    })
+}
+
+pub fn kya_select(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(Error::TypeError(format!(
+            "select() takes 1 or 2 arguments, but got {}",
+            args.len()
+        )));
+    }
+
+    let objects: Vec<KyaObjectRef> = match &*args[0].lock().unwrap() {
+        KyaObject::ListObject(list_object) => list_object.items.iter().cloned().collect(),
+        _ => {
+            return Err(Error::TypeError(
+                "select() expects a list of sockets and connections".to_string(),
+            ))
+        }
+    };
+
+    let timeout = match args.get(1) {
+        Some(arg) => select_timeout(arg)?,
+        None => None,
+    };
+
+    #[cfg(unix)]
+    {
+        let mut fds = Vec::new();
+        let mut buffered = Vec::new();
+
+        for (index, object) in objects.iter().enumerate() {
+            let fd = match &*object.lock().unwrap() {
+                KyaObject::SocketObject(socket_object) => socket_object.raw_fd(),
+                KyaObject::ConnectionObject(connection_object) => {
+                    if connection_object.has_buffered() {
+                        buffered.push(index);
+                    }
+
+                    connection_object.raw_fd()
+                }
+                _ => {
+                    return Err(Error::TypeError(
+                        "select() expects a list of sockets and connections".to_string(),
+                    ))
+                }
+            };
+
+            fds.push(fd.ok_or_else(|| {
+                Error::ValueError("Cannot select on a closed or unbound socket".to_string())
+            })?);
+        }
+
+        let timeout = if buffered.is_empty() {
+            timeout
+        } else {
+            Some(Duration::ZERO)
+        };
+
+        kya_release_lock();
+
+        let ready = select(&fds, timeout);
+
+        kya_acquire_lock();
+
+        let mut ready = ready.map_err(|e| socket_error("Failed to select", e))?;
+
+        ready.extend(buffered);
+        ready.sort();
+        ready.dedup();
+
+        Ok(list_new(
+            ready
+                .into_iter()
+                .map(|index| objects[index].clone())
+                .collect(),
+        ))
+    }
+
+    #[cfg(not(unix))]
+    Err(Error::RuntimeError(format!(
+        "select() is not supported on this platform ({} objects, timeout {:?})",
+        objects.len(),
+        timeout
+    )))
+}
+
+pub fn sockets_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "sockets",
+        vec![
+            (
+                "tcp",
+                rs_function_with_doc(
+                    kya_socket,
+                    "tcp(): returns a TCP socket to bind(host, port) and accept() connections on, or to connect(host, port) from.",
+                ),
+            ),
+            (
+                "udp",
+                rs_function_with_doc(
+                    kya_udp,
+                    "udp(): returns a UDP socket. send_to(data, host, port) sends a datagram and recv_from(size) returns a [Bytes, address] pair.",
+                ),
+            ),
+            (
+                "select",
+                rs_function_with_doc(
+                    kya_select,
+                    "select(objects, timeout): waits until some of the listed sockets and connections can be read from or accepted on, and returns them. Returns an empty list once the timeout in seconds passes; without one it waits forever.",
+                ),
+            ),
+            (
+                "unix",
+                rs_function_with_doc(
+                    kya_unix,
+                    "unix(path): returns a Unix domain socket for path. bind() listens on it and connect() returns a Connection to it.",
+                ),
+            ),
+            ("ConnectionSet", class_new(CONNECTION_SET_TYPE.clone())),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::number_object::number_new;
+    use crate::objects::singletons::kya_none;
+
+    #[test]
+    fn test_select_timeout() {
+        assert_eq!(select_timeout(&kya_none()).unwrap(), None);
+        assert_eq!(
+            select_timeout(&number_new(0.0)).unwrap(),
+            Some(Duration::ZERO)
+        );
+
+        for seconds in [-1.0, 1e20, f64::INFINITY, f64::NAN] {
+            assert!(matches!(
+                select_timeout(&number_new(seconds)),
+                Err(Error::ValueError(_))
+            ));
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod connection_object;
+pub mod connection_set_object;
 pub mod functions;
 pub mod socket_object;
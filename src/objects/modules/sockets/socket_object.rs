@@ -1,20 +1,74 @@
 use std::collections::HashMap;
 
 use crate::errors::Error;
-use crate::internal::socket::Connection;
 use crate::internal::socket::{self};
-use crate::interpreter::NONE_OBJECT;
+use crate::internal::socket::{Address, Connection, Family, SocketError, SocketOption};
 use crate::lock::{kya_acquire_lock, kya_release_lock};
-use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::base::{
+    CallableFunctionPtr, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
+};
+use crate::objects::bytes_object::bytes_new;
+use crate::objects::exception_object::TIMEOUT_ERROR_TYPE;
+use crate::objects::list_object::list_new;
+use crate::objects::modules::limits::functions::{check, Limit};
 use crate::objects::modules::sockets::connection_object::connection_new;
-use crate::objects::number_object::NUMBER_TYPE;
+use crate::objects::number_object::{number_new, NUMBER_TYPE};
 use crate::objects::rs_function_object::rs_function_new;
-use crate::objects::string_object::STRING_TYPE;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::{string_new, STRING_TYPE};
 use crate::objects::utils::{
-    number_object_to_float, parse_arg, parse_receiver, string_object_to_string,
+    kya_is_true, number_object_to_float, parse_arg, parse_receiver, string_object_to_string,
 };
 use once_cell::sync::Lazy;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Timeouts are raised as TimeoutError so that a server can tell an idle peer
+// apart from a broken one.
+pub fn socket_error(context: &str, error: SocketError) -> Error {
+    match error {
+        SocketError::Timeout(message) => Error::exception(
+            TIMEOUT_ERROR_TYPE.lock().unwrap().name.clone(),
+            format!("{}: {}", context, message),
+        ),
+        other => Error::RuntimeError(format!("{}. Error: {}", context, other)),
+    }
+}
+
+pub fn parse_timeout(arg: &KyaObjectRef) -> Result<Option<Duration>, Error> {
+    if let KyaObject::NoneObject(_) = &*arg.lock().unwrap() {
+        return Ok(None);
+    }
+
+    let seconds = number_object_to_float(arg)
+        .map_err(|_| Error::TypeError("The timeout must be a number or None".to_string()))?;
+
+    match Duration::try_from_secs_f64(seconds) {
+        Ok(timeout) if !timeout.is_zero() => Ok(Some(timeout)),
+        _ => Err(Error::ValueError(format!(
+            "The timeout must be a positive number of seconds, got {}",
+            seconds
+        ))),
+    }
+}
+
+// The size of a receive buffer, which is allocated before anything arrives.
+fn parse_buffer_size(arg: &KyaObjectRef) -> Result<usize, Error> {
+    let size = number_object_to_float(arg)
+        .map_err(|_| Error::TypeError("The 'size' argument must be a number".to_string()))?;
+
+    if !(0.0..=isize::MAX as f64).contains(&size) || size.fract() != 0.0 {
+        return Err(Error::ValueError(format!(
+            "The size must be a non-negative whole number of bytes, got {}",
+            size
+        )));
+    }
+
+    check(Limit::Bytes, size as usize)?;
+
+    Ok(size as usize)
+}
 
 pub struct SocketObject {
     ob_type: TypeRef,
@@ -26,19 +80,58 @@ impl SocketObject {
         Self { ob_type, socket }
     }
 
-    pub fn bind(&mut self, host: &str, port: u16) -> Result<(), Error> {
-        self.socket.bind(host, port).map_err(|e| {
+    pub fn bind(&mut self, address: &Address) -> Result<(), Error> {
+        self.socket.bind(address).map_err(|e| {
             Error::RuntimeError(format!(
-                "Failed to bind socket to {}:{}. Error: {}",
-                host, port, e
+                "Failed to bind socket to {}. Error: {}",
+                address, e
             ))
         })
     }
 
+    pub fn connect(&mut self, address: &Address) -> Result<Connection, Error> {
+        self.socket
+            .connect(address)
+            .map_err(|e| socket_error(&format!("Failed to connect to {}", address), e))
+    }
+
     pub fn accept(&mut self) -> Result<Connection, Error> {
         self.socket
             .accept()
-            .map_err(|e| Error::RuntimeError(format!("Failed to accept connection. Error: {}", e)))
+            .map_err(|e| socket_error("Failed to accept connection", e))
+    }
+
+    pub fn send_to(&mut self, data: &[u8], host: &str, port: u16) -> Result<usize, Error> {
+        self.socket
+            .send_to(data, host, port)
+            .map_err(|e| socket_error(&format!("Failed to send datagram to {}:{}", host, port), e))
+    }
+
+    pub fn recv_from(&mut self, buffer_size: usize) -> Result<(Vec<u8>, String), Error> {
+        self.socket
+            .recv_from(buffer_size)
+            .map_err(|e| socket_error("Failed to receive datagram", e))
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.socket
+            .set_timeout(timeout)
+            .map_err(|e| socket_error("Failed to set the socket timeout", e))
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), Error> {
+        self.socket
+            .set_nonblocking(nonblocking)
+            .map_err(|e| socket_error("Failed to set the socket blocking mode", e))
+    }
+
+    #[cfg(unix)]
+    pub fn raw_fd(&self) -> Option<std::os::fd::RawFd> {
+        self.socket.raw_fd()
+    }
+
+    pub fn close(&mut self) {
+        self.socket.close()
     }
 }
 
@@ -65,7 +158,53 @@ pub fn socket_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
+}
+
+// bind() and connect() take a host and port, a socket path, or nothing for
+// a Unix socket that already knows its path.
+fn parse_address(socket_object: &SocketObject, args: &[KyaObjectRef]) -> Result<Address, Error> {
+    match args.len() {
+        0 => socket_object
+            .socket
+            .default_address()
+            .ok_or_else(|| Error::TypeError("Expected a host and port to be given".to_string())),
+        1 => {
+            let path = string_object_to_string(&args[0]).map_err(|_| {
+                Error::ValueError("The 'path' argument must be a string".to_string())
+            })?;
+
+            Ok(Address::Unix(PathBuf::from(path)))
+        }
+        2 => {
+            args[0]
+                .lock()
+                .unwrap()
+                .is_instance_of(&STRING_TYPE)?
+                .then_some(())
+                .ok_or_else(|| {
+                    Error::ValueError("The 'host' argument must be a string".to_string())
+                })?;
+
+            args[1]
+                .lock()
+                .unwrap()
+                .is_instance_of(&NUMBER_TYPE)?
+                .then_some(())
+                .ok_or_else(|| {
+                    Error::ValueError("The 'port' argument must be a number".to_string())
+                })?;
+
+            Ok(Address::Inet(
+                string_object_to_string(&args[0])?,
+                number_object_to_float(&args[1])? as u16,
+            ))
+        }
+        count => Err(Error::TypeError(format!(
+            "Expected at most 2 arguments, but got {}",
+            count
+        ))),
+    }
 }
 
 pub fn socket_bind(
@@ -76,27 +215,132 @@ pub fn socket_bind(
     let instance = parse_receiver(&receiver)?;
 
     if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
-        let host = parse_arg(&args, 0, 2)?;
-        let port = parse_arg(&args, 1, 2)?;
-
-        host.lock()
-            .unwrap()
-            .is_instance_of(&STRING_TYPE)?
-            .then_some(())
-            .ok_or_else(|| Error::ValueError("The 'host' argument must be a string".to_string()))?;
-
-        port.lock()
-            .unwrap()
-            .is_instance_of(&NUMBER_TYPE)?
-            .then_some(())
-            .ok_or_else(|| Error::ValueError("The 'port' argument must be a string".to_string()))?;
-
-        socket_object.bind(
-            &string_object_to_string(&host)?,
-            number_object_to_float(&port)? as u16,
-        )?;
-
-        Ok(NONE_OBJECT.clone())
+        let address = parse_address(socket_object, args)?;
+
+        socket_object.bind(&address)?;
+
+        Ok(kya_none())
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_connect(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        let address = parse_address(socket_object, args)?;
+
+        kya_release_lock();
+
+        let connection = socket_object.connect(&address);
+
+        kya_acquire_lock();
+
+        Ok(connection_new(connection?))
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+// bind6() and connect6() resolve host names, "localhost" included, to IPv6
+// addresses only. The socket keeps the family for later calls.
+fn with_ipv6(
+    callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+    function: CallableFunctionPtr,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object.socket.set_family(Family::V6);
+    } else {
+        return Err(Error::TypeError("Expected a Socket object".to_string()));
+    }
+
+    function(callable, args, receiver)
+}
+
+pub fn socket_bind6(
+    callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_ipv6(callable, args, receiver, socket_bind)
+}
+
+pub fn socket_connect6(
+    callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    with_ipv6(callable, args, receiver, socket_connect)
+}
+
+pub fn socket_set_dual_stack(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let dual_stack = kya_is_true(parse_arg(args, 0, 1)?)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object.socket.set_dual_stack(dual_stack);
+
+        Ok(kya_none())
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+fn parse_socket_option(name: &str, value: &KyaObjectRef) -> Result<SocketOption, Error> {
+    match name {
+        "reuse_address" => Ok(SocketOption::ReuseAddress(kya_is_true(value.clone())?)),
+        "nodelay" => Ok(SocketOption::NoDelay(kya_is_true(value.clone())?)),
+        "keepalive" => Ok(SocketOption::KeepAlive(kya_is_true(value.clone())?)),
+        "backlog" => {
+            let backlog = number_object_to_float(value)
+                .map_err(|_| Error::TypeError("The backlog must be a number".to_string()))?;
+
+            if backlog.fract() != 0.0 || backlog < 1.0 {
+                return Err(Error::ValueError(format!(
+                    "The backlog must be a positive integer, got {}",
+                    backlog
+                )));
+            }
+
+            Ok(SocketOption::Backlog(backlog as u32))
+        }
+        _ => Err(Error::ValueError(format!(
+            "Unknown socket option '{}'; expected reuse_address, backlog, nodelay or keepalive",
+            name
+        ))),
+    }
+}
+
+pub fn socket_set_option(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let name = string_object_to_string(&parse_arg(args, 0, 2)?)
+        .map_err(|_| Error::TypeError("The option name must be a string".to_string()))?;
+    let option = parse_socket_option(&name, &parse_arg(args, 1, 2)?)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object
+            .socket
+            .set_option(option)
+            .map_err(|e| socket_error(&format!("Failed to set the '{}' option", name), e))?;
+
+        Ok(kya_none())
     } else {
         Err(Error::TypeError("Expected a Socket object".to_string()))
     }
@@ -116,36 +360,226 @@ pub fn socket_accept(
 
         kya_acquire_lock();
 
-        if let Err(e) = connection {
-            return Err(Error::RuntimeError(format!(
-                "Failed to accept connection: {}",
-                e.to_string()
-            )));
+        Ok(connection_new(connection?))
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_send_to(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let data = parse_arg(args, 0, 3)?;
+    let host = parse_arg(args, 1, 3)?;
+    let port = parse_arg(args, 2, 3)?;
+
+    let data = match &*data.lock().unwrap() {
+        KyaObject::BytesObject(bytes_object) => bytes_object.value.clone(),
+        KyaObject::StringObject(string_object) => string_object.value.as_bytes().to_vec(),
+        KyaObject::ByteViewObject(view) => view.as_slice().to_vec(),
+        _ => {
+            return Err(Error::TypeError(
+                "The 'data' argument must be bytes or a string".to_string(),
+            ))
         }
+    };
+
+    let host = string_object_to_string(&host)
+        .map_err(|_| Error::TypeError("The 'host' argument must be a string".to_string()))?;
+    let port = number_object_to_float(&port)
+        .map_err(|_| Error::TypeError("The 'port' argument must be a number".to_string()))?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        kya_release_lock();
+
+        let sent = socket_object.send_to(&data, &host, port as u16);
+
+        kya_acquire_lock();
+
+        Ok(number_new(sent? as f64))
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_recv_from(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let buffer_size = parse_buffer_size(&parse_arg(args, 0, 1)?)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        kya_release_lock();
+
+        let received = socket_object.recv_from(buffer_size);
+
+        kya_acquire_lock();
+
+        let (data, address) = received?;
+
+        Ok(list_new(vec![bytes_new(data), string_new(&address)]))
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_set_timeout(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let timeout = parse_timeout(&parse_arg(args, 0, 1)?)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object.set_timeout(timeout)?;
+
+        Ok(kya_none())
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_set_nonblocking(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let nonblocking = kya_is_true(parse_arg(args, 0, 1)?)?;
 
-        Ok(connection_new(connection.unwrap()))
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object.set_nonblocking(nonblocking)?;
+
+        Ok(kya_none())
     } else {
         Err(Error::TypeError("Expected a Socket object".to_string()))
     }
 }
 
+pub fn socket_close(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let _ = parse_arg(args, 0, 0)?;
+
+    if let KyaObject::SocketObject(ref mut socket_object) = *instance.lock().unwrap() {
+        socket_object.close();
+
+        Ok(kya_none())
+    } else {
+        Err(Error::TypeError("Expected a Socket object".to_string()))
+    }
+}
+
+pub fn socket_tp_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    socket_close(obj.clone(), &mut vec![], Some(obj))?;
+
+    Ok(())
+}
+
 pub static SOCKET_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     let dict = Arc::new(Mutex::new(HashMap::new()));
 
     dict.lock()
         .unwrap()
-        .insert("bind".to_string(), rs_function_new(socket_bind));
+        .insert("bind".into(), rs_function_new(socket_bind));
+
+    dict.lock()
+        .unwrap()
+        .insert("accept".into(), rs_function_new(socket_accept));
+
+    dict.lock()
+        .unwrap()
+        .insert("connect".into(), rs_function_new(socket_connect));
+
+    dict.lock()
+        .unwrap()
+        .insert("bind6".into(), rs_function_new(socket_bind6));
+
+    dict.lock()
+        .unwrap()
+        .insert("connect6".into(), rs_function_new(socket_connect6));
+
+    dict.lock().unwrap().insert(
+        "set_dual_stack".into(),
+        rs_function_new(socket_set_dual_stack),
+    );
+
+    dict.lock()
+        .unwrap()
+        .insert("set_option".into(), rs_function_new(socket_set_option));
+
+    dict.lock()
+        .unwrap()
+        .insert("close".into(), rs_function_new(socket_close));
+
+    dict.lock()
+        .unwrap()
+        .insert("send_to".into(), rs_function_new(socket_send_to));
 
     dict.lock()
         .unwrap()
-        .insert("accept".to_string(), rs_function_new(socket_accept));
+        .insert("recv_from".into(), rs_function_new(socket_recv_from));
+
+    dict.lock()
+        .unwrap()
+        .insert("set_timeout".into(), rs_function_new(socket_set_timeout));
+
+    dict.lock().unwrap().insert(
+        "set_nonblocking".into(),
+        rs_function_new(socket_set_nonblocking),
+    );
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "sockets.Socket".to_string(),
         tp_new: Some(socket_tp_new),
         tp_init: Some(socket_tp_init),
+        tp_exit: Some(socket_tp_exit),
+        tp_del: Some(socket_tp_exit),
         dict,
         ..Default::default()
     })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::number_object::number_new;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(&kya_none()).unwrap(), None);
+        assert_eq!(
+            parse_timeout(&number_new(1.5)).unwrap(),
+            Some(Duration::from_millis(1500))
+        );
+
+        for seconds in [0.0, -1.0, 1e20, f64::INFINITY, f64::NAN] {
+            assert!(matches!(
+                parse_timeout(&number_new(seconds)),
+                Err(Error::ValueError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_buffer_size() {
+        assert_eq!(parse_buffer_size(&number_new(1024.0)).unwrap(), 1024);
+
+        for size in [-1.0, 1.5, 1e20, f64::INFINITY, f64::NAN] {
+            assert!(matches!(
+                parse_buffer_size(&number_new(size)),
+                Err(Error::ValueError(_))
+            ));
+        }
+    }
+}
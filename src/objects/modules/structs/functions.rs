@@ -0,0 +1,262 @@
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::host_function_object::{host_function_with_doc, ArgType, HostValue};
+use crate::objects::module_object::module_from_functions;
+use crate::objects::number_object::number_new;
+use crate::objects::utils::number_object_to_float;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ByteOrder {
+    Big,
+    Little,
+}
+
+fn byte_order(name: &str) -> Result<ByteOrder, Error> {
+    match name {
+        "big" => Ok(ByteOrder::Big),
+        "little" => Ok(ByteOrder::Little),
+        _ => Err(Error::ValueError(format!(
+            "Byte order must be \"big\" or \"little\", not \"{}\"",
+            name
+        ))),
+    }
+}
+
+fn to_integer(value: f64, bits: u32) -> Result<u64, Error> {
+    if value.fract() != 0.0 || value < 0.0 || value >= 2f64.powi(bits as i32) {
+        return Err(Error::ValueError(format!(
+            "{} does not fit in an unsigned {}-bit integer",
+            value, bits
+        )));
+    }
+
+    Ok(value as u64)
+}
+
+fn pack(values: &[f64], size: usize, order: ByteOrder) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(values.len() * size);
+
+    for value in values {
+        let be_bytes = to_integer(*value, size as u32 * 8)?.to_be_bytes();
+        let value_bytes = &be_bytes[8 - size..];
+
+        match order {
+            ByteOrder::Big => bytes.extend(value_bytes),
+            ByteOrder::Little => bytes.extend(value_bytes.iter().rev()),
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn unpack(bytes: &[u8], size: usize, order: ByteOrder) -> Result<Vec<f64>, Error> {
+    if !bytes.len().is_multiple_of(size) {
+        return Err(Error::ValueError(format!(
+            "Expected a multiple of {} bytes, got {}",
+            size,
+            bytes.len()
+        )));
+    }
+
+    Ok(bytes
+        .chunks(size)
+        .map(|chunk| {
+            let fold = |acc: u64, byte: &u8| acc << 8 | *byte as u64;
+
+            match order {
+                ByteOrder::Big => chunk.iter().fold(0, fold),
+                ByteOrder::Little => chunk.iter().rev().fold(0, fold),
+            }
+        })
+        .map(|value| value as f64)
+        .collect())
+}
+
+fn host_numbers(value: &HostValue) -> Result<Vec<f64>, Error> {
+    let HostValue::Object(obj) = value else {
+        return Err(Error::TypeError("Expected a Number or a List".to_string()));
+    };
+
+    if let Some(HostValue::List(items)) = HostValue::from_object(obj, ArgType::List) {
+        return items
+            .iter()
+            .map(|item| {
+                number_object_to_float(item).map_err(|_| {
+                    Error::TypeError("Only lists of numbers can be packed".to_string())
+                })
+            })
+            .collect();
+    }
+
+    number_object_to_float(obj)
+        .map(|number| vec![number])
+        .map_err(|_| Error::TypeError("Expected a Number or a List".to_string()))
+}
+
+fn pack_function(name: &'static str, size: usize, doc: &'static str) -> KyaObjectRef {
+    host_function_with_doc(
+        name,
+        &[ArgType::Any, ArgType::String],
+        ArgType::Bytes,
+        doc,
+        move |args| {
+            let values = host_numbers(&args[0])?;
+
+            Ok(HostValue::Bytes(pack(
+                &values,
+                size,
+                byte_order(args[1].as_str()?)?,
+            )?))
+        },
+    )
+}
+
+fn unpack_function(name: &'static str, size: usize, doc: &'static str) -> KyaObjectRef {
+    host_function_with_doc(
+        name,
+        &[ArgType::Bytes, ArgType::String],
+        ArgType::List,
+        doc,
+        move |args| {
+            let values = unpack(args[0].as_bytes()?, size, byte_order(args[1].as_str()?)?)?;
+
+            Ok(HostValue::List(
+                values.into_iter().map(number_new).collect(),
+            ))
+        },
+    )
+}
+
+fn bit_index(value: f64) -> Result<u32, Error> {
+    if value.fract() != 0.0 || !(0.0..53.0).contains(&value) {
+        return Err(Error::ValueError(format!("Invalid bit index {}", value)));
+    }
+
+    Ok(value as u32)
+}
+
+pub fn structs_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "struct",
+        vec![
+            (
+                "pack_u8",
+                pack_function(
+                    "pack_u8",
+                    1,
+                    "pack_u8(value, order): packs a Number or a List of Numbers as unsigned 8-bit integers.",
+                ),
+            ),
+            (
+                "pack_u16",
+                pack_function(
+                    "pack_u16",
+                    2,
+                    "pack_u16(value, order): packs a Number or a List of Numbers as unsigned 16-bit integers in \"big\" or \"little\" endian order.",
+                ),
+            ),
+            (
+                "pack_u32",
+                pack_function(
+                    "pack_u32",
+                    4,
+                    "pack_u32(value, order): packs a Number or a List of Numbers as unsigned 32-bit integers in \"big\" or \"little\" endian order.",
+                ),
+            ),
+            (
+                "unpack_u8",
+                unpack_function(
+                    "unpack_u8",
+                    1,
+                    "unpack_u8(bytes, order): returns the List of unsigned 8-bit integers in bytes.",
+                ),
+            ),
+            (
+                "unpack_u16",
+                unpack_function(
+                    "unpack_u16",
+                    2,
+                    "unpack_u16(bytes, order): returns the List of unsigned 16-bit integers in bytes.",
+                ),
+            ),
+            (
+                "unpack_u32",
+                unpack_function(
+                    "unpack_u32",
+                    4,
+                    "unpack_u32(bytes, order): returns the List of unsigned 32-bit integers in bytes.",
+                ),
+            ),
+            (
+                "get_bit",
+                host_function_with_doc(
+                    "get_bit",
+                    &[ArgType::Number, ArgType::Number],
+                    ArgType::Bool,
+                    "get_bit(value, index): returns whether bit index of the integer value is set.",
+                    |args| {
+                        let value = to_integer(args[0].as_number()?, 53)?;
+                        let index = bit_index(args[1].as_number()?)?;
+
+                        Ok(HostValue::Bool(value >> index & 1 == 1))
+                    },
+                ),
+            ),
+            (
+                "set_bit",
+                host_function_with_doc(
+                    "set_bit",
+                    &[ArgType::Number, ArgType::Number, ArgType::Bool],
+                    ArgType::Number,
+                    "set_bit(value, index, on): returns value with bit index set or cleared.",
+                    |args| {
+                        let value = to_integer(args[0].as_number()?, 53)?;
+                        let mask = 1u64 << bit_index(args[1].as_number()?)?;
+
+                        let result = if args[2].as_bool()? {
+                            value | mask
+                        } else {
+                            value & !mask
+                        };
+
+                        Ok(HostValue::Number(result as f64))
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        assert_eq!(
+            pack(&[1.0, 515.0], 2, ByteOrder::Big).unwrap(),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            pack(&[0x01020304 as f64], 4, ByteOrder::Little).unwrap(),
+            vec![4, 3, 2, 1]
+        );
+        assert_eq!(
+            unpack(&[4, 3, 2, 1], 4, ByteOrder::Little).unwrap(),
+            vec![0x01020304 as f64]
+        );
+        assert_eq!(
+            unpack(&[0, 1, 2, 3], 2, ByteOrder::Big).unwrap(),
+            vec![1.0, 515.0]
+        );
+    }
+
+    #[test]
+    fn test_pack_unpack_errors() {
+        assert!(pack(&[256.0], 1, ByteOrder::Big).is_err());
+        assert!(pack(&[-1.0], 2, ByteOrder::Big).is_err());
+        assert!(pack(&[1.5], 4, ByteOrder::Big).is_err());
+        assert!(unpack(&[1, 2, 3], 2, ByteOrder::Big).is_err());
+        assert!(byte_order("middle").is_err());
+    }
+}
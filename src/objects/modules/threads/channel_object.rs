@@ -0,0 +1,211 @@
+use crate::errors::Error;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::exception_object::TIMEOUT_ERROR_TYPE;
+use crate::objects::modules::sockets::socket_object::parse_timeout;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::utils::{parse_arg, parse_receiver};
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+pub struct ChannelObject {
+    pub ob_type: TypeRef,
+    // None once the channel is closed.
+    pub sender: Option<Sender<KyaObjectRef>>,
+    // Shared so that recv() can wait without keeping the channel locked.
+    pub receiver: Arc<Mutex<Receiver<KyaObjectRef>>>,
+}
+
+impl KyaObjectTrait for ChannelObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+fn with_channel<T>(
+    receiver: &Option<KyaObjectRef>,
+    f: impl FnOnce(&mut ChannelObject) -> T,
+) -> Result<T, Error> {
+    let instance = parse_receiver(receiver)?;
+
+    match &mut *instance.lock().unwrap() {
+        KyaObject::ChannelObject(channel) => Ok(f(channel)),
+        _ => Err(Error::RuntimeError("Expected a Channel object".to_string())),
+    }
+}
+
+pub fn channel_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let (sender, receiver) = mpsc::channel();
+
+    Ok(KyaObject::from_channel_object(ChannelObject {
+        ob_type,
+        sender: Some(sender),
+        receiver: Arc::new(Mutex::new(receiver)),
+    }))
+}
+
+pub fn channel_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn channel_send(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let value = parse_arg(args, 0, 1)?;
+
+    with_channel(&receiver, |channel| match &channel.sender {
+        Some(sender) => sender
+            .send(value)
+            .map_err(|_| Error::RuntimeError("Channel is closed".to_string())),
+        None => Err(Error::RuntimeError("Channel is closed".to_string())),
+    })??;
+
+    Ok(kya_none())
+}
+
+// Waits for the next value, up to an optional timeout in seconds. Returns
+// None once the channel is closed and every value has been received.
+pub fn channel_recv(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let timeout = match args.first() {
+        Some(arg) => parse_timeout(arg)?,
+        None => None,
+    };
+    let queue = with_channel(&receiver, |channel| channel.receiver.clone())?;
+
+    kya_release_lock();
+
+    let queue = queue.lock().unwrap();
+    let value = match timeout {
+        Some(timeout) => queue.recv_timeout(timeout),
+        None => queue.recv().map_err(RecvTimeoutError::from),
+    };
+
+    drop(queue);
+    kya_acquire_lock();
+
+    match value {
+        Ok(value) => Ok(value),
+        Err(RecvTimeoutError::Disconnected) => Ok(kya_none()),
+        Err(RecvTimeoutError::Timeout) => Err(Error::exception(
+            TIMEOUT_ERROR_TYPE.lock().unwrap().name.clone(),
+            "Channel.recv() did not receive a value in time".to_string(),
+        )),
+    }
+}
+
+// Returns the next value if one is waiting, or None otherwise.
+pub fn channel_try_recv(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let queue = with_channel(&receiver, |channel| channel.receiver.clone())?;
+
+    // Another thread may be blocked in recv() holding the receiver.
+    let Ok(queue) = queue.try_lock() else {
+        return Ok(kya_none());
+    };
+
+    match queue.try_recv() {
+        Ok(value) => Ok(value),
+        Err(TryRecvError::Empty | TryRecvError::Disconnected) => Ok(kya_none()),
+    }
+}
+
+pub fn channel_close(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    with_channel(&receiver, |channel| channel.sender = None)?;
+
+    Ok(kya_none())
+}
+
+pub static CHANNEL_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("send".into(), rs_function_new(channel_send));
+
+    dict.lock()
+        .unwrap()
+        .insert("recv".into(), rs_function_new(channel_recv));
+
+    dict.lock()
+        .unwrap()
+        .insert("try_recv".into(), rs_function_new(channel_try_recv));
+
+    dict.lock()
+        .unwrap()
+        .insert("close".into(), rs_function_new(channel_close));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "threads.Channel".to_string(),
+        tp_new: Some(channel_tp_new),
+        tp_init: Some(channel_tp_init),
+        dict,
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::embed::{eval_str, Value};
+
+    #[test]
+    fn test_values_cross_threads_in_order() {
+        let source = "import threads\n\ndef produce(channel, count)\n    i = 0\n\n    while i < count\n        channel.send(i)\n        i = i + 1\n    end\n\n    channel.close()\nend\n\nchannel = threads.Channel()\nproducer = threads.Thread(produce, channel, 3)\nproducer.start()\n\nreceived = List()\nreceived.append(channel.recv(5))\nreceived.append(channel.recv(5))\nreceived.append(channel.recv(5))\nreceived.append(channel.recv())\nproducer.join()\nreceived.append(channel.try_recv())\nreceived\n";
+
+        assert_eq!(
+            eval_str(source),
+            Ok(Value::List(vec![
+                Value::Number(0.0),
+                Value::Number(1.0),
+                Value::Number(2.0),
+                Value::None,
+                Value::None,
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_recv_times_out_and_send_fails_once_closed() {
+        let error = eval_str("import threads\nthreads.Channel().recv(0.01)\n").unwrap_err();
+        assert_eq!(error.exception_type(), Some("TimeoutError"));
+
+        let error = eval_str(
+            "import threads\nchannel = threads.Channel()\nchannel.close()\nchannel.send(1)\n",
+        )
+        .unwrap_err();
+        assert!(
+            error.message().contains("Channel is closed"),
+            "{}",
+            error.message()
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::Error;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::KyaObjectRef;
+use crate::objects::class_object::class_new;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::modules::threads::channel_object::CHANNEL_TYPE;
+use crate::objects::modules::threads::lock_object::LOCK_TYPE;
+use crate::objects::modules::threads::queue_object::QUEUE_TYPE;
+use crate::objects::modules::threads::semaphore_object::SEMAPHORE_TYPE;
+use crate::objects::modules::threads::thread_object::THREAD_OBJECT;
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{number_object_to_float, parse_arg};
+
+/// "main" for the main thread, and the thread's number for the others.
+pub fn current_thread_name() -> String {
+    let thread = thread::current();
+
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id())
+            .trim_start_matches("ThreadId(")
+            .trim_end_matches(')')
+            .to_string(),
+    }
+}
+
+pub fn threads_sleep(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let seconds = number_object_to_float(&parse_arg(args, 0, 1)?)?;
+    let duration = Duration::try_from_secs_f64(seconds).map_err(|_| {
+        Error::ValueError(format!(
+            "sleep() expects a non-negative number of seconds, got {}",
+            seconds
+        ))
+    })?;
+
+    kya_release_lock();
+    thread::sleep(duration);
+    kya_acquire_lock();
+
+    Ok(kya_none())
+}
+
+pub fn threads_current(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    Ok(string_new(&current_thread_name()))
+}
+
+pub fn threads_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "threads",
+        vec![
+            ("Thread", class_new(THREAD_OBJECT.clone())),
+            ("Lock", class_new(LOCK_TYPE.clone())),
+            ("Channel", class_new(CHANNEL_TYPE.clone())),
+            ("Queue", class_new(QUEUE_TYPE.clone())),
+            ("Semaphore", class_new(SEMAPHORE_TYPE.clone())),
+            (
+                "sleep",
+                rs_function_with_doc(
+                    threads_sleep,
+                    "sleep(seconds): pauses the current thread, letting the others run meanwhile.",
+                ),
+            ),
+            (
+                "current",
+                rs_function_with_doc(
+                    threads_current,
+                    "current(): returns \"main\" on the main thread and the thread's number elsewhere.",
+                ),
+            ),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::number_object::number_new;
+    use crate::objects::utils::string_object_to_string;
+
+    #[test]
+    fn test_sleep() {
+        kya_acquire_lock();
+        let slept = threads_sleep(kya_none(), &mut vec![number_new(0.01)], None);
+        let too_long: Vec<_> = [1e20, f64::INFINITY, f64::NAN, -1.0]
+            .into_iter()
+            .map(|seconds| threads_sleep(kya_none(), &mut vec![number_new(seconds)], None))
+            .collect();
+        kya_release_lock();
+
+        assert!(slept.is_ok());
+
+        for result in too_long {
+            assert!(matches!(result, Err(Error::ValueError(_))));
+        }
+    }
+
+    #[test]
+    fn test_current() {
+        let current = || {
+            string_object_to_string(&threads_current(kya_none(), &mut vec![], None).unwrap())
+                .unwrap()
+        };
+
+        let main = thread::Builder::new()
+            .name("main".to_string())
+            .spawn(current)
+            .unwrap()
+            .join()
+            .unwrap();
+        let other = thread::spawn(current).join().unwrap();
+
+        assert_eq!(main, "main");
+        assert!(other.parse::<u64>().is_ok());
+    }
+}
@@ -1,10 +1,10 @@
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::lock::{kya_acquire_lock, kya_release_lock};
 use crate::objects::base::{
     kya_call, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
 };
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
 use crate::objects::utils::{parse_arg, parse_receiver};
 
@@ -98,7 +98,7 @@ pub fn lock_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn lock_acquire(
@@ -121,7 +121,7 @@ pub fn lock_acquire(
             )));
         }
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError(format!(
             "The object '{}' is not a lock",
@@ -152,7 +152,7 @@ pub fn lock_release(
             )));
         }
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError(format!(
             "The object '{}' is not a lock",
@@ -161,16 +161,46 @@ pub fn lock_release(
     }
 }
 
+// Calls `function` while holding the lock and releases it afterwards, also
+// when the call raises. Returns what the function returned.
+pub fn lock_synchronize(
+    callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let function = parse_arg(args, 0, 1)?;
+
+    lock_acquire(callable.clone(), &mut vec![], receiver.clone())?;
+
+    let result = kya_call(function, &mut vec![], None);
+    let released = lock_release(callable, &mut vec![], receiver);
+
+    let value = result?;
+    released?;
+
+    Ok(value)
+}
+
+pub fn lock_tp_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    lock_release(obj.clone(), &mut vec![], Some(obj))?;
+
+    Ok(())
+}
+
 pub static LOCK_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     let dict = Arc::new(Mutex::new(HashMap::new()));
 
     dict.lock()
         .unwrap()
-        .insert("acquire".to_string(), rs_function_new(lock_acquire));
+        .insert("acquire".into(), rs_function_new(lock_acquire));
+
+    dict.lock()
+        .unwrap()
+        .insert("release".into(), rs_function_new(lock_release));
 
     dict.lock()
         .unwrap()
-        .insert("release".to_string(), rs_function_new(lock_release));
+        .insert("synchronize".into(), rs_function_new(lock_synchronize));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -178,7 +208,42 @@ pub static LOCK_TYPE: Lazy<TypeRef> = Lazy::new(|| {
         tp_repr: Some(lock_tp_repr),
         tp_new: Some(lock_tp_new),
         tp_init: Some(lock_tp_init),
+        tp_exit: Some(lock_tp_exit),
+        tp_del: Some(lock_tp_exit),
         dict: dict,
         ..Default::default()
     })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(
+        _callable: KyaObjectRef,
+        _args: &mut Vec<KyaObjectRef>,
+        _receiver: Option<KyaObjectRef>,
+    ) -> Result<KyaObjectRef, Error> {
+        Err(Error::RuntimeError("boom".to_string()))
+    }
+
+    #[test]
+    fn test_synchronize_releases_when_the_function_raises() {
+        let lock = lock_new().unwrap();
+
+        kya_acquire_lock();
+        let result = lock_synchronize(
+            lock.clone(),
+            &mut vec![rs_function_new(fail)],
+            Some(lock.clone()),
+        );
+        kya_release_lock();
+
+        assert!(matches!(result, Err(Error::RuntimeError(message)) if message == "boom"));
+
+        match &*lock.lock().unwrap() {
+            KyaObject::LockObject(lock_object) => assert!(!*lock_object.lock.lock().unwrap()),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod channel_object;
+pub mod functions;
 pub mod lock_object;
+pub mod queue_object;
+pub mod semaphore_object;
 pub mod thread_object;
@@ -0,0 +1,234 @@
+use crate::errors::Error;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{number_object_to_float, parse_arg, parse_receiver};
+
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+
+pub struct SharedQueue {
+    items: Mutex<VecDeque<KyaObjectRef>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    max_size: Option<usize>,
+}
+
+impl SharedQueue {
+    fn new(max_size: Option<usize>) -> Self {
+        SharedQueue {
+            items: Mutex::new(VecDeque::new()),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            max_size,
+        }
+    }
+
+    fn push(&self, value: KyaObjectRef) {
+        let mut items = self.items.lock().unwrap();
+
+        while self
+            .max_size
+            .is_some_and(|max_size| items.len() >= max_size)
+        {
+            items = self.not_full.wait(items).unwrap();
+        }
+
+        items.push_back(value);
+        self.not_empty.notify_one();
+    }
+
+    fn pop(&self) -> KyaObjectRef {
+        let mut items = self.items.lock().unwrap();
+
+        loop {
+            if let Some(value) = items.pop_front() {
+                self.not_full.notify_one();
+
+                return value;
+            }
+
+            items = self.not_empty.wait(items).unwrap();
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+}
+
+pub struct QueueObject {
+    pub ob_type: TypeRef,
+    // Shared so that waiting in push() or pop() does not keep the object
+    // locked for other threads.
+    pub queue: Arc<SharedQueue>,
+}
+
+impl KyaObjectTrait for QueueObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+fn shared_queue(instance: &KyaObjectRef) -> Result<Arc<SharedQueue>, Error> {
+    match &*instance.lock().unwrap() {
+        KyaObject::QueueObject(queue_object) => Ok(queue_object.queue.clone()),
+        _ => Err(Error::RuntimeError("Expected a Queue object".to_string())),
+    }
+}
+
+pub fn queue_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let max_size = match args.first() {
+        None => None,
+        Some(arg) if matches!(&*arg.lock().unwrap(), KyaObject::NoneObject(_)) => None,
+        Some(arg) => {
+            let max_size = number_object_to_float(arg).map_err(|_| {
+                Error::TypeError("The queue size must be a number or None".to_string())
+            })?;
+
+            if max_size.fract() != 0.0 || max_size < 1.0 {
+                return Err(Error::ValueError(format!(
+                    "The queue size must be a positive integer, got {}",
+                    max_size
+                )));
+            }
+
+            Some(max_size as usize)
+        }
+    };
+
+    Ok(KyaObject::from_queue_object(QueueObject {
+        ob_type,
+        queue: Arc::new(SharedQueue::new(max_size)),
+    }))
+}
+
+pub fn queue_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn queue_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let queue = shared_queue(&callable)?;
+
+    Ok(string_new(&format!(
+        "<threads.Queue with {} items>",
+        queue.len()
+    )))
+}
+
+pub fn queue_sq_len(object: KyaObjectRef) -> Result<usize, Error> {
+    Ok(shared_queue(&object)?.len())
+}
+
+// Blocks while a bounded queue is full.
+pub fn queue_push(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let value = parse_arg(args, 0, 1)?;
+    let queue = shared_queue(&parse_receiver(&receiver)?)?;
+
+    kya_release_lock();
+    queue.push(value);
+    kya_acquire_lock();
+
+    Ok(kya_none())
+}
+
+// Blocks until an item is available.
+pub fn queue_pop(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let queue = shared_queue(&parse_receiver(&receiver)?)?;
+
+    kya_release_lock();
+    let value = queue.pop();
+    kya_acquire_lock();
+
+    Ok(value)
+}
+
+pub fn queue_len(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let queue = shared_queue(&parse_receiver(&receiver)?)?;
+
+    Ok(number_new(queue.len() as f64))
+}
+
+pub static QUEUE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("push".into(), rs_function_new(queue_push));
+
+    dict.lock()
+        .unwrap()
+        .insert("pop".into(), rs_function_new(queue_pop));
+
+    dict.lock()
+        .unwrap()
+        .insert("len".into(), rs_function_new(queue_len));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "threads.Queue".to_string(),
+        tp_repr: Some(queue_tp_repr),
+        tp_new: Some(queue_tp_new),
+        tp_init: Some(queue_tp_init),
+        sq_len: Some(queue_sq_len),
+        dict,
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_bounded_queue_blocks_until_popped() {
+        let queue = Arc::new(SharedQueue::new(Some(1)));
+        let producer = queue.clone();
+
+        let handle = thread::spawn(move || {
+            for value in 0..3 {
+                producer.push(number_new(value as f64));
+            }
+        });
+
+        let popped: Vec<f64> = (0..3)
+            .map(|_| number_object_to_float(&queue.pop()).unwrap())
+            .collect();
+
+        handle.join().unwrap();
+
+        assert_eq!(popped, [0.0, 1.0, 2.0]);
+        assert_eq!(queue.len(), 0);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::errors::Error;
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::{number_object_to_float, parse_arg, parse_receiver};
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+
+pub struct SharedSemaphore {
+    available: Mutex<usize>,
+    released: Condvar,
+    permits: usize,
+}
+
+impl SharedSemaphore {
+    fn new(permits: usize) -> Self {
+        SharedSemaphore {
+            available: Mutex::new(permits),
+            released: Condvar::new(),
+            permits,
+        }
+    }
+
+    fn acquire(&self) {
+        let mut available = self.available.lock().unwrap();
+
+        while *available == 0 {
+            available = self.released.wait(available).unwrap();
+        }
+
+        *available -= 1;
+    }
+
+    fn release(&self) -> Result<(), Error> {
+        let mut available = self.available.lock().unwrap();
+
+        if *available == self.permits {
+            return Err(Error::RuntimeError(
+                "Semaphore released more times than it was acquired".to_string(),
+            ));
+        }
+
+        *available += 1;
+        self.released.notify_one();
+
+        Ok(())
+    }
+
+    fn available(&self) -> usize {
+        *self.available.lock().unwrap()
+    }
+}
+
+pub struct SemaphoreObject {
+    pub ob_type: TypeRef,
+    // Shared so that waiting in acquire() does not keep the object locked
+    // for the threads that would release it.
+    pub semaphore: Arc<SharedSemaphore>,
+}
+
+impl KyaObjectTrait for SemaphoreObject {
+    fn get_type(&self) -> TypeRef {
+        self.ob_type.clone()
+    }
+}
+
+fn shared_semaphore(instance: &KyaObjectRef) -> Result<Arc<SharedSemaphore>, Error> {
+    match &*instance.lock().unwrap() {
+        KyaObject::SemaphoreObject(semaphore_object) => Ok(semaphore_object.semaphore.clone()),
+        _ => Err(Error::RuntimeError(
+            "Expected a Semaphore object".to_string(),
+        )),
+    }
+}
+
+pub fn semaphore_tp_new(
+    ob_type: TypeRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let permits = number_object_to_float(&parse_arg(args, 0, 1)?)
+        .map_err(|_| Error::TypeError("The number of permits must be a number".to_string()))?;
+
+    if permits.fract() != 0.0 || permits < 1.0 {
+        return Err(Error::ValueError(format!(
+            "The number of permits must be a positive integer, got {}",
+            permits
+        )));
+    }
+
+    Ok(KyaObject::from_semaphore_object(SemaphoreObject {
+        ob_type,
+        semaphore: Arc::new(SharedSemaphore::new(permits as usize)),
+    }))
+}
+
+pub fn semaphore_tp_init(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    Ok(kya_none())
+}
+
+pub fn semaphore_tp_repr(
+    callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let semaphore = shared_semaphore(&callable)?;
+
+    Ok(string_new(&format!(
+        "<threads.Semaphore with {} of {} permits available>",
+        semaphore.available(),
+        semaphore.permits
+    )))
+}
+
+// Blocks until a permit is available.
+pub fn semaphore_acquire(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let semaphore = shared_semaphore(&parse_receiver(&receiver)?)?;
+
+    kya_release_lock();
+    semaphore.acquire();
+    kya_acquire_lock();
+
+    Ok(kya_none())
+}
+
+pub fn semaphore_release(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    shared_semaphore(&parse_receiver(&receiver)?)?.release()?;
+
+    Ok(kya_none())
+}
+
+// Leaving a with block gives back the permit taken before entering it.
+pub fn semaphore_tp_exit(obj: KyaObjectRef) -> Result<(), Error> {
+    shared_semaphore(&obj)?.release()
+}
+
+pub static SEMAPHORE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("acquire".into(), rs_function_new(semaphore_acquire));
+
+    dict.lock()
+        .unwrap()
+        .insert("release".into(), rs_function_new(semaphore_release));
+
+    Type::as_ref(Type {
+        ob_type: Some(BASE_TYPE.clone()),
+        name: "threads.Semaphore".to_string(),
+        tp_repr: Some(semaphore_tp_repr),
+        tp_new: Some(semaphore_tp_new),
+        tp_init: Some(semaphore_tp_init),
+        tp_exit: Some(semaphore_tp_exit),
+        dict,
+        ..Default::default()
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_semaphore_caps_concurrency() {
+        let semaphore = Arc::new(SharedSemaphore::new(2));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let (semaphore, running, peak) = (semaphore.clone(), running.clone(), peak.clone());
+
+                thread::spawn(move || {
+                    semaphore.acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    semaphore.release().unwrap();
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(semaphore.available(), 2);
+        assert!(semaphore.release().is_err());
+    }
+}
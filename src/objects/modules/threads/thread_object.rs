@@ -1,21 +1,85 @@
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::lock::{kya_acquire_lock, kya_release_lock};
 use crate::objects::base::{
     kya_call, KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE,
 };
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
-use crate::objects::utils::parse_arg;
+use crate::objects::utils::{kya_is_true, number_object_to_float, parse_arg, parse_receiver};
+use crate::runtime::{self, Runtime};
 
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 
+// Stack size in bytes for threads that do not set their own, or 0 for the
+// platform default.
+static DEFAULT_STACK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_default_stack_size(bytes: usize) {
+    DEFAULT_STACK_SIZE.store(bytes, Ordering::Relaxed);
+}
+
+// How many non-daemon threads a runtime has started that have not finished
+// yet.
+#[derive(Default)]
+pub struct RunningThreads {
+    running: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl RunningThreads {
+    fn thread_started(&self) {
+        *self.running.lock().unwrap() += 1;
+    }
+
+    fn thread_finished(&self) {
+        *self.running.lock().unwrap() -= 1;
+        self.finished.notify_all();
+    }
+}
+
+// Held by a started thread for as long as it runs. Dropping it releases the
+// interpreter lock and, for a non-daemon thread, counts the thread as
+// finished, which also happens when the target panics.
+struct ThreadExit {
+    runtime: Arc<Runtime>,
+    daemon: bool,
+}
+
+impl Drop for ThreadExit {
+    fn drop(&mut self) {
+        kya_release_lock();
+
+        if !self.daemon {
+            self.runtime.threads.thread_finished();
+        }
+    }
+}
+
+/// Blocks until every non-daemon thread of the current runtime has finished.
+/// Must be called without holding the interpreter lock, which those threads
+/// need to make progress.
+pub fn wait_for_threads() {
+    let runtime = runtime::current();
+    let mut running = runtime.threads.running.lock().unwrap();
+
+    while *running > 0 {
+        running = runtime.threads.finished.wait(running).unwrap();
+    }
+}
+
 pub struct ThreadObject {
     pub ob_type: TypeRef,
     pub target: KyaObjectRef,
+    // Extra arguments given to Thread(), passed on to the target.
+    pub args: Vec<KyaObjectRef>,
+    pub stack_size: Option<usize>,
+    // Daemon threads are not waited for when the program ends.
+    pub daemon: bool,
     pub thread_handle: Option<thread::JoinHandle<Result<KyaObjectRef, Error>>>,
 }
 
@@ -60,6 +124,9 @@ pub fn thread_tp_new(
     Ok(KyaObject::from_thread_object(ThreadObject {
         ob_type: ob_type.clone(),
         target: target_arg.clone(),
+        args: args[1..].to_vec(),
+        stack_size: None,
+        daemon: false,
         thread_handle: None,
     }))
 }
@@ -85,24 +152,56 @@ pub fn thread_start(
 
     if let KyaObject::ThreadObject(ref mut thread_obj) = *receiver.lock().unwrap() {
         let target = thread_obj.target.clone();
+        let mut target_args = thread_obj.args.clone();
+        let runtime = runtime::current();
+
+        let mut builder = thread::Builder::new();
+        let stack_size = thread_obj
+            .stack_size
+            .unwrap_or(DEFAULT_STACK_SIZE.load(Ordering::Relaxed));
+
+        if stack_size > 0 {
+            builder = builder.stack_size(stack_size);
+        }
+
+        let daemon = thread_obj.daemon;
+
+        if !daemon {
+            runtime.threads.thread_started();
+        }
+
+        let thread_runtime = runtime.clone();
+
+        let thread_handle = builder.spawn(move || {
+            runtime::enter(thread_runtime.clone());
+
+            let _exit = ThreadExit {
+                runtime: thread_runtime,
+                daemon,
+            };
 
-        let thread_handle = thread::spawn(move || {
             kya_acquire_lock();
 
-            let result = kya_call(target.clone(), &mut vec![], None);
+            let result = kya_call(target.clone(), &mut target_args, None);
 
             if result.is_err() {
                 eprintln!("{}", result.as_ref().err().unwrap());
             }
 
-            kya_release_lock();
-
             result
         });
 
+        let thread_handle = thread_handle.map_err(|e| {
+            if !daemon {
+                runtime.threads.thread_finished();
+            }
+
+            Error::RuntimeError(format!("Failed to start thread: {}", e))
+        })?;
+
         thread_obj.thread_handle = Some(thread_handle);
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         return Err(Error::RuntimeError(
             "The object is not a thread".to_string(),
@@ -129,26 +228,88 @@ pub fn thread_join(
 
     let receiver = receiver.unwrap();
 
-    if let KyaObject::ThreadObject(ref mut thread_obj) = *receiver.lock().unwrap() {
-        if let Some(handle) = thread_obj.thread_handle.take() {
-            kya_release_lock();
+    // The handle is taken out so that the thread object is not locked while
+    // waiting, when other threads may still use it.
+    let handle = match &mut *receiver.lock().unwrap() {
+        KyaObject::ThreadObject(thread_obj) => thread_obj.thread_handle.take(),
+        _ => {
+            return Err(Error::RuntimeError(
+                "The object is not a thread".to_string(),
+            ))
+        }
+    };
 
-            let result = handle
-                .join()
-                .map_err(|_| Error::RuntimeError("Thread join failed".to_string()));
+    let Some(handle) = handle else {
+        return Err(Error::RuntimeError(
+            "Thread has not been started".to_string(),
+        ));
+    };
 
-            kya_acquire_lock();
+    kya_release_lock();
 
-            if let Err(e) = result {
-                return Err(e);
-            }
+    let joined = handle.join();
 
-            Ok(NONE_OBJECT.clone())
-        } else {
-            Err(Error::RuntimeError(
-                "Thread has not been started".to_string(),
-            ))
+    kya_acquire_lock();
+
+    if joined.is_err() {
+        return Err(Error::RuntimeError("Thread join failed".to_string()));
+    }
+
+    Ok(kya_none())
+}
+
+// Threads get the platform's default stack, which is smaller than the main
+// thread's, so deeply recursive targets may need more.
+pub fn thread_set_stack_size(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let bytes = number_object_to_float(&parse_arg(args, 0, 1)?)?;
+    let instance = parse_receiver(&receiver)?;
+
+    if bytes.fract() != 0.0 || bytes < 1.0 {
+        return Err(Error::ValueError(format!(
+            "The stack size must be a positive number of bytes, got {}",
+            bytes
+        )));
+    }
+
+    if let KyaObject::ThreadObject(ref mut thread_obj) = *instance.lock().unwrap() {
+        if thread_obj.thread_handle.is_some() {
+            return Err(Error::RuntimeError(
+                "The stack size must be set before the thread starts".to_string(),
+            ));
+        }
+
+        thread_obj.stack_size = Some(bytes as usize);
+
+        Ok(kya_none())
+    } else {
+        Err(Error::RuntimeError(
+            "The object is not a thread".to_string(),
+        ))
+    }
+}
+
+pub fn thread_set_daemon(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let daemon = kya_is_true(parse_arg(args, 0, 1)?)?;
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::ThreadObject(ref mut thread_obj) = *instance.lock().unwrap() {
+        if thread_obj.thread_handle.is_some() {
+            return Err(Error::RuntimeError(
+                "A thread must be made a daemon before it starts".to_string(),
+            ));
         }
+
+        thread_obj.daemon = daemon;
+
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError(
             "The object is not a thread".to_string(),
@@ -161,7 +322,7 @@ pub fn thread_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub static THREAD_OBJECT: Lazy<TypeRef> = Lazy::new(|| {
@@ -169,11 +330,20 @@ pub static THREAD_OBJECT: Lazy<TypeRef> = Lazy::new(|| {
 
     dict.lock()
         .unwrap()
-        .insert("start".to_string(), rs_function_new(thread_start));
+        .insert("start".into(), rs_function_new(thread_start));
 
     dict.lock()
         .unwrap()
-        .insert("join".to_string(), rs_function_new(thread_join));
+        .insert("join".into(), rs_function_new(thread_join));
+
+    dict.lock()
+        .unwrap()
+        .insert("set_daemon".into(), rs_function_new(thread_set_daemon));
+
+    dict.lock().unwrap().insert(
+        "set_stack_size".into(),
+        rs_function_new(thread_set_stack_size),
+    );
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -185,3 +355,87 @@ pub static THREAD_OBJECT: Lazy<TypeRef> = Lazy::new(|| {
         ..Default::default()
     })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::CallableFunctionPtr;
+    use crate::objects::singletons::kya_true;
+    use std::sync::{mpsc, OnceLock};
+    use std::time::Duration;
+
+    static JOINED: OnceLock<KyaObjectRef> = OnceLock::new();
+
+    fn thread_new(target: CallableFunctionPtr) -> KyaObjectRef {
+        thread_tp_new(
+            THREAD_OBJECT.clone(),
+            &mut vec![rs_function_new(target)],
+            None,
+        )
+        .unwrap()
+    }
+
+    fn call(method: CallableFunctionPtr, thread: &KyaObjectRef) -> Result<KyaObjectRef, Error> {
+        kya_acquire_lock();
+        let result = method(kya_none(), &mut vec![], Some(thread.clone()));
+        kya_release_lock();
+
+        result
+    }
+
+    fn panics(
+        _callable: KyaObjectRef,
+        _args: &mut Vec<KyaObjectRef>,
+        _receiver: Option<KyaObjectRef>,
+    ) -> Result<KyaObjectRef, Error> {
+        panic!("the target panicked");
+    }
+
+    // Uses the thread it runs in while it is being joined.
+    fn uses_itself(
+        _callable: KyaObjectRef,
+        _args: &mut Vec<KyaObjectRef>,
+        _receiver: Option<KyaObjectRef>,
+    ) -> Result<KyaObjectRef, Error> {
+        kya_release_lock();
+        thread::sleep(Duration::from_millis(50));
+        kya_acquire_lock();
+
+        let thread = JOINED.get().unwrap().clone();
+        let _ = thread_set_daemon(kya_none(), &mut vec![kya_true()], Some(thread));
+
+        Ok(kya_none())
+    }
+
+    #[test]
+    fn test_panicking_thread_is_not_waited_for() {
+        let thread = thread_new(panics);
+
+        call(thread_start, &thread).unwrap();
+        assert!(call(thread_join, &thread).is_err());
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            wait_for_threads();
+            sender.send(()).unwrap();
+        });
+
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    }
+
+    #[test]
+    fn test_thread_can_be_used_while_joined() {
+        let thread = thread_new(uses_itself);
+        assert!(JOINED.set(thread.clone()).is_ok());
+
+        call(thread_start, &thread).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let joined = thread.clone();
+        thread::spawn(move || {
+            sender.send(call(thread_join, &joined).is_ok()).unwrap();
+        });
+
+        assert!(receiver.recv_timeout(Duration::from_secs(10)).unwrap());
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::errors::Error;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::module_object::module_from_functions;
+use crate::objects::number_object::number_new;
+use crate::objects::rs_function_object::rs_function_with_doc;
+use crate::objects::utils::parse_arg;
+use crate::replay;
+
+pub fn now_seconds() -> Result<f64, Error> {
+    replay::capture("time.now", || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs_f64())
+            .map_err(|e| e.to_string())
+    })
+    .map_err(Error::RuntimeError)
+}
+
+pub fn kya_time_now(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    _receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+
+    Ok(number_new(now_seconds()?))
+}
+
+pub fn time_module_new() -> KyaObjectRef {
+    module_from_functions(
+        "time",
+        vec![(
+            "now",
+            rs_function_with_doc(
+                kya_time_now,
+                "now(): returns the seconds elapsed since the Unix epoch as a Number.",
+            ),
+        )],
+    )
+}
@@ -1,11 +1,13 @@
+use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::string_object::string_new;
+use crate::objects::utils::bool_to_bool_object;
 
 use once_cell::sync::Lazy;
 
 pub struct NoneObject {
-    ob_type: TypeRef,
+    pub ob_type: TypeRef,
 }
 
 impl KyaObjectTrait for NoneObject {
@@ -28,11 +30,34 @@ pub fn none_repr(
     Ok(string_new("None"))
 }
 
+pub fn none_tp_compare(
+    _obj1: KyaObjectRef,
+    obj2: KyaObjectRef,
+    operator: ComparisonOperator,
+) -> Result<KyaObjectRef, Error> {
+    let is_none = matches!(&*obj2.lock().unwrap(), KyaObject::NoneObject(_));
+
+    match operator {
+        ComparisonOperator::Equal => Ok(bool_to_bool_object(is_none)),
+        ComparisonOperator::Neq => Ok(bool_to_bool_object(!is_none)),
+        _ => Err(Error::RuntimeError(format!(
+            "Comparison operator '{:?}' is not supported",
+            operator
+        ))),
+    }
+}
+
+pub fn none_tp_hash(_obj: KyaObjectRef) -> Result<usize, Error> {
+    Ok(0)
+}
+
 pub static NONE_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "None".to_string(),
         tp_repr: Some(none_repr),
+        tp_compare: Some(none_tp_compare),
+        tp_hash: Some(none_tp_hash),
         ..Default::default()
     })
 });
@@ -1,12 +1,20 @@
 use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
+use crate::internal::number::{format_fixed, format_number};
 
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::bool_object::{BoolObject, BOOL_TYPE};
-use crate::objects::string_object::{StringObject, STRING_TYPE};
-use crate::objects::utils::bool_to_bool_object;
+use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::string_object::{string_new, StringObject, STRING_TYPE};
+use crate::objects::utils::{
+    bool_to_bool_object, number_object_to_float, parse_arg, parse_receiver,
+};
 
 use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 
 pub struct NumberObject {
     pub ob_type: TypeRef,
@@ -29,7 +37,7 @@ pub fn number_tp_repr(
     if let KyaObject::NumberObject(number) = &*object {
         Ok(KyaObject::from_string_object(StringObject {
             ob_type: STRING_TYPE.clone(),
-            value: number.value.to_string(),
+            value: format_number(number.value),
         }))
     } else {
         Err(Error::RuntimeError(format!(
@@ -50,6 +58,16 @@ pub fn number_nb_bool(object: KyaObjectRef) -> Result<f64, Error> {
     }
 }
 
+pub fn number_tp_hash(obj: KyaObjectRef) -> Result<usize, Error> {
+    let value = number_object_to_float(&obj)?;
+    let mut hasher = DefaultHasher::new();
+
+    // 0 and -0 are equal, so they must hash the same.
+    (value + 0.0).to_bits().hash(&mut hasher);
+
+    Ok(hasher.finish() as usize)
+}
+
 pub fn number_tp_add(obj1: KyaObjectRef, obj2: KyaObjectRef) -> Result<KyaObjectRef, Error> {
     let a;
     let b;
@@ -136,22 +154,77 @@ pub fn number_tp_compare(
     }
 }
 
-pub fn number_new(value: f64) -> KyaObjectRef {
+// Numbers cannot be changed once created, so the small integers that loops
+// and counters keep producing are shared instead of allocated each time.
+const SMALL_INTEGERS: std::ops::RangeInclusive<i32> = -5..=256;
+
+static SMALL_NUMBERS: Lazy<Vec<KyaObjectRef>> = Lazy::new(|| {
+    SMALL_INTEGERS
+        .map(|value| allocate_number(value as f64))
+        .collect()
+});
+
+fn allocate_number(value: f64) -> KyaObjectRef {
     KyaObject::from_number_object(NumberObject {
         ob_type: NUMBER_TYPE.clone(),
         value,
     })
 }
 
+pub fn number_new(value: f64) -> KyaObjectRef {
+    // `-0.0` is kept apart from `0`, since it prints differently.
+    if value.fract() == 0.0
+        && value >= *SMALL_INTEGERS.start() as f64
+        && value <= *SMALL_INTEGERS.end() as f64
+        && !(value == 0.0 && value.is_sign_negative())
+    {
+        return SMALL_NUMBERS[(value as i32 - SMALL_INTEGERS.start()) as usize].clone();
+    }
+
+    allocate_number(value)
+}
+
+pub fn number_format(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let instance = parse_receiver(&receiver)?;
+    let arg = parse_arg(args, 0, 1)?;
+    let precision = number_object_to_float(&arg)
+        .map_err(|_| Error::TypeError("The 'precision' argument must be a number".to_string()))?;
+
+    if precision < 0.0 || precision.fract() != 0.0 {
+        return Err(Error::ValueError(format!(
+            "Precision must be a non-negative integer, got {}",
+            format_number(precision)
+        )));
+    }
+
+    let value = number_object_to_float(&instance)?;
+
+    Ok(string_new(
+        &format_fixed(value, precision as usize).map_err(Error::ValueError)?,
+    ))
+}
+
 pub static NUMBER_TYPE: Lazy<TypeRef> = Lazy::new(|| {
+    let dict = Arc::new(Mutex::new(HashMap::new()));
+
+    dict.lock()
+        .unwrap()
+        .insert("format".into(), rs_function_new(number_format));
+
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
         name: "Number".to_string(),
         tp_repr: Some(number_tp_repr),
         nb_bool: Some(number_nb_bool),
         tp_compare: Some(number_tp_compare),
+        tp_hash: Some(number_tp_hash),
         tp_add: Some(number_tp_add),
         tp_sub: Some(number_tp_sub),
+        dict,
         ..Default::default()
     })
 });
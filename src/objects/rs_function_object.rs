@@ -8,6 +8,7 @@ use once_cell::sync::Lazy;
 pub struct RsFunctionObject {
     pub ob_type: TypeRef,
     pub function_ptr: CallableFunctionPtr,
+    pub doc: Option<&'static str>,
 }
 
 impl RsFunctionObject {
@@ -15,6 +16,7 @@ impl RsFunctionObject {
         Self {
             ob_type,
             function_ptr,
+            doc: None,
         }
     }
 }
@@ -50,6 +52,14 @@ pub fn rs_function_new(function_ptr: CallableFunctionPtr) -> KyaObjectRef {
     ))
 }
 
+pub fn rs_function_with_doc(function_ptr: CallableFunctionPtr, doc: &'static str) -> KyaObjectRef {
+    let mut function = RsFunctionObject::new(RS_FUNCTION_TYPE.clone(), function_ptr);
+
+    function.doc = Some(doc);
+
+    KyaObject::from_rs_function_object(function)
+}
+
 pub static RS_FUNCTION_TYPE: Lazy<TypeRef> = Lazy::new(|| {
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::objects::base::{KyaObject, KyaObjectRef};
+use crate::objects::bool_object::bool_new;
+use crate::objects::none_object::none_new;
+use crate::runtime;
+
+// None, true and false are compared by identity, so every runtime owns one
+// instance of each and objects coming from elsewhere are mapped onto them.
+pub struct Singletons {
+    pub none: KyaObjectRef,
+    pub true_object: KyaObjectRef,
+    pub false_object: KyaObjectRef,
+}
+
+impl Singletons {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Singletons {
+            none: none_new().expect("Failed to create None object"),
+            true_object: bool_new(true),
+            false_object: bool_new(false),
+        })
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Singletons>>> = const { RefCell::new(None) };
+}
+
+pub fn enter(singletons: Arc<Singletons>) {
+    CURRENT.with(|cell| *cell.borrow_mut() = Some(singletons));
+}
+
+// A thread that never entered a runtime uses those of the default one, like
+// `runtime::current`.
+pub fn current() -> Arc<Singletons> {
+    CURRENT
+        .try_with(|cell| cell.borrow().clone())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| runtime::current().singletons.clone())
+}
+
+pub fn kya_none() -> KyaObjectRef {
+    current().none.clone()
+}
+
+pub fn kya_true() -> KyaObjectRef {
+    current().true_object.clone()
+}
+
+pub fn kya_false() -> KyaObjectRef {
+    current().false_object.clone()
+}
+
+pub fn canonicalize(obj: KyaObjectRef) -> KyaObjectRef {
+    let value = match &*obj.lock().unwrap() {
+        KyaObject::NoneObject(_) => None,
+        KyaObject::BoolObject(bool_object) => Some(bool_object.value),
+        _ => return obj.clone(),
+    };
+
+    match value {
+        None => kya_none(),
+        Some(true) => kya_true(),
+        Some(false) => kya_false(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gc::Gc;
+
+    #[test]
+    fn test_canonicalize() {
+        let foreign = Singletons::new();
+
+        assert!(!Gc::ptr_eq(&foreign.none, &kya_none()));
+        assert!(Gc::ptr_eq(&canonicalize(foreign.none.clone()), &kya_none()));
+        assert!(Gc::ptr_eq(
+            &canonicalize(foreign.true_object.clone()),
+            &kya_true()
+        ));
+        assert!(Gc::ptr_eq(
+            &canonicalize(foreign.false_object.clone()),
+            &kya_false()
+        ));
+    }
+
+    #[test]
+    fn test_runtimes_are_isolated() {
+        let first = Singletons::new();
+        let second = Singletons::new();
+
+        enter(first.clone());
+        assert!(Gc::ptr_eq(&kya_none(), &first.none));
+
+        enter(second.clone());
+        assert!(Gc::ptr_eq(&kya_none(), &second.none));
+    }
+}
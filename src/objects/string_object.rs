@@ -1,12 +1,18 @@
 use crate::bytecode::ComparisonOperator;
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
-use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
+use crate::internal::number::parse_number;
+use crate::objects::base::{
+    KyaObject, KyaObjectRef, KyaObjectTrait, SliceRange, Type, TypeRef, BASE_TYPE,
+};
 use crate::objects::bytes_object::bytes_new;
 use crate::objects::list_object::list_new;
+use crate::objects::modules::limits::functions::{check, Limit};
 use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
-use crate::objects::utils::{bool_to_bool_object, parse_arg, parse_receiver};
+use crate::objects::singletons::kya_none;
+use crate::objects::utils::{
+    bool_to_bool_object, number_object_to_float, parse_arg, parse_receiver,
+};
 use once_cell::sync::Lazy;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
@@ -24,13 +30,31 @@ impl KyaObjectTrait for StringObject {
     }
 }
 
-pub fn string_new(value: &str) -> KyaObjectRef {
+// The empty string and the single ASCII characters that indexing and
+// iterating over text produce are shared. `String()` builds its result in
+// place, so it allocates its own.
+static SHORT_STRINGS: Lazy<Vec<KyaObjectRef>> = Lazy::new(|| {
+    std::iter::once(String::new())
+        .chain((0..128u8).map(|byte| (byte as char).to_string()))
+        .map(|value| allocate_string(&value))
+        .collect()
+});
+
+fn allocate_string(value: &str) -> KyaObjectRef {
     KyaObject::from_string_object(StringObject {
         ob_type: STRING_TYPE.clone(),
         value: value.to_string(),
     })
 }
 
+pub fn string_new(value: &str) -> KyaObjectRef {
+    match value.as_bytes() {
+        [] => SHORT_STRINGS[0].clone(),
+        [byte] if byte.is_ascii() => SHORT_STRINGS[*byte as usize + 1].clone(),
+        _ => allocate_string(value),
+    }
+}
+
 pub fn string_tp_repr(
     callable: KyaObjectRef,
     _args: &mut Vec<KyaObjectRef>,
@@ -68,7 +92,7 @@ pub fn string_tp_init(
             return Err(Error::RuntimeError("Expected a string object".to_string()));
         }
 
-        Ok(NONE_OBJECT.clone())
+        Ok(kya_none())
     } else {
         Err(Error::RuntimeError("Expected a string object".to_string()))
     }
@@ -79,7 +103,7 @@ pub fn string_tp_new(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(string_new(""))
+    Ok(allocate_string(""))
 }
 
 pub fn string_length(
@@ -97,6 +121,23 @@ pub fn string_length(
     }
 }
 
+pub fn string_to_n(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    let _ = parse_arg(args, 0, 0)?;
+    let instance = parse_receiver(&receiver)?;
+
+    if let KyaObject::StringObject(string_object) = &*instance.lock().unwrap() {
+        Ok(number_new(
+            parse_number(&string_object.value).map_err(Error::ValueError)?,
+        ))
+    } else {
+        Err(Error::RuntimeError("Expected a string object".to_string()))
+    }
+}
+
 pub fn string_tp_hash(obj: KyaObjectRef) -> Result<usize, Error> {
     let mut hasher = DefaultHasher::new();
 
@@ -147,6 +188,8 @@ pub fn string_tp_compare(
 pub fn string_tp_add(obj1: KyaObjectRef, obj2: KyaObjectRef) -> Result<KyaObjectRef, Error> {
     if let KyaObject::StringObject(string1) = &*obj1.lock().unwrap() {
         if let KyaObject::StringObject(string2) = &*obj2.lock().unwrap() {
+            check(Limit::String, string1.value.len() + string2.value.len())?;
+
             let new_value = format!("{}{}", string1.value, string2.value);
 
             return Ok(string_new(&new_value));
@@ -188,62 +231,118 @@ pub fn string_char_at(
     }
 }
 
-pub fn string_split(
-    _callable: KyaObjectRef,
-    args: &mut Vec<KyaObjectRef>,
-    receiver: Option<KyaObjectRef>,
+fn parse_max_splits(args: &[KyaObjectRef]) -> Result<Option<usize>, Error> {
+    let Some(arg) = args.get(1) else {
+        return Ok(None);
+    };
+
+    let value = number_object_to_float(arg)
+        .map_err(|_| Error::TypeError("Expected a number for the max splits".to_string()))?;
+
+    Ok(if value < 0.0 {
+        None
+    } else {
+        Some(value as usize)
+    })
+}
+
+fn split_with(
+    args: &Vec<KyaObjectRef>,
+    receiver: &Option<KyaObjectRef>,
+    reverse: bool,
 ) -> Result<KyaObjectRef, Error> {
-    let separator = parse_arg(&args, 0, 1)?;
-    let instance = parse_receiver(&receiver)?;
+    let separator = parse_arg(args, 0, 2)?;
+    let max_splits = parse_max_splits(args)?;
+    let instance = parse_receiver(receiver)?;
+
+    let separator = if let KyaObject::StringObject(separator_string) = &*separator.lock().unwrap() {
+        separator_string.value.clone()
+    } else {
+        return Err(Error::TypeError("Expected a string".to_string()));
+    };
+
+    if separator.is_empty() {
+        return Err(Error::ValueError("Empty separator".to_string()));
+    }
 
     if let KyaObject::StringObject(string_object) = &*instance.lock().unwrap() {
-        if let KyaObject::StringObject(separator_string) = &*separator.lock().unwrap() {
-            let parts: Vec<KyaObjectRef> = string_object
-                .value
-                .split(&separator_string.value)
-                .map(|s| string_new(s))
-                .collect();
-
-            Ok(list_new(parts))
-        } else {
-            Err(Error::TypeError("Expected a string".to_string()))
+        let value = string_object.value.as_str();
+
+        let mut parts: Vec<&str> = match (max_splits, reverse) {
+            (None, _) => value.split(separator.as_str()).collect(),
+            (Some(n), false) => value.splitn(n + 1, separator.as_str()).collect(),
+            (Some(n), true) => value.rsplitn(n + 1, separator.as_str()).collect(),
+        };
+
+        if reverse && max_splits.is_some() {
+            parts.reverse();
         }
+
+        Ok(list_new(parts.into_iter().map(string_new).collect()))
     } else {
         Err(Error::RuntimeError("Expected a string object".to_string()))
     }
 }
 
-pub fn string_substr(
+pub fn string_split(
+    _callable: KyaObjectRef,
+    args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
+    split_with(args, &receiver, false)
+}
+
+pub fn string_rsplit(
     _callable: KyaObjectRef,
     args: &mut Vec<KyaObjectRef>,
     receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    let start = parse_arg(&args, 0, 1)?;
-    let end = parse_arg(&args, 1, 2)?;
+    split_with(args, &receiver, true)
+}
+
+pub fn string_splitlines(
+    _callable: KyaObjectRef,
+    _args: &mut Vec<KyaObjectRef>,
+    receiver: Option<KyaObjectRef>,
+) -> Result<KyaObjectRef, Error> {
     let instance = parse_receiver(&receiver)?;
 
     if let KyaObject::StringObject(string_object) = &*instance.lock().unwrap() {
-        if let (KyaObject::NumberObject(start_num), KyaObject::NumberObject(end_num)) =
-            (&*start.lock().unwrap(), &*end.lock().unwrap())
-        {
-            let start_idx = start_num.value as usize;
-            let end_idx = end_num.value as usize;
-
-            if start_idx <= end_idx && end_idx <= string_object.value.len() {
-                Ok(string_new(&string_object.value[start_idx..end_idx]))
+        let mut lines = Vec::new();
+        let mut rest = string_object.value.as_str();
+
+        while let Some(position) = rest.find(['\r', '\n']) {
+            lines.push(string_new(&rest[..position]));
+
+            let break_length = if rest[position..].starts_with("\r\n") {
+                2
             } else {
-                Err(Error::RuntimeError(format!(
-                    "Invalid substring range: {} to {} for string of length {}",
-                    start_idx,
-                    end_idx,
-                    string_object.value.len()
-                )))
-            }
-        } else {
-            Err(Error::TypeError(
-                "Expected numbers for start and end".to_string(),
-            ))
+                1
+            };
+
+            rest = &rest[position + break_length..];
+        }
+
+        if !rest.is_empty() {
+            lines.push(string_new(rest));
         }
+
+        Ok(list_new(lines))
+    } else {
+        Err(Error::RuntimeError("Expected a string object".to_string()))
+    }
+}
+
+pub fn string_sq_slice(obj: KyaObjectRef, range: &SliceRange) -> Result<KyaObjectRef, Error> {
+    if let KyaObject::StringObject(string_object) = &*obj.lock().unwrap() {
+        let chars: Vec<char> = string_object.value.chars().collect();
+        let sliced: String = range
+            .indices(chars.len())
+            .into_iter()
+            .map(|index| chars[index])
+            .collect();
+
+        Ok(string_new(&sliced))
     } else {
         Err(Error::RuntimeError("Expected a string object".to_string()))
     }
@@ -254,31 +353,39 @@ pub static STRING_TYPE: Lazy<TypeRef> = Lazy::new(|| {
 
     dict.lock()
         .unwrap()
-        .insert("length".to_string(), rs_function_new(string_length));
+        .insert("length".into(), rs_function_new(string_length));
 
     dict.lock()
         .unwrap()
-        .insert("char_at".to_string(), rs_function_new(string_char_at));
+        .insert("to_n".into(), rs_function_new(string_to_n));
 
     dict.lock()
         .unwrap()
-        .insert("split".to_string(), rs_function_new(string_split));
+        .insert("char_at".into(), rs_function_new(string_char_at));
 
     dict.lock()
         .unwrap()
-        .insert("substr".to_string(), rs_function_new(string_substr));
+        .insert("split".into(), rs_function_new(string_split));
 
     dict.lock()
         .unwrap()
-        .insert("concat".to_string(), rs_function_new(string_concat));
+        .insert("rsplit".into(), rs_function_new(string_rsplit));
 
     dict.lock()
         .unwrap()
-        .insert("strip".to_string(), rs_function_new(string_strip));
+        .insert("splitlines".into(), rs_function_new(string_splitlines));
 
     dict.lock()
         .unwrap()
-        .insert("encode".to_string(), rs_function_new(string_encode));
+        .insert("concat".into(), rs_function_new(string_concat));
+
+    dict.lock()
+        .unwrap()
+        .insert("strip".into(), rs_function_new(string_strip));
+
+    dict.lock()
+        .unwrap()
+        .insert("encode".into(), rs_function_new(string_encode));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -289,6 +396,7 @@ pub static STRING_TYPE: Lazy<TypeRef> = Lazy::new(|| {
         tp_compare: Some(string_tp_compare),
         tp_hash: Some(string_tp_hash),
         tp_add: Some(string_tp_add),
+        sq_slice: Some(string_sq_slice),
         dict: dict,
         ..Default::default()
     })
@@ -304,6 +412,11 @@ pub fn string_concat(
 
     if let KyaObject::StringObject(string_object) = &*instance.lock().unwrap() {
         if let KyaObject::StringObject(arg_string) = &*arg.lock().unwrap() {
+            check(
+                Limit::String,
+                string_object.value.len() + arg_string.value.len(),
+            )?;
+
             let new_value = format!("{}{}", string_object.value, arg_string.value);
             Ok(string_new(&new_value))
         } else {
@@ -337,6 +450,8 @@ pub fn string_encode(
     let instance = parse_receiver(&receiver)?;
 
     if let KyaObject::StringObject(string_object) = &*instance.lock().unwrap() {
+        check(Limit::Bytes, string_object.value.len())?;
+
         Ok(bytes_new(string_object.value.as_bytes().to_vec()))
     } else {
         Err(Error::RuntimeError("Expected a string object".to_string()))
@@ -346,6 +461,7 @@ pub fn string_encode(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gc::Gc;
 
     #[test]
     fn test_string_new() {
@@ -370,6 +486,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_short_strings_are_shared() {
+        assert!(Gc::ptr_eq(&string_new(""), &string_new("")));
+        assert!(Gc::ptr_eq(&string_new("a"), &string_new("a")));
+        assert!(!Gc::ptr_eq(&string_new("ab"), &string_new("ab")));
+        assert!(!Gc::ptr_eq(&string_new("é"), &string_new("é")));
+        assert!(Gc::ptr_eq(&number_new(256.0), &number_new(256.0)));
+        assert!(!Gc::ptr_eq(&number_new(0.5), &number_new(0.5)));
+        assert!(!Gc::ptr_eq(&number_new(-0.0), &number_new(0.0)));
+    }
+
     #[test]
     fn test_string_length() {
         let string = string_new("Hello, World!");
@@ -423,23 +550,68 @@ mod tests {
         }
     }
 
+    fn list_values(list: KyaObjectRef) -> Vec<String> {
+        if let KyaObject::ListObject(list_object) = &*list.lock().unwrap() {
+            list_object
+                .items
+                .iter()
+                .map(|item| crate::objects::utils::string_object_to_string(item).unwrap())
+                .collect()
+        } else {
+            panic!("Expected a ListObject");
+        }
+    }
+
     #[test]
-    fn test_string_substr() {
-        let string = string_new("Hello, World!");
-        let substr_result = string_substr(
+    fn test_string_split_max_splits() {
+        let string = string_new("Host: example.com:8080");
+
+        let split_result = string_split(
+            string.clone(),
+            &mut vec![string_new(":"), number_new(1.0)],
+            Some(string.clone()),
+        )
+        .unwrap();
+        assert_eq!(list_values(split_result), vec!["Host", " example.com:8080"]);
+
+        let rsplit_result = string_rsplit(
             string.clone(),
-            &mut vec![number_new(7.0), number_new(12.0)],
+            &mut vec![string_new(":"), number_new(1.0)],
             Some(string.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            list_values(rsplit_result),
+            vec!["Host: example.com", "8080"]
+        );
+    }
+
+    #[test]
+    fn test_string_splitlines() {
+        let string = string_new("GET / HTTP/1.1\r\nHost: a\n\rlast\r\n");
+        let lines = string_splitlines(string.clone(), &mut vec![], Some(string.clone())).unwrap();
+
+        assert_eq!(
+            list_values(lines),
+            vec!["GET / HTTP/1.1", "Host: a", "", "last"]
         );
+    }
 
-        assert!(substr_result.is_ok());
-        if let Ok(substr_obj) = substr_result {
-            if let KyaObject::StringObject(string_object) = &*substr_obj.lock().unwrap() {
-                assert_eq!(string_object.value, "World");
-            } else {
-                panic!("Expected a StringObject");
-            }
-        }
+    #[test]
+    fn test_string_sq_slice() {
+        use crate::objects::utils::string_object_to_string;
+
+        let string = string_new("Hello, World!");
+        let slice = |start, end, step| {
+            let range = SliceRange::new(start, end, step).unwrap();
+            string_object_to_string(&string_sq_slice(string.clone(), &range).unwrap()).unwrap()
+        };
+
+        assert_eq!(slice(Some(7), Some(12), 1), "World");
+        assert_eq!(slice(Some(-6), None, 1), "World!");
+        assert_eq!(slice(None, Some(5), 2), "Hlo");
+        assert_eq!(slice(None, None, -1), "!dlroW ,olleH");
+        assert_eq!(slice(Some(20), Some(30), 1), "");
     }
 
     #[test]
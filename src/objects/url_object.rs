@@ -1,8 +1,8 @@
 use crate::errors::Error;
-use crate::interpreter::NONE_OBJECT;
 use crate::objects::base::{KyaObject, KyaObjectRef, KyaObjectTrait, Type, TypeRef, BASE_TYPE};
 use crate::objects::number_object::number_new;
 use crate::objects::rs_function_object::rs_function_new;
+use crate::objects::singletons::kya_none;
 use crate::objects::string_object::string_new;
 use crate::objects::utils::{parse_arg, parse_receiver};
 use once_cell::sync::Lazy;
@@ -33,7 +33,7 @@ pub fn url_tp_init(
     _args: &mut Vec<KyaObjectRef>,
     _receiver: Option<KyaObjectRef>,
 ) -> Result<KyaObjectRef, Error> {
-    Ok(NONE_OBJECT.clone())
+    Ok(kya_none())
 }
 
 pub fn url_tp_new(
@@ -121,7 +121,7 @@ pub fn url_port(
         if let Some(port) = obj.url.port() {
             Ok(number_new(port as f64))
         } else {
-            Ok(NONE_OBJECT.clone())
+            Ok(kya_none())
         }
     } else {
         Err(Error::TypeError(
@@ -171,27 +171,27 @@ pub static URL_TYPE: Lazy<TypeRef> = Lazy::new(|| {
 
     dict.lock()
         .unwrap()
-        .insert("parse".to_string(), rs_function_new(url_parse));
+        .insert("parse".into(), rs_function_new(url_parse));
 
     dict.lock()
         .unwrap()
-        .insert("scheme".to_string(), rs_function_new(url_scheme));
+        .insert("scheme".into(), rs_function_new(url_scheme));
 
     dict.lock()
         .unwrap()
-        .insert("host".to_string(), rs_function_new(url_host));
+        .insert("host".into(), rs_function_new(url_host));
 
     dict.lock()
         .unwrap()
-        .insert("port".to_string(), rs_function_new(url_port));
+        .insert("port".into(), rs_function_new(url_port));
 
     dict.lock()
         .unwrap()
-        .insert("path".to_string(), rs_function_new(url_path));
+        .insert("path".into(), rs_function_new(url_path));
 
     dict.lock()
         .unwrap()
-        .insert("query".to_string(), rs_function_new(url_query));
+        .insert("query".into(), rs_function_new(url_query));
 
     Type::as_ref(Type {
         ob_type: Some(BASE_TYPE.clone()),
@@ -1,6 +1,6 @@
 use crate::errors::Error;
-use crate::interpreter::{FALSE_OBJECT, NONE_OBJECT, TRUE_OBJECT};
 use crate::objects::base::{kya_nb_bool, kya_repr, KyaObject, KyaObjectRef};
+use crate::objects::singletons::{kya_false, kya_none, kya_true};
 
 pub fn parse_arg(
     args: &Vec<KyaObjectRef>,
@@ -8,7 +8,7 @@ pub fn parse_arg(
     args_count: usize,
 ) -> Result<KyaObjectRef, Error> {
     if args_count == 0 && args.is_empty() {
-        return Ok(NONE_OBJECT.clone());
+        return Ok(kya_none());
     }
 
     if index >= args_count {
@@ -45,9 +45,9 @@ pub fn number_object_to_float(obj: &KyaObjectRef) -> Result<f64, Error> {
 
 pub fn bool_to_bool_object(value: bool) -> KyaObjectRef {
     if value {
-        TRUE_OBJECT.clone()
+        kya_true()
     } else {
-        FALSE_OBJECT.clone()
+        kya_false()
     }
 }
 
@@ -1,13 +1,26 @@
-use crate::bytecode::{ComparisonOperator, Operator};
+use crate::bytecode::{
+    ComparisonOperator, Operator, SLICE_HAS_END, SLICE_HAS_START, SLICE_HAS_STEP,
+};
 use crate::errors::Error;
-use crate::interpreter::{eval_frame, Frame};
+use crate::extensions;
+use crate::gc::Gc;
+use crate::importer;
+use crate::instrument;
+use crate::interpreter::{eval_frame, EnsureBlock, Frame};
 use crate::objects::base::{
-    kya_add, kya_call, kya_compare, kya_set_attr, kya_sub, KyaObject, Type, BASE_TYPE,
+    generic_set_attr, kya_add, kya_call, kya_compare, kya_exit, kya_get_attr, kya_get_method,
+    kya_set_attr, kya_sq_slice, kya_sub, KyaObject, KyaObjectRef, SliceRange, Type, BASE_TYPE,
 };
 use crate::objects::class_object::class_new;
 use crate::objects::function_object::function_new;
-use crate::objects::utils::kya_is_false;
+use crate::objects::instance_object::{get_slot, set_slot};
+use crate::objects::none_object::none_new;
+use crate::objects::singletons::canonicalize;
+use crate::objects::utils::{bool_to_bool_object, kya_is_false, number_object_to_float};
+use crate::runtime;
+use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 pub static OPCODE_HANDLERS: &[fn(&mut Frame) -> Result<(), Error>] = &[
@@ -27,26 +40,46 @@ pub static OPCODE_HANDLERS: &[fn(&mut Frame) -> Result<(), Error>] = &[
     op_return,
     op_raise,
     op_bin_op,
+    op_slice,
+    op_setup_ensure,
+    op_pop_block,
+    op_end_ensure,
+    op_exit_resource,
+    op_probe,
+    op_load_fast,
+    op_store_fast,
+    op_load_method,
+    op_call_method,
+    op_is,
+    op_import,
+    op_load_slot,
+    op_store_slot,
 ];
 
+// Stands in for the receiver on the stack when `LoadMethod` finds something
+// other than a function, which is then called without one.
+static NO_RECEIVER: Lazy<KyaObjectRef> =
+    Lazy::new(|| none_new().expect("Failed to create the no-receiver marker"));
+
 fn op_load_const(frame: &mut Frame) -> Result<(), Error> {
-    let const_index = frame.next_opcode() as usize;
+    let const_index = frame.next_wide_operand();
     let const_value = frame.get_const(const_index).ok_or_else(|| {
         Error::RuntimeError(format!("Constant at index {} not found", const_index))
     })?;
 
-    frame.push_stack(const_value.clone());
+    // Folded comparisons hold the bools of the runtime that compiled them.
+    frame.push_stack(canonicalize(const_value.clone()));
 
     Ok(())
 }
 
 fn op_load_name(frame: &mut Frame) -> Result<(), Error> {
-    let name_index = frame.next_opcode() as usize;
+    let name_index = frame.next_wide_operand();
     let name = frame
         .get_name(name_index)
         .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
 
-    let object = frame.resolve(&name)?;
+    let object = frame.resolve(name)?;
 
     frame.push_stack(object);
 
@@ -54,18 +87,34 @@ fn op_load_name(frame: &mut Frame) -> Result<(), Error> {
 }
 
 fn op_store_name(frame: &mut Frame) -> Result<(), Error> {
-    let name_index = frame.next_opcode() as usize;
+    let name_index = frame.next_wide_operand();
     let name = frame
         .get_name(name_index)
         .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
 
     let value = frame.pop_stack()?;
 
-    frame.register_local(&name, value.clone());
+    frame.register_local(name, value.clone());
 
     Ok(())
 }
 
+fn op_load_fast(frame: &mut Frame) -> Result<(), Error> {
+    let index = frame.next_wide_operand();
+    let object = frame.load_fast(index)?;
+
+    frame.push_stack(object);
+
+    Ok(())
+}
+
+fn op_store_fast(frame: &mut Frame) -> Result<(), Error> {
+    let index = frame.next_wide_operand();
+    let value = frame.pop_stack()?;
+
+    frame.store_fast(index, value)
+}
+
 fn op_call(frame: &mut Frame) -> Result<(), Error> {
     let args_count = frame.next_opcode() as usize;
 
@@ -85,6 +134,43 @@ fn op_call(frame: &mut Frame) -> Result<(), Error> {
     Ok(())
 }
 
+// Pushes the callable for `obj.name(...)` and then the receiver to call it
+// with, or `NO_RECEIVER`.
+fn op_load_method(frame: &mut Frame) -> Result<(), Error> {
+    let instance = frame.pop_stack()?;
+    let name_index = frame.next_wide_operand();
+    let name = frame.get_name(name_index).ok_or_else(|| {
+        Error::RuntimeError(format!("Attribute at index {} not defined", name_index))
+    })?;
+
+    let (callable, receiver) = kya_get_method(instance, name)?;
+
+    frame.push_stack(callable);
+    frame.push_stack(receiver.unwrap_or_else(|| NO_RECEIVER.clone()));
+
+    Ok(())
+}
+
+fn op_call_method(frame: &mut Frame) -> Result<(), Error> {
+    let args_count = frame.next_opcode() as usize;
+
+    let mut args = Vec::with_capacity(args_count);
+
+    for _ in 0..args_count {
+        args.push(frame.pop_stack()?);
+    }
+
+    let mut args = args.into_iter().rev().collect::<Vec<_>>();
+
+    let receiver = Some(frame.pop_stack()?).filter(|receiver| !Gc::ptr_eq(receiver, &NO_RECEIVER));
+    let callable = frame.pop_stack()?;
+    let result = kya_call(callable, &mut args, receiver)?;
+
+    frame.push_stack(result);
+
+    Ok(())
+}
+
 fn op_pop_top(frame: &mut Frame) -> Result<(), Error> {
     frame.pop_stack()?;
     Ok(())
@@ -115,7 +201,7 @@ pub fn op_load_attr(frame: &mut Frame) -> Result<(), Error> {
     let tp_get_attr = instance_type.lock().unwrap().tp_get_attr;
 
     if let Some(get_attr_fn) = tp_get_attr {
-        let attr_name_index = frame.next_opcode() as usize;
+        let attr_name_index = frame.next_wide_operand();
         let attr_name = frame.get_name(attr_name_index).ok_or_else(|| {
             Error::RuntimeError(format!(
                 "Attribute at index {} not defined",
@@ -150,8 +236,62 @@ pub fn op_compare(frame: &mut Frame) -> Result<(), Error> {
     Ok(())
 }
 
+// Compares identity, not value. The operand is 1 for `is not`.
+pub fn op_is(frame: &mut Frame) -> Result<(), Error> {
+    let right = frame.pop_stack()?;
+    let left = frame.pop_stack()?;
+    let negated = frame.next_opcode() != 0;
+
+    frame.push_stack(bool_to_bool_object(Gc::ptr_eq(&left, &right) != negated));
+
+    Ok(())
+}
+
+// Pushes the module named by the operand: the builtin or registered module
+// of that name, or else the first source file or native extension of that
+// name found in the search paths.
+pub fn op_import(frame: &mut Frame) -> Result<(), Error> {
+    let name_index = frame.next_wide_operand();
+    let name = frame
+        .get_name(name_index)
+        .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
+
+    let search_paths = runtime::current().search_paths();
+    let components: Vec<&str> = name.as_str().split('.').collect();
+
+    // `import net.http` binds `net`, with `http` as an attribute of it.
+    let top = frame
+        .resolve(components[0])
+        .ok()
+        .filter(|object| matches!(&*object.lock().unwrap(), KyaObject::ModuleObject(_)));
+    let top = match top {
+        Some(module) => module,
+        None => import_module(components[0], &search_paths)?,
+    };
+    let mut parent = top.clone();
+
+    for index in 1..components.len() {
+        let module = import_module(&components[..=index].join("."), &search_paths)?;
+
+        kya_set_attr(parent, components[index], module.clone())?;
+        parent = module;
+    }
+
+    frame.push_stack(top);
+
+    Ok(())
+}
+
+fn import_module(name: &str, search_paths: &[PathBuf]) -> Result<KyaObjectRef, Error> {
+    if let Some(module) = importer::load(name, search_paths)? {
+        return Ok(module);
+    }
+
+    extensions::load(name, search_paths)?.ok_or_else(|| importer::not_found(name, search_paths))
+}
+
 pub fn op_jump_back(frame: &mut Frame) -> Result<(), Error> {
-    let jump_offset = frame.next_opcode() as usize;
+    let jump_offset = frame.next_wide_operand();
     let current_pc = frame.current_pc();
 
     frame.set_pc(current_pc - jump_offset);
@@ -161,7 +301,7 @@ pub fn op_jump_back(frame: &mut Frame) -> Result<(), Error> {
 
 pub fn op_pop_and_jump_if_false(frame: &mut Frame) -> Result<(), Error> {
     let condition = frame.pop_stack()?;
-    let jump = frame.next_opcode() as usize;
+    let jump = frame.next_wide_operand();
 
     if kya_is_false(condition.clone())? {
         frame.set_pc(jump);
@@ -171,7 +311,7 @@ pub fn op_pop_and_jump_if_false(frame: &mut Frame) -> Result<(), Error> {
 }
 
 pub fn op_jump(frame: &mut Frame) -> Result<(), Error> {
-    let target_pc = frame.next_opcode() as usize;
+    let target_pc = frame.next_wide_operand();
 
     frame.set_pc(target_pc);
 
@@ -186,12 +326,15 @@ pub fn op_make_class(frame: &mut Frame) -> Result<(), Error> {
 
         let mut frame_ref = Frame {
             locals: Arc::new(Mutex::new(locals)),
+            fast_locals: vec![],
             globals: frame.globals.clone(),
             code: c.code.clone(),
             pc: 0,
             stack: vec![],
             return_value: None,
             error: None,
+            blocks: vec![],
+            pending_error: None,
         };
 
         let _ = eval_frame(&mut frame_ref);
@@ -199,7 +342,10 @@ pub fn op_make_class(frame: &mut Frame) -> Result<(), Error> {
         let class_type = Type::as_ref(Type {
             ob_type: Some(BASE_TYPE.clone()),
             name: c.code.name.clone(),
+            tp_set_attr: Some(generic_set_attr),
             dict: frame_ref.locals.clone(),
+            slots: c.code.slots.as_deref().map(Arc::from),
+            instance_size: c.code.instance_size,
             ..Default::default()
         });
 
@@ -217,12 +363,51 @@ pub fn op_make_class(frame: &mut Frame) -> Result<(), Error> {
 pub fn op_store_attr(frame: &mut Frame) -> Result<(), Error> {
     let instance = frame.pop_stack()?;
     let value = frame.pop_stack()?;
-    let name_index = frame.next_opcode() as usize;
+    let name_index = frame.next_wide_operand();
     let name = frame
         .get_name(name_index)
         .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
 
-    kya_set_attr(instance.clone(), name.clone(), value.clone())?;
+    kya_set_attr(instance.clone(), name, value.clone())?;
+
+    frame.push_stack(value);
+
+    Ok(())
+}
+
+// `self.name` in a class declared with slots, compiled with the index of the
+// name among them. An instance laid out otherwise, like one of a subclass,
+// is looked up by name.
+fn op_load_slot(frame: &mut Frame) -> Result<(), Error> {
+    let instance = frame.pop_stack()?;
+    let name_index = frame.next_wide_operand();
+    let slot = frame.next_opcode() as usize;
+    let name = frame
+        .get_name(name_index)
+        .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
+
+    let result = match get_slot(&instance, slot, name) {
+        Some(value) => value,
+        None => kya_get_attr(instance, name)?,
+    };
+
+    frame.push_stack(result);
+
+    Ok(())
+}
+
+fn op_store_slot(frame: &mut Frame) -> Result<(), Error> {
+    let instance = frame.pop_stack()?;
+    let value = frame.pop_stack()?;
+    let name_index = frame.next_wide_operand();
+    let slot = frame.next_opcode() as usize;
+    let name = frame
+        .get_name(name_index)
+        .ok_or_else(|| Error::RuntimeError(format!("Name at index {} not defined", name_index)))?;
+
+    if !set_slot(&instance, slot, name, value.clone()) {
+        kya_set_attr(instance, name, value.clone())?;
+    }
 
     frame.push_stack(value);
 
@@ -238,6 +423,11 @@ pub fn op_return(frame: &mut Frame) -> Result<(), Error> {
 }
 
 pub fn op_raise(frame: &mut Frame) -> Result<(), Error> {
+    let cause = if frame.next_opcode() != 0 {
+        Some(frame.pop_stack()?)
+    } else {
+        None
+    };
     let exception = frame.pop_stack()?;
 
     if !matches!(*exception.lock().unwrap(), KyaObject::ExceptionObject(_)) {
@@ -247,11 +437,83 @@ pub fn op_raise(frame: &mut Frame) -> Result<(), Error> {
         )));
     }
 
+    if let Some(cause) = cause {
+        let cause = match &*cause.lock().unwrap() {
+            KyaObject::ExceptionObject(_) => Some(cause.clone()),
+            KyaObject::NoneObject(_) => None,
+            object => {
+                return Err(Error::TypeError(format!(
+                    "Exception cause must be an Exception or None, not '{}'",
+                    object.get_type()?.lock().unwrap().name
+                )))
+            }
+        };
+
+        if cause
+            .as_ref()
+            .is_some_and(|cause| Gc::ptr_eq(cause, &exception))
+        {
+            return Err(Error::ValueError(
+                "An exception cannot be its own cause".to_string(),
+            ));
+        }
+
+        if let KyaObject::ExceptionObject(exception) = &mut *exception.lock().unwrap() {
+            exception.cause = cause;
+        }
+    }
+
     frame.set_error(Some(exception.clone()));
 
     Ok(())
 }
 
+pub fn op_setup_ensure(frame: &mut Frame) -> Result<(), Error> {
+    let handler = frame.next_wide_operand();
+    let stack_depth = frame.stack.len();
+
+    frame.blocks.push(EnsureBlock {
+        handler,
+        stack_depth,
+    });
+
+    Ok(())
+}
+
+pub fn op_pop_block(frame: &mut Frame) -> Result<(), Error> {
+    frame
+        .blocks
+        .pop()
+        .ok_or_else(|| Error::RuntimeError("No ensure block to pop".to_string()))?;
+
+    Ok(())
+}
+
+pub fn op_end_ensure(frame: &mut Frame) -> Result<(), Error> {
+    match frame.pending_error.take() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+pub fn op_exit_resource(frame: &mut Frame) -> Result<(), Error> {
+    let resource = frame.pop_stack()?;
+
+    kya_exit(resource)
+}
+
+pub fn op_probe(frame: &mut Frame) -> Result<(), Error> {
+    let probe_index = frame.next_wide_operand();
+    let probe =
+        frame.code.probes.get(probe_index).copied().ok_or_else(|| {
+            Error::RuntimeError(format!("Probe at index {} not found", probe_index))
+        })?;
+
+    instrument::fire(probe);
+
+    Ok(())
+}
+
 pub fn op_bin_op(frame: &mut Frame) -> Result<(), Error> {
     let right = frame.pop_stack()?;
     let left = frame.pop_stack()?;
@@ -268,3 +530,37 @@ pub fn op_bin_op(frame: &mut Frame) -> Result<(), Error> {
 
     Ok(())
 }
+
+fn slice_bound(value: KyaObjectRef) -> Result<i64, Error> {
+    let number = number_object_to_float(&value)
+        .map_err(|_| Error::TypeError("Slice bounds must be numbers".to_string()))?;
+
+    Ok(number as i64)
+}
+
+pub fn op_slice(frame: &mut Frame) -> Result<(), Error> {
+    let flags = frame.next_opcode();
+
+    let step = if flags & SLICE_HAS_STEP != 0 {
+        slice_bound(frame.pop_stack()?)?
+    } else {
+        1
+    };
+    let end = if flags & SLICE_HAS_END != 0 {
+        Some(slice_bound(frame.pop_stack()?)?)
+    } else {
+        None
+    };
+    let start = if flags & SLICE_HAS_START != 0 {
+        Some(slice_bound(frame.pop_stack()?)?)
+    } else {
+        None
+    };
+    let value = frame.pop_stack()?;
+
+    let result = kya_sq_slice(value, &SliceRange::new(start, end, step)?)?;
+
+    frame.push_stack(result);
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+//! `ConstantBranches` removes the branches that a constant condition makes
+//! dead before the program is compiled:
+//!
+//! - `if` with a true condition is replaced by its body, and one with a false
+//!   condition is dropped.
+//! - `while` with a false condition is dropped. One with a true condition
+//!   keeps its body but loses the test that guards each iteration, so it only
+//!   ends through `break`, `return` or an error.
+//!
+//! The constant conditions are the expressions `evaluate` can compute, where
+//! only `0` is false, and `true` and `false`, unless the program assigns to
+//! either name.
+
+use crate::ast::{ASTNode, BinOp, Block, Compare, Operator, UnaryOp, While};
+use crate::errors::Error;
+use crate::lexer::TokenType;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::number_object::number_new;
+use crate::objects::string_object::string_new;
+use crate::objects::utils::bool_to_bool_object;
+use crate::visitor::CompilerPass;
+
+use std::cmp::Ordering;
+
+/// The value of an expression that can be computed while compiling.
+#[derive(Debug, PartialEq)]
+pub enum Constant {
+    Number(f64),
+    String(String),
+    Bool(bool),
+}
+
+impl Constant {
+    pub fn to_object(&self) -> KyaObjectRef {
+        match self {
+            Constant::Number(value) => number_new(*value),
+            Constant::String(value) => string_new(value),
+            Constant::Bool(value) => bool_to_bool_object(*value),
+        }
+    }
+}
+
+/// Computes literals and the arithmetic, concatenations and comparisons made
+/// only of them, the same way the interpreter would at run time.
+pub fn evaluate(node: &ASTNode) -> Option<Constant> {
+    match node {
+        ASTNode::NumberLiteral(value) => Some(Constant::Number(*value)),
+        ASTNode::StringLiteral(value) => Some(Constant::String(value.clone())),
+        ASTNode::UnaryOp(unary_op) => evaluate_unary_op(unary_op),
+        ASTNode::BinOp(bin_op) => evaluate_bin_op(bin_op),
+        ASTNode::Compare(compare) => evaluate_compare(compare),
+        _ => None,
+    }
+}
+
+pub fn evaluate_unary_op(unary_op: &UnaryOp) -> Option<Constant> {
+    match (&unary_op.operator, evaluate(&unary_op.operand)?) {
+        (TokenType::Plus, Constant::Number(value)) => Some(Constant::Number(value)),
+        // Compiled as `0 - value`, which decides the sign of a zero result.
+        (TokenType::Minus, Constant::Number(value)) => Some(Constant::Number(0.0 - value)),
+        _ => None,
+    }
+}
+
+pub fn evaluate_bin_op(bin_op: &BinOp) -> Option<Constant> {
+    match (
+        &bin_op.operator,
+        evaluate(&bin_op.left)?,
+        evaluate(&bin_op.right)?,
+    ) {
+        (Operator::Plus, Constant::Number(a), Constant::Number(b)) => Some(Constant::Number(a + b)),
+        (Operator::Minus, Constant::Number(a), Constant::Number(b)) => {
+            Some(Constant::Number(a - b))
+        }
+        (Operator::Plus, Constant::String(a), Constant::String(b)) => {
+            Some(Constant::String(a + &b))
+        }
+        _ => None,
+    }
+}
+
+pub fn evaluate_compare(compare: &Compare) -> Option<Constant> {
+    let ordering = match (evaluate(&compare.left)?, evaluate(&compare.right)?) {
+        (Constant::Number(a), Constant::Number(b)) => a.partial_cmp(&b),
+        (Constant::String(a), Constant::String(b)) => a.partial_cmp(&b),
+        _ => return None,
+    };
+
+    let result = match compare.operator {
+        Operator::Equal => ordering.is_some_and(Ordering::is_eq),
+        Operator::Neq => !ordering.is_some_and(Ordering::is_eq),
+        Operator::Gt => ordering.is_some_and(Ordering::is_gt),
+        Operator::Lt => ordering.is_some_and(Ordering::is_lt),
+        Operator::Gte => ordering.is_some_and(Ordering::is_ge),
+        Operator::Lte => ordering.is_some_and(Ordering::is_le),
+        _ => return None,
+    };
+
+    Some(Constant::Bool(result))
+}
+
+const BOOLEAN_NAMES: [&str; 2] = ["true", "false"];
+
+#[derive(Default)]
+pub struct ConstantBranches {
+    // Names from `BOOLEAN_NAMES` that the program rebinds.
+    rebound: Vec<String>,
+}
+
+impl ConstantBranches {
+    pub fn new() -> Self {
+        ConstantBranches { rebound: vec![] }
+    }
+
+    fn constant_value(&self, condition: &ASTNode) -> Option<bool> {
+        if let ASTNode::Identifier(identifier) = condition {
+            return Some(identifier.name == "true").filter(|_| {
+                BOOLEAN_NAMES.contains(&identifier.name.as_str())
+                    && !self
+                        .rebound
+                        .iter()
+                        .any(|name| identifier.name == name.as_str())
+            });
+        }
+
+        match evaluate(condition)? {
+            Constant::Number(value) => Some(value != 0.0),
+            Constant::Bool(value) => Some(value),
+            Constant::String(_) => None,
+        }
+    }
+
+    fn rewrite(&self, node: ASTNode) -> ASTNode {
+        match node {
+            ASTNode::Module(mut module) => {
+                module.block = Box::new(self.rewrite(*module.block));
+
+                ASTNode::Module(module)
+            }
+            ASTNode::Block(block) => {
+                let mut rewritten = Block::new(vec![]);
+
+                for (index, statement) in block.statements.into_iter().enumerate() {
+                    let line = block.lines.get(index).copied().unwrap_or(0);
+
+                    for (line, statement) in self.rewrite_statement(*statement, line) {
+                        rewritten.statements.push(Box::new(statement));
+                        rewritten.lines.push(line);
+                    }
+                }
+
+                ASTNode::Block(rewritten)
+            }
+            ASTNode::MethodDef(mut method_def) => {
+                method_def.body = Box::new(self.rewrite(*method_def.body));
+
+                ASTNode::MethodDef(method_def)
+            }
+            ASTNode::ClassDef(mut class_def) => {
+                class_def.body = Box::new(self.rewrite(*class_def.body));
+
+                ASTNode::ClassDef(class_def)
+            }
+            ASTNode::Try(mut try_node) => {
+                try_node.body = Box::new(self.rewrite(*try_node.body));
+                try_node.ensure = Box::new(self.rewrite(*try_node.ensure));
+
+                ASTNode::Try(try_node)
+            }
+            ASTNode::With(mut with) => {
+                with.body = Box::new(self.rewrite(*with.body));
+
+                ASTNode::With(with)
+            }
+            ASTNode::If(mut if_node) => {
+                if_node.body = Box::new(self.rewrite(*if_node.body));
+
+                ASTNode::If(if_node)
+            }
+            ASTNode::While(mut while_node) => {
+                while_node.body = Box::new(self.rewrite(*while_node.body));
+
+                ASTNode::While(while_node)
+            }
+            node => node,
+        }
+    }
+
+    // Rewrites a statement of a block, which starts on `line`, into the
+    // statements that replace it and their lines.
+    fn rewrite_statement(&self, statement: ASTNode, line: usize) -> Vec<(usize, ASTNode)> {
+        let statement = self.rewrite(statement);
+
+        match statement {
+            ASTNode::If(if_node) => match self.constant_value(&if_node.test) {
+                Some(true) => match *if_node.body {
+                    ASTNode::Block(block) => {
+                        let lines = block.lines;
+
+                        block
+                            .statements
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, statement)| {
+                                (lines.get(index).copied().unwrap_or(0), *statement)
+                            })
+                            .collect()
+                    }
+                    body => vec![(line, body)],
+                },
+                Some(false) => vec![],
+                None => vec![(line, ASTNode::If(if_node))],
+            },
+            ASTNode::While(while_node) => match self.constant_value(&while_node.condition) {
+                Some(true) => vec![(
+                    line,
+                    ASTNode::While(While::new(
+                        Box::new(ASTNode::NumberLiteral(1.0)),
+                        while_node.body,
+                    )),
+                )],
+                Some(false) => vec![],
+                None => vec![(line, ASTNode::While(while_node))],
+            },
+            statement => vec![(line, statement)],
+        }
+    }
+}
+
+fn bind(name: &str, rebound: &mut Vec<String>) {
+    if BOOLEAN_NAMES.contains(&name) && !rebound.iter().any(|bound| bound == name) {
+        rebound.push(name.to_string());
+    }
+}
+
+// Records which of `BOOLEAN_NAMES` are bound anywhere in the program.
+fn find_rebound(node: &ASTNode, rebound: &mut Vec<String>) {
+    match node {
+        ASTNode::Module(module) => find_rebound(&module.block, rebound),
+        ASTNode::Block(block) => {
+            for statement in &block.statements {
+                find_rebound(statement, rebound);
+            }
+        }
+        ASTNode::Assignment(assignment) => {
+            if let ASTNode::Identifier(identifier) = &*assignment.name {
+                bind(identifier.name.as_str(), rebound);
+            }
+        }
+        ASTNode::MethodDef(method_def) => {
+            bind(&method_def.name, rebound);
+
+            for parameter in &method_def.parameters {
+                if let ASTNode::Identifier(identifier) = &**parameter {
+                    bind(identifier.name.as_str(), rebound);
+                }
+            }
+
+            find_rebound(&method_def.body, rebound);
+        }
+        ASTNode::ClassDef(class_def) => {
+            bind(&class_def.name, rebound);
+            find_rebound(&class_def.body, rebound);
+        }
+        ASTNode::With(with) => {
+            if let Some(name) = &with.name {
+                bind(name, rebound);
+            }
+
+            find_rebound(&with.body, rebound);
+        }
+        ASTNode::Try(try_node) => {
+            find_rebound(&try_node.body, rebound);
+            find_rebound(&try_node.ensure, rebound);
+        }
+        ASTNode::If(if_node) => find_rebound(&if_node.body, rebound),
+        ASTNode::While(while_node) => find_rebound(&while_node.body, rebound),
+        _ => {}
+    }
+}
+
+impl CompilerPass for ConstantBranches {
+    fn transform_ast(&mut self, ast: ASTNode) -> Result<ASTNode, Error> {
+        self.rebound.clear();
+        find_rebound(&ast, &mut self.rebound);
+
+        Ok(self.rewrite(ast))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> ASTNode {
+        Parser::new(Lexer::new(source.to_string())).parse().unwrap()
+    }
+
+    fn optimize(source: &str) -> ASTNode {
+        ConstantBranches::new()
+            .transform_ast(parse(source))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_constant_branches() {
+        assert_eq!(
+            optimize(
+                "if true\n    a = 1\nend\nif 0\n    b = 2\nend\nwhile false\n    c = 3\nend\nif 1 > 2\n    d = 4\nend\n"
+            ),
+            parse("a = 1\n")
+        );
+        assert_eq!(
+            optimize("def f()\n    while true\n        break\n    end\nend\n"),
+            parse("def f()\n    while 1\n        break\n    end\nend\n")
+        );
+
+        let source = "true = 0\nif true\n    a = 1\nend\n";
+        assert_eq!(optimize(source), parse(source));
+    }
+}
@@ -1,7 +1,10 @@
 use crate::ast;
+use crate::diagnostics::Diagnostic;
 use crate::errors::Error;
+use crate::internal::number::parse_number;
 use crate::lexer::Lexer;
 use crate::lexer::{Token, TokenType};
+use crate::symbol::Symbol;
 
 pub struct Parser {
     lexer: Lexer,
@@ -17,7 +20,7 @@ impl Parser {
     }
 
     pub fn parse(&mut self) -> Result<ast::ASTNode, Error> {
-        self.next_token().unwrap();
+        self.next_token()?;
 
         let block = self.parse_block()?;
 
@@ -25,37 +28,52 @@ impl Parser {
     }
 
     fn parse_block(&mut self) -> Result<Box<ast::ASTNode>, Error> {
-        let mut statements = Vec::new();
+        let mut block = ast::Block::new(vec![]);
 
         while self.current_token.is_some() {
-            match self.parse_statement() {
-                Ok(statement) => statements.push(statement),
-                Err(e) => return Err(e),
-            }
+            self.push_statement(&mut block)?;
         }
 
-        Ok(Box::new(ast::ASTNode::Block(ast::Block { statements })))
+        Ok(Box::new(ast::ASTNode::Block(block)))
     }
 
-    fn parse_statement(&mut self) -> Result<Box<ast::ASTNode>, Error> {
-        self.skip_newlines();
+    // Parses a statement into `block`, along with the line it starts on.
+    fn push_statement(&mut self, block: &mut ast::Block) -> Result<(), Error> {
+        self.skip_newlines()?;
+
+        let line = self.peek().map_or(0, |token| token.line);
 
-        let stmt = if self.accept(TokenType::Def).is_some() {
-            self.parse_method_def()?
-        } else if self.accept(TokenType::Class).is_some() {
-            self.parse_class_def()?
-        } else if self.accept(TokenType::If).is_some() {
+        block.statements.push(self.parse_statement()?);
+        block.lines.push(line);
+
+        Ok(())
+    }
+
+    fn parse_statement(&mut self) -> Result<Box<ast::ASTNode>, Error> {
+        self.skip_newlines()?;
+
+        let stmt = if let Some(token) = self.accept(TokenType::Def)? {
+            let doc = self.lexer.take_doc_comment(token.line);
+            self.parse_method_def(doc)?
+        } else if let Some(token) = self.accept(TokenType::Class)? {
+            let doc = self.lexer.take_doc_comment(token.line);
+            self.parse_class_def(doc)?
+        } else if self.accept(TokenType::If)?.is_some() {
             self.parse_if_statement()?
-        } else if self.accept(TokenType::Import).is_some() {
+        } else if self.accept(TokenType::Import)?.is_some() {
             self.parse_import()?
-        } else if self.accept(TokenType::While).is_some() {
+        } else if self.accept(TokenType::While)?.is_some() {
             self.parse_while()?
-        } else if self.accept(TokenType::Break).is_some() {
+        } else if self.accept(TokenType::Break)?.is_some() {
             Box::new(ast::ASTNode::Break())
-        } else if self.accept(TokenType::Return).is_some() {
+        } else if self.accept(TokenType::Return)?.is_some() {
             self.parse_return()?
-        } else if self.accept(TokenType::Raise).is_some() {
+        } else if self.accept(TokenType::Raise)?.is_some() {
             self.parse_raise()?
+        } else if self.accept(TokenType::Try)?.is_some() {
+            self.parse_try()?
+        } else if self.accept(TokenType::With)?.is_some() {
+            self.parse_with()?
         } else {
             self.parse_expression()?
         };
@@ -65,35 +83,31 @@ impl Parser {
         }
 
         self.expect(TokenType::Newline)?;
-        self.skip_newlines();
+        self.skip_newlines()?;
 
         Ok(stmt)
     }
 
-    fn parse_class_def(&mut self) -> Result<Box<ast::ASTNode>, Error> {
+    fn parse_class_def(&mut self, doc: Option<String>) -> Result<Box<ast::ASTNode>, Error> {
         let identifier = self.expect(TokenType::Identifier)?;
 
-        let mut body = Vec::new();
+        let mut body = ast::Block::new(vec![]);
 
         self.expect(TokenType::Newline)?;
 
         while self.peek().is_some() {
-            if let Some(_) = self.accept(TokenType::End) {
+            if let Some(_) = self.accept(TokenType::End)? {
                 break;
             }
 
-            match self.parse_statement() {
-                Ok(statement) => {
-                    body.push(statement);
-                }
-                Err(e) => return Err(e),
-            }
+            self.push_statement(&mut body)?;
         }
 
-        let class_def = ast::ClassDef::new(
+        let mut class_def = ast::ClassDef::new(
             identifier.value.clone(),
-            Box::new(ast::ASTNode::Block(ast::Block { statements: body })),
+            Box::new(ast::ASTNode::Block(body)),
         );
+        class_def.doc = doc;
 
         Ok(Box::new(ast::ASTNode::ClassDef(class_def)))
     }
@@ -103,25 +117,17 @@ impl Parser {
 
         self.expect(TokenType::Newline)?;
 
-        let mut body = Vec::new();
+        let mut body = ast::Block::new(vec![]);
 
         while self.peek().is_some() {
-            if let Some(_) = self.accept(TokenType::End) {
+            if let Some(_) = self.accept(TokenType::End)? {
                 break;
             }
 
-            match self.parse_statement() {
-                Ok(statement) => {
-                    body.push(statement);
-                }
-                Err(e) => return Err(e),
-            }
+            self.push_statement(&mut body)?;
         }
 
-        let if_node = ast::If::new(
-            test,
-            Box::new(ast::ASTNode::Block(ast::Block { statements: body })),
-        );
+        let if_node = ast::If::new(test, Box::new(ast::ASTNode::Block(body)));
 
         Ok(Box::new(ast::ASTNode::If(if_node)))
     }
@@ -129,9 +135,11 @@ impl Parser {
     fn parse_import(&mut self) -> Result<Box<ast::ASTNode>, Error> {
         let mut module_name = String::new();
 
-        while self.peek().is_some() && self.peek().unwrap().kind != TokenType::Newline {
-            module_name.push_str(&self.peek().unwrap().value);
-            self.next_token().unwrap();
+        while let Some(token) = self.peek()
+            && token.kind != TokenType::Newline
+        {
+            module_name.push_str(&token.value);
+            self.next_token()?;
         }
 
         Ok(Box::new(ast::ASTNode::Import(ast::Import {
@@ -144,31 +152,26 @@ impl Parser {
 
         self.expect(TokenType::Newline)?;
 
-        let mut body = Vec::new();
+        let mut body = ast::Block::new(vec![]);
 
         while self.peek().is_some() {
-            if let Some(_) = self.accept(TokenType::End) {
+            if let Some(_) = self.accept(TokenType::End)? {
                 break;
             }
 
-            match self.parse_statement() {
-                Ok(statement) => {
-                    body.push(statement);
-                }
-                Err(e) => return Err(e),
-            }
+            self.push_statement(&mut body)?;
         }
 
-        let while_node = ast::While::new(
-            condition,
-            Box::new(ast::ASTNode::Block(ast::Block { statements: body })),
-        );
+        let while_node = ast::While::new(condition, Box::new(ast::ASTNode::Block(body)));
 
         Ok(Box::new(ast::ASTNode::While(while_node)))
     }
 
     fn parse_return(&mut self) -> Result<Box<ast::ASTNode>, Error> {
-        let value = if self.peek().is_some() && self.peek().unwrap().kind != TokenType::Newline {
+        let value = if self
+            .peek_kind()
+            .is_some_and(|kind| kind != TokenType::Newline)
+        {
             Some(self.parse_expression()?)
         } else {
             None
@@ -178,21 +181,96 @@ impl Parser {
     }
 
     fn parse_raise(&mut self) -> Result<Box<ast::ASTNode>, Error> {
-        let value = if self.peek().is_some() && self.peek().unwrap().kind != TokenType::Newline {
+        let value = if self
+            .peek_kind()
+            .is_some_and(|kind| kind != TokenType::Newline)
+        {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        let cause = if value.is_some() && self.accept(TokenType::From)?.is_some() {
             Some(self.parse_expression()?)
         } else {
             None
         };
 
-        Ok(Box::new(ast::ASTNode::Raise(ast::Raise { message: value })))
+        Ok(Box::new(ast::ASTNode::Raise(ast::Raise {
+            message: value,
+            cause,
+        })))
+    }
+
+    fn parse_try(&mut self) -> Result<Box<ast::ASTNode>, Error> {
+        self.expect(TokenType::Newline)?;
+
+        let mut body = ast::Block::new(vec![]);
+
+        loop {
+            if self.peek().is_none() {
+                return Err(Error::ParserError(Diagnostic::UnclosedTry));
+            }
+
+            if self.accept(TokenType::Ensure)?.is_some() {
+                break;
+            }
+
+            self.push_statement(&mut body)?;
+        }
+
+        self.expect(TokenType::Newline)?;
+
+        let mut ensure = ast::Block::new(vec![]);
+
+        while self.peek().is_some() {
+            if self.accept(TokenType::End)?.is_some() {
+                break;
+            }
+
+            self.push_statement(&mut ensure)?;
+        }
+
+        Ok(Box::new(ast::ASTNode::Try(ast::Try {
+            body: Box::new(ast::ASTNode::Block(body)),
+            ensure: Box::new(ast::ASTNode::Block(ensure)),
+        })))
+    }
+
+    fn parse_with(&mut self) -> Result<Box<ast::ASTNode>, Error> {
+        let value = self.parse_expression()?;
+
+        let name = if self.accept(TokenType::As)?.is_some() {
+            Some(self.expect(TokenType::Identifier)?.value)
+        } else {
+            None
+        };
+
+        self.expect(TokenType::Newline)?;
+
+        let mut body = ast::Block::new(vec![]);
+
+        while self.peek().is_some() {
+            if self.accept(TokenType::End)?.is_some() {
+                break;
+            }
+
+            self.push_statement(&mut body)?;
+        }
+
+        Ok(Box::new(ast::ASTNode::With(ast::With {
+            value,
+            name,
+            body: Box::new(ast::ASTNode::Block(body)),
+        })))
     }
 
-    fn parse_method_def(&mut self) -> Result<Box<ast::ASTNode>, Error> {
+    fn parse_method_def(&mut self, doc: Option<String>) -> Result<Box<ast::ASTNode>, Error> {
         let mut parameters = Vec::new();
-        let mut body = Vec::new();
+        let mut body = ast::Block::new(vec![]);
         let identifier = self.expect(TokenType::Identifier)?;
 
-        if let Some(_) = self.accept(TokenType::LeftParen) {
+        if let Some(_) = self.accept(TokenType::LeftParen)? {
             parameters = self.parse_parameters()?;
             self.expect(TokenType::RightParen)?;
         }
@@ -200,23 +278,19 @@ impl Parser {
         self.expect(TokenType::Newline)?;
 
         while self.peek().is_some() {
-            if let Some(_) = self.accept(TokenType::End) {
+            if let Some(_) = self.accept(TokenType::End)? {
                 break;
             }
 
-            match self.parse_statement() {
-                Ok(statement) => {
-                    body.push(statement);
-                }
-                Err(e) => return Err(e),
-            }
+            self.push_statement(&mut body)?;
         }
 
-        let method_def = ast::MethodDef::new(
+        let mut method_def = ast::MethodDef::new(
             identifier.value.clone(),
             parameters,
-            Box::new(ast::ASTNode::Block(ast::Block { statements: body })),
+            Box::new(ast::ASTNode::Block(body)),
         );
+        method_def.doc = doc;
 
         Ok(Box::new(ast::ASTNode::MethodDef(method_def)))
     }
@@ -224,12 +298,12 @@ impl Parser {
     fn parse_parameters(&mut self) -> Result<Vec<Box<ast::ASTNode>>, Error> {
         let mut parameters = Vec::new();
 
-        while let Some(token) = self.accept(TokenType::Identifier) {
+        while let Some(token) = self.accept(TokenType::Identifier)? {
             parameters.push(Box::new(ast::ASTNode::Identifier(ast::Identifier {
-                name: token.value.clone(),
+                name: Symbol::intern(&token.value),
             })));
 
-            if self.accept(TokenType::Comma).is_none() {
+            if self.accept(TokenType::Comma)?.is_none() {
                 break;
             }
         }
@@ -251,17 +325,33 @@ impl Parser {
             TokenType::Gte,
             TokenType::Lte,
             TokenType::Neq,
+            TokenType::Is,
         ];
 
         loop {
             let mut check = false;
 
             for operator in &operators {
-                if let Some(_) = self.accept(operator.clone()) {
+                if let Some(_) = self.accept(operator.clone())? {
+                    // `not` is only special right after `is`.
+                    let negated = *operator == TokenType::Is
+                        && matches!(self.peek(), Some(token) if token.kind == TokenType::Identifier && token.value == "not");
+
+                    if negated {
+                        self.next_token()?;
+                    }
+
                     let right = self.parse_sum()?;
-                    let op = ast::Operator::from_token(operator).ok_or_else(|| {
-                        Error::ParserError(format!("Invalid operator: {:?}", operator))
-                    })?;
+                    let op = match ast::Operator::from_token(operator) {
+                        Some(ast::Operator::Is) if negated => ast::Operator::IsNot,
+                        Some(op) => op,
+                        None => {
+                            return Err(Error::ParserError(Diagnostic::InvalidOperator(format!(
+                                "{:?}",
+                                operator
+                            ))))
+                        }
+                    };
 
                     primary = Box::new(ast::ASTNode::Compare(ast::Compare {
                         left: primary,
@@ -289,12 +379,15 @@ impl Parser {
             let mut check = false;
 
             for operator in &operators {
-                if let Some(_) = self.accept(operator.clone()) {
+                if let Some(_) = self.accept(operator.clone())? {
                     let right = self.parse_primary()?;
                     primary = Box::new(ast::ASTNode::BinOp(ast::BinOp {
                         left: primary,
                         operator: ast::Operator::from_token(operator).ok_or_else(|| {
-                            Error::ParserError(format!("Invalid operator: {:?}", operator))
+                            Error::ParserError(Diagnostic::InvalidOperator(format!(
+                                "{:?}",
+                                operator
+                            )))
                         })?,
                         right,
                     }));
@@ -314,13 +407,16 @@ impl Parser {
         let mut primary = self.parse_atom()?;
 
         loop {
-            if self.accept(TokenType::LeftParen).is_some() {
+            if self.accept(TokenType::LeftParen)?.is_some() {
                 let mut arguments = Vec::new();
 
-                while self.peek().is_some() && self.peek().unwrap().kind != TokenType::RightParen {
+                while self
+                    .peek_kind()
+                    .is_some_and(|kind| kind != TokenType::RightParen)
+                {
                     arguments.push(self.parse_expression()?);
 
-                    if self.accept(TokenType::Comma).is_none() {
+                    if self.accept(TokenType::Comma)?.is_none() {
                         break;
                     }
                 }
@@ -330,18 +426,20 @@ impl Parser {
                 primary = Box::new(ast::ASTNode::MethodCall(ast::MethodCall::new(
                     primary, arguments,
                 )));
-            } else if self.accept(TokenType::Equal).is_some() {
+            } else if self.accept(TokenType::Equal)?.is_some() {
                 let value = self.parse_expression()?;
                 primary = Box::new(ast::ASTNode::Assignment(ast::Assignment::new(
                     primary, value,
                 )));
-            } else if self.accept(TokenType::Dot).is_some() {
+            } else if self.accept(TokenType::Dot)?.is_some() {
                 let identifier = self.expect(TokenType::Identifier)?;
 
                 primary = Box::new(ast::ASTNode::Attribute(ast::Attribute::new(
                     primary,
                     identifier.value.clone(),
                 )));
+            } else if self.accept(TokenType::LeftBracket)?.is_some() {
+                primary = self.parse_slice(primary)?;
             } else {
                 break;
             }
@@ -350,18 +448,48 @@ impl Parser {
         Ok(primary)
     }
 
+    fn parse_slice(&mut self, value: Box<ast::ASTNode>) -> Result<Box<ast::ASTNode>, Error> {
+        let start = if self.peek_kind() == Some(TokenType::DotDot) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+
+        self.expect(TokenType::DotDot)?;
+
+        let end = match self.peek_kind() {
+            Some(TokenType::Colon) | Some(TokenType::RightBracket) => None,
+            _ => Some(self.parse_expression()?),
+        };
+
+        let step = if self.accept(TokenType::Colon)?.is_some() {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        self.expect(TokenType::RightBracket)?;
+
+        Ok(Box::new(ast::ASTNode::Slice(ast::Slice {
+            value,
+            start,
+            end,
+            step,
+        })))
+    }
+
     fn parse_atom(&mut self) -> Result<Box<ast::ASTNode>, Error> {
-        if let Some(token) = self.accept(TokenType::Identifier) {
+        if let Some(token) = self.accept(TokenType::Identifier)? {
             return Ok(Box::new(ast::ASTNode::Identifier(ast::Identifier {
-                name: token.value.clone(),
+                name: Symbol::intern(&token.value),
             })));
         }
 
-        if let Some(token) = self.accept(TokenType::StringLiteral) {
+        if let Some(token) = self.accept(TokenType::StringLiteral)? {
             return Ok(Box::new(ast::ASTNode::StringLiteral(token.value.clone())));
         }
 
-        if let Some(token) = self.accept(TokenType::Plus) {
+        if let Some(token) = self.accept(TokenType::Plus)? {
             let operand = self.parse_atom()?;
 
             return Ok(Box::new(ast::ASTNode::UnaryOp(ast::UnaryOp {
@@ -370,7 +498,7 @@ impl Parser {
             })));
         }
 
-        if let Some(token) = self.accept(TokenType::Minus) {
+        if let Some(token) = self.accept(TokenType::Minus)? {
             let operand = self.parse_atom()?;
 
             return Ok(Box::new(ast::ASTNode::UnaryOp(ast::UnaryOp {
@@ -379,56 +507,71 @@ impl Parser {
             })));
         }
 
-        if let Some(token) = self.accept(TokenType::NumberLiteral) {
+        if let Some(token) = self.accept(TokenType::NumberLiteral)? {
             return Ok(Box::new(ast::ASTNode::NumberLiteral(
-                token.value.parse::<f64>().map_err(|_| {
-                    Error::ParserError(format!(
-                        "Invalid number literal: {} at line {}, column {}",
-                        token.value, token.line, token.column
-                    ))
+                parse_number(&token.value.replace('_', "")).map_err(|_| {
+                    Error::ParserError(Diagnostic::InvalidNumberLiteral {
+                        value: token.value.clone(),
+                        line: token.line,
+                        column: token.column,
+                    })
                 })?,
             )));
         }
 
-        Err(Error::ParserError(format!(
-            "Unexpected token {} at line {}, column {}",
-            self.peek().unwrap().value,
-            self.peek().unwrap().line,
-            self.peek().unwrap().column
-        )))
+        let token = self.current()?;
+
+        Err(Error::ParserError(Diagnostic::UnexpectedToken {
+            value: token.value.clone(),
+            line: token.line,
+            column: token.column,
+        }))
     }
 
     fn peek(&self) -> Option<&Token> {
         self.current_token.as_ref()
     }
 
-    fn accept(&mut self, token_type: TokenType) -> Option<Token> {
-        if let Some(ref token) = self.current_token {
-            if token.kind == token_type {
-                let token = self.current_token.clone();
-                self.next_token().unwrap();
-                return token;
+    fn peek_kind(&self) -> Option<TokenType> {
+        self.peek().map(|token| token.kind.clone())
+    }
+
+    // The token being looked at, which the parser needs to go on.
+    fn current(&self) -> Result<&Token, Error> {
+        self.peek()
+            .ok_or(Error::ParserError(Diagnostic::UnexpectedEnd))
+    }
+
+    fn accept(&mut self, token_type: TokenType) -> Result<Option<Token>, Error> {
+        match self.current_token.take() {
+            Some(token) if token.kind == token_type => {
+                self.next_token()?;
+
+                Ok(Some(token))
             }
-        }
+            token => {
+                self.current_token = token;
 
-        None
+                Ok(None)
+            }
+        }
     }
 
     fn expect(&mut self, token_type: TokenType) -> Result<Token, Error> {
-        if let Some(ref token) = self.current_token {
-            if token.kind == token_type {
-                let token = self.current_token.clone();
-                self.next_token().unwrap();
-                return Ok(token.unwrap());
-            } else {
-                return Err(Error::ParserError(format!(
-                    "Expected token \"{}\" at line {}, column {}",
-                    token.value, token.line, token.column
-                )));
-            }
+        let token = self.current()?;
+
+        if token.kind != token_type {
+            return Err(Error::ParserError(Diagnostic::ExpectedToken {
+                value: token.value.clone(),
+                line: token.line,
+                column: token.column,
+            }));
         }
 
-        Err(Error::ParserError(format!("Unexpected token",)))
+        let token = token.clone();
+        self.next_token()?;
+
+        Ok(token)
     }
 
     fn next_token(&mut self) -> Result<(), Error> {
@@ -437,8 +580,10 @@ impl Parser {
         Ok(())
     }
 
-    fn skip_newlines(&mut self) {
-        while self.accept(TokenType::Newline).is_some() {}
+    fn skip_newlines(&mut self) -> Result<(), Error> {
+        while self.accept(TokenType::Newline)?.is_some() {}
+
+        Ok(())
     }
 }
 
@@ -460,6 +605,92 @@ mod tests {
                 statements: vec![Box::new(ast::ASTNode::Return(ast::Return {
                     value: Some(Box::new(ast::ASTNode::NumberLiteral(42.0))),
                 }))],
+                lines: vec![],
+            })),
+        });
+
+        assert_eq!(ast, expected_ast);
+    }
+
+    #[test]
+    fn test_parse_doc_comments() {
+        let input = "# Adds two numbers.\n# Returns the sum.\ndef add(a, b)\n    return a + b\nend\n\n# Unrelated\n\nclass Point\nend\n";
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+
+        let ast = parser.parse().unwrap();
+
+        let statements = match ast {
+            ast::ASTNode::Module(module) => match *module.block {
+                ast::ASTNode::Block(block) => block.statements,
+                _ => panic!("Expected Block"),
+            },
+            _ => panic!("Expected Module"),
+        };
+
+        match &*statements[0] {
+            ast::ASTNode::MethodDef(method_def) => assert_eq!(
+                method_def.doc.as_deref(),
+                Some("Adds two numbers.\nReturns the sum.")
+            ),
+            _ => panic!("Expected MethodDef"),
+        }
+
+        match &*statements[1] {
+            ast::ASTNode::ClassDef(class_def) => assert_eq!(class_def.doc, None),
+            _ => panic!("Expected ClassDef"),
+        }
+    }
+
+    #[test]
+    fn test_parse_slice() {
+        let input = "items[..2:-1]\n";
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+
+        let ast = parser.parse().unwrap();
+
+        let expected_ast = ast::ASTNode::Module(ast::Module {
+            block: Box::new(ast::ASTNode::Block(ast::Block {
+                statements: vec![Box::new(ast::ASTNode::Slice(ast::Slice {
+                    value: Box::new(ast::ASTNode::Identifier(ast::Identifier {
+                        name: "items".into(),
+                    })),
+                    start: None,
+                    end: Some(Box::new(ast::ASTNode::NumberLiteral(2.0))),
+                    step: Some(Box::new(ast::ASTNode::UnaryOp(ast::UnaryOp {
+                        operator: TokenType::Minus,
+                        operand: Box::new(ast::ASTNode::NumberLiteral(1.0)),
+                    }))),
+                }))],
+                lines: vec![],
+            })),
+        });
+
+        assert_eq!(ast, expected_ast);
+    }
+
+    #[test]
+    fn test_parse_raise_from() {
+        let input = "raise Error(\"context\") from e\n";
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+
+        let ast = parser.parse().unwrap();
+
+        let expected_ast = ast::ASTNode::Module(ast::Module {
+            block: Box::new(ast::ASTNode::Block(ast::Block {
+                statements: vec![Box::new(ast::ASTNode::Raise(ast::Raise {
+                    message: Some(Box::new(ast::ASTNode::MethodCall(ast::MethodCall {
+                        name: Box::new(ast::ASTNode::Identifier(ast::Identifier {
+                            name: "Error".into(),
+                        })),
+                        arguments: vec![Box::new(ast::ASTNode::StringLiteral(
+                            "context".to_string(),
+                        ))],
+                    }))),
+                    cause: Some(Box::new(ast::ASTNode::Identifier(ast::Identifier {
+                        name: "e".into(),
+                    }))),
+                }))],
+                lines: vec![],
             })),
         });
 
@@ -0,0 +1,167 @@
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::manifest::{Manifest, MANIFEST_FILE};
+
+pub const MODULES_DIR: &str = "kya_modules";
+
+pub fn modules_dir(root: &Path) -> PathBuf {
+    root.join(MODULES_DIR)
+}
+
+pub fn package_name_from_url(url: &str) -> Result<String, String> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(".git");
+
+    if name.is_empty() {
+        return Err(format!(
+            "Error: Could not infer a package name from {}",
+            url
+        ));
+    }
+
+    Ok(name.to_string())
+}
+
+fn load_or_create_manifest(root: &Path) -> Result<Manifest, String> {
+    let path = root.join(MANIFEST_FILE);
+
+    if path.exists() {
+        return Manifest::load(&path);
+    }
+
+    let name = root
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "app".to_string());
+
+    Ok(Manifest::new(&name))
+}
+
+// A package is cloned into `kya_modules/<name>`, so its name must be a single
+// plain path component that cannot point outside of it. It is also written
+// between quotes into the manifest and into scaffolded sources, so it cannot
+// contain quotes, backslashes or control characters either.
+pub fn check_package_name(name: &str) -> Result<(), String> {
+    let mut components = Path::new(name).components();
+    let single =
+        matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+
+    if !single || name.contains(['/', '\\', '"']) || name.chars().any(char::is_control) {
+        return Err(format!("Error: Invalid package name '{}'", name));
+    }
+
+    Ok(())
+}
+
+// git would read a URL starting with a dash as one of its options.
+fn check_package_url(url: &str) -> Result<(), String> {
+    if url.is_empty() || url.starts_with('-') {
+        return Err(format!("Error: Invalid package URL '{}'", url));
+    }
+
+    Ok(())
+}
+
+fn clone_package(root: &Path, name: &str, url: &str) -> Result<(), String> {
+    check_package_name(name)?;
+    check_package_url(url)?;
+
+    let target = modules_dir(root).join(name);
+
+    if target.exists() {
+        println!("{} is already installed", name);
+
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(modules_dir(root))
+        .map_err(|e| format!("Error: Could not create {}: {}", MODULES_DIR, e))?;
+
+    println!("Installing {} from {}", name, url);
+
+    let status = Command::new("git")
+        .arg("clone")
+        .arg("--depth")
+        .arg("1")
+        .arg("--")
+        .arg(url)
+        .arg(&target)
+        .status()
+        .map_err(|e| format!("Error: Could not run git: {}", e))?;
+
+    if !status.success() {
+        return Err(format!("Error: git clone of {} failed", url));
+    }
+
+    Ok(())
+}
+
+pub fn add(root: &Path, url: &str) -> Result<(), String> {
+    let mut manifest = load_or_create_manifest(root)?;
+    let name = package_name_from_url(url)?;
+
+    clone_package(root, &name, url)?;
+
+    manifest.add_dependency(&name, url);
+    manifest.save(&root.join(MANIFEST_FILE))
+}
+
+pub fn install(root: &Path) -> Result<(), String> {
+    let manifest = Manifest::load(&root.join(MANIFEST_FILE))?;
+
+    for (name, url) in &manifest.dependencies {
+        clone_package(root, name, url)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_name_from_url() {
+        assert_eq!(
+            package_name_from_url("https://github.com/user/http.git").unwrap(),
+            "http"
+        );
+        assert_eq!(
+            package_name_from_url("git@github.com:user/json").unwrap(),
+            "json"
+        );
+        assert!(package_name_from_url("").is_err());
+    }
+
+    #[test]
+    fn test_clone_package_rejects_unsafe_names() {
+        let root = std::env::temp_dir().join("kyanite-pkg-names");
+
+        for name in ["..", ".", "", "a/b", "../escape", "/tmp/escape", "a\\b"] {
+            assert!(
+                clone_package(&root, name, "https://example.com/pkg.git").is_err(),
+                "{}",
+                name
+            );
+        }
+
+        assert!(!modules_dir(&root).exists());
+    }
+
+    #[test]
+    fn test_clone_package_rejects_option_urls() {
+        let root = std::env::temp_dir().join("kyanite-pkg-urls");
+
+        for url in ["--upload-pack=touch pwned", "-u", ""] {
+            assert!(clone_package(&root, "pkg", url).is_err(), "{}", url);
+        }
+
+        assert!(!modules_dir(&root).exists());
+    }
+}
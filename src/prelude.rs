@@ -0,0 +1,53 @@
+//! The builtins written in Kyanite: `range`, `map`, `filter`, `min` and
+//! `max`. `prelude.k` is compiled into the binary and runs once per runtime,
+//! into the builtins every main frame starts with.
+
+use crate::bytecode::CodeObject;
+use crate::compiler::Compiler;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+use std::sync::Arc;
+
+pub const SOURCE: &str = include_str!("prelude.k");
+
+pub fn code() -> CodeObject {
+    let ast = Parser::new(Lexer::new(SOURCE.to_string()))
+        .parse()
+        .expect("the prelude parses");
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.compile().expect("the prelude compiles");
+
+    let mut code = compiler.get_output();
+    code.name = "<prelude>".to_string();
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_prelude() {
+        let source = "def double(n)\n    return n + n\nend\n\n\
+                      def large(n)\n    return n > 1\nend\n\n\
+                      numbers = range(0, 4)\n\
+                      assert_eq(numbers.length(), 4)\n\
+                      assert_eq(map(double, numbers).at(3), 6)\n\
+                      assert_eq(filter(large, numbers).length(), 2)\n\
+                      assert_eq(min(2, 1), 1)\n\
+                      assert_eq(max(2, 1), 2)\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let result = Interpreter::new(".").eval(&compiler.get_output());
+
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Where a program spends its time, for `--profile`. While a `Profile` is
+//! running, the interpreter times every instruction by opcode and every call
+//! of a function defined in the program. An instruction's time leaves out the
+//! instructions it runs itself, like those of the function a call runs, so
+//! the times of the opcodes add up. A function's time is inclusive: it counts
+//! the functions it calls, and a recursive call is counted again by each of
+//! its callers.
+
+use crate::bytecode::Opcode;
+
+use once_cell::sync::Lazy;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+struct Stats {
+    opcodes: HashMap<u8, (u64, Duration)>,
+    functions: HashMap<String, (u64, Duration)>,
+}
+
+static STATS: Lazy<Mutex<Stats>> = Lazy::new(|| Mutex::new(Stats::default()));
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+thread_local! {
+    // The time taken by the instructions run inside the current one.
+    static NESTED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Runs the handler of `opcode` and records the time it took on its own.
+pub fn time_opcode<T>(opcode: u8, handler: impl FnOnce() -> T) -> T {
+    let outer = NESTED.with(|nested| nested.replace(Duration::ZERO));
+    let started = Instant::now();
+
+    let result = handler();
+
+    let elapsed = started.elapsed();
+    let nested = NESTED.with(|nested| nested.replace(outer + elapsed));
+
+    record_opcode(opcode, elapsed.saturating_sub(nested));
+
+    result
+}
+
+fn record_opcode(opcode: u8, elapsed: Duration) {
+    let mut stats = STATS.lock().unwrap();
+    let entry = stats.opcodes.entry(opcode).or_default();
+
+    entry.0 += 1;
+    entry.1 += elapsed;
+}
+
+pub fn record_call(function: &str, elapsed: Duration) {
+    let mut stats = STATS.lock().unwrap();
+
+    match stats.functions.get_mut(function) {
+        Some(entry) => {
+            entry.0 += 1;
+            entry.1 += elapsed;
+        }
+        None => {
+            stats.functions.insert(function.to_string(), (1, elapsed));
+        }
+    }
+}
+
+pub struct Profile {
+    started: Instant,
+}
+
+impl Profile {
+    pub fn start() -> Self {
+        *STATS.lock().unwrap() = Stats::default();
+        ENABLED.store(true, Ordering::Relaxed);
+
+        Profile {
+            started: Instant::now(),
+        }
+    }
+
+    /// Stops profiling and describes what was recorded, the slowest first.
+    pub fn report(self) -> String {
+        ENABLED.store(false, Ordering::Relaxed);
+
+        describe(&STATS.lock().unwrap(), self.started.elapsed())
+    }
+}
+
+fn describe(stats: &Stats, elapsed: Duration) -> String {
+    let instructions: u64 = stats.opcodes.values().map(|(count, _)| count).sum();
+
+    let mut output = format!(
+        "Profile: {} instructions in {}",
+        instructions,
+        format_duration(elapsed)
+    );
+
+    output.push_str(&format!(
+        "\n  {:<24}{:>12}{:>12}",
+        "opcode", "count", "time"
+    ));
+
+    for (opcode, (count, time)) in slowest_first(&stats.opcodes) {
+        let name = Opcode::from_u8(*opcode)
+            .map(|opcode| opcode.to_string())
+            .unwrap_or_else(|| format!("<opcode {}>", opcode));
+
+        output.push_str(&format!(
+            "\n  {:<24}{:>12}{:>12}",
+            name,
+            count,
+            format_duration(*time)
+        ));
+    }
+
+    output.push_str(&format!(
+        "\n  {:<24}{:>12}{:>12}",
+        "function", "calls", "time"
+    ));
+
+    for (function, (count, time)) in slowest_first(&stats.functions) {
+        output.push_str(&format!(
+            "\n  {:<24}{:>12}{:>12}",
+            function,
+            count,
+            format_duration(*time)
+        ));
+    }
+
+    output
+}
+
+fn slowest_first<K: Ord>(entries: &HashMap<K, (u64, Duration)>) -> Vec<(&K, &(u64, Duration))> {
+    let mut entries: Vec<_> = entries.iter().collect();
+
+    entries.sort_by(|(key_a, (_, time_a)), (key_b, (_, time_b))| {
+        time_b.cmp(time_a).then_with(|| key_a.cmp(key_b))
+    });
+    entries
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_sorts_by_time() {
+        let mut stats = Stats::default();
+
+        stats
+            .opcodes
+            .insert(Opcode::LoadConst as u8, (1, Duration::from_millis(1)));
+        stats
+            .opcodes
+            .insert(Opcode::Call as u8, (1, Duration::from_millis(5)));
+        stats
+            .functions
+            .insert("fib".to_string(), (2, Duration::from_millis(6)));
+
+        let report = describe(&stats, Duration::from_millis(10));
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "Profile: 2 instructions in 10.000ms");
+        assert!(lines[2].starts_with("  CALL_FUNCTION"));
+        assert!(lines[3].starts_with("  LOAD_CONST"));
+        assert_eq!(
+            lines[5],
+            format!("  {:<24}{:>12}{:>12}", "fib", 2, "6.000ms")
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! The interactive mode, started when `kyanite` is run without a file. Lines
+//! are read until they form a complete statement, which then runs in a
+//! session that keeps its globals. The repr of an expression's value is
+//! printed unless it is `None`.
+
+use crate::compiler::Compiler;
+use crate::errors::Error;
+use crate::interpreter::{Interpreter, Session};
+use crate::lexer::{Lexer, TokenType};
+use crate::lock::{kya_acquire_lock, kya_release_lock};
+use crate::objects::base::KyaObject;
+use crate::objects::utils::object_to_string_repr;
+use crate::optimizer;
+use crate::parser::Parser;
+
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+/// Whether `source` can run as it is, or still has a block or bracket to
+/// close. Source the lexer rejects counts as complete, so that the error
+/// is reported rather than waited on.
+pub fn is_complete(source: &str) -> bool {
+    let mut lexer = Lexer::new(source.to_string());
+    let mut blocks = 0;
+    let mut brackets = 0;
+
+    while let Ok(Some(token)) = lexer.next_token() {
+        match token.kind {
+            TokenType::Def
+            | TokenType::Class
+            | TokenType::If
+            | TokenType::While
+            | TokenType::Try
+            | TokenType::With => blocks += 1,
+            TokenType::End => blocks -= 1,
+            TokenType::LeftParen | TokenType::LeftBracket => brackets += 1,
+            TokenType::RightParen | TokenType::RightBracket => brackets -= 1,
+            _ => {}
+        }
+    }
+
+    blocks <= 0 && brackets <= 0
+}
+
+// Runs `source` and returns the repr of its value, if there is one to show.
+fn run(session: &mut Session, source: &str) -> Result<Option<String>, Error> {
+    let ast = Parser::new(Lexer::new(source.to_string())).parse()?;
+    let mut compiler = Compiler::new(Arc::new(ast));
+    compiler.add_pass(optimizer::ConstantBranches::new());
+    compiler.keep_result();
+    compiler.compile()?;
+
+    let value = session.eval(&compiler.get_output())?;
+
+    kya_acquire_lock();
+
+    let is_none = matches!(&*value.lock().unwrap(), KyaObject::NoneObject(_));
+    let repr = if is_none {
+        Ok(None)
+    } else {
+        object_to_string_repr(&value).map(Some)
+    };
+
+    kya_release_lock();
+
+    repr
+}
+
+pub fn start(interpreter: Interpreter) -> Result<(), String> {
+    let mut session = Session::new(interpreter);
+    let mut lines = io::stdin().lock().lines();
+    let mut source = String::new();
+
+    loop {
+        print!(
+            "{}",
+            if source.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            }
+        );
+        io::stdout().flush().map_err(|e| e.to_string())?;
+
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+
+        source.push_str(&line.map_err(|e| e.to_string())?);
+        source.push('\n');
+
+        if source.trim().is_empty() {
+            source.clear();
+            continue;
+        }
+
+        if !is_complete(&source) {
+            continue;
+        }
+
+        match run(&mut session, &source) {
+            Ok(Some(repr)) => println!("{}", repr),
+            Ok(None) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+
+        source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete("x = 1\n"));
+        assert!(!is_complete("def add(a, b)\n"));
+        assert!(!is_complete("if x\n    while y\n    end\n"));
+        assert!(is_complete("if x\n    while y\n    end\nend\n"));
+        assert!(!is_complete("print(1,\n"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+enum Mode {
+    Off,
+    Record(File),
+    Replay(VecDeque<Entry>),
+}
+
+struct Entry {
+    kind: String,
+    result: Result<Vec<u8>, String>,
+}
+
+static MODE: Lazy<Mutex<Mode>> = Lazy::new(|| Mutex::new(Mode::Off));
+
+pub trait Replayable: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Replayable for Vec<u8> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl Replayable for f64 {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bits().to_be_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(f64::from_bits(u64::from_be_bytes(bytes.try_into().ok()?)))
+    }
+}
+
+// A datagram and the address it came from, stored as the address length
+// followed by the address and the payload.
+impl Replayable for (Vec<u8>, String) {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.1.len() as u8];
+        bytes.extend_from_slice(self.1.as_bytes());
+        bytes.extend_from_slice(&self.0);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (length, rest) = bytes.split_first()?;
+        let address = String::from_utf8(rest.get(..*length as usize)?.to_vec()).ok()?;
+
+        Some((rest[*length as usize..].to_vec(), address))
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn format_entry(kind: &str, result: &Result<Vec<u8>, String>) -> String {
+    match result {
+        Ok(bytes) => format!("{} ok {}\n", kind, encode_hex(bytes)),
+        Err(message) => format!("{} err {}\n", kind, encode_hex(message.as_bytes())),
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let mut parts = line.splitn(3, ' ');
+    let kind = parts.next()?.to_string();
+    let status = parts.next()?;
+    let payload = decode_hex(parts.next().unwrap_or(""))?;
+
+    let result = match status {
+        "ok" => Ok(payload),
+        "err" => Err(String::from_utf8(payload).ok()?),
+        _ => return None,
+    };
+
+    Some(Entry { kind, result })
+}
+
+pub fn start_recording(path: &Path) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Error: Could not create {}: {}", path.display(), e))?;
+
+    *MODE.lock().unwrap() = Mode::Record(file);
+
+    Ok(())
+}
+
+pub fn start_replay(path: &Path) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|_| format!("Error: Could not read file {}", path.display()))?;
+
+    let mut entries = VecDeque::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        entries.push_back(parse_entry(line).ok_or_else(|| {
+            format!(
+                "Error: Malformed replay entry at {}:{}",
+                path.display(),
+                i + 1
+            )
+        })?);
+    }
+
+    *MODE.lock().unwrap() = Mode::Replay(entries);
+
+    Ok(())
+}
+
+pub fn capture<T: Replayable>(
+    kind: &str,
+    produce: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    if let Mode::Replay(entries) = &mut *MODE.lock().unwrap() {
+        let entry = entries
+            .pop_front()
+            .ok_or_else(|| format!("Replay log exhausted while waiting for '{}'", kind))?;
+
+        if entry.kind != kind {
+            return Err(format!(
+                "Replay diverged: expected '{}' but the program requested '{}'",
+                entry.kind, kind
+            ));
+        }
+
+        return entry.result.and_then(|bytes| {
+            T::from_bytes(&bytes).ok_or_else(|| format!("Malformed replay value for '{}'", kind))
+        });
+    }
+
+    // The producer may block (socket reads), so the mode lock is not held
+    // while it runs; entries are logged in completion order.
+    let result = produce();
+
+    if let Mode::Record(file) = &mut *MODE.lock().unwrap() {
+        let line = format_entry(
+            kind,
+            &result.as_ref().map(T::to_bytes).map_err(String::clone),
+        );
+
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| format!("Failed to write replay log: {}", e))?;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_round_trip() {
+        let line = format_entry("socket.read", &Ok(b"GET / HTTP/1.1".to_vec()));
+        let entry = parse_entry(line.trim_end()).unwrap();
+
+        assert_eq!(entry.kind, "socket.read");
+        assert_eq!(entry.result, Ok(b"GET / HTTP/1.1".to_vec()));
+
+        let line = format_entry("time.now", &Err("clock failure".to_string()));
+        let entry = parse_entry(line.trim_end()).unwrap();
+
+        assert_eq!(entry.result, Err("clock failure".to_string()));
+        assert_eq!(f64::from_bytes(&1.5f64.to_bytes()), Some(1.5));
+        assert!(parse_entry("time.now ok abc").is_none());
+    }
+}
@@ -0,0 +1,163 @@
+//! The state that belongs to one interpreter rather than to the process: its
+//! lock, its `None`, `true` and `false`, its builtins, the threads it started,
+//! its pending finalizers, the containers its collector tracks, what its run
+//! has spent of its limits, the capabilities it grants, where its imports
+//! are looked for and the modules it loaded from source. Several
+//! interpreters can run side by side, each on its own threads, as long as
+//! they do not pass objects to each other.
+//!
+//! A thread works for the runtime it entered last, and the interpreter finds
+//! it through `current()` rather than having it passed along. Code that
+//! never entered one, like unit tests, shares a default runtime.
+//!
+//! The builtin types are process-wide statics shared by every runtime, so
+//! programs cannot set attributes on them or on their instances; only
+//! classes declared in a program take new attributes. A few settings are still process-wide and
+//! apply to every runtime alike: the maximums of the `limits` module, the
+//! `log` module's logger, the replay mode and the instrumentation probes.
+
+use crate::capabilities::Capabilities;
+use crate::gc::{self, Collector};
+use crate::limits::Budget;
+use crate::lock::Gil;
+use crate::objects::base::KyaObjectRef;
+use crate::objects::finalizers::PendingFinalizers;
+use crate::objects::modules::threads::thread_object::RunningThreads;
+use crate::objects::singletons::{self, Singletons};
+use crate::symbol::Symbol;
+
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub struct Runtime {
+    pub singletons: Arc<Singletons>,
+    pub gil: Gil,
+    // Built the first time a main frame is needed and copied into the
+    // globals of each one after that.
+    pub builtins: OnceLock<HashMap<Symbol, KyaObjectRef>>,
+    pub threads: RunningThreads,
+    pub finalizers: PendingFinalizers,
+    pub collector: Collector,
+    pub budget: Budget,
+    capabilities: Mutex<Capabilities>,
+    search_paths: Mutex<Vec<PathBuf>>,
+    // By the path of their source file.
+    modules: Mutex<HashMap<PathBuf, KyaObjectRef>>,
+}
+
+impl Runtime {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Runtime {
+            singletons: Singletons::new(),
+            gil: Gil::default(),
+            builtins: OnceLock::new(),
+            threads: RunningThreads::default(),
+            finalizers: PendingFinalizers::default(),
+            collector: Collector::default(),
+            budget: Budget::default(),
+            capabilities: Mutex::new(Capabilities::default()),
+            search_paths: Mutex::new(vec![]),
+            modules: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        *self.capabilities.lock().unwrap()
+    }
+
+    pub fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.capabilities.lock().unwrap() = capabilities;
+    }
+
+    pub fn search_paths(&self) -> Vec<PathBuf> {
+        self.search_paths.lock().unwrap().clone()
+    }
+
+    pub fn set_search_paths(&self, search_paths: Vec<PathBuf>) {
+        *self.search_paths.lock().unwrap() = search_paths;
+    }
+
+    pub fn module(&self, path: &Path) -> Option<KyaObjectRef> {
+        self.modules.lock().unwrap().get(path).cloned()
+    }
+
+    pub fn add_module(&self, path: PathBuf, module: KyaObjectRef) {
+        self.modules.lock().unwrap().insert(path, module);
+    }
+
+    pub fn remove_module(&self, path: &Path) {
+        self.modules.lock().unwrap().remove(path);
+    }
+}
+
+static DEFAULT: Lazy<Arc<Runtime>> = Lazy::new(Runtime::new);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Runtime>>> = const { RefCell::new(None) };
+}
+
+pub fn enter(runtime: Arc<Runtime>) {
+    gc::flush();
+    singletons::enter(runtime.singletons.clone());
+    CURRENT.with(|cell| *cell.borrow_mut() = Some(runtime));
+}
+
+// Objects can be dropped while the thread is exiting, after its runtime is
+// gone; their finalizers go to the default runtime.
+pub fn current() -> Arc<Runtime> {
+    CURRENT
+        .try_with(|cell| cell.borrow().clone())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| DEFAULT.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::Compiler;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_interpreters_are_isolated() {
+        // Both programs change the same builtin module at the same time.
+        let source = "import json\njson.counter = 0\ni = 0\nwhile i < 1000\n    json.counter = json.counter + 1\n    i = i + 1\nend\nassert(json.counter == 1000)\n";
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                thread::spawn(move || {
+                    let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+                    let mut compiler = Compiler::new(Arc::new(ast));
+                    compiler.compile().unwrap();
+
+                    Interpreter::new(".").eval(&compiler.get_output()).is_ok()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_builtin_types_are_read_only() {
+        let eval = |source: &str| {
+            let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+            let mut compiler = Compiler::new(Arc::new(ast));
+            compiler.compile().unwrap();
+
+            Interpreter::new(".").eval(&compiler.get_output())
+        };
+
+        assert!(eval("List.tag = 1\n").is_err());
+        assert!(eval("a = List()\na.tag = 1\n").is_err());
+        assert!(eval("class Point\nend\nPoint.count = 1\nassert(Point.count == 1)\n").is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use crate::manifest::{Manifest, MANIFEST_FILE};
+use crate::pkg::{check_package_name, MODULES_DIR};
+
+const MAIN_TEMPLATE: &str = r#"# {name}: a minimal HTTP server
+
+HOST = "localhost"
+PORT = 8080
+
+def build_response(body)
+    response = "HTTP/1.1 200 OK\r\n"
+    response = response.concat("Content-Type: text/plain; charset=UTF-8\r\n")
+    response = response.concat("Content-Length: ").concat(body.encode().length().__repr__()).concat("\r\n")
+    response = response.concat("Connection: close\r\n\r\n")
+
+    return response.concat(body)
+end
+
+def handle(connection)
+    request = connection.recv(1024).decode()
+
+    if request != ""
+        connection.send(build_response("Hello from {name}!").encode())
+    end
+
+    connection.close()
+end
+
+server = socket()
+server.bind(HOST, PORT)
+
+print("Listening on http://", HOST, ":", PORT)
+
+while true
+    connection = server.accept()
+    handle(connection)
+end
+"#;
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Error: Could not write {}: {}", path.display(), e))
+}
+
+fn create_dir(path: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| format!("Error: Could not create {}: {}", path.display(), e))
+}
+
+pub fn new_project(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        return Err(format!("Error: {} already exists", path.display()));
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Error: Invalid project path {}", path.display()))?;
+
+    check_package_name(&name)?;
+
+    create_dir(path)?;
+    create_dir(&path.join("lib"))?;
+    create_dir(&path.join("tests"))?;
+
+    write_file(&path.join("lib").join(".gitkeep"), "")?;
+    write_file(&path.join("tests").join(".gitkeep"), "")?;
+    write_file(
+        &path.join("main.k"),
+        &MAIN_TEMPLATE.replace("{name}", &name),
+    )?;
+    write_file(&path.join(".gitignore"), &format!("{}/\n", MODULES_DIR))?;
+
+    Manifest::new(&name).save(&path.join(MANIFEST_FILE))?;
+
+    println!("Created project {} in {}", name, path.display());
+    println!("Run it with: kyanite {}", path.join("main.k").display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_new_project() {
+        let root = std::env::temp_dir().join(format!("kyanite-new-{}", std::process::id()));
+        let path = root.join("greeter");
+        std::fs::create_dir_all(&root).unwrap();
+
+        let created = new_project(&path);
+        let again = new_project(&path);
+
+        let manifest = Manifest::load(&path.join(MANIFEST_FILE));
+        let main = std::fs::read_to_string(path.join("main.k")).unwrap();
+        let gitignore = std::fs::read_to_string(path.join(".gitignore")).unwrap();
+        let layout = [
+            path.join("lib").join(".gitkeep"),
+            path.join("tests").join(".gitkeep"),
+        ]
+        .map(|file| file.is_file());
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(created.is_ok(), "{:?}", created);
+        assert!(again.unwrap_err().contains("already exists"));
+        assert_eq!(manifest.unwrap().name, "greeter");
+        assert_eq!(gitignore, format!("{}/\n", MODULES_DIR));
+        assert_eq!(layout, [true, true]);
+
+        assert!(main.contains("Hello from greeter!"));
+        let ast = Parser::new(Lexer::new(main)).parse().unwrap();
+        Compiler::new(Arc::new(ast)).compile().unwrap();
+    }
+
+    #[test]
+    fn test_new_project_rejects_names_with_quotes() {
+        let root = std::env::temp_dir().join(format!("kyanite-quoted-{}", std::process::id()));
+        let path = root.join("say \"hi\"");
+
+        let created = new_project(&path);
+
+        assert!(created.unwrap_err().contains("Invalid package name"));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,305 @@
+//! Kyanite scopes names by function, not by block:
+//!
+//! - A name assigned anywhere in a function, including inside `if`, `while`,
+//!   `try` and `with` bodies, is local to the whole call and stays visible
+//!   after the block ends.
+//! - Reading a name looks in the function's locals, then in the module
+//!   globals, which also hold the builtins.
+//! - Functions do not capture the variables of the functions they are
+//!   defined in. Assigning to an outer name from a nested function creates a
+//!   new local and leaves the outer one unchanged.
+//! - Names assigned in a class body become attributes of the class.
+//!
+//! `ScopeWarnings` reports the cases where these rules are likely to surprise:
+//! rebinding a builtin, and reusing an enclosing function's parameter.
+//!
+//! `function_locals` is the symbol table of a function: the compiler numbers
+//! its locals in that order and reads and writes them with `LoadFast` and
+//! `StoreFast` instead of looking them up by name.
+
+use crate::ast::{ASTNode, MethodDef};
+use crate::bytecode::CodeObject;
+use crate::errors::Error;
+use crate::symbol::Symbol;
+use crate::visitor::CompilerPass;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+#[derive(PartialEq)]
+enum ScopeKind {
+    Module,
+    Function,
+    Class,
+}
+
+struct Scope {
+    name: String,
+    kind: ScopeKind,
+    parameters: Vec<String>,
+}
+
+pub struct ScopeWarnings {
+    builtins: HashSet<String>,
+    scopes: Vec<Scope>,
+    warnings: Rc<RefCell<Vec<String>>>,
+}
+
+impl ScopeWarnings {
+    pub fn new(builtins: impl IntoIterator<Item = String>) -> Self {
+        ScopeWarnings {
+            builtins: builtins.into_iter().collect(),
+            scopes: vec![Scope {
+                name: "<main>".to_string(),
+                kind: ScopeKind::Module,
+                parameters: vec![],
+            }],
+            warnings: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// The warnings found so far, shared with the pass once it is added to a
+    /// compiler.
+    pub fn warnings(&self) -> Rc<RefCell<Vec<String>>> {
+        self.warnings.clone()
+    }
+
+    fn current(&self) -> &Scope {
+        self.scopes.last().unwrap()
+    }
+
+    fn warn(&self, message: String) {
+        let mut warnings = self.warnings.borrow_mut();
+
+        if !warnings.contains(&message) {
+            warnings.push(message);
+        }
+    }
+
+    fn enclosing_parameter(&self, name: &str) -> Option<&Scope> {
+        self.scopes
+            .iter()
+            .rev()
+            .skip(1)
+            .find(|scope| scope.parameters.iter().any(|parameter| parameter == name))
+    }
+
+    // Class bodies bind attributes, which cannot shadow anything.
+    fn check_binding(&self, name: &str, what: &str) {
+        let scope = self.current();
+
+        if scope.kind == ScopeKind::Class {
+            return;
+        }
+
+        if self.builtins.contains(name) {
+            self.warn(format!(
+                "{} '{}' in {} shadows the builtin '{}'",
+                what, name, scope.name, name
+            ));
+        }
+
+        let outer = match scope.kind {
+            ScopeKind::Function => self.enclosing_parameter(name),
+            _ => None,
+        };
+
+        if let Some(outer) = outer {
+            self.warn(format!(
+                "{} '{}' in {} does not refer to the parameter '{}' of {}: functions do not capture outer variables",
+                what, name, scope.name, name, outer.name
+            ));
+        }
+    }
+}
+
+impl CompilerPass for ScopeWarnings {
+    fn before_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        match node {
+            ASTNode::Assignment(assignment) => {
+                if let ASTNode::Identifier(identifier) = &*assignment.name {
+                    self.check_binding(identifier.name.as_str(), "assignment to");
+                }
+            }
+            ASTNode::With(with) => {
+                if let Some(name) = &with.name {
+                    self.check_binding(name, "with binding");
+                }
+            }
+            ASTNode::MethodDef(method_def) => {
+                self.check_binding(&method_def.name, "def");
+
+                let parameters: Vec<String> = method_def
+                    .parameters
+                    .iter()
+                    .filter_map(|parameter| match &**parameter {
+                        ASTNode::Identifier(identifier) => Some(identifier.name.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+
+                self.scopes.push(Scope {
+                    name: method_def.name.clone(),
+                    kind: ScopeKind::Function,
+                    parameters: parameters.clone(),
+                });
+
+                for parameter in &parameters {
+                    self.check_binding(parameter, "parameter");
+                }
+            }
+            ASTNode::ClassDef(class_def) => {
+                self.check_binding(&class_def.name, "class");
+
+                self.scopes.push(Scope {
+                    name: class_def.name.clone(),
+                    kind: ScopeKind::Class,
+                    parameters: vec![],
+                });
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn after_node(&mut self, node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        if let ASTNode::MethodDef(_) | ASTNode::ClassDef(_) = node {
+            self.scopes.pop();
+        }
+
+        Ok(())
+    }
+}
+
+/// The parameters of `method_def`, then `self`, then every name its body
+/// assigns or binds with `with`, in the order they first appear. Nested
+/// functions and classes have locals of their own and are not searched.
+pub fn function_locals(method_def: &MethodDef) -> Vec<Symbol> {
+    let mut names = vec![];
+
+    for parameter in &method_def.parameters {
+        if let ASTNode::Identifier(identifier) = &**parameter {
+            add_local(identifier.name, &mut names);
+        }
+    }
+
+    add_local(Symbol::SELF, &mut names);
+    collect_locals(&method_def.body, &mut names);
+
+    names
+}
+
+fn add_local(name: Symbol, names: &mut Vec<Symbol>) {
+    if !names.contains(&name) {
+        names.push(name);
+    }
+}
+
+fn collect_locals(node: &ASTNode, names: &mut Vec<Symbol>) {
+    match node {
+        ASTNode::Block(block) => block
+            .statements
+            .iter()
+            .for_each(|node| collect_locals(node, names)),
+        ASTNode::Assignment(assignment) => {
+            collect_locals(&assignment.value, names);
+
+            match &*assignment.name {
+                ASTNode::Identifier(identifier) => add_local(identifier.name, names),
+                name => collect_locals(name, names),
+            }
+        }
+        ASTNode::With(with) => {
+            collect_locals(&with.value, names);
+
+            if let Some(name) = &with.name {
+                add_local(Symbol::intern(name), names);
+            }
+
+            collect_locals(&with.body, names);
+        }
+        ASTNode::MethodCall(method_call) => {
+            collect_locals(&method_call.name, names);
+            method_call
+                .arguments
+                .iter()
+                .for_each(|node| collect_locals(node, names));
+        }
+        ASTNode::Attribute(attribute) => collect_locals(&attribute.name, names),
+        ASTNode::Slice(slice) => {
+            collect_locals(&slice.value, names);
+            [&slice.start, &slice.end, &slice.step]
+                .into_iter()
+                .flatten()
+                .for_each(|node| collect_locals(node, names));
+        }
+        ASTNode::Compare(compare) => {
+            collect_locals(&compare.left, names);
+            collect_locals(&compare.right, names);
+        }
+        ASTNode::BinOp(bin_op) => {
+            collect_locals(&bin_op.left, names);
+            collect_locals(&bin_op.right, names);
+        }
+        ASTNode::UnaryOp(unary_op) => collect_locals(&unary_op.operand, names),
+        ASTNode::If(if_node) => {
+            collect_locals(&if_node.test, names);
+            collect_locals(&if_node.body, names);
+        }
+        ASTNode::While(while_node) => {
+            collect_locals(&while_node.condition, names);
+            collect_locals(&while_node.body, names);
+        }
+        ASTNode::Try(try_node) => {
+            collect_locals(&try_node.body, names);
+            collect_locals(&try_node.ensure, names);
+        }
+        ASTNode::Return(return_node) => return_node
+            .value
+            .iter()
+            .for_each(|node| collect_locals(node, names)),
+        ASTNode::Raise(raise) => [&raise.message, &raise.cause]
+            .into_iter()
+            .flatten()
+            .for_each(|node| collect_locals(node, names)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::sync::Arc;
+
+    fn warnings_for(source: &str) -> Vec<String> {
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let pass = ScopeWarnings::new(["print".to_string(), "List".to_string()]);
+        let warnings = pass.warnings();
+
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.add_pass(pass);
+        compiler.compile().unwrap();
+
+        warnings.borrow().clone()
+    }
+
+    #[test]
+    fn test_scope_warnings() {
+        let source = "def f(items, print)\n    def g(x)\n        items = List()\n    end\n    if true\n        List = 1\n    end\nend\nclass Logger\n    def print(message)\n    end\nend\n";
+
+        assert_eq!(
+            warnings_for(source),
+            [
+                "parameter 'print' in f shadows the builtin 'print'",
+                "assignment to 'items' in g does not refer to the parameter 'items' of f: functions do not capture outer variables",
+                "assignment to 'List' in f shadows the builtin 'List'",
+            ]
+        );
+        assert!(warnings_for("x = 1\ndef f(y)\n    x = y\nend\n").is_empty());
+    }
+}
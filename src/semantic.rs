@@ -0,0 +1,289 @@
+//! The definitions of a program and the scopes they are made in, for tools
+//! like the language server that need to know what a name refers to. Names
+//! are resolved the way `scoping` describes: in the function they are used
+//! in and then in the module, skipping the functions around it.
+
+use crate::ast::{self, ASTNode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefinitionKind {
+    Class,
+    Function,
+    Method,
+    Variable,
+    Parameter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub line: usize,
+    // The scope the name is defined in, and the one its body opens for a
+    // class or function.
+    pub scope: usize,
+    pub body: Option<usize>,
+    pub parameters: Vec<String>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeKind {
+    Module,
+    Function,
+    Class,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<usize>,
+    // The lines from the header to the last statement of the body.
+    pub first_line: usize,
+    pub last_line: usize,
+}
+
+#[derive(Debug)]
+pub struct Analysis {
+    pub definitions: Vec<Definition>,
+    // The module is always the first scope, even for a program that did not
+    // parse.
+    pub scopes: Vec<Scope>,
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Analysis {
+            definitions: vec![],
+            scopes: vec![Scope {
+                kind: ScopeKind::Module,
+                parent: None,
+                first_line: 1,
+                last_line: usize::MAX,
+            }],
+        }
+    }
+}
+
+impl Analysis {
+    /// The classes and functions defined directly in `scope`.
+    pub fn declarations(&self, scope: usize) -> impl Iterator<Item = &Definition> {
+        self.definitions
+            .iter()
+            .filter(move |definition| definition.scope == scope && definition.body.is_some())
+    }
+
+    /// What `name` refers to when used on `line`. Falls back to any
+    /// definition of the name, as for a method called on an object.
+    pub fn resolve(&self, name: &str, line: usize) -> Option<&Definition> {
+        let mut scope = Some(self.innermost_scope(line));
+
+        while let Some(index) = scope {
+            let found = self
+                .definitions
+                .iter()
+                .find(|definition| definition.scope == index && definition.name == name);
+
+            if found.is_some() {
+                return found;
+            }
+
+            // Everything but the module goes on to the module.
+            scope = self.scopes[index].parent.map(|_| 0);
+        }
+
+        self.definitions
+            .iter()
+            .find(|definition| definition.name == name && definition.body.is_some())
+    }
+
+    fn innermost_scope(&self, line: usize) -> usize {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, scope)| scope.first_line <= line && line <= scope.last_line)
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+}
+
+pub fn analyze(module: &ASTNode) -> Analysis {
+    let mut analysis = Analysis::default();
+
+    if let ASTNode::Module(module) = module {
+        collect(&module.block, 0, 1, &mut analysis);
+    }
+
+    analysis
+}
+
+// Adds the definitions of `node`, a statement on `line` or a block, to
+// `scope`, returning the last line it reaches.
+fn collect(node: &ASTNode, scope: usize, line: usize, analysis: &mut Analysis) -> usize {
+    match node {
+        ASTNode::Block(block) => {
+            let mut last_line = line;
+
+            for (index, statement) in block.statements.iter().enumerate() {
+                let line = block.line(index).unwrap_or(last_line);
+
+                last_line = last_line.max(collect(statement, scope, line, analysis));
+            }
+
+            last_line
+        }
+        ASTNode::Assignment(assignment) => {
+            if let ASTNode::Identifier(identifier) = &*assignment.name {
+                define(
+                    analysis,
+                    scope,
+                    identifier.name.as_str(),
+                    DefinitionKind::Variable,
+                    line,
+                );
+            }
+
+            line
+        }
+        ASTNode::MethodDef(method_def) => {
+            let kind = match analysis.scopes[scope].kind {
+                ScopeKind::Class => DefinitionKind::Method,
+                _ => DefinitionKind::Function,
+            };
+            let parameters = parameter_names(method_def);
+            let definition = define(analysis, scope, &method_def.name, kind, line);
+            let body = open_scope(analysis, scope, ScopeKind::Function, line);
+
+            analysis.definitions[definition].body = Some(body);
+            analysis.definitions[definition].doc = method_def.doc.clone();
+
+            for parameter in &parameters {
+                define(analysis, body, parameter, DefinitionKind::Parameter, line);
+            }
+
+            analysis.definitions[definition].parameters = parameters;
+
+            close_scope(analysis, body, &method_def.body, line)
+        }
+        ASTNode::ClassDef(class_def) => {
+            let definition = define(
+                analysis,
+                scope,
+                &class_def.name,
+                DefinitionKind::Class,
+                line,
+            );
+            let body = open_scope(analysis, scope, ScopeKind::Class, line);
+
+            analysis.definitions[definition].body = Some(body);
+            analysis.definitions[definition].doc = class_def.doc.clone();
+
+            close_scope(analysis, body, &class_def.body, line)
+        }
+        ASTNode::With(with) => {
+            if let Some(name) = &with.name {
+                define(analysis, scope, name, DefinitionKind::Variable, line);
+            }
+
+            collect(&with.body, scope, line, analysis)
+        }
+        ASTNode::If(if_node) => collect(&if_node.body, scope, line, analysis),
+        ASTNode::While(while_node) => collect(&while_node.body, scope, line, analysis),
+        ASTNode::Try(try_node) => {
+            let last_line = collect(&try_node.body, scope, line, analysis);
+
+            collect(&try_node.ensure, scope, last_line, analysis)
+        }
+        _ => line,
+    }
+}
+
+// Names are defined where they are first bound.
+fn define(
+    analysis: &mut Analysis,
+    scope: usize,
+    name: &str,
+    kind: DefinitionKind,
+    line: usize,
+) -> usize {
+    if let Some(index) = analysis
+        .definitions
+        .iter()
+        .position(|definition| definition.scope == scope && definition.name == name)
+    {
+        return index;
+    }
+
+    analysis.definitions.push(Definition {
+        name: name.to_string(),
+        kind,
+        line,
+        scope,
+        body: None,
+        parameters: vec![],
+        doc: None,
+    });
+
+    analysis.definitions.len() - 1
+}
+
+fn open_scope(analysis: &mut Analysis, parent: usize, kind: ScopeKind, line: usize) -> usize {
+    analysis.scopes.push(Scope {
+        kind,
+        parent: Some(parent),
+        first_line: line,
+        last_line: line,
+    });
+
+    analysis.scopes.len() - 1
+}
+
+fn close_scope(analysis: &mut Analysis, scope: usize, body: &ASTNode, line: usize) -> usize {
+    let last_line = collect(body, scope, line, analysis);
+
+    analysis.scopes[scope].last_line = last_line;
+
+    last_line
+}
+
+fn parameter_names(method_def: &ast::MethodDef) -> Vec<String> {
+    method_def
+        .parameters
+        .iter()
+        .filter_map(|parameter| match &**parameter {
+            ASTNode::Identifier(identifier) => Some(identifier.name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_resolve() {
+        let source = "x = 1\n\
+                      class Point\n  def constructor(x, y)\n    self.x = x\n  end\nend\n\
+                      def main\n  x = 2\n  print(x)\nend\n\
+                      print(x)\n";
+        let ast = Parser::new(Lexer::new(source.to_string())).parse().unwrap();
+        let analysis = analyze(&ast);
+
+        let point = analysis.resolve("Point", 11).unwrap();
+        assert_eq!((point.kind, point.line), (DefinitionKind::Class, 2));
+
+        let constructor: Vec<&Definition> = analysis.declarations(point.body.unwrap()).collect();
+        assert_eq!(constructor[0].parameters, vec!["x", "y"]);
+        assert_eq!(constructor[0].kind, DefinitionKind::Method);
+
+        let x = analysis.resolve("x", 4).unwrap();
+        assert_eq!((x.kind, x.line), (DefinitionKind::Parameter, 3));
+        assert_eq!(analysis.resolve("x", 9).unwrap().line, 8);
+        assert_eq!(analysis.resolve("x", 11).unwrap().line, 1);
+        assert_eq!(analysis.resolve("constructor", 11).unwrap().line, 3);
+    }
+}
@@ -0,0 +1,146 @@
+//! Names of attributes and variables are interned once, when code is
+//! compiled or a type is built, so that dictionary lookups hash and compare
+//! a small id instead of the whole string.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+#[derive(Default)]
+struct SymbolTable {
+    ids: HashMap<&'static str, Symbol>,
+    names: Vec<&'static str>,
+}
+
+impl SymbolTable {
+    fn insert(&mut self, name: &'static str) -> Symbol {
+        let symbol = Symbol(self.names.len() as u32);
+
+        self.names.push(name);
+        self.ids.insert(name, symbol);
+
+        symbol
+    }
+}
+
+// Names the runtime looks up by itself. They are interned first, in this
+// order, so that the constants below can refer to them by id.
+const PREDEFINED: [&str; 10] = [
+    "constructor",
+    "self",
+    "__repr__",
+    "__exit__",
+    "__eq__",
+    "__ne__",
+    "__lt__",
+    "__gt__",
+    "__le__",
+    "__ge__",
+];
+
+// Interned names live for the rest of the program.
+static SYMBOLS: Lazy<RwLock<SymbolTable>> = Lazy::new(|| {
+    let mut table = SymbolTable::default();
+
+    for name in PREDEFINED {
+        table.insert(name);
+    }
+
+    RwLock::new(table)
+});
+
+impl Symbol {
+    pub const CONSTRUCTOR: Symbol = Symbol(0);
+    pub const SELF: Symbol = Symbol(1);
+    pub const REPR: Symbol = Symbol(2);
+    pub const EXIT: Symbol = Symbol(3);
+    pub const EQ: Symbol = Symbol(4);
+    pub const NE: Symbol = Symbol(5);
+    pub const LT: Symbol = Symbol(6);
+    pub const GT: Symbol = Symbol(7);
+    pub const LE: Symbol = Symbol(8);
+    pub const GE: Symbol = Symbol(9);
+
+    pub fn intern(name: &str) -> Symbol {
+        if let Some(symbol) = SYMBOLS.read().unwrap().ids.get(name) {
+            return *symbol;
+        }
+
+        let mut table = SYMBOLS.write().unwrap();
+
+        // Another thread may have interned it between the two locks.
+        if let Some(symbol) = table.ids.get(name) {
+            return *symbol;
+        }
+
+        table.insert(Box::leak(name.to_string().into_boxed_str()))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        SYMBOLS.read().unwrap().names[self.0 as usize]
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Symbol::intern(&name)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(name: &String) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let a = Symbol::intern("constructor");
+        let b = Symbol::from("constructor".to_string());
+
+        assert_eq!(a, b);
+        assert_ne!(a, Symbol::intern("__repr__"));
+        assert_eq!(a.as_str(), "constructor");
+        assert_eq!(a.to_string(), "constructor");
+        assert_eq!(a, Symbol::CONSTRUCTOR);
+        assert_eq!(Symbol::intern("__ge__"), Symbol::GE);
+    }
+}
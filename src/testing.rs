@@ -0,0 +1,177 @@
+//! `kyanite test`: runs the tests of a project. Tests live in files named
+//! `*_test.k`, and a test is a function defined at the top of one whose name
+//! starts with `test_`. Each file runs once in an interpreter of its own, and
+//! then each of its tests is called in turn; a test fails when it raises,
+//! usually through `assert`, `assert_eq` or `assert_raises`.
+
+use crate::bytecode::CodeObject;
+use crate::compiler::Compiler;
+use crate::doc::source_files;
+use crate::errors::Error;
+use crate::interpreter::{Interpreter, Session};
+use crate::lexer::Lexer;
+use crate::optimizer;
+use crate::parser::Parser;
+use crate::{ast, ast::ASTNode};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const TEST_FILE_SUFFIX: &str = "_test.k";
+const TEST_PREFIX: &str = "test_";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} passed, {} failed", self.passed, self.failed)
+    }
+}
+
+/// The test files under `path`, or `path` itself if it is a file.
+pub fn test_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    Ok(source_files(path)?
+        .into_iter()
+        .filter(|file| {
+            file.file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(TEST_FILE_SUFFIX))
+        })
+        .collect())
+}
+
+/// Runs the tests under `path`, printing each outcome as it comes. A file
+/// that does not compile or run counts as one failure.
+pub fn run(path: &Path) -> Result<Summary, String> {
+    let mut summary = Summary::default();
+
+    for file in test_files(path)? {
+        println!("{}", file.display());
+
+        if let Err(e) = run_file(&file, &mut summary) {
+            println!("  FAIL {}", describe(&e));
+            summary.failed += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn run_file(file: &Path, summary: &mut Summary) -> Result<(), Error> {
+    let source = crate::internal::source::read_source(file).map_err(Error::RuntimeError)?;
+    let module = parse(&source)?;
+    let code = compile(module.clone())?;
+
+    let root = file
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut session = Session::new(Interpreter::new(&root.to_string_lossy()));
+
+    session.eval(&code)?;
+
+    for name in functions_named(&module, TEST_PREFIX) {
+        let call = compile(parse(&format!("{}()\n", name))?)?;
+
+        match session.eval(&call) {
+            Ok(_) => {
+                println!("  PASS {}", name);
+                summary.passed += 1;
+            }
+            Err(e) => {
+                println!("  FAIL {}: {}", name, describe(&e));
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn parse(source: &str) -> Result<ASTNode, Error> {
+    Parser::new(Lexer::new(source.to_string())).parse()
+}
+
+pub(crate) fn compile(module: ASTNode) -> Result<CodeObject, Error> {
+    let mut compiler = Compiler::new(Arc::new(module));
+    compiler.add_pass(optimizer::ConstantBranches::new());
+    compiler.compile()?;
+
+    Ok(compiler.get_output())
+}
+
+// The top-level functions of the module whose names start with `prefix`, in
+// source order.
+pub(crate) fn functions_named(module: &ASTNode, prefix: &str) -> Vec<String> {
+    let ASTNode::Module(module) = module else {
+        return vec![];
+    };
+    let ASTNode::Block(ast::Block { statements, .. }) = &*module.block else {
+        return vec![];
+    };
+
+    statements
+        .iter()
+        .filter_map(|statement| match &**statement {
+            ASTNode::MethodDef(method_def) if method_def.name.starts_with(prefix) => {
+                Some(method_def.name.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn describe(error: &Error) -> String {
+    match error {
+        Error::Exception(exception_type, message, ..) => {
+            format!("{}: {}", exception_type, message)
+        }
+        error => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let root = std::env::temp_dir().join(format!("kyanite-test-{}", std::process::id()));
+
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("math_test.k"),
+            "def add(a, b)\n  return a + b\nend\n\n\
+             def test_add\n  assert_eq(add(1, 2), 3)\nend\n\n\
+             def test_wrong\n  assert_eq(add(1, 1), 3)\nend\n\n\
+             def test_raises\n  assert_raises(Exception, add, 1)\nend\n\n\
+             def helper\n  assert(false)\nend\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("math.k"),
+            "def test_ignored\n  assert(false)\nend\n",
+        )
+        .unwrap();
+
+        let summary = run(&root);
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            summary,
+            Ok(Summary {
+                passed: 2,
+                failed: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! `--trace`: prints every instruction the interpreter runs to stderr, with
+//! the code it belongs to, its offset, its operand and the repr of the top of
+//! the stack once it ran. Reprs that run code of the program are not traced
+//! themselves, and an object without a repr is shown by its type.
+
+use crate::bytecode::{Opcode, WIDE_OPERAND_SIZE};
+use crate::interpreter::Frame;
+use crate::objects::base::kya_type_name;
+use crate::objects::utils::object_to_string_repr;
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static IN_TRACE: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) && !IN_TRACE.with(|in_trace| in_trace.get())
+}
+
+/// Describes the instruction at `pc` in the frame's code.
+pub fn describe(frame: &Frame, pc: usize) -> String {
+    let code = &frame.code;
+    let name = if code.name.is_empty() {
+        "<main>"
+    } else {
+        &code.name
+    };
+
+    let Some(opcode) = Opcode::from_u8(code.code[pc]) else {
+        return format!("{}:{:04} <opcode {}>", name, pc, code.code[pc]);
+    };
+
+    let operand = match opcode.operand_size() {
+        WIDE_OPERAND_SIZE => u16::from_le_bytes([code.code[pc + 1], code.code[pc + 2]]).to_string(),
+        1 => code.code[pc + 1].to_string(),
+        // A name and the slot it is at.
+        3 => format!(
+            "{}/{}",
+            u16::from_le_bytes([code.code[pc + 1], code.code[pc + 2]]),
+            code.code[pc + 3]
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "{}:{:04} {:<22} {:>5}",
+        name,
+        pc,
+        opcode.to_string(),
+        operand
+    )
+}
+
+/// Prints the instruction that started at `pc`, once it ran.
+pub fn trace(frame: &Frame, pc: usize) {
+    IN_TRACE.with(|in_trace| in_trace.set(true));
+
+    let top = match frame.stack.last() {
+        Some(object) => {
+            object_to_string_repr(object).unwrap_or_else(|_| format!("<{}>", kya_type_name(object)))
+        }
+        None => "<empty>".to_string(),
+    };
+
+    IN_TRACE.with(|in_trace| in_trace.set(false));
+
+    eprintln!("{}  {}", describe(frame, pc), top);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_describe() {
+        let ast = Parser::new(Lexer::new("x = 1\n".to_string()))
+            .parse()
+            .unwrap();
+        let mut compiler = Compiler::new(Arc::new(ast));
+        compiler.compile().unwrap();
+
+        let frame = Frame::for_call(
+            Arc::new(compiler.get_output()),
+            Arc::new(Mutex::new(HashMap::new())),
+        );
+
+        assert_eq!(
+            describe(&frame, 0),
+            format!("<main>:0000 {:<22} {:>5}", "LOAD_CONST", 0)
+        );
+    }
+}
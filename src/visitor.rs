@@ -1,7 +1,8 @@
 use crate::ast::{
-    Assignment, Attribute, BinOp, Block, ClassDef, Compare, Identifier, If, Import, MethodCall,
-    MethodDef, Module, Raise, Return, UnaryOp, While,
+    ASTNode, Assignment, Attribute, BinOp, Block, ClassDef, Compare, Identifier, If, Import,
+    MethodCall, MethodDef, Module, Raise, Return, Slice, Try, UnaryOp, While, With,
 };
+use crate::bytecode::CodeObject;
 use crate::errors::Error;
 use crate::objects::base::KyaObjectRef;
 
@@ -15,6 +16,7 @@ pub trait Visitor {
     fn visit_method_def(&mut self, method_def: &MethodDef);
     fn visit_class_def(&mut self, class_def: &ClassDef);
     fn visit_attribute(&mut self, attribute: &Attribute);
+    fn visit_slice(&mut self, slice: &Slice);
     fn visit_compare(&mut self, compare: &Compare);
     fn visit_if(&mut self, if_node: &If);
     fn visit_import(&mut self, import: &Import);
@@ -25,6 +27,8 @@ pub trait Visitor {
     fn visit_block(&mut self, block: &Block);
     fn visit_return(&mut self, return_node: &Return);
     fn visit_raise(&mut self, raise: &Raise);
+    fn visit_try(&mut self, try_node: &Try);
+    fn visit_with(&mut self, with: &With);
 }
 
 pub trait CompilerVisitor {
@@ -37,6 +41,7 @@ pub trait CompilerVisitor {
     fn compile_method_def(&mut self, method_def: &MethodDef) -> Result<(), Error>;
     fn compile_class_def(&mut self, class_def: &ClassDef) -> Result<(), Error>;
     fn compile_attribute(&mut self, attribute: &Attribute) -> Result<(), Error>;
+    fn compile_slice(&mut self, slice: &Slice) -> Result<(), Error>;
     fn compile_compare(&mut self, compare: &Compare) -> Result<(), Error>;
     fn compile_if(&mut self, if_node: &If) -> Result<(), Error>;
     fn compile_import(&mut self, import: &Import) -> Result<(), Error>;
@@ -47,4 +52,50 @@ pub trait CompilerVisitor {
     fn compile_block(&mut self, block: &Block) -> Result<(), Error>;
     fn compile_return(&mut self, return_node: &Return) -> Result<(), Error>;
     fn compile_raise(&mut self, raise: &Raise) -> Result<(), Error>;
+    fn compile_try(&mut self, try_node: &Try) -> Result<(), Error>;
+    fn compile_with(&mut self, with: &With) -> Result<(), Error>;
+
+    // Called around every node that `ASTNode::compile` dispatches.
+    fn before_node(&mut self, _node: &ASTNode) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn after_node(&mut self, _node: &ASTNode) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A pass registered on a `Compiler`, for tools that need to inspect or
+/// rewrite a program while it compiles. Every hook defaults to doing nothing.
+///
+/// `transform_ast` runs once on the whole tree before any code is emitted.
+/// `before_node` and `after_node` run around each node, in nested functions
+/// and classes too, and may emit instructions into `code`; `before_statement`
+/// does the same but only at the start of each statement of a block.
+/// `transform_code` runs on every finished code object, innermost first; it
+/// must keep jump targets valid if it moves instructions.
+pub trait CompilerPass {
+    fn transform_ast(&mut self, ast: ASTNode) -> Result<ASTNode, Error> {
+        Ok(ast)
+    }
+
+    fn before_node(&mut self, _node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn after_node(&mut self, _node: &ASTNode, _code: &mut CodeObject) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn before_statement(
+        &mut self,
+        _statement: &ASTNode,
+        _code: &mut CodeObject,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn transform_code(&mut self, _code: &mut CodeObject) -> Result<(), Error> {
+        Ok(())
+    }
 }
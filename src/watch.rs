@@ -0,0 +1,156 @@
+//! `--watch` runs the script in a child process and starts it again whenever
+//! a source file changes. The child is asked to stop first, so the operating
+//! system closes its sockets and ends its threads before the next run binds
+//! the same ports.
+
+use crate::doc::source_files;
+use crate::interpreter::Interpreter;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+// Where the child looks for the modules the script imports: its directory,
+// the `kya_modules` there, `KYA_PATH` and the `-I` paths in `paths`.
+fn search_paths(file: &Path, paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let root = file
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut interpreter = Interpreter::new(&root.to_string_lossy());
+
+    for path in paths {
+        interpreter.add_search_path(path)?;
+    }
+
+    Ok(interpreter.search_paths().to_vec())
+}
+
+// The script and every `.k` file under the search paths.
+fn snapshot(file: &Path, search_paths: &[PathBuf]) -> Snapshot {
+    let mut files = vec![file.to_path_buf()];
+
+    for path in search_paths {
+        files.extend(source_files(path).unwrap_or_default());
+    }
+
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+
+            Some((path, modified))
+        })
+        .collect()
+}
+
+fn spawn(arguments: &[String]) -> Result<Child, String> {
+    let executable = std::env::current_exe()
+        .map_err(|e| format!("Error: Could not find the kyanite executable: {}", e))?;
+
+    Command::new(executable)
+        .args(arguments)
+        .spawn()
+        .map_err(|e| format!("Error: Could not start the script: {}", e))
+}
+
+// Sends SIGTERM and waits a moment for the run to end by itself before
+// killing it.
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+        }
+
+        let deadline = Instant::now() + STOP_GRACE_PERIOD;
+
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Runs `file` with `arguments`, which are the command line without
+/// `--watch`, until the process is interrupted. `paths` are the search paths
+/// given with `-I`.
+pub fn run(file: &str, paths: &[String], arguments: Vec<String>) -> Result<(), String> {
+    let file = Path::new(file);
+    let search_paths = search_paths(file, paths)?;
+    let mut files = snapshot(file, &search_paths);
+    let mut child = Some(spawn(&arguments)?);
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let exited = child
+            .as_mut()
+            .and_then(|running| running.try_wait().ok().flatten());
+
+        if let Some(status) = exited {
+            eprintln!(
+                "[watch] {} ({}); waiting for changes",
+                file.display(),
+                status
+            );
+            child = None;
+        }
+
+        let current = snapshot(file, &search_paths);
+
+        if current == files {
+            continue;
+        }
+
+        files = current;
+
+        if let Some(mut running) = child.take() {
+            stop(&mut running);
+        }
+
+        eprintln!("[watch] change detected, restarting {}", file.display());
+        child = Some(spawn(&arguments)?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_covers_the_search_paths() {
+        let root = std::env::temp_dir().join(format!("kyanite-watch-{}", std::process::id()));
+        let script_dir = root.join("app");
+        let lib_dir = root.join("lib");
+        std::fs::create_dir_all(script_dir.join("kya_modules/dep")).unwrap();
+        std::fs::create_dir_all(&lib_dir).unwrap();
+
+        let script = script_dir.join("main.k");
+        std::fs::write(&script, "import helper\n").unwrap();
+        std::fs::write(script_dir.join("kya_modules/dep/dep.k"), "").unwrap();
+        std::fs::write(lib_dir.join("helper.k"), "").unwrap();
+
+        let search_paths = search_paths(&script, &[lib_dir.to_string_lossy().to_string()]).unwrap();
+        let files = snapshot(&script, &search_paths);
+
+        assert!(files.contains_key(&script));
+        assert!(files.contains_key(&script_dir.join("kya_modules/dep/dep.k")));
+        assert!(files.contains_key(&lib_dir.join("helper.k")));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
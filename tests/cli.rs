@@ -0,0 +1,168 @@
+//! Runs the `kyanite` binary on small programs and checks what it reports.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+fn kyanite(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kyanite"))
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn run(code: &str) -> Output {
+    kyanite(&["-e", code])
+}
+
+// Fails with a diagnostic carrying `code` instead of a panic.
+fn assert_diagnostic(code: &str, expected: &str) {
+    let output = run(code);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1), "{}: {}", code, stderr);
+    assert!(stderr.contains(expected), "{}: {}", code, stderr);
+    assert!(!stderr.contains("panicked"), "{}: {}", code, stderr);
+}
+
+#[test]
+fn test_invalid_escapes_are_reported() {
+    for code in [
+        r#"print("\q")"#,
+        r#"print("\x4")"#,
+        r#"print("\u{110000}")"#,
+        r#"print("\u{D800}")"#,
+        r#"print("\u{}")"#,
+    ] {
+        assert_diagnostic(code, "K0104");
+    }
+
+    let output = run(r#"print("say \"hi\"\t\x41\u{e9}")"#);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "say \"hi\"\tAé\n");
+}
+
+#[test]
+fn test_malformed_numbers_are_reported() {
+    for code in ["x = 1e", "x = 1_", "x = 1__0", "x = 1e+"] {
+        assert_diagnostic(code, "K0103");
+    }
+
+    let output = run("print(1.5e-3 + 1_000_000)");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1000000.0015\n");
+}
+
+#[test]
+fn test_uncaught_errors_report_their_code() {
+    assert_diagnostic(
+        "import json\njson.parse(\"{\")",
+        "ValueError[K0403]: Invalid JSON at position 1",
+    );
+    assert_diagnostic("raise Exception(\"boom\")", "Exception[K0410]: boom");
+
+    let output =
+        run("import json\nassert_raises(ValueError, json.parse, \"{\")\nprint(\"raised\")");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "raised\n");
+}
+
+#[test]
+fn test_break_outside_loop_is_reported() {
+    let output = kyanite(&["--check", "-e", "x = 1\nbreak\n"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains("K0312"), "{}", stderr);
+    assert!(stderr.contains("break outside loop"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+
+    assert_diagnostic("def stop()\n    break\nend\n", "K0312");
+
+    let output = run("while true\n    if true\n        break\n    end\nend\nprint(\"done\")\n");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+}
+
+#[test]
+fn test_invalid_timeouts_are_usage_errors() {
+    for timeout in ["inf", "1e20", "-1", "NaN", "soon"] {
+        let output = kyanite(&[&format!("--timeout={}", timeout), "-e", "print(1)"]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(output.status.code(), Some(2), "{}: {}", timeout, stderr);
+        assert!(stderr.contains("--timeout"), "{}: {}", timeout, stderr);
+        assert!(!stderr.contains("panicked"), "{}: {}", timeout, stderr);
+    }
+
+    let output = kyanite(&["--timeout", "5", "-e", "print(1)"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+}
+
+#[test]
+fn test_repl_applies_the_run_settings() {
+    let log = std::env::temp_dir().join(format!("kyanite-repl-{}.log", std::process::id()));
+
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_kyanite"))
+        .arg("--trace")
+        .arg("--record")
+        .arg(&log)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    repl.stdin
+        .take()
+        .unwrap()
+        .write_all(b"import time\nprint(time.now() > 0)\n")
+        .unwrap();
+
+    let output = repl.wait_with_output().unwrap();
+    let recorded = std::fs::read_to_string(&log).unwrap_or_default();
+    let _ = std::fs::remove_file(&log);
+
+    assert!(String::from_utf8_lossy(&output.stdout).contains("true"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("LOAD_CONST"));
+    assert!(recorded.starts_with("time.now"), "{}", recorded);
+}
+
+#[test]
+fn test_watch_reruns_the_script_when_it_changes() {
+    let dir = std::env::temp_dir().join(format!("kyanite-watch-{}", std::process::id()));
+    let script = dir.join("main.k");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&script, "print(\"first\")\n").unwrap();
+
+    let mut watcher = Command::new(env!("CARGO_BIN_EXE_kyanite"))
+        .arg("--watch")
+        .arg(&script)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let (lines, received) = mpsc::channel();
+    let stdout = watcher.stdout.take().unwrap();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let _ = lines.send(line.unwrap());
+        }
+    });
+
+    let first = received.recv_timeout(Duration::from_secs(10));
+
+    // Far enough apart that the modification time changes.
+    thread::sleep(Duration::from_millis(50));
+    std::fs::write(&script, "print(\"second\")\n").unwrap();
+
+    let second = received.recv_timeout(Duration::from_secs(10));
+
+    watcher.kill().unwrap();
+    watcher.wait().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(first.as_deref(), Ok("first"));
+    assert_eq!(second.as_deref(), Ok("second"));
+}
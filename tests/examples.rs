@@ -0,0 +1,212 @@
+//! Runs the programs in `examples/apps` and compares what they do with the
+//! transcripts in `tests/golden`. Servers are driven by scripted clients and
+//! killed once the exchange is over; their output is appended to the
+//! transcript after a `--- server output` line.
+//!
+//! To update a transcript after an intended change, run the test with
+//! `KYANITE_BLESS=1` and review the diff.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn manifest_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn kyanite(example: &str) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_kyanite"));
+
+    command
+        .arg(manifest_path(&format!("examples/apps/{}.k", example)))
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit());
+
+    command
+}
+
+fn assert_golden(name: &str, transcript: &str) {
+    let path = manifest_path(&format!("tests/golden/{}.out", name));
+
+    if std::env::var_os("KYANITE_BLESS").is_some() {
+        std::fs::write(&path, transcript).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+
+    assert_eq!(transcript, expected, "{} does not match", path.display());
+}
+
+// A running example, killed when dropped so that a failing test does not
+// leave it holding its port.
+struct Server {
+    child: Child,
+    stdout: BufReader<ChildStdout>,
+    transcript: String,
+}
+
+impl Server {
+    // Starts the example and waits for the line it prints once it listens.
+    fn start(example: &str) -> Server {
+        let mut child = kyanite(example).stdout(Stdio::piped()).spawn().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut banner = String::new();
+
+        stdout.read_line(&mut banner).unwrap();
+        assert!(!banner.is_empty(), "{} exited before listening", example);
+
+        Server {
+            child,
+            stdout,
+            transcript: banner,
+        }
+    }
+
+    // Some examples print their banner just before they start listening, so
+    // the first attempts may be refused.
+    fn connect(&self, port: u16) -> TcpStream {
+        let deadline = Instant::now() + READ_TIMEOUT;
+
+        let stream = loop {
+            match TcpStream::connect(("localhost", port)) {
+                Ok(stream) => break stream,
+                Err(e) if Instant::now() >= deadline => panic!("Could not connect: {}", e),
+                Err(_) => thread::sleep(Duration::from_millis(20)),
+            }
+        };
+
+        stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+        stream
+    }
+
+    fn log(&mut self, line: String) {
+        self.transcript.push_str(&line);
+        self.transcript.push('\n');
+    }
+
+    fn send(&mut self, client: &str, stream: &mut TcpStream, line: &str) {
+        stream.write_all(format!("{}\n", line).as_bytes()).unwrap();
+        self.log(format!("{} > {}", client, line));
+    }
+
+    fn receive(&mut self, client: &str, reader: &mut BufReader<TcpStream>) {
+        let mut line = String::new();
+
+        reader.read_line(&mut line).unwrap();
+        self.log(format!("{} < {}", client, line.trim_end()));
+    }
+
+    // Stops the server and returns the transcript with its output.
+    fn finish(mut self) -> String {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+
+        let mut output = String::new();
+        self.stdout.read_to_string(&mut output).unwrap();
+
+        format!("{}--- server output\n{}", self.transcript, output)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn http_request(stream: &mut TcpStream, method: &str, path: &str, body: &str) -> String {
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    );
+    let mut response = String::new();
+
+    stream.write_all(request.as_bytes()).unwrap();
+    stream.read_to_string(&mut response).unwrap();
+
+    response.replace("\r\n", "\n")
+}
+
+#[test]
+fn fib() {
+    let output = kyanite("fib").output().unwrap();
+
+    assert!(output.status.success());
+    assert_golden("fib", &String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn echo_server() {
+    let mut server = Server::start("echo_server");
+    let mut stream = server.connect(9301);
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    for line in ["hello", "kyanite", ""] {
+        server.send("client", &mut stream, line);
+        server.receive("client", &mut reader);
+    }
+
+    assert_golden("echo_server", &server.finish());
+}
+
+#[test]
+fn chat_server() {
+    let mut server = Server::start("chat_server");
+    let mut alice = server.connect(9302);
+    let mut alice_reader = BufReader::new(alice.try_clone().unwrap());
+
+    server.send("alice", &mut alice, "alice");
+    server.receive("alice", &mut alice_reader);
+
+    let mut bob = server.connect(9302);
+    let mut bob_reader = BufReader::new(bob.try_clone().unwrap());
+
+    server.send("bob", &mut bob, "bob");
+    server.receive("alice", &mut alice_reader);
+    server.receive("bob", &mut bob_reader);
+
+    server.send("alice", &mut alice, "hi bob");
+    server.receive("alice", &mut alice_reader);
+    server.receive("bob", &mut bob_reader);
+
+    server.send("bob", &mut bob, "hello alice");
+    server.receive("alice", &mut alice_reader);
+    server.receive("bob", &mut bob_reader);
+
+    drop(bob_reader);
+    drop(bob);
+    server.receive("alice", &mut alice_reader);
+
+    assert_golden("chat_server", &server.finish());
+}
+
+#[test]
+fn json_api() {
+    let mut server = Server::start("json_api");
+
+    for (method, path, body) in [
+        ("GET", "/fib/30", ""),
+        ("POST", "/sum", "[1, 2, 3.5]"),
+        ("GET", "/missing", ""),
+    ] {
+        let mut stream = server.connect(9303);
+        let response = http_request(&mut stream, method, path, body);
+
+        let request = format!("{} {} {}", method, path, body);
+
+        server.log(format!("{}\n{}", request.trim_end(), response));
+    }
+
+    assert_golden("json_api", &server.finish());
+}
@@ -0,0 +1,77 @@
+//! Builds the extension in `tests/fixtures/extension` with the same cargo
+//! and imports it from a program.
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+fn manifest_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn build_extension() -> PathBuf {
+    let fixture = manifest_path("tests/fixtures/extension");
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("extension");
+
+    // The fixture resolves to the same dependencies as the interpreter, so it
+    // builds without going to the network.
+    if !fixture.join("Cargo.lock").exists() {
+        std::fs::copy(manifest_path("Cargo.lock"), fixture.join("Cargo.lock")).unwrap();
+    }
+
+    let status = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .args(["build", "--quiet"])
+        .arg("--manifest-path")
+        .arg(fixture.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .stdin(Stdio::null())
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+
+    target
+        .join("debug")
+        .join(format!("{}shapes{}", DLL_PREFIX, DLL_SUFFIX))
+}
+
+fn kyanite(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_kyanite"))
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_import_extension() {
+    let library = build_extension();
+    let dir = std::env::temp_dir().join(format!("kyanite-extension-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(&library, dir.join(library.file_name().unwrap())).unwrap();
+
+    let output = kyanite(&[
+        "-I",
+        dir.to_str().unwrap(),
+        "-e",
+        "import shapes\nprint(shapes.name)\nprint(shapes.square(7))",
+    ]);
+    let sandboxed = kyanite(&[
+        "--sandbox",
+        "-I",
+        dir.to_str().unwrap(),
+        "-e",
+        "import shapes",
+    ]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "shapes\n49\n",
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&sandboxed.stderr).contains("PermissionError"));
+}
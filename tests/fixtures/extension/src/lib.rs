@@ -0,0 +1,11 @@
+//! The extension `tests/extensions.rs` builds and imports.
+
+use kyanite::objects::host_function_object::{ArgType, HostValue};
+
+kyanite::extension!(|module| {
+    module
+        .function("square", &[ArgType::Number], ArgType::Number, |args| {
+            Ok(HostValue::Number(args[0].as_number()?.powi(2)))
+        })
+        .value("name", HostValue::String("shapes".to_string()));
+});
@@ -0,0 +1,84 @@
+//! Runs the `kyanite` binary on the parts of Windows that differ from the
+//! other platforms: CRLF line endings, backslash paths and socket options.
+
+#![cfg(windows)]
+
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn kyanite(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kyanite"))
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kyanite-{}-{}", name, std::process::id()));
+
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[test]
+fn test_crlf_sources_report_the_right_line() {
+    let dir = temp_dir("crlf");
+    let file = dir.join("crlf.k");
+    std::fs::write(&file, "x = 1\r\n\r\ny = $\r\n").unwrap();
+
+    let output = kyanite(&[file.to_str().unwrap()]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(stderr.contains("line 3, column 5"), "{}", stderr);
+}
+
+#[test]
+fn test_dotted_imports_are_found_under_backslash_paths() {
+    let dir = temp_dir("imports");
+    std::fs::create_dir_all(dir.join("net")).unwrap();
+    std::fs::write(dir.join("net").join("http.k"), "name = \"http\"\r\n").unwrap();
+
+    let output = kyanite(&[
+        "-I",
+        dir.to_str().unwrap(),
+        "-e",
+        "import net.http\nprint(net.http.name)",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "http\n",
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_reuse_address_is_refused() {
+    let output = kyanite(&[
+        "-e",
+        "import sockets\nserver = sockets.tcp()\nserver.set_option(\"reuse_address\", true)",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(
+        stderr.contains("reuse_address is not available on Windows"),
+        "{}",
+        stderr
+    );
+
+    let output = kyanite(&[
+        "-e",
+        "import sockets\nserver = sockets.tcp()\nserver.set_option(\"nodelay\", true)\nserver.set_option(\"keepalive\", true)\nserver.bind(\"localhost\", 0)\nprint(\"bound\")",
+    ]);
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "bound\n");
+}